@@ -1,4 +1,6 @@
-use std::{ops::Deref, rc::Rc, sync::Arc, thread};
+#[cfg(not(feature = "single-threaded"))]
+use std::thread;
+use std::{ops::Deref, rc::Rc, sync::Arc};
 
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use trc::{SharedTrc, Trc};
@@ -19,26 +21,34 @@ pub fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("Multiple deref Trc", |b| b.iter(multi_deref_trc));
     c.bench_function("Multiple deref Arc", |b| b.iter(multi_deref_arc));
     c.bench_function("Multiple deref Rc", |b| b.iter(multi_deref_rc));
-    c.bench_function("Multiple threads Trc", |b| b.iter(multi_thread_trc));
-    c.bench_function("Multiple threads Arc", |b| b.iter(multi_thread_arc));
-    c.bench_function("Multiple threads Trc Medium", |b| {
-        b.iter(multi_thread_trc_medium)
-    });
-    c.bench_function("Multiple threads Arc Medium", |b| {
-        b.iter(multi_thread_arc_medium)
-    });
-    c.bench_function("Multiple threads Trc Long", |b| {
-        b.iter(multi_thread_trc_long)
-    });
-    c.bench_function("Multiple threads Arc Long", |b| {
-        b.iter(multi_thread_arc_long)
-    });
-    c.bench_function("Multiple threads Trc Super", |b| {
-        b.iter(multi_thread_trc_super)
-    });
-    c.bench_function("Multiple threads Arc Super", |b| {
-        b.iter(multi_thread_arc_super)
-    });
+    //The handles of the `single-threaded` feature cannot be sent to other threads.
+    #[cfg(not(feature = "single-threaded"))]
+    {
+        c.bench_function("Multiple threads Trc", |b| b.iter(multi_thread_trc));
+        c.bench_function("Multiple threads Arc", |b| b.iter(multi_thread_arc));
+        c.bench_function("Multiple threads Trc Medium", |b| {
+            b.iter(multi_thread_trc_medium)
+        });
+        c.bench_function("Multiple threads Arc Medium", |b| {
+            b.iter(multi_thread_arc_medium)
+        });
+        c.bench_function("Multiple threads Trc Long", |b| {
+            b.iter(multi_thread_trc_long)
+        });
+        c.bench_function("Multiple threads Arc Long", |b| {
+            b.iter(multi_thread_arc_long)
+        });
+        c.bench_function("Multiple threads Trc Super", |b| {
+            b.iter(multi_thread_trc_super)
+        });
+        c.bench_function("Multiple threads Arc Super", |b| {
+            b.iter(multi_thread_arc_super)
+        });
+        //Compare with and without the `padded` feature.
+        c.bench_function("Shared churn Trc", |b| b.iter(shared_churn_trc));
+    }
+    c.bench_function("Release SharedTrc", |b| b.iter(release_shared_trc));
+    c.bench_function("Release Arc", |b| b.iter(release_arc));
 }
 
 fn clone_trc() {
@@ -113,6 +123,7 @@ fn multi_deref_rc() {
     }
 }
 
+#[cfg(not(feature = "single-threaded"))]
 fn multi_thread_trc() {
     let trc = Trc::new(100);
     for _ in 0..100 {
@@ -131,6 +142,7 @@ fn multi_thread_trc() {
     }
 }
 
+#[cfg(not(feature = "single-threaded"))]
 fn multi_thread_arc() {
     let arc = Arc::new(100);
     for _ in 0..100 {
@@ -148,6 +160,7 @@ fn multi_thread_arc() {
     }
 }
 
+#[cfg(not(feature = "single-threaded"))]
 fn multi_thread_trc_medium() {
     let trc = Trc::new(100);
     for _ in 0..100 {
@@ -166,6 +179,7 @@ fn multi_thread_trc_medium() {
     }
 }
 
+#[cfg(not(feature = "single-threaded"))]
 fn multi_thread_arc_medium() {
     let arc = Arc::new(100);
     for _ in 0..100 {
@@ -183,6 +197,7 @@ fn multi_thread_arc_medium() {
     }
 }
 
+#[cfg(not(feature = "single-threaded"))]
 fn multi_thread_trc_long() {
     let trc = Trc::new(100);
     for _ in 0..100 {
@@ -201,6 +216,7 @@ fn multi_thread_trc_long() {
     }
 }
 
+#[cfg(not(feature = "single-threaded"))]
 fn multi_thread_arc_long() {
     let arc = Arc::new(100);
     for _ in 0..100 {
@@ -218,6 +234,7 @@ fn multi_thread_arc_long() {
     }
 }
 
+#[cfg(not(feature = "single-threaded"))]
 fn multi_thread_trc_super() {
     let trc = Trc::new(100);
     for _ in 0..100 {
@@ -236,6 +253,7 @@ fn multi_thread_trc_super() {
     }
 }
 
+#[cfg(not(feature = "single-threaded"))]
 fn multi_thread_arc_super() {
     let arc = Arc::new(100);
     for _ in 0..100 {
@@ -253,5 +271,40 @@ fn multi_thread_arc_super() {
     }
 }
 
+#[cfg(not(feature = "single-threaded"))]
+fn shared_churn_trc() {
+    let shared = SharedTrc::new(100);
+    thread::scope(|scope| {
+        //One thread changes the counts while another reads the data next to them.
+        scope.spawn(|| {
+            for _ in 0..100000 {
+                black_box(SharedTrc::clone(&shared));
+            }
+        });
+        scope.spawn(|| {
+            let mut sum = 0;
+            for _ in 0..100000 {
+                sum += *black_box(&*shared);
+            }
+            sum
+        });
+    });
+}
+
+//Every drop takes the count from 2 to 1, which is where waiting for a unique handle is woken.
+fn release_shared_trc() {
+    let shared = SharedTrc::new(100);
+    for _ in 0..100 {
+        drop(black_box(shared.clone()));
+    }
+}
+
+fn release_arc() {
+    let arc = Arc::new(100);
+    for _ in 0..100 {
+        drop(black_box(arc.clone()));
+    }
+}
+
 criterion_group!(benches, criterion_benchmark);
 criterion_main!(benches);
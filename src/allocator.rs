@@ -0,0 +1,627 @@
+//! Support for custom allocators with the nightly `allocator_api` feature.
+//!
+//! With the feature, `Trc`, `SharedTrc` and `Weak` are generic over an [`Allocator`], which defaults to [`Global`]. The
+//! allocator is stored in the allocation, next to the reference counts, so that it is not duplicated in every handle, and
+//! it is dropped when the allocation is freed. The local counts of `Trc`s are still allocated with the global allocator.
+//!
+//! The core of `Trc` and `SharedTrc`, which has to work with every allocator, is implemented here instead of in the crate
+//! root, where the same items are only compiled without the feature.
+
+use alloc::alloc::{handle_alloc_error, Allocator, Layout};
+use core::{
+    marker::PhantomData,
+    mem::{ManuallyDrop, MaybeUninit},
+    ops::Deref,
+    pin::Pin,
+    ptr::{self, addr_of, addr_of_mut, NonNull},
+};
+
+#[cfg(feature = "arena")]
+use crate::arena;
+#[cfg(feature = "hooks")]
+use crate::hooks::{self, HandleKind};
+#[cfg(feature = "leak-debug")]
+use crate::leak_debug;
+#[cfg(feature = "stats")]
+use crate::stats;
+#[cfg(feature = "std")]
+use crate::wait;
+use crate::{
+    free_threadref, is_dangling, new_threadref,
+    ordering::{AcqRel, Acquire, Relaxed, Release},
+    overflow, sub_value, sum_value,
+    sync::AtomicUsize,
+    try_new_threadref, unique_weak_count, weak_count_value, AllocError, SharedTrc,
+    SharedTrcInternal, Trc, Weak, MAX_REFCOUNT, MAX_WEAKCOUNT,
+};
+
+/// Allocate `value` with `alloc`. The returned allocation holds one atomic reference and the implicit weak reference.
+fn try_allocate<T, A: Allocator>(
+    value: T,
+    alloc: A,
+) -> Result<NonNull<SharedTrcInternal<T, A>>, AllocError> {
+    let layout = Layout::new::<SharedTrcInternal<T, A>>();
+    let data = alloc
+        .allocate(layout)
+        .map_err(|_| AllocError)?
+        .cast::<SharedTrcInternal<T, A>>();
+    #[cfg(feature = "stats")]
+    stats::on_alloc(core::mem::size_of::<T>());
+    unsafe {
+        data.as_ptr().write(SharedTrcInternal {
+            atomicref: AtomicUsize::new(1),
+            weakcount: AtomicUsize::new(1),
+            alloc,
+            #[cfg(feature = "padded")]
+            padding: crate::CachePadding,
+            data: value,
+        });
+    }
+    return Ok(data);
+}
+
+/// Allocate `value` with `alloc`, aborting if the allocation fails.
+fn allocate<T, A: Allocator>(value: T, alloc: A) -> NonNull<SharedTrcInternal<T, A>> {
+    match try_allocate(value, alloc) {
+        Ok(data) => data,
+        Err(AllocError) => handle_alloc_error(Layout::new::<SharedTrcInternal<T, A>>()),
+    }
+}
+
+/// Return whether the allocation at `data` has no other handles than the one calling this. See [`Trc::is_unique`].
+fn is_unique_internal<T: ?Sized, A: Allocator>(data: NonNull<SharedTrcInternal<T, A>>) -> bool {
+    //Acquire the weakcount if it is == 1
+    let weakcount = unique_weak_count(&unsafe { data.as_ref() }.weakcount);
+    if unsafe { data.as_ref() }
+        .weakcount
+        .compare_exchange(weakcount, usize::MAX, Acquire, Relaxed)
+        .is_ok()
+    {
+        //Acquire the atomicref
+        let unique = unsafe { data.as_ref() }.atomicref.load(Acquire) == 1;
+
+        //Synchronize with the previous Acquire
+        unsafe { data.as_ref() }.weakcount.store(weakcount, Release);
+
+        unique
+    } else {
+        false
+    }
+}
+
+impl<T, A: Allocator> Trc<T, A> {
+    /// Creates a new `Trc` holding `value`, allocated with `alloc`.
+    ///
+    /// # Examples
+    /// ```
+    /// #![feature(allocator_api)]
+    /// use std::alloc::System;
+    /// use trc::Trc;
+    ///
+    /// let trc = Trc::new_in(100, System);
+    /// assert_eq!(*trc, 100);
+    /// ```
+    #[inline]
+    pub fn new_in(value: T, alloc: A) -> Self {
+        return Self {
+            shared: allocate(value, alloc),
+            threadref: new_threadref(),
+            phantom: PhantomData,
+        };
+    }
+
+    /// Creates a new `Trc` holding `value`, allocated with `alloc`, or returns an error if the allocation fails.
+    ///
+    /// # Examples
+    /// ```
+    /// #![feature(allocator_api)]
+    /// use std::alloc::System;
+    /// use trc::Trc;
+    ///
+    /// let trc = Trc::try_new_in(100, System).unwrap();
+    /// assert_eq!(*trc, 100);
+    /// ```
+    #[inline]
+    pub fn try_new_in(value: T, alloc: A) -> Result<Self, AllocError> {
+        let threadref = try_new_threadref().ok_or(AllocError)?;
+        return match try_allocate(value, alloc) {
+            Ok(shared) => Ok(Self {
+                shared,
+                threadref,
+                phantom: PhantomData,
+            }),
+            Err(err) => {
+                unsafe { free_threadref(ptr::null::<T>(), threadref) };
+                Err(err)
+            }
+        };
+    }
+
+    /// Creates a new uninitialized `Trc`, allocated with `alloc`.
+    ///
+    /// # Examples
+    /// ```
+    /// #![feature(allocator_api)]
+    /// use std::alloc::System;
+    /// use trc::Trc;
+    ///
+    /// let mut trc = Trc::<i32, _>::new_uninit_in(System);
+    ///
+    /// Trc::get_mut(&mut trc).unwrap().write(5);
+    ///
+    /// let five = unsafe { trc.assume_init() };
+    ///
+    /// assert_eq!(*five, 5);
+    /// ```
+    #[inline]
+    pub fn new_uninit_in(alloc: A) -> Trc<MaybeUninit<T>, A> {
+        return Trc::new_in(MaybeUninit::uninit(), alloc);
+    }
+
+    /// Creates a new pinned `Trc` holding `value`, allocated with `alloc`. `T` will be pinned in memory and unable to be
+    /// moved.
+    ///
+    /// # Examples
+    /// ```
+    /// #![feature(allocator_api)]
+    /// use std::alloc::System;
+    /// use trc::Trc;
+    ///
+    /// let pinned = Trc::pin_in(100, System);
+    /// assert_eq!(*pinned, 100);
+    /// ```
+    #[inline]
+    pub fn pin_in(value: T, alloc: A) -> Pin<Self>
+    where
+        A: 'static,
+    {
+        return unsafe { Pin::new_unchecked(Self::new_in(value, alloc)) };
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Trc<T, A> {
+    /// Return a reference to the allocator of the `Trc`.
+    ///
+    /// # Examples
+    /// ```
+    /// #![feature(allocator_api)]
+    /// use std::alloc::System;
+    /// use trc::Trc;
+    ///
+    /// let trc = Trc::new_in(100, System);
+    /// let _: &System = Trc::allocator(&trc);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn allocator(this: &Self) -> &A {
+        return unsafe { &*addr_of!((*this.shared.as_ptr()).alloc) };
+    }
+
+    /// Get a &mut reference to the internal data if there are no other `Trc`, [`SharedTrc`] or [`Weak`] pointers to the
+    /// same allocation. Otherwise, return [`None`] because it would be unsafe to mutate a shared value.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let mut trc = Trc::new(100);
+    /// let mutref = Trc::get_mut(&mut trc).unwrap();
+    /// *mutref = 300;
+    /// assert_eq!(*trc, 300);
+    /// ```
+    #[inline]
+    pub fn get_mut(this: &mut Self) -> Option<&mut T> {
+        if Self::is_unique(this) {
+            Some(unsafe { &mut (*this.shared.as_ptr()).data })
+        } else {
+            None
+        }
+    }
+
+    /// Return whether this is the only pointer to the allocation, so that there are no other `Trc` (in any thread),
+    /// [`SharedTrc`] or [`Weak`] pointers. This is the condition under which [`Trc::get_mut`] succeeds.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let trc = Trc::new(100);
+    /// assert!(Trc::is_unique(&trc));
+    ///
+    /// let _weak = Trc::downgrade(&trc);
+    /// assert!(!Trc::is_unique(&trc));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn is_unique(this: &Self) -> bool {
+        if *unsafe { this.threadref.as_ref() } != 1 {
+            return false;
+        }
+        return is_unique_internal(this.shared);
+    }
+}
+
+impl<T, A: Allocator> Trc<MaybeUninit<T>, A> {
+    /// Assume that `Trc<MaybeUninit<T>, A>` is initialized, converting it to `Trc<T, A>`.
+    ///
+    /// # Safety
+    /// As with `MaybeUninit::assume_init`, it is up to the caller to guarantee that the inner value really is in an
+    /// initialized state. Calling this when the content is not yet fully initialized causes immediate undefined behavior.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let mut trc = Trc::new_uninit();
+    ///
+    /// Trc::get_mut(&mut trc).unwrap().write(5);
+    ///
+    /// let five = unsafe { trc.assume_init() };
+    ///
+    /// assert_eq!(*five, 5);
+    /// ```
+    #[must_use]
+    pub unsafe fn assume_init(self) -> Trc<T, A> {
+        let this = ManuallyDrop::new(self);
+        return Trc {
+            shared: this.shared.cast(),
+            threadref: this.threadref,
+            phantom: PhantomData,
+        };
+    }
+}
+
+impl<T, A: Allocator> SharedTrc<T, A> {
+    /// Creates a new `SharedTrc` holding `value`, allocated with `alloc`.
+    ///
+    /// # Examples
+    /// ```
+    /// #![feature(allocator_api)]
+    /// use std::alloc::System;
+    /// use trc::SharedTrc;
+    ///
+    /// let shared = SharedTrc::new_in(100, System);
+    /// let handle = std::thread::spawn(move || *shared);
+    /// assert_eq!(handle.join().unwrap(), 100);
+    /// ```
+    #[inline]
+    pub fn new_in(value: T, alloc: A) -> Self {
+        return Self {
+            data: allocate(value, alloc),
+            phantom: PhantomData,
+        };
+    }
+
+    /// Creates a new `SharedTrc` holding `value`, allocated with `alloc`, or returns an error if the allocation fails.
+    ///
+    /// # Examples
+    /// ```
+    /// #![feature(allocator_api)]
+    /// use std::alloc::System;
+    /// use trc::SharedTrc;
+    ///
+    /// let shared = SharedTrc::try_new_in(100, System).unwrap();
+    /// assert_eq!(*shared, 100);
+    /// ```
+    #[inline]
+    pub fn try_new_in(value: T, alloc: A) -> Result<Self, AllocError> {
+        return Ok(Self {
+            data: try_allocate(value, alloc)?,
+            phantom: PhantomData,
+        });
+    }
+
+    /// Creates a new uninitialized `SharedTrc`, allocated with `alloc`.
+    ///
+    /// # Examples
+    /// ```
+    /// #![feature(allocator_api)]
+    /// use std::alloc::System;
+    /// use trc::SharedTrc;
+    ///
+    /// let mut shared = SharedTrc::<i32, _>::new_uninit_in(System);
+    ///
+    /// SharedTrc::get_mut(&mut shared).unwrap().write(5);
+    ///
+    /// let five = unsafe { shared.assume_init() };
+    ///
+    /// assert_eq!(*five, 5);
+    /// ```
+    #[inline]
+    pub fn new_uninit_in(alloc: A) -> SharedTrc<MaybeUninit<T>, A> {
+        return SharedTrc::new_in(MaybeUninit::uninit(), alloc);
+    }
+
+    /// Creates a new pinned `SharedTrc` holding `value`, allocated with `alloc`. `T` will be pinned in memory and unable
+    /// to be moved.
+    ///
+    /// # Examples
+    /// ```
+    /// #![feature(allocator_api)]
+    /// use std::alloc::System;
+    /// use trc::SharedTrc;
+    ///
+    /// let pinned = SharedTrc::pin_in(100, System);
+    /// assert_eq!(*pinned, 100);
+    /// ```
+    #[inline]
+    pub fn pin_in(value: T, alloc: A) -> Pin<Self>
+    where
+        A: 'static,
+    {
+        return unsafe { Pin::new_unchecked(Self::new_in(value, alloc)) };
+    }
+}
+
+impl<T: ?Sized, A: Allocator> SharedTrc<T, A> {
+    /// Return a reference to the allocator of the `SharedTrc`.
+    ///
+    /// # Examples
+    /// ```
+    /// #![feature(allocator_api)]
+    /// use std::alloc::System;
+    /// use trc::SharedTrc;
+    ///
+    /// let shared = SharedTrc::new_in(100, System);
+    /// let _: &System = SharedTrc::allocator(&shared);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn allocator(this: &Self) -> &A {
+        return unsafe { &*addr_of!((*this.data.as_ptr()).alloc) };
+    }
+
+    /// Get a &mut reference to the internal data if there are no other `SharedTrc`, [`Trc`] or [`Weak`] pointers to the
+    /// same allocation. Otherwise, return [`None`] because it would be unsafe to mutate a shared value.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::SharedTrc;
+    ///
+    /// let mut shared = SharedTrc::new(100);
+    /// *SharedTrc::get_mut(&mut shared).unwrap() = 300;
+    /// assert_eq!(*shared, 300);
+    ///
+    /// let _shared2 = shared.clone();
+    /// assert!(SharedTrc::get_mut(&mut shared).is_none());
+    /// ```
+    #[inline]
+    pub fn get_mut(this: &mut Self) -> Option<&mut T> {
+        if Self::is_unique(this) {
+            Some(unsafe { &mut (*this.data.as_ptr()).data })
+        } else {
+            None
+        }
+    }
+
+    /// Return whether this is the only pointer to the allocation, so that there are no other `SharedTrc`, [`Trc`] or
+    /// [`Weak`] pointers. This is the condition under which [`SharedTrc::get_mut`] succeeds.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::SharedTrc;
+    ///
+    /// let shared = SharedTrc::new(100);
+    /// assert!(SharedTrc::is_unique(&shared));
+    ///
+    /// let _shared2 = shared.clone();
+    /// assert!(!SharedTrc::is_unique(&shared));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn is_unique(this: &Self) -> bool {
+        return is_unique_internal(this.data);
+    }
+}
+
+impl<T, A: Allocator> SharedTrc<MaybeUninit<T>, A> {
+    /// Assume that `SharedTrc<MaybeUninit<T>, A>` is initialized, converting it to `SharedTrc<T, A>`.
+    ///
+    /// # Safety
+    /// As with `MaybeUninit::assume_init`, it is up to the caller to guarantee that the inner value really is in an
+    /// initialized state. Calling this when the content is not yet fully initialized causes immediate undefined behavior.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::SharedTrc;
+    ///
+    /// let mut shared = SharedTrc::<i32>::new_uninit();
+    ///
+    /// SharedTrc::get_mut(&mut shared).unwrap().write(5);
+    ///
+    /// let five = unsafe { shared.assume_init() };
+    ///
+    /// assert_eq!(*five, 5);
+    /// ```
+    #[must_use]
+    pub unsafe fn assume_init(self) -> SharedTrc<T, A> {
+        return SharedTrc {
+            data: ManuallyDrop::new(self).data.cast(),
+            phantom: PhantomData,
+        };
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Deref for Trc<T, A> {
+    type Target = T;
+
+    /// Get an immutable reference to the internal data.
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        return &unsafe { self.shared.as_ref() }.data;
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Deref for SharedTrc<T, A> {
+    type Target = T;
+
+    /// Get an immutable reference to the internal data.
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        return &unsafe { self.data.as_ref() }.data;
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Clone for Trc<T, A> {
+    /// Clone a `Trc` (increment it's local reference count).
+    /// The process is aborted if the local reference count overflows, see [overflow](crate#reference-count-overflow).
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        let threadref = unsafe { &mut *self.threadref.as_ptr() };
+        *threadref += 1;
+        if *threadref > MAX_REFCOUNT {
+            overflow::local(threadref, "Overflow of maximum local reference count.");
+        }
+        #[cfg(feature = "hooks")]
+        hooks::on_clone(self.shared.as_ptr(), HandleKind::Trc);
+        #[cfg(feature = "leak-debug")]
+        leak_debug::record(self.shared.as_ptr(), Some(self.threadref));
+
+        Self {
+            shared: self.shared,
+            threadref: self.threadref,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Clone for SharedTrc<T, A> {
+    /// Clone a `SharedTrc` (increment the atomic count).
+    #[inline]
+    fn clone(&self) -> Self {
+        let prev = sum_value(&unsafe { self.data.as_ref() }.atomicref, 1, Relaxed);
+        if prev > MAX_REFCOUNT {
+            overflow::atomic(
+                &unsafe { self.data.as_ref() }.atomicref,
+                "Overflow of maximum atomic reference count.",
+            );
+        }
+        #[cfg(feature = "hooks")]
+        hooks::on_clone(self.data.as_ptr(), HandleKind::SharedTrc);
+        #[cfg(feature = "leak-debug")]
+        leak_debug::record(self.data.as_ptr(), None);
+        Self {
+            data: self.data,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Clone for Weak<T, A> {
+    /// Clone a `Weak` (increment the weak count).
+    #[inline]
+    fn clone(&self) -> Self {
+        if is_dangling(self.data.as_ptr()) {
+            return Self { data: self.data };
+        }
+        let prev = unsafe { self.data.as_ref() }
+            .weakcount
+            .fetch_add(1, Relaxed);
+
+        //If an absurd number of threads are created, and then they are aborted before this, UB can
+        //occur if the refcount wraps around.
+        if weak_count_value(prev) > MAX_WEAKCOUNT {
+            overflow::atomic(
+                &unsafe { self.data.as_ref() }.weakcount,
+                "Overflow of maximum weak reference count.",
+            );
+        }
+        #[cfg(feature = "hooks")]
+        hooks::on_clone(self.data.as_ptr(), HandleKind::Weak);
+
+        Self { data: self.data }
+    }
+}
+
+impl_drop! {
+    impl<T, A: Allocator> Drop for Trc<T, A> {
+        #[inline]
+        fn drop(&mut self) {
+            #[cfg(feature = "hooks")]
+            hooks::on_drop(self.shared.as_ptr(), HandleKind::Trc);
+            #[cfg(feature = "leak-debug")]
+            leak_debug::release(self.shared.as_ptr(), Some(self.threadref));
+            *unsafe { self.threadref.as_mut() } -= 1;
+            if *unsafe { self.threadref.as_ref() } == 0 {
+                unsafe { free_threadref(self.shared.as_ptr(), self.threadref) };
+                let prev = sub_value(&unsafe { self.shared.as_ref() }.atomicref, 1, AcqRel);
+                #[cfg(feature = "std")]
+                wait::on_release(prev, self.shared.as_ptr());
+                if prev != 1 {
+                    return;
+                }
+
+                unsafe { ptr::drop_in_place(addr_of_mut!((*self.shared.as_ptr()).data)) };
+                Weak { data: self.shared };
+            }
+        }
+    }
+}
+
+impl_drop! {
+    impl<T, A: Allocator> Drop for SharedTrc<T, A> {
+        #[inline]
+        fn drop(&mut self) {
+            #[cfg(feature = "hooks")]
+            hooks::on_drop(self.data.as_ptr(), HandleKind::SharedTrc);
+            #[cfg(feature = "leak-debug")]
+            leak_debug::release(self.data.as_ptr(), None);
+            let prev = sub_value(unsafe { &(*self.data.as_ptr()).atomicref }, 1, AcqRel);
+            #[cfg(feature = "std")]
+            wait::on_release(prev, self.data.as_ptr());
+            if prev != 1 {
+                return;
+            }
+
+            unsafe { ptr::drop_in_place(addr_of_mut!((*self.data.as_ptr()).data)) };
+            Weak { data: self.data };
+        }
+    }
+}
+
+impl_drop! {
+    impl<T, A: Allocator> Drop for Weak<T, A> {
+        #[inline]
+        fn drop(&mut self) {
+            if is_dangling(self.data.as_ptr()) {
+                return;
+            }
+            #[cfg(feature = "hooks")]
+            hooks::on_drop(self.data.as_ptr(), HandleKind::Weak);
+            let prev = unsafe { self.data.as_ref() }
+                .weakcount
+                .fetch_sub(1, AcqRel);
+            let weakcount = weak_count_value(prev);
+            #[cfg(feature = "std")]
+            wait::on_release(weakcount, self.data.as_ptr());
+            if weakcount != 1 {
+                return;
+            }
+            //The last `Weak` to a value in an arena releases it from its chunk.
+            #[cfg(feature = "arena")]
+            if prev & arena::ARENA_FLAG != 0 {
+                unsafe { arena::release(self.data) };
+                return;
+            }
+
+            #[cfg(feature = "leak-debug")]
+            leak_debug::release_all(self.data.as_ptr());
+            #[cfg(feature = "stats")]
+            stats::on_dealloc(core::mem::size_of_val(unsafe {
+                &(*self.data.as_ptr()).data
+            }));
+            let layout = Layout::for_value(unsafe { &*self.data.as_ptr() });
+            //Move the allocator out of the allocation before freeing it.
+            let alloc = unsafe { ptr::read(addr_of!((*self.data.as_ptr()).alloc)) };
+            unsafe { alloc.deallocate(self.data.cast(), layout) };
+        }
+    }
+}
+
+if_thread_safe! {
+    unsafe impl<T: ?Sized + Sync + Send, A: Allocator + Send + Sync> Send for SharedTrc<T, A> {}
+    unsafe impl<T: ?Sized + Sync + Send, A: Allocator + Send + Sync> Sync for SharedTrc<T, A> {}
+
+    unsafe impl<T: ?Sized + Sync + Send, A: Allocator + Send + Sync> Send for Weak<T, A> {}
+    unsafe impl<T: ?Sized + Sync + Send, A: Allocator + Send + Sync> Sync for Weak<T, A> {}
+}
@@ -0,0 +1,175 @@
+//! Zero-copy archiving of `Trc` and `SharedTrc` with [`rkyv`].
+//!
+//! A `Trc<T>` or `SharedTrc<T>` is archived as an [`ArchivedTrc`], which points to the archived value. Handles to the
+//! same allocation are archived once and share the archived value, and deserializing an `ArchivedTrc` creates a fresh
+//! `Trc` or `SharedTrc` which is shared by every handle to the same archived value.
+//!
+//! This module is only available with the `rkyv` feature.
+//!
+//! # Examples
+//! ```
+//! use rkyv::rancor::Error;
+//! use trc::Trc;
+//!
+//! let trc = Trc::new(100u32);
+//! let pair = (trc.clone(), trc);
+//! let bytes = rkyv::to_bytes::<Error>(&pair).unwrap();
+//!
+//! let archived = unsafe { rkyv::access_unchecked::<rkyv::Archived<(Trc<u32>, Trc<u32>)>>(&bytes) };
+//! assert_eq!(*archived.0.get(), 100);
+//!
+//! let pair = rkyv::deserialize::<(Trc<u32>, Trc<u32>), Error>(archived).unwrap();
+//! assert!(Trc::ptr_eq(&pair.0, &pair.1));
+//! ```
+
+use core::alloc::LayoutError;
+
+use alloc::{
+    alloc::{alloc, handle_alloc_error},
+    boxed::Box,
+};
+use rkyv::{
+    de::{FromMetadata, Metadata, Pooling, PoolingExt, SharedPointer},
+    ptr_meta::{from_raw_parts_mut, Pointee},
+    rancor::{Fallible, Source},
+    rc::{ArchivedRc, Flavor, RcResolver},
+    ser::{Sharing, Writer},
+    traits::{ArchivePointee, LayoutRaw},
+    Archive, ArchiveUnsized, Deserialize, DeserializeUnsized, Place, Serialize, SerializeUnsized,
+};
+
+use crate::{internal_from_data_ptr, SharedTrc, Trc};
+
+/// The flavor of [`ArchivedTrc`], which prevents archived `Trc`s from being mixed up with other archived shared pointers
+/// during validation.
+pub struct TrcFlavor;
+
+impl Flavor for TrcFlavor {
+    const ALLOW_CYCLES: bool = false;
+}
+
+/// An archived `Trc` or `SharedTrc`. Use [`ArchivedRc::get`] to access the archived value.
+pub type ArchivedTrc<T> = ArchivedRc<T, TrcFlavor>;
+
+impl<T: ArchiveUnsized + ?Sized> Archive for Trc<T> {
+    type Archived = ArchivedTrc<T::Archived>;
+    type Resolver = RcResolver;
+
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        ArchivedTrc::resolve_from_ref(&**self, resolver, out);
+    }
+}
+
+impl<T, S> Serialize<S> for Trc<T>
+where
+    T: SerializeUnsized<S> + ?Sized + 'static,
+    S: Fallible + Writer + Sharing + ?Sized,
+    S::Error: Source,
+{
+    fn serialize(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+        ArchivedTrc::<T::Archived>::serialize_from_ref(&**self, serializer)
+    }
+}
+
+impl<T: ArchiveUnsized + ?Sized> Archive for SharedTrc<T> {
+    type Archived = ArchivedTrc<T::Archived>;
+    type Resolver = RcResolver;
+
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        ArchivedTrc::resolve_from_ref(&**self, resolver, out);
+    }
+}
+
+impl<T, S> Serialize<S> for SharedTrc<T>
+where
+    T: SerializeUnsized<S> + ?Sized + 'static,
+    S: Fallible + Writer + Sharing + ?Sized,
+    S::Error: Source,
+{
+    fn serialize(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+        ArchivedTrc::<T::Archived>::serialize_from_ref(&**self, serializer)
+    }
+}
+
+//The deserializer pools `SharedTrc`s, which are converted to `Trc`s when needed.
+unsafe impl<T: LayoutRaw + Pointee + ?Sized> SharedPointer<T> for SharedTrc<T> {
+    fn alloc(metadata: T::Metadata) -> Result<*mut T, LayoutError> {
+        let layout = T::layout_raw(metadata)?;
+        let data_address = if layout.size() > 0 {
+            let ptr = unsafe { alloc(layout) };
+            if ptr.is_null() {
+                handle_alloc_error(layout);
+            }
+            ptr
+        } else {
+            core::ptr::without_provenance_mut::<u8>(layout.align())
+        };
+        Ok(from_raw_parts_mut(data_address.cast(), metadata))
+    }
+
+    unsafe fn from_value(ptr: *mut T) -> *mut T {
+        let shared = SharedTrc::from(unsafe { Box::from_raw(ptr) });
+        SharedTrc::into_raw(shared).cast_mut()
+    }
+
+    unsafe fn drop(ptr: *mut T) {
+        drop(unsafe { shared_from_raw(ptr) });
+    }
+}
+
+/// Recreate a `SharedTrc` from a pointer returned by [`SharedTrc::into_raw`], which may be unsized.
+unsafe fn shared_from_raw<T: ?Sized>(ptr: *const T) -> SharedTrc<T> {
+    SharedTrc {
+        data: unsafe { core::ptr::NonNull::new_unchecked(internal_from_data_ptr(ptr)) },
+        phantom: core::marker::PhantomData,
+    }
+}
+
+impl<T, D> Deserialize<SharedTrc<T>, D> for ArchivedTrc<T::Archived>
+where
+    T: ArchiveUnsized + LayoutRaw + Pointee + ?Sized + 'static,
+    T::Archived: DeserializeUnsized<T, D>,
+    T::Metadata: Into<Metadata> + FromMetadata,
+    D: Fallible + Pooling + ?Sized,
+    D::Error: Source,
+{
+    fn deserialize(&self, deserializer: &mut D) -> Result<SharedTrc<T>, D::Error> {
+        let ptr = deserializer.deserialize_shared::<_, SharedTrc<T>>(self.get())?;
+        //The pool keeps its own handle, so a new one is created for the caller.
+        let pooled = core::mem::ManuallyDrop::new(unsafe { shared_from_raw(ptr) });
+        Ok(SharedTrc::clone(&pooled))
+    }
+}
+
+impl<T, D> Deserialize<Trc<T>, D> for ArchivedTrc<T::Archived>
+where
+    T: ArchiveUnsized + LayoutRaw + Pointee + ?Sized + 'static,
+    T::Archived: DeserializeUnsized<T, D>,
+    T::Metadata: Into<Metadata> + FromMetadata,
+    D: Fallible + Pooling + ?Sized,
+    D::Error: Source,
+{
+    fn deserialize(&self, deserializer: &mut D) -> Result<Trc<T>, D::Error> {
+        Deserialize::<SharedTrc<T>, D>::deserialize(self, deserializer).map(SharedTrc::to_trc)
+    }
+}
+
+impl<T, U> PartialEq<Trc<U>> for ArchivedTrc<T>
+where
+    T: ArchivePointee + PartialEq<U> + ?Sized,
+    U: ?Sized,
+{
+    fn eq(&self, other: &Trc<U>) -> bool {
+        self.get().eq(&**other)
+    }
+}
+
+impl<T, U> PartialEq<SharedTrc<U>> for ArchivedTrc<T>
+where
+    T: ArchivePointee + PartialEq<U> + ?Sized,
+    U: ?Sized,
+{
+    fn eq(&self, other: &SharedTrc<U>) -> bool {
+        self.get().eq(&**other)
+    }
+}
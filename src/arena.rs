@@ -0,0 +1,285 @@
+//! An arena which allocates `Trc`s out of large chunks of memory.
+//!
+//! Allocating many small values with [`Trc::new`] makes one heap allocation for each of them, which dominates the time
+//! spent building large graphs of tiny nodes. A [`TrcArena`] instead places the reference counts and values of many
+//! `Trc`s next to each other in chunks of 64 KiB. The values are dropped as usual when their last `Trc` or `SharedTrc`
+//! is dropped, but their memory is only reclaimed with the rest of the chunk, once the arena has moved on to another
+//! chunk or was dropped, and no `Trc`, `SharedTrc` or `Weak` to any value in the chunk is left.
+//!
+//! # Examples
+//! ```
+//! use trc::{arena::TrcArena, Trc};
+//!
+//! struct Node {
+//!     value: u32,
+//!     children: Vec<Trc<Node>>,
+//! }
+//!
+//! let arena = TrcArena::new();
+//! let leaves: Vec<Trc<Node>> = (0..1000)
+//!     .map(|value| arena.alloc(Node { value, children: Vec::new() }))
+//!     .collect();
+//! let root = arena.alloc(Node { value: 0, children: leaves });
+//! drop(arena);
+//!
+//! //The values outlive the arena, and the chunks are freed with the last of them.
+//! assert_eq!(root.children.iter().map(|node| node.value).sum::<u32>(), 499500);
+//! ```
+//!
+//! [`Trc::new`]: crate::Trc::new
+
+use alloc::alloc::{handle_alloc_error, Layout};
+use core::{
+    cell::Cell,
+    fmt::{self, Debug},
+    marker::PhantomData,
+    mem::{align_of, size_of},
+    ptr::{addr_of_mut, write, NonNull},
+};
+
+use crate::{
+    new_threadref,
+    ordering::{Acquire, Release},
+    sync::{alloc, dealloc, fence, AtomicUsize},
+    SharedTrc, SharedTrcInternal, Trc, MAX_REFCOUNT,
+};
+
+/// The size and alignment of a chunk. Chunks are aligned to their size, so the chunk of a value is found by rounding its
+/// address down.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// The bit of the weak count which marks a value as part of a chunk, so that the last `Weak` releases it from its chunk
+/// instead of deallocating it. It is above `MAX_REFCOUNT`, and weak counts are limited to half of that with the `arena`
+/// feature, so the weak count of another allocation never reaches it.
+pub(crate) const ARENA_FLAG: usize = !MAX_REFCOUNT;
+
+/// The start of a chunk, followed by the values.
+struct ChunkHeader {
+    //The number of values in the chunk which are still referenced, plus one while the arena allocates from the chunk.
+    live: AtomicUsize,
+}
+
+/// An arena which allocates `Trc`s and `SharedTrc`s out of large chunks, instead of making an allocation for each value.
+/// This is only available with the `arena` feature. See the [module documentation](self).
+///
+/// The handles behave like other `Trc`s and `SharedTrc`s, and may be sent to and dropped on other threads. Values which
+/// do not fit into a chunk are allocated individually.
+///
+/// A `Trc` still allocates its thread-local count, so [`TrcArena::alloc_shared`] avoids the heap entirely for most values.
+pub struct TrcArena<T> {
+    chunk: Cell<Option<NonNull<ChunkHeader>>>,
+    //The index of the next unused value in the current chunk.
+    next: Cell<usize>,
+    _marker: PhantomData<T>,
+}
+
+//SAFETY: The arena does not access the values after they are handed out, and chunks are released with atomics.
+if_thread_safe! {
+    unsafe impl<T> Send for TrcArena<T> {}
+}
+
+impl<T> TrcArena<T> {
+    /// The offset of the first value in a chunk.
+    const OFFSET: usize =
+        size_of::<ChunkHeader>().next_multiple_of(align_of::<SharedTrcInternal<T>>());
+
+    /// The number of values in a chunk.
+    const CHUNK_CAPACITY: usize =
+        CHUNK_SIZE.saturating_sub(Self::OFFSET) / size_of::<SharedTrcInternal<T>>();
+
+    /// Create a new arena. No memory is allocated until the first value.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::arena::TrcArena;
+    ///
+    /// let arena = TrcArena::<i32>::new();
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self {
+        return Self {
+            chunk: Cell::new(None),
+            next: Cell::new(0),
+            _marker: PhantomData,
+        };
+    }
+
+    /// Return the number of values which are placed in one chunk, or 0 if the values are too large and are allocated
+    /// individually.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::arena::TrcArena;
+    ///
+    /// assert!(TrcArena::<u64>::new().chunk_capacity() > 100);
+    /// assert_eq!(TrcArena::<[u8; 1 << 20]>::new().chunk_capacity(), 0);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn chunk_capacity(&self) -> usize {
+        return Self::CHUNK_CAPACITY;
+    }
+
+    /// Return a new `Trc` holding `value`, placed in the current chunk of the arena.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{arena::TrcArena, Trc};
+    ///
+    /// let arena = TrcArena::new();
+    /// let first = arena.alloc(1);
+    /// let second = arena.alloc(2);
+    ///
+    /// //The values are next to each other, after their reference counts (which are padded with the `padded` feature).
+    /// let distance = Trc::addr(&second) - Trc::addr(&first);
+    /// assert!(distance <= 256);
+    /// ```
+    #[inline]
+    pub fn alloc(&self, value: T) -> Trc<T> {
+        if Self::CHUNK_CAPACITY == 0 {
+            return Trc::new(value);
+        }
+        return Trc {
+            shared: self.alloc_internal(value),
+            threadref: new_threadref(),
+            phantom: PhantomData,
+        };
+    }
+
+    /// Return a new `SharedTrc` holding `value`, placed in the current chunk of the arena.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::thread;
+    /// use trc::arena::TrcArena;
+    ///
+    /// let arena = TrcArena::new();
+    /// let values: Vec<_> = (0..100).map(|value| arena.alloc_shared(value)).collect();
+    /// let handle = thread::spawn(move || values.iter().map(|value| **value).sum::<i32>());
+    /// assert_eq!(handle.join().unwrap(), 4950);
+    /// ```
+    #[inline]
+    pub fn alloc_shared(&self, value: T) -> SharedTrc<T> {
+        if Self::CHUNK_CAPACITY == 0 {
+            return SharedTrc::new(value);
+        }
+        return SharedTrc {
+            data: self.alloc_internal(value),
+            phantom: PhantomData,
+        };
+    }
+
+    /// Place `value` in the next unused slot of the current chunk, starting a new chunk if it is full. The returned
+    /// allocation holds one atomic reference and the implicit weak reference.
+    fn alloc_internal(&self, value: T) -> NonNull<SharedTrcInternal<T>> {
+        let chunk = match self.chunk.get() {
+            Some(chunk) if self.next.get() < Self::CHUNK_CAPACITY => chunk,
+            _ => self.new_chunk(),
+        };
+
+        let index = self.next.get();
+        self.next.set(index + 1);
+        unsafe {
+            let slot = chunk
+                .as_ptr()
+                .cast::<u8>()
+                .add(Self::OFFSET)
+                .cast::<SharedTrcInternal<T>>()
+                .add(index);
+            write(addr_of_mut!((*slot).atomicref), AtomicUsize::new(1));
+            write(
+                addr_of_mut!((*slot).weakcount),
+                AtomicUsize::new(ARENA_FLAG | 1),
+            );
+            write(addr_of_mut!((*slot).data), value);
+            return NonNull::new_unchecked(slot);
+        }
+    }
+
+    /// Release the current chunk, and allocate a new one to place values in.
+    #[cold]
+    fn new_chunk(&self) -> NonNull<ChunkHeader> {
+        self.release_chunk();
+
+        let layout = chunk_layout();
+        let chunk = unsafe { alloc(layout) }.cast::<ChunkHeader>();
+        let Some(chunk) = NonNull::new(chunk) else {
+            handle_alloc_error(layout);
+        };
+        //Every value in the chunk is counted as live up front, and the unused ones are subtracted once the arena is done
+        //with the chunk, so that handing out a value needs no atomic operation.
+        unsafe {
+            write(
+                chunk.as_ptr(),
+                ChunkHeader {
+                    live: AtomicUsize::new(Self::CHUNK_CAPACITY + 1),
+                },
+            );
+        }
+        self.chunk.set(Some(chunk));
+        self.next.set(0);
+        return chunk;
+    }
+
+    /// Release the arena's reference to the current chunk and its unused values, freeing it if no values are referenced.
+    fn release_chunk(&self) {
+        if let Some(chunk) = self.chunk.take() {
+            let unused = Self::CHUNK_CAPACITY - self.next.get();
+            unsafe { release_chunk(chunk, unused + 1) };
+        }
+    }
+}
+
+impl<T> Drop for TrcArena<T> {
+    fn drop(&mut self) {
+        self.release_chunk();
+    }
+}
+
+impl<T> Default for TrcArena<T> {
+    /// Create a new arena. See [`TrcArena::new`].
+    #[inline]
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+impl<T> Debug for TrcArena<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TrcArena")
+            .field("chunk_capacity", &Self::CHUNK_CAPACITY)
+            .finish_non_exhaustive()
+    }
+}
+
+#[inline]
+fn chunk_layout() -> Layout {
+    return unsafe { Layout::from_size_align_unchecked(CHUNK_SIZE, CHUNK_SIZE) };
+}
+
+/// Subtract `count` from the live values of `chunk`, and free it if none are left.
+unsafe fn release_chunk(chunk: NonNull<ChunkHeader>, count: usize) {
+    if unsafe { chunk.as_ref() }.live.fetch_sub(count, Release) != count {
+        return;
+    }
+
+    fence(Acquire);
+    unsafe { dealloc(chunk.as_ptr().cast(), chunk_layout()) };
+}
+
+/// Release the value at `data` from its chunk, after its last `Weak` was dropped. The value was already dropped.
+///
+/// # Safety
+/// `data` must have been allocated by a [`TrcArena`], and its last `Weak` must have been dropped.
+pub(crate) unsafe fn release<T: ?Sized>(data: NonNull<T>) {
+    #[cfg(feature = "leak-debug")]
+    crate::leak_debug::release_all(data.as_ptr());
+
+    //The chunk is found by rounding the address down, keeping the provenance of the chunk's allocation.
+    let chunk = data
+        .as_ptr()
+        .cast::<ChunkHeader>()
+        .map_addr(|addr| addr - addr % CHUNK_SIZE);
+    unsafe { release_chunk(NonNull::new_unchecked(chunk), 1) };
+}
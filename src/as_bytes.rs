@@ -0,0 +1,105 @@
+//! Serialization of byte slices as byte buffers.
+//!
+//! `Trc<[u8]>` and `SharedTrc<[u8]>` are serialized as sequences of integers by default, like any other slice. The
+//! functions in this module can be used with `#[serde(with = "trc::as_bytes")]` to serialize them with
+//! `serialize_bytes` instead, which is much more compact in binary formats. Deserializing writes borrowed or owned byte
+//! buffers directly into the new allocation, and also accepts strings and sequences of integers.
+//!
+//! This module is only available with the `serde` feature.
+//!
+//! # Examples
+//! ```
+//! use serde::{Deserialize, Serialize};
+//! use trc::{as_bytes, Trc};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Packet {
+//!     #[serde(with = "as_bytes")]
+//!     payload: Trc<[u8]>,
+//! }
+//!
+//! let packet = Packet { payload: Trc::from(&b"Trc"[..]) };
+//! let json = serde_json::to_string(&packet).unwrap();
+//! assert_eq!(json, r#"{"payload":[84,114,99]}"#);
+//!
+//! let packet: Packet = serde_json::from_str(&json).unwrap();
+//! assert_eq!(*packet.payload, *b"Trc");
+//! ```
+
+use alloc::{borrow::Cow, string::String, vec::Vec};
+use core::{fmt, marker::PhantomData, ops::Deref};
+
+use serde::de::{Deserializer, Error, SeqAccess, Visitor};
+use serde::ser::Serializer;
+
+struct BytesVisitor<P>(PhantomData<P>);
+
+impl<'de, P: for<'a> From<Cow<'a, [u8]>>> Visitor<'de> for BytesVisitor<P> {
+    type Value = P;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a byte buffer")
+    }
+
+    fn visit_bytes<E: Error>(self, v: &[u8]) -> Result<P, E> {
+        Ok(P::from(Cow::Borrowed(v)))
+    }
+
+    fn visit_byte_buf<E: Error>(self, v: Vec<u8>) -> Result<P, E> {
+        Ok(P::from(Cow::Owned(v)))
+    }
+
+    fn visit_str<E: Error>(self, v: &str) -> Result<P, E> {
+        self.visit_bytes(v.as_bytes())
+    }
+
+    fn visit_string<E: Error>(self, v: String) -> Result<P, E> {
+        self.visit_byte_buf(v.into_bytes())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<P, A::Error> {
+        //Sequences do not know their exact length, so the bytes are collected first.
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+        self.visit_byte_buf(bytes)
+    }
+}
+
+/// Serialize the bytes pointed to by a `Trc<[u8]>` or `SharedTrc<[u8]>` as a byte buffer.
+///
+/// # Examples
+/// ```
+/// use trc::{as_bytes, SharedTrc};
+///
+/// let shared = SharedTrc::<[u8]>::from(&[1, 2][..]);
+/// let mut json = Vec::new();
+/// as_bytes::serialize(&shared, &mut serde_json::Serializer::new(&mut json)).unwrap();
+/// assert_eq!(json, b"[1,2]");
+/// ```
+pub fn serialize<P, S>(bytes: &P, serializer: S) -> Result<S::Ok, S::Error>
+where
+    P: Deref<Target = [u8]>,
+    S: Serializer,
+{
+    serializer.serialize_bytes(bytes)
+}
+
+/// Deserialize a `Trc<[u8]>` or `SharedTrc<[u8]>` from a byte buffer, writing the bytes directly into the new allocation.
+///
+/// # Examples
+/// ```
+/// use trc::{as_bytes, Trc};
+///
+/// let mut deserializer = serde_json::Deserializer::from_str(r#""Trc""#);
+/// let trc: Trc<[u8]> = as_bytes::deserialize(&mut deserializer).unwrap();
+/// assert_eq!(*trc, *b"Trc");
+/// ```
+pub fn deserialize<'de, P, D>(deserializer: D) -> Result<P, D::Error>
+where
+    P: for<'a> From<Cow<'a, [u8]>>,
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_bytes(BytesVisitor(PhantomData))
+}
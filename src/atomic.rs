@@ -0,0 +1,287 @@
+//! A `SharedTrc` slot which can be loaded and replaced atomically.
+//!
+//! [`AtomicSharedTrc`] is intended for read-mostly shared state, such as configuration which is read on every request and
+//! replaced occasionally. Loading only touches a few atomics and never waits for other loads, so readers do not contend
+//! on a lock.
+
+use core::{
+    fmt::{self, Debug},
+    marker::PhantomData,
+    mem::ManuallyDrop,
+    ptr::NonNull,
+};
+
+use crate::{
+    ordering::{Acquire, Relaxed, Release, SeqCst},
+    sync::{spin_loop, AtomicBool, AtomicPtr, AtomicUsize},
+    SharedTrc, SharedTrcInternal,
+};
+
+/// A slot holding a [`SharedTrc<T>`] which can be loaded, stored, swapped and compared-and-exchanged from many threads
+/// at once, like an atomic pointer which manages the reference counts of the values it holds.
+///
+/// Loads never block. Stores are serialized with each other, and each one waits for the loads which were in progress
+/// when it replaced the value, which only takes as long as incrementing a reference count.
+///
+/// # Examples
+#[cfg_attr(feature = "single-threaded", doc = "```ignore")]
+#[cfg_attr(not(feature = "single-threaded"), doc = "```")]
+/// use trc::{AtomicSharedTrc, SharedTrc};
+///
+/// let config = AtomicSharedTrc::new(SharedTrc::new(String::from("v1")));
+///
+/// std::thread::scope(|scope| {
+///     scope.spawn(|| {
+///         let current = config.load();
+///         assert!(*current == "v1" || *current == "v2");
+///     });
+///     config.store(SharedTrc::new(String::from("v2")));
+/// });
+///
+/// assert_eq!(*config.load(), "v2");
+/// ```
+pub struct AtomicSharedTrc<T> {
+    ptr: AtomicPtr<SharedTrcInternal<T>>,
+    //Loads register themselves in the counter selected by the epoch, and a store waits for the counter of the epoch it ends.
+    readers: [AtomicUsize; 2],
+    epoch: AtomicUsize,
+    writer: AtomicBool,
+    phantom: PhantomData<SharedTrc<T>>,
+}
+
+/// Releases the writer lock of an `AtomicSharedTrc` when dropped.
+struct WriterGuard<'a>(&'a AtomicBool);
+
+impl Drop for WriterGuard<'_> {
+    #[inline]
+    fn drop(&mut self) {
+        self.0.store(false, Release);
+    }
+}
+
+impl<T> AtomicSharedTrc<T> {
+    /// Create a new `AtomicSharedTrc` holding `value`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{AtomicSharedTrc, SharedTrc};
+    ///
+    /// let slot = AtomicSharedTrc::new(SharedTrc::new(100));
+    /// assert_eq!(*slot.load(), 100);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn new(value: SharedTrc<T>) -> Self {
+        let value = ManuallyDrop::new(value);
+        return Self {
+            ptr: AtomicPtr::new(value.data.as_ptr()),
+            readers: [AtomicUsize::new(0), AtomicUsize::new(0)],
+            epoch: AtomicUsize::new(0),
+            writer: AtomicBool::new(false),
+            phantom: PhantomData,
+        };
+    }
+
+    /// Return a new handle to the current value. This increments its atomic count.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{AtomicSharedTrc, SharedTrc};
+    ///
+    /// let shared = SharedTrc::new(100);
+    /// let slot = AtomicSharedTrc::new(shared.clone());
+    /// let loaded = slot.load();
+    /// assert!(SharedTrc::ptr_eq(&loaded, &shared));
+    /// assert_eq!(SharedTrc::atomic_count(&shared), 3);
+    /// ```
+    #[must_use]
+    pub fn load(&self) -> SharedTrc<T> {
+        let readers = loop {
+            let epoch = self.epoch.load(SeqCst);
+            let readers = &self.readers[epoch & 1];
+            readers.fetch_add(1, SeqCst);
+            //If a store ended the epoch in the meantime, it may not have seen this load.
+            if self.epoch.load(SeqCst) == epoch {
+                break readers;
+            }
+            readers.fetch_sub(1, SeqCst);
+        };
+
+        let current = ManuallyDrop::new(SharedTrc {
+            data: unsafe { NonNull::new_unchecked(self.ptr.load(SeqCst)) },
+            phantom: PhantomData,
+        });
+        let res = SharedTrc::clone(&current);
+
+        readers.fetch_sub(1, SeqCst);
+        return res;
+    }
+
+    /// Replace the current value with `value`, dropping the previous handle.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{AtomicSharedTrc, SharedTrc};
+    ///
+    /// let slot = AtomicSharedTrc::new(SharedTrc::new(1));
+    /// slot.store(SharedTrc::new(2));
+    /// assert_eq!(*slot.load(), 2);
+    /// ```
+    #[inline]
+    pub fn store(&self, value: SharedTrc<T>) {
+        drop(self.swap(value));
+    }
+
+    /// Replace the current value with `value`, returning the previous handle.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{AtomicSharedTrc, SharedTrc};
+    ///
+    /// let slot = AtomicSharedTrc::new(SharedTrc::new(1));
+    /// let previous = slot.swap(SharedTrc::new(2));
+    /// assert_eq!(*previous, 1);
+    /// assert_eq!(*slot.load(), 2);
+    /// ```
+    #[must_use = "use `store` to drop the previous value"]
+    pub fn swap(&self, value: SharedTrc<T>) -> SharedTrc<T> {
+        let _guard = self.lock();
+        let value = ManuallyDrop::new(value);
+        let previous = self.ptr.swap(value.data.as_ptr(), SeqCst);
+        self.wait_for_readers();
+        return SharedTrc {
+            data: unsafe { NonNull::new_unchecked(previous) },
+            phantom: PhantomData,
+        };
+    }
+
+    /// Replace the current value with `new` if it points to the same allocation as `current`. On success, the previous
+    /// handle is returned. Otherwise, `new` is returned in the [`Err`] and the value is left unchanged.
+    ///
+    /// # Errors
+    /// Returns `new` if the current value does not point to the same allocation as `current`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{AtomicSharedTrc, SharedTrc};
+    ///
+    /// let slot = AtomicSharedTrc::new(SharedTrc::new(1));
+    ///
+    /// //Increment the value, retrying if another thread replaced it in the meantime.
+    /// loop {
+    ///     let current = slot.load();
+    ///     if slot.compare_exchange(&current, SharedTrc::new(*current + 1)).is_ok() {
+    ///         break;
+    ///     }
+    /// }
+    /// assert_eq!(*slot.load(), 2);
+    ///
+    /// let stale = SharedTrc::new(1);
+    /// assert_eq!(*slot.compare_exchange(&stale, SharedTrc::new(3)).unwrap_err(), 3);
+    /// ```
+    pub fn compare_exchange(
+        &self,
+        current: &SharedTrc<T>,
+        new: SharedTrc<T>,
+    ) -> Result<SharedTrc<T>, SharedTrc<T>> {
+        let _guard = self.lock();
+        if self.ptr.load(SeqCst) != current.data.as_ptr() {
+            return Err(new);
+        }
+        let new = ManuallyDrop::new(new);
+        let previous = self.ptr.swap(new.data.as_ptr(), SeqCst);
+        self.wait_for_readers();
+        return Ok(SharedTrc {
+            data: unsafe { NonNull::new_unchecked(previous) },
+            phantom: PhantomData,
+        });
+    }
+
+    /// Consume the `AtomicSharedTrc`, returning the handle it holds.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{AtomicSharedTrc, SharedTrc};
+    ///
+    /// let slot = AtomicSharedTrc::new(SharedTrc::new(100));
+    /// assert_eq!(*slot.into_inner(), 100);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn into_inner(self) -> SharedTrc<T> {
+        let this = ManuallyDrop::new(self);
+        return SharedTrc {
+            data: unsafe { NonNull::new_unchecked(this.ptr.load(Relaxed)) },
+            phantom: PhantomData,
+        };
+    }
+
+    fn lock(&self) -> WriterGuard<'_> {
+        while self
+            .writer
+            .compare_exchange_weak(false, true, Acquire, Relaxed)
+            .is_err()
+        {
+            spin_loop();
+        }
+        return WriterGuard(&self.writer);
+    }
+
+    /// End the current epoch, and wait for the loads registered in it, which may still be cloning the previous value.
+    fn wait_for_readers(&self) {
+        let epoch = self.epoch.fetch_add(1, SeqCst);
+        while self.readers[epoch & 1].load(SeqCst) != 0 {
+            spin_loop();
+        }
+    }
+}
+
+impl<T> Drop for AtomicSharedTrc<T> {
+    #[inline]
+    fn drop(&mut self) {
+        drop(SharedTrc {
+            data: unsafe { NonNull::new_unchecked(self.ptr.load(Relaxed)) },
+            phantom: PhantomData,
+        });
+    }
+}
+
+impl<T> From<SharedTrc<T>> for AtomicSharedTrc<T> {
+    /// Create a new `AtomicSharedTrc` holding `value`. See [`AtomicSharedTrc::new`].
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{AtomicSharedTrc, SharedTrc};
+    ///
+    /// let slot = AtomicSharedTrc::from(SharedTrc::new(100));
+    /// assert_eq!(*slot.load(), 100);
+    /// ```
+    #[inline]
+    fn from(value: SharedTrc<T>) -> Self {
+        return Self::new(value);
+    }
+}
+
+impl<T: Default> Default for AtomicSharedTrc<T> {
+    /// Create a new `AtomicSharedTrc` holding the default value of `T`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::AtomicSharedTrc;
+    ///
+    /// let slot = AtomicSharedTrc::<i32>::default();
+    /// assert_eq!(*slot.load(), 0);
+    /// ```
+    #[inline]
+    fn default() -> Self {
+        return Self::new(SharedTrc::default());
+    }
+}
+
+impl<T: Debug> Debug for AtomicSharedTrc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("AtomicSharedTrc")
+            .field(&self.load())
+            .finish()
+    }
+}
@@ -0,0 +1,201 @@
+//! Integration with the [`bytes`] crate.
+//!
+//! [`TrcBuf`] is a cursor over a `Trc<[u8]>` which implements [`Buf`], and `Trc<[u8]>` and `SharedTrc<[u8]>` can be
+//! converted into [`Bytes`] without copying the data.
+//!
+//! This module is only available with the `bytes` feature.
+
+use bytes::Buf;
+
+use crate::Trc;
+
+/// A cursor over the bytes of a `Trc<[u8]>`, which implements [`Buf`].
+///
+/// # Examples
+/// ```
+/// use bytes::Buf;
+/// use trc::{buf::TrcBuf, Trc};
+///
+/// let mut buf = TrcBuf::new(Trc::from(&[0, 1, 0, 2, 7][..]));
+/// assert_eq!(buf.get_u16(), 1);
+/// assert_eq!(buf.get_u16(), 2);
+/// assert_eq!(buf.remaining(), 1);
+/// assert_eq!(buf.chunk(), [7]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct TrcBuf {
+    data: Trc<[u8]>,
+    pos: usize,
+}
+
+impl TrcBuf {
+    /// Create a cursor at the start of `data`.
+    ///
+    /// # Examples
+    /// ```
+    /// use bytes::Buf;
+    /// use trc::{buf::TrcBuf, Trc};
+    ///
+    /// let buf = TrcBuf::new(Trc::from(&b"Trc"[..]));
+    /// assert_eq!(buf.remaining(), 3);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn new(data: Trc<[u8]>) -> Self {
+        return Self { data, pos: 0 };
+    }
+
+    /// Return the number of bytes which have been consumed.
+    ///
+    /// # Examples
+    /// ```
+    /// use bytes::Buf;
+    /// use trc::{buf::TrcBuf, Trc};
+    ///
+    /// let mut buf = TrcBuf::new(Trc::from(&b"Trc"[..]));
+    /// buf.advance(2);
+    /// assert_eq!(buf.position(), 2);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn position(&self) -> usize {
+        return self.pos;
+    }
+
+    /// Return a reference to the underlying `Trc`, including the bytes which have been consumed.
+    ///
+    /// # Examples
+    /// ```
+    /// use bytes::Buf;
+    /// use trc::{buf::TrcBuf, Trc};
+    ///
+    /// let mut buf = TrcBuf::new(Trc::from(&b"Trc"[..]));
+    /// buf.advance(3);
+    /// assert_eq!(**buf.get_ref(), *b"Trc");
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn get_ref(&self) -> &Trc<[u8]> {
+        return &self.data;
+    }
+
+    /// Return the underlying `Trc`, including the bytes which have been consumed.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{buf::TrcBuf, Trc};
+    ///
+    /// let trc = Trc::<[u8]>::from(&b"Trc"[..]);
+    /// let buf = TrcBuf::new(trc.clone());
+    /// assert!(Trc::ptr_eq(&buf.into_inner(), &trc));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn into_inner(self) -> Trc<[u8]> {
+        return self.data;
+    }
+}
+
+impl Buf for TrcBuf {
+    #[inline]
+    fn remaining(&self) -> usize {
+        return self.data.len() - self.pos;
+    }
+
+    #[inline]
+    fn chunk(&self) -> &[u8] {
+        return &self.data[self.pos..];
+    }
+
+    #[inline]
+    fn advance(&mut self, cnt: usize) {
+        assert!(
+            cnt <= self.remaining(),
+            "Cannot advance past the end of the buffer."
+        );
+        self.pos += cnt;
+    }
+}
+
+impl From<Trc<[u8]>> for TrcBuf {
+    /// Create a cursor at the start of a `Trc<[u8]>`. See [`TrcBuf::new`].
+    ///
+    /// # Examples
+    /// ```
+    /// use bytes::Buf;
+    /// use trc::{buf::TrcBuf, Trc};
+    ///
+    /// let buf = TrcBuf::from(Trc::from(&b"Trc"[..]));
+    /// assert_eq!(buf.chunk(), b"Trc");
+    /// ```
+    #[inline]
+    fn from(value: Trc<[u8]>) -> Self {
+        return Self::new(value);
+    }
+}
+
+if_thread_safe! {
+    use bytes::Bytes;
+
+    use crate::SharedTrc;
+
+    impl From<SharedTrc<[u8]>> for Bytes {
+        /// Convert a `SharedTrc<[u8]>` into [`Bytes`] without copying. The `SharedTrc` is kept alive until the last
+        /// `Bytes` referring to it is dropped.
+        ///
+        /// # Examples
+        /// ```
+        /// use bytes::Bytes;
+        /// use trc::SharedTrc;
+        ///
+        /// let shared = SharedTrc::<[u8]>::from(&b"Trc"[..]);
+        /// let bytes = Bytes::from(shared.clone());
+        /// assert_eq!(bytes, b"Trc"[..]);
+        /// assert_eq!(bytes.as_ptr(), shared.as_ptr());
+        /// ```
+        #[inline]
+        fn from(value: SharedTrc<[u8]>) -> Self {
+            return Bytes::from_owner(value);
+        }
+    }
+
+    impl From<Trc<[u8]>> for Bytes {
+        /// Convert a `Trc<[u8]>` into [`Bytes`] without copying. Because `Bytes` can be sent to other threads, the `Trc`
+        /// is converted into a [`SharedTrc`] first.
+        ///
+        /// # Examples
+        /// ```
+        /// use bytes::Bytes;
+        /// use trc::Trc;
+        ///
+        /// let trc = Trc::<[u8]>::from(&b"Trc"[..]);
+        /// let bytes = Bytes::from(trc.clone());
+        /// assert_eq!(bytes.slice(1..), b"rc"[..]);
+        /// assert_eq!(Trc::atomic_count(&trc), 2);
+        /// ```
+        #[inline]
+        fn from(value: Trc<[u8]>) -> Self {
+            return Self::from(SharedTrc::from(value));
+        }
+    }
+
+    impl From<TrcBuf> for Bytes {
+        /// Convert the remaining bytes of a [`TrcBuf`] into [`Bytes`] without copying.
+        ///
+        /// # Examples
+        /// ```
+        /// use bytes::{Buf, Bytes};
+        /// use trc::{buf::TrcBuf, Trc};
+        ///
+        /// let mut buf = TrcBuf::new(Trc::from(&b"Trc"[..]));
+        /// buf.advance(1);
+        /// assert_eq!(Bytes::from(buf), b"rc"[..]);
+        /// ```
+        #[inline]
+        fn from(value: TrcBuf) -> Self {
+            let mut bytes = Self::from(value.data);
+            bytes.advance(value.pos);
+            return bytes;
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! Comparing and hashing handles by the address of their allocation.
+//!
+//! [`ByPtr`] wraps a `Trc`, `SharedTrc` or `Weak` so that it can be used as an identity key in a `HashMap` or `BTreeSet`
+//! without hashing or comparing the value it points to.
+
+use core::{
+    cmp::Ordering,
+    fmt::{self, Debug},
+    hash::{Hash, Hasher},
+    ops::Deref,
+};
+
+use crate::{SharedTrc, Trc, Weak};
+
+/// A wrapper around a `Trc<T>`, `SharedTrc<T>` or `Weak<T>` whose [`Hash`], [`Eq`] and [`Ord`] implementations use the
+/// address of the allocation instead of the value. Two `ByPtr`s are equal if and only if they point to the same
+/// allocation, even if the values are equal or `T` does not implement these traits.
+///
+/// Only the address is compared, so the metadata of unsized values is ignored. A `Weak` created with [`Weak::new`] has
+/// no allocation and is equal to every other such `Weak`.
+///
+/// # Examples
+/// ```
+/// use std::collections::HashSet;
+/// use trc::{ByPtr, Trc};
+///
+/// let a = Trc::new(100);
+/// let b = Trc::new(100);
+///
+/// let mut seen = HashSet::new();
+/// assert!(seen.insert(ByPtr(a.clone())));
+/// assert!(seen.insert(ByPtr(b)));
+/// assert!(!seen.insert(ByPtr(a)));
+/// ```
+#[derive(Clone, Copy, Default)]
+#[repr(transparent)]
+pub struct ByPtr<P>(pub P);
+
+/// A handle which points to an allocation. This is implemented for `Trc`, `SharedTrc` and `Weak`.
+pub trait Address {
+    /// Return the address of the allocation.
+    fn address(&self) -> *const ();
+}
+
+impl<T: ?Sized> Address for Trc<T> {
+    #[inline]
+    fn address(&self) -> *const () {
+        return Trc::as_ptr(self).cast();
+    }
+}
+
+impl<T: ?Sized> Address for SharedTrc<T> {
+    #[inline]
+    fn address(&self) -> *const () {
+        return SharedTrc::as_ptr(self).cast();
+    }
+}
+
+impl<T: ?Sized> Address for Weak<T> {
+    #[inline]
+    fn address(&self) -> *const () {
+        return Weak::as_ptr(self).cast();
+    }
+}
+
+impl<P> ByPtr<P> {
+    /// Return the wrapped handle.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{ByPtr, Trc};
+    ///
+    /// let trc = Trc::new(100);
+    /// let key = ByPtr(trc.clone());
+    /// assert!(Trc::ptr_eq(&key.into_inner(), &trc));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn into_inner(self) -> P {
+        return self.0;
+    }
+}
+
+impl<P: Address> ByPtr<P> {
+    /// Return the address of the allocation, which is used for hashing and comparisons.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{ByPtr, Trc};
+    ///
+    /// let trc = Trc::new(100);
+    /// assert_eq!(ByPtr(trc.clone()).as_ptr(), Trc::as_ptr(&trc).cast());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn as_ptr(&self) -> *const () {
+        return self.0.address();
+    }
+}
+
+impl<P> Deref for ByPtr<P> {
+    type Target = P;
+
+    #[inline]
+    fn deref(&self) -> &P {
+        return &self.0;
+    }
+}
+
+impl<P> From<P> for ByPtr<P> {
+    /// Wrap a handle in a `ByPtr`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{ByPtr, SharedTrc};
+    ///
+    /// let key: ByPtr<_> = SharedTrc::new(100).into();
+    /// assert_eq!(**key, 100);
+    /// ```
+    #[inline]
+    fn from(value: P) -> Self {
+        return Self(value);
+    }
+}
+
+impl<P: Debug> Debug for ByPtr<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ByPtr").field(&self.0).finish()
+    }
+}
+
+impl<P: Address> Hash for ByPtr<P> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_ptr().hash(state);
+    }
+}
+
+impl<P: Address> PartialEq for ByPtr<P> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        return self.as_ptr() == other.as_ptr();
+    }
+}
+
+impl<P: Address> Eq for ByPtr<P> {}
+
+impl<P: Address> PartialOrd for ByPtr<P> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+impl<P: Address> Ord for ByPtr<P> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        return self.as_ptr().cmp(&other.as_ptr());
+    }
+}
@@ -0,0 +1,222 @@
+//! A single-threaded mutable slot holding a `Trc`.
+
+use core::{
+    cell::UnsafeCell,
+    fmt::{self, Debug},
+    mem, ptr,
+};
+
+use crate::Trc;
+
+/// A mutable slot holding a [`Trc<T>`], which can be read and replaced through a shared reference.
+///
+/// This is like a `Cell<Trc<T>>` which can also be read, because cloning a `Trc` only increments its local count and
+/// cannot run any user code. Unlike `RefCell<Trc<T>>`, there is no borrow flag to check or update.
+///
+/// # Examples
+/// ```
+/// use trc::{Trc, TrcCell};
+///
+/// let current = TrcCell::new(Trc::new(String::from("v1")));
+/// let old = current.get_clone();
+///
+/// current.set(Trc::new(String::from("v2")));
+/// assert_eq!(*old, "v1");
+/// assert_eq!(*current.get_clone(), "v2");
+/// ```
+pub struct TrcCell<T: ?Sized> {
+    value: UnsafeCell<Trc<T>>,
+}
+
+impl<T: ?Sized> TrcCell<T> {
+    /// Create a new `TrcCell` holding `value`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{Trc, TrcCell};
+    ///
+    /// let cell = TrcCell::new(Trc::new(100));
+    /// assert_eq!(*cell.get_clone(), 100);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn new(value: Trc<T>) -> Self {
+        return Self {
+            value: UnsafeCell::new(value),
+        };
+    }
+
+    /// Return a clone of the `Trc` in the cell. This increments its local count.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{Trc, TrcCell};
+    ///
+    /// let trc = Trc::new(100);
+    /// let cell = TrcCell::new(trc.clone());
+    /// assert!(Trc::ptr_eq(&cell.get_clone(), &trc));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn get_clone(&self) -> Trc<T> {
+        //Cloning a `Trc` cannot access the cell, so the reference does not overlap a mutation.
+        return unsafe { &*self.value.get() }.clone();
+    }
+
+    /// Replace the `Trc` in the cell with `value`, dropping the previous one.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{Trc, TrcCell};
+    ///
+    /// let cell = TrcCell::new(Trc::new(1));
+    /// cell.set(Trc::new(2));
+    /// assert_eq!(*cell.get_clone(), 2);
+    /// ```
+    #[inline]
+    pub fn set(&self, value: Trc<T>) {
+        //The previous value is dropped after the cell is updated, in case its destructor uses the cell.
+        drop(self.replace(value));
+    }
+
+    /// Replace the `Trc` in the cell with `value`, returning the previous one.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{Trc, TrcCell};
+    ///
+    /// let cell = TrcCell::new(Trc::new(1));
+    /// assert_eq!(*cell.replace(Trc::new(2)), 1);
+    /// assert_eq!(*cell.get_clone(), 2);
+    /// ```
+    #[inline]
+    #[must_use = "use `set` to drop the previous value"]
+    pub fn replace(&self, value: Trc<T>) -> Trc<T> {
+        return mem::replace(unsafe { &mut *self.value.get() }, value);
+    }
+
+    /// Swap the `Trc`s in two cells.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{Trc, TrcCell};
+    ///
+    /// let a = TrcCell::new(Trc::new(1));
+    /// let b = TrcCell::new(Trc::new(2));
+    /// a.swap(&b);
+    /// assert_eq!(*a.get_clone(), 2);
+    /// assert_eq!(*b.get_clone(), 1);
+    /// ```
+    #[inline]
+    pub fn swap(&self, other: &Self) {
+        if ptr::eq(self, other) {
+            return;
+        }
+        //The cells are distinct, and neither is accessed while the values are swapped.
+        unsafe { ptr::swap(self.value.get(), other.value.get()) };
+    }
+
+    /// Return a mutable reference to the `Trc` in the cell. Because this requires exclusive access to the cell, no
+    /// clone is needed.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{Trc, TrcCell};
+    ///
+    /// let mut cell = TrcCell::new(Trc::new(1));
+    /// *cell.get_mut() = Trc::new(2);
+    /// assert_eq!(*cell.get_clone(), 2);
+    /// ```
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut Trc<T> {
+        return self.value.get_mut();
+    }
+
+    /// Consume the cell, returning the `Trc` it holds.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{Trc, TrcCell};
+    ///
+    /// let cell = TrcCell::new(Trc::new(100));
+    /// assert_eq!(*cell.into_inner(), 100);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn into_inner(self) -> Trc<T> {
+        return self.value.into_inner();
+    }
+}
+
+impl<T: Default> TrcCell<T> {
+    /// Replace the `Trc` in the cell with a new `Trc` holding the default value of `T`, returning the previous one.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{Trc, TrcCell};
+    ///
+    /// let cell = TrcCell::new(Trc::new(vec![1, 2]));
+    /// assert_eq!(*cell.take(), [1, 2]);
+    /// assert!(cell.get_clone().is_empty());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn take(&self) -> Trc<T> {
+        return self.replace(Trc::default());
+    }
+}
+
+impl<T: ?Sized> Clone for TrcCell<T> {
+    /// Create a new cell holding a clone of the `Trc` in this one.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{Trc, TrcCell};
+    ///
+    /// let cell = TrcCell::new(Trc::new(100));
+    /// let cell2 = cell.clone();
+    /// assert!(Trc::ptr_eq(&cell.get_clone(), &cell2.get_clone()));
+    /// ```
+    #[inline]
+    fn clone(&self) -> Self {
+        return Self::new(self.get_clone());
+    }
+}
+
+impl<T: Default> Default for TrcCell<T> {
+    /// Create a new cell holding a `Trc` of the default value of `T`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::TrcCell;
+    ///
+    /// let cell = TrcCell::<i32>::default();
+    /// assert_eq!(*cell.get_clone(), 0);
+    /// ```
+    #[inline]
+    fn default() -> Self {
+        return Self::new(Trc::default());
+    }
+}
+
+impl<T: ?Sized> From<Trc<T>> for TrcCell<T> {
+    /// Create a new `TrcCell` holding `value`. See [`TrcCell::new`].
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{Trc, TrcCell};
+    ///
+    /// let cell = TrcCell::from(Trc::new(100));
+    /// assert_eq!(*cell.get_clone(), 100);
+    /// ```
+    #[inline]
+    fn from(value: Trc<T>) -> Self {
+        return Self::new(value);
+    }
+}
+
+impl<T: ?Sized + Debug> Debug for TrcCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("TrcCell").field(&self.get_clone()).finish()
+    }
+}
@@ -0,0 +1,180 @@
+//! A clone-on-write wrapper around a `Trc`.
+
+use core::{
+    fmt::{self, Debug, Display},
+    ops::Deref,
+};
+
+use crate::Trc;
+
+/// A clone-on-write handle to a value shared through a [`Trc<T>`].
+///
+/// Reading the value is free, as with a `Trc`. The first call to [`TrcCow::to_mut`] clones the value into a new
+/// allocation if it is shared (see [`Trc::make_mut`]), and the `TrcCow` remembers that it has been modified, so callers
+/// can tell whether the original value can be reused.
+///
+/// # Examples
+/// ```
+/// use trc::{Trc, TrcCow};
+///
+/// let defaults = Trc::new(vec![String::from("a"), String::from("b")]);
+///
+/// let mut settings = TrcCow::new(defaults.clone());
+/// assert_eq!(settings.len(), 2);
+/// assert!(!settings.is_modified());
+///
+/// settings.to_mut().push(String::from("c"));
+/// assert!(settings.is_modified());
+/// assert_eq!(settings.len(), 3);
+/// assert_eq!(defaults.len(), 2);
+/// ```
+#[derive(Clone)]
+pub struct TrcCow<T: Clone> {
+    trc: Trc<T>,
+    modified: bool,
+}
+
+impl<T: Clone> TrcCow<T> {
+    /// Create a new, unmodified `TrcCow` which reads the value of `trc`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{Trc, TrcCow};
+    ///
+    /// let cow = TrcCow::new(Trc::new(100));
+    /// assert_eq!(*cow, 100);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn new(trc: Trc<T>) -> Self {
+        return Self {
+            trc,
+            modified: false,
+        };
+    }
+
+    /// Return a mutable reference to the value, cloning it first if the allocation is shared. This marks the `TrcCow`
+    /// as modified.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{Trc, TrcCow};
+    ///
+    /// let original = Trc::new(1);
+    /// let mut cow = TrcCow::new(original.clone());
+    /// *cow.to_mut() += 1;
+    /// assert_eq!(*cow, 2);
+    /// assert_eq!(*original, 1);
+    /// ```
+    #[inline]
+    pub fn to_mut(&mut self) -> &mut T {
+        self.modified = true;
+        return Trc::make_mut(&mut self.trc);
+    }
+
+    /// Return whether [`TrcCow::to_mut`] has been called, so the value may differ from the one the `TrcCow` was created
+    /// with.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{Trc, TrcCow};
+    ///
+    /// let mut cow = TrcCow::new(Trc::new(1));
+    /// assert!(!cow.is_modified());
+    /// cow.to_mut();
+    /// assert!(cow.is_modified());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn is_modified(&self) -> bool {
+        return self.modified;
+    }
+
+    /// Return a reference to the `Trc` holding the current value.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{Trc, TrcCow};
+    ///
+    /// let trc = Trc::new(1);
+    /// let cow = TrcCow::new(trc.clone());
+    /// assert!(Trc::ptr_eq(cow.as_trc(), &trc));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn as_trc(&self) -> &Trc<T> {
+        return &self.trc;
+    }
+
+    /// Consume the `TrcCow`, returning the `Trc` holding the current value.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{Trc, TrcCow};
+    ///
+    /// let mut cow = TrcCow::new(Trc::new(1));
+    /// *cow.to_mut() = 2;
+    /// assert_eq!(*cow.into_trc(), 2);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn into_trc(self) -> Trc<T> {
+        return self.trc;
+    }
+}
+
+impl<T: Clone> Deref for TrcCow<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        return &self.trc;
+    }
+}
+
+impl<T: Clone> From<Trc<T>> for TrcCow<T> {
+    /// Create a new, unmodified `TrcCow`. See [`TrcCow::new`].
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{Trc, TrcCow};
+    ///
+    /// let cow = TrcCow::from(Trc::new(100));
+    /// assert!(!cow.is_modified());
+    /// ```
+    #[inline]
+    fn from(value: Trc<T>) -> Self {
+        return Self::new(value);
+    }
+}
+
+impl<T: Clone> From<TrcCow<T>> for Trc<T> {
+    /// Return the `Trc` holding the current value. See [`TrcCow::into_trc`].
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{Trc, TrcCow};
+    ///
+    /// let trc: Trc<i32> = TrcCow::new(Trc::new(100)).into();
+    /// assert_eq!(*trc, 100);
+    /// ```
+    #[inline]
+    fn from(value: TrcCow<T>) -> Self {
+        return value.into_trc();
+    }
+}
+
+impl<T: Clone + Debug> Debug for TrcCow<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TrcCow")
+            .field("value", &**self)
+            .field("modified", &self.modified)
+            .finish()
+    }
+}
+
+impl<T: Clone + Display> Display for TrcCow<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&**self, f)
+    }
+}
@@ -0,0 +1,472 @@
+//! An optional collector for reference cycles between `Trc`s.
+//!
+//! Values created with [`Trc::new_traced`] are registered with the collector of the current thread. [`collect_cycles`]
+//! finds the registered allocations which are only kept alive by references from other registered allocations, and
+//! reclaims them. It uses trial deletion, like the `bacon-rajan-cc` crate: the references between registered values are
+//! found with [`Trace`] and subtracted from the reference counts, and everything which is not reachable from an allocation
+//! with a remaining count is garbage.
+//!
+//! Allocations are only tracked on the thread which registered them. The references of other threads are counted as
+//! external, so allocations which are shared with other threads are never collected while the other threads use them.
+//!
+//! This module is only available with the `cycles` feature.
+//!
+//! # Examples
+//! ```
+//! use std::cell::RefCell;
+//! use trc::{cycles::{self, Trace, Tracer}, Trc};
+//!
+//! struct Node {
+//!     next: RefCell<Option<Trc<Node>>>,
+//! }
+//!
+//! unsafe impl Trace for Node {
+//!     fn trace(&self, tracer: &mut Tracer) {
+//!         self.next.trace(tracer);
+//!     }
+//! }
+//!
+//! let a = Trc::new_traced(Node { next: RefCell::new(None) });
+//! let b = Trc::new_traced(Node { next: RefCell::new(Some(a.clone())) });
+//! *a.next.borrow_mut() = Some(b.clone());
+//!
+//! //While `a` and `b` are in use, nothing is collected.
+//! assert_eq!(cycles::collect_cycles(), 0);
+//!
+//! let weak = Trc::downgrade(&a);
+//! drop(a);
+//! drop(b);
+//! assert!(weak.upgrade().is_some());
+//!
+//! //Now the cycle is only kept alive by itself.
+//! assert_eq!(cycles::collect_cycles(), 2);
+//! assert!(weak.upgrade().is_none());
+//! ```
+
+use alloc::{boxed::Box, collections::VecDeque, string::String, vec::Vec};
+use core::{
+    cell::{Cell, RefCell},
+    marker::PhantomData,
+    mem,
+    ptr::{self, addr_of_mut, NonNull},
+};
+use std::collections::HashMap;
+
+use crate::{
+    ordering::{Acquire, Relaxed, Release},
+    sum_value, SharedTrcInternal, Trc, Weak,
+};
+
+/// A value which can report the `Trc`s it owns to the cycle collector.
+///
+/// # Safety
+/// [`Trace::trace`] must visit every `Trc` which is owned by the value and may be part of a cycle, and must not visit any
+/// `Trc` which is not owned by it. Visiting too few `Trc`s only keeps cycles alive, but visiting a `Trc` which is not
+/// owned by the value can cause a live value to be collected.
+///
+/// Traced values must not be accessed from other threads while [`collect_cycles`] runs, and their destructors must not
+/// upgrade `Weak`s to other traced values, because those may already have been dropped by the collector.
+pub unsafe trait Trace {
+    /// Visit the `Trc`s owned by this value with [`Tracer::visit`], or by calling `trace` on the fields holding them.
+    fn trace(&self, tracer: &mut Tracer);
+}
+
+/// Collects the `Trc`s visited by [`Trace::trace`].
+pub struct Tracer {
+    //The allocation and the local count of each visited `Trc`.
+    edges: Vec<(usize, NonNull<usize>)>,
+    //The allocation of each visited `Weak`. These do not keep values alive, so the collector ignores them.
+    weak_edges: Vec<usize>,
+}
+
+impl Tracer {
+    #[inline]
+    const fn new() -> Self {
+        return Self {
+            edges: Vec::new(),
+            weak_edges: Vec::new(),
+        };
+    }
+
+    /// Report a `Trc` owned by the value which is being traced.
+    #[inline]
+    pub fn visit<T: ?Sized>(&mut self, trc: &Trc<T>) {
+        self.edges.push((trc.shared.as_ptr().addr(), trc.threadref));
+    }
+
+    /// Report a `Weak` owned by the value which is being traced. `Weak`s never keep a cycle alive, so they are only used
+    /// to show the references between values, for example by the `debug` module.
+    #[inline]
+    pub fn visit_weak<T: ?Sized>(&mut self, weak: &Weak<T>) {
+        self.weak_edges.push(weak.data.as_ptr().addr());
+    }
+}
+
+unsafe impl<T: ?Sized> Trace for Trc<T> {
+    #[inline]
+    fn trace(&self, tracer: &mut Tracer) {
+        tracer.visit(self);
+    }
+}
+
+unsafe impl<T: ?Sized> Trace for Weak<T> {
+    #[inline]
+    fn trace(&self, tracer: &mut Tracer) {
+        tracer.visit_weak(self);
+    }
+}
+
+unsafe impl<T: Trace> Trace for Option<T> {
+    #[inline]
+    fn trace(&self, tracer: &mut Tracer) {
+        if let Some(value) = self {
+            value.trace(tracer);
+        }
+    }
+}
+
+unsafe impl<T: Trace> Trace for [T] {
+    #[inline]
+    fn trace(&self, tracer: &mut Tracer) {
+        for value in self {
+            value.trace(tracer);
+        }
+    }
+}
+
+unsafe impl<T: Trace, const N: usize> Trace for [T; N] {
+    #[inline]
+    fn trace(&self, tracer: &mut Tracer) {
+        self.as_slice().trace(tracer);
+    }
+}
+
+unsafe impl<T: Trace> Trace for Vec<T> {
+    #[inline]
+    fn trace(&self, tracer: &mut Tracer) {
+        self.as_slice().trace(tracer);
+    }
+}
+
+unsafe impl<T: Trace> Trace for VecDeque<T> {
+    #[inline]
+    fn trace(&self, tracer: &mut Tracer) {
+        for value in self {
+            value.trace(tracer);
+        }
+    }
+}
+
+unsafe impl<T: ?Sized + Trace> Trace for Box<T> {
+    #[inline]
+    fn trace(&self, tracer: &mut Tracer) {
+        (**self).trace(tracer);
+    }
+}
+
+unsafe impl<T: ?Sized + Trace> Trace for RefCell<T> {
+    /// Trace the value if it is not mutably borrowed. Otherwise, its `Trc`s are not visited, which keeps the allocations
+    /// they point to alive.
+    #[inline]
+    fn trace(&self, tracer: &mut Tracer) {
+        if let Ok(value) = self.try_borrow() {
+            value.trace(tracer);
+        }
+    }
+}
+
+unsafe impl<T: Trace + Default> Trace for Cell<T> {
+    #[inline]
+    fn trace(&self, tracer: &mut Tracer) {
+        let value = self.take();
+        value.trace(tracer);
+        self.set(value);
+    }
+}
+
+macro_rules! impl_trace_leaf {
+    ($($ty:ty),*) => {
+        $(
+            unsafe impl Trace for $ty {
+                #[inline]
+                fn trace(&self, _tracer: &mut Tracer) {}
+            }
+        )*
+    };
+}
+
+impl_trace_leaf!(
+    (),
+    bool,
+    char,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+    str,
+    String
+);
+
+/// The operations on a registered allocation, whose type is erased.
+struct Vtable {
+    trace: unsafe fn(NonNull<u8>, &mut Tracer),
+    drop_value: unsafe fn(NonNull<u8>),
+    drop_weak: unsafe fn(NonNull<u8>),
+    #[cfg(feature = "debug-graph")]
+    data: unsafe fn(NonNull<u8>) -> *const u8,
+    #[cfg(feature = "debug-graph")]
+    type_name: fn() -> &'static str,
+}
+
+struct Erased<T>(PhantomData<T>);
+
+impl<T: Trace + 'static> Erased<T> {
+    const VTABLE: Vtable = Vtable {
+        trace: Self::trace,
+        drop_value: Self::drop_value,
+        drop_weak: Self::drop_weak,
+        #[cfg(feature = "debug-graph")]
+        data: Self::data,
+        #[cfg(feature = "debug-graph")]
+        type_name: core::any::type_name::<T>,
+    };
+
+    unsafe fn trace(shared: NonNull<u8>, tracer: &mut Tracer) {
+        (*shared.cast::<SharedTrcInternal<T>>().as_ptr())
+            .data
+            .trace(tracer);
+    }
+
+    unsafe fn drop_value(shared: NonNull<u8>) {
+        ptr::drop_in_place(addr_of_mut!(
+            (*shared.cast::<SharedTrcInternal<T>>().as_ptr()).data
+        ));
+    }
+
+    unsafe fn drop_weak(shared: NonNull<u8>) {
+        drop(Weak::<T> {
+            data: shared.cast(),
+        });
+    }
+
+    #[cfg(feature = "debug-graph")]
+    unsafe fn data(shared: NonNull<u8>) -> *const u8 {
+        return ptr::addr_of!((*shared.cast::<SharedTrcInternal<T>>().as_ptr()).data).cast();
+    }
+}
+
+/// A registered allocation. The registry holds a `Weak` to it, so that its counts can be read after the value is
+/// dropped.
+struct Entry {
+    shared: NonNull<u8>,
+    vtable: &'static Vtable,
+}
+
+impl Entry {
+    #[inline]
+    fn atomic_count(&self) -> usize {
+        return unsafe { self.shared.cast::<SharedTrcInternal<()>>().as_ref() }
+            .atomicref
+            .load(Acquire);
+    }
+}
+
+impl Drop for Entry {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe { (self.vtable.drop_weak)(self.shared) };
+    }
+}
+
+std::thread_local! {
+    static REGISTRY: RefCell<Vec<Entry>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Register the allocation of `trc` with the collector of the current thread.
+pub(crate) fn register<T: Trace + 'static>(trc: &Trc<T>) {
+    let weak = mem::ManuallyDrop::new(Trc::downgrade(trc));
+    let entry = Entry {
+        shared: weak.data.cast(),
+        vtable: &Erased::<T>::VTABLE,
+    };
+    REGISTRY.with(|registry| registry.borrow_mut().push(entry));
+}
+
+/// Return the number of allocations registered with the collector of the current thread, including the ones whose
+/// values have been dropped but which have not been removed by [`collect_cycles`] yet.
+///
+/// # Examples
+/// ```
+/// use trc::{cycles, Trc};
+///
+/// let before = cycles::tracked();
+/// let trc = Trc::new_traced(100);
+/// assert_eq!(cycles::tracked(), before + 1);
+/// ```
+#[must_use]
+pub fn tracked() -> usize {
+    return REGISTRY.with(|registry| registry.borrow().len());
+}
+
+/// Reclaim the registered allocations of the current thread which are only kept alive by cycles of references between
+/// registered values, and return how many were reclaimed. Registered allocations whose values have already been dropped
+/// are removed from the registry.
+///
+/// # Examples
+/// ```
+/// use std::cell::RefCell;
+/// use trc::{cycles::{self, Trace, Tracer}, Trc};
+///
+/// struct SelfRef(RefCell<Option<Trc<SelfRef>>>);
+///
+/// unsafe impl Trace for SelfRef {
+///     fn trace(&self, tracer: &mut Tracer) {
+///         self.0.trace(tracer);
+///     }
+/// }
+///
+/// let trc = Trc::new_traced(SelfRef(RefCell::new(None)));
+/// *trc.0.borrow_mut() = Some(trc.clone());
+/// drop(trc);
+/// assert_eq!(cycles::collect_cycles(), 1);
+/// ```
+pub fn collect_cycles() -> usize {
+    //Values dropped by the collector may register or drop other traced values, so the registry is not borrowed then.
+    let mut entries = REGISTRY.with(|registry| mem::take(&mut *registry.borrow_mut()));
+    entries.retain(|entry| entry.atomic_count() != 0);
+
+    let index: HashMap<usize, usize> = entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| (entry.shared.as_ptr().addr(), i))
+        .collect();
+
+    //Find the references between registered values, and how many handles of each local count they account for.
+    let mut children = Vec::with_capacity(entries.len());
+    let mut internal_handles: HashMap<NonNull<usize>, (usize, usize)> = HashMap::new();
+    for entry in &entries {
+        let mut tracer = Tracer::new();
+        unsafe { (entry.vtable.trace)(entry.shared, &mut tracer) };
+        let mut edges = Vec::new();
+        for (shared, threadref) in tracer.edges {
+            if let Some(&target) = index.get(&shared) {
+                internal_handles.entry(threadref).or_insert((0, target)).0 += 1;
+                edges.push(target);
+            }
+        }
+        children.push(edges);
+    }
+
+    //A local count holds one atomic reference. If all of its handles are owned by registered values, so is the atomic
+    //reference.
+    let mut internal_counts = alloc::vec![0; entries.len()];
+    for (threadref, (handles, target)) in internal_handles {
+        if unsafe { *threadref.as_ptr() } == handles {
+            internal_counts[target] += 1;
+        }
+    }
+
+    let mut alive: Vec<bool> = entries
+        .iter()
+        .zip(&internal_counts)
+        .map(|(entry, &internal)| entry.atomic_count() > internal)
+        .collect();
+    let mut stack: Vec<usize> = (0..entries.len()).filter(|&i| alive[i]).collect();
+    while let Some(i) = stack.pop() {
+        for &child in &children[i] {
+            if !alive[child] {
+                alive[child] = true;
+                stack.push(child);
+            }
+        }
+    }
+
+    let mut live = Vec::new();
+    let mut garbage = Vec::new();
+    for (entry, alive) in entries.into_iter().zip(alive) {
+        if alive {
+            live.push(entry);
+        } else {
+            garbage.push(entry);
+        }
+    }
+
+    //Hold an extra atomic reference to each unreachable allocation, so that dropping the values, which drops the
+    //references between them, does not drop any of them a second time.
+    for entry in &garbage {
+        let internal = unsafe { entry.shared.cast::<SharedTrcInternal<()>>().as_ref() };
+        sum_value(&internal.atomicref, 1, Relaxed);
+    }
+    for entry in &garbage {
+        unsafe { (entry.vtable.drop_value)(entry.shared) };
+    }
+    for entry in &garbage {
+        let internal = unsafe { entry.shared.cast::<SharedTrcInternal<()>>().as_ref() };
+        debug_assert_eq!(internal.atomicref.load(Relaxed), 1);
+        internal.atomicref.store(0, Release);
+        //Release the implicit `Weak` of the strong references. The registry's `Weak` is released when the entry is dropped.
+        unsafe { (entry.vtable.drop_weak)(entry.shared) };
+    }
+
+    REGISTRY.with(|registry| {
+        registry.borrow_mut().extend(live);
+    });
+    return garbage.len();
+}
+
+/// Return the live registered allocations of the current thread, and the references between them.
+#[cfg(feature = "debug-graph")]
+pub(crate) fn graph() -> crate::debug::Graph {
+    return REGISTRY.with(|registry| {
+        let registry = registry.borrow();
+        let entries: Vec<&Entry> = registry
+            .iter()
+            .filter(|entry| entry.atomic_count() != 0)
+            .collect();
+        let index: HashMap<usize, usize> = entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| (entry.shared.as_ptr().addr(), i))
+            .collect();
+
+        let mut graph = crate::debug::Graph::default();
+        for entry in entries {
+            let mut tracer = Tracer::new();
+            unsafe { (entry.vtable.trace)(entry.shared, &mut tracer) };
+            let internal = unsafe { entry.shared.cast::<SharedTrcInternal<()>>().as_ref() };
+            graph.allocations.push(crate::debug::Allocation {
+                address: unsafe { (entry.vtable.data)(entry.shared) }.addr(),
+                type_name: (entry.vtable.type_name)(),
+                atomic_count: entry.atomic_count(),
+                //Do not count the implicit `Weak` of the strong references, or the registry's `Weak`.
+                weak_count: crate::weak_count_value(internal.weakcount.load(Acquire))
+                    .saturating_sub(2),
+            });
+            graph.strong.push(
+                tracer
+                    .edges
+                    .iter()
+                    .filter_map(|(shared, _)| index.get(shared).copied())
+                    .collect(),
+            );
+            graph.weak.push(
+                tracer
+                    .weak_edges
+                    .iter()
+                    .filter_map(|shared| index.get(shared).copied())
+                    .collect(),
+            );
+        }
+        return graph;
+    });
+}
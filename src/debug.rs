@@ -0,0 +1,224 @@
+//! Inspection of the graph of references between traced `Trc`s, for diagnosing leaked cycles.
+//!
+//! The allocations of values created with [`Trc::new_traced`](crate::Trc::new_traced) on the current thread are the
+//! nodes of the graph, and the `Trc`s and `Weak`s reported by their [`Trace`](crate::cycles::Trace) implementations are
+//! its edges. References to allocations which are not traced are not shown.
+//!
+//! This module is only available with the `debug-graph` feature, which enables the `cycles` feature.
+//!
+//! # Examples
+//! ```
+//! use std::cell::RefCell;
+//! use trc::{cycles::{Trace, Tracer}, debug, Trc};
+//!
+//! struct Node {
+//!     next: RefCell<Option<Trc<Node>>>,
+//! }
+//!
+//! unsafe impl Trace for Node {
+//!     fn trace(&self, tracer: &mut Tracer) {
+//!         self.next.trace(tracer);
+//!     }
+//! }
+//!
+//! let a = Trc::new_traced(Node { next: RefCell::new(None) });
+//! let b = Trc::new_traced(Node { next: RefCell::new(Some(a.clone())) });
+//! *a.next.borrow_mut() = Some(b.clone());
+//!
+//! let cycles = debug::find_cycles();
+//! assert_eq!(cycles.len(), 1);
+//! assert!(cycles[0].iter().any(|allocation| allocation.address == Trc::addr(&a)));
+//!
+//! println!("{}", debug::dump_graphviz());
+//! ```
+
+use alloc::{format, string::String, vec::Vec};
+use core::fmt::Write;
+
+use crate::cycles;
+
+/// A live traced allocation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Allocation {
+    /// The address of the value, as returned by `Trc::as_ptr`.
+    pub address: usize,
+    /// The name of the type of the value.
+    pub type_name: &'static str,
+    /// The atomic reference count of the allocation. See `Trc::atomic_count`.
+    pub atomic_count: usize,
+    /// The number of `Weak`s to the allocation.
+    pub weak_count: usize,
+}
+
+/// The traced allocations and the references between them, as indices into `allocations`.
+#[derive(Default)]
+pub(crate) struct Graph {
+    pub(crate) allocations: Vec<Allocation>,
+    pub(crate) strong: Vec<Vec<usize>>,
+    pub(crate) weak: Vec<Vec<usize>>,
+}
+
+/// Return the live traced allocations of the current thread.
+///
+/// # Examples
+/// ```
+/// use trc::{debug, Trc};
+///
+/// let trc = Trc::new_traced(100);
+/// let allocation = debug::allocations()
+///     .into_iter()
+///     .find(|allocation| allocation.address == Trc::addr(&trc))
+///     .unwrap();
+/// assert_eq!(allocation.type_name, "i32");
+/// assert_eq!(allocation.atomic_count, 1);
+/// ```
+#[must_use]
+pub fn allocations() -> Vec<Allocation> {
+    return cycles::graph().allocations;
+}
+
+/// Return the graph of the live traced allocations of the current thread in the Graphviz DOT format. `Trc`s are drawn
+/// as solid edges, and `Weak`s as dashed edges.
+///
+/// # Examples
+/// ```
+/// use trc::{cycles::{Trace, Tracer}, debug, Trc, Weak};
+///
+/// struct Parent(Weak<i32>);
+///
+/// unsafe impl Trace for Parent {
+///     fn trace(&self, tracer: &mut Tracer) {
+///         self.0.trace(tracer);
+///     }
+/// }
+///
+/// let child = Trc::new_traced(1);
+/// let parent = Trc::new_traced(Parent(Trc::downgrade(&child)));
+///
+/// let dot = debug::dump_graphviz();
+/// assert!(dot.starts_with("digraph trc {"));
+/// assert!(dot.contains("[style=dashed]"));
+/// ```
+#[must_use]
+pub fn dump_graphviz() -> String {
+    let graph = cycles::graph();
+    let mut dot = String::from("digraph trc {\n");
+    for (i, allocation) in graph.allocations.iter().enumerate() {
+        let label = format!(
+            "{}\\n{:#x}\\natomic: {}, weak: {}",
+            allocation
+                .type_name
+                .replace('\\', "\\\\")
+                .replace('"', "\\\""),
+            allocation.address,
+            allocation.atomic_count,
+            allocation.weak_count
+        );
+        let _ = writeln!(dot, "    n{i} [label=\"{label}\"];");
+    }
+    for (i, targets) in graph.strong.iter().enumerate() {
+        for target in targets {
+            let _ = writeln!(dot, "    n{i} -> n{target};");
+        }
+    }
+    for (i, targets) in graph.weak.iter().enumerate() {
+        for target in targets {
+            let _ = writeln!(dot, "    n{i} -> n{target} [style=dashed];");
+        }
+    }
+    dot.push_str("}\n");
+    return dot;
+}
+
+/// Return the cycles of `Trc`s between the live traced allocations of the current thread. Each cycle is a strongly
+/// connected group of allocations, which can only be freed by breaking one of the references between them or by
+/// [`collect_cycles`](crate::collect_cycles). `Weak`s are not part of any cycle.
+///
+/// # Examples
+/// ```
+/// use std::cell::RefCell;
+/// use trc::{cycles::{Trace, Tracer}, debug, Trc};
+///
+/// struct SelfRef(RefCell<Option<Trc<SelfRef>>>);
+///
+/// unsafe impl Trace for SelfRef {
+///     fn trace(&self, tracer: &mut Tracer) {
+///         self.0.trace(tracer);
+///     }
+/// }
+///
+/// let trc = Trc::new_traced(SelfRef(RefCell::new(None)));
+/// assert!(debug::find_cycles().is_empty());
+///
+/// *trc.0.borrow_mut() = Some(trc.clone());
+/// assert_eq!(debug::find_cycles().len(), 1);
+/// ```
+#[must_use]
+pub fn find_cycles() -> Vec<Vec<Allocation>> {
+    let graph = cycles::graph();
+    let len = graph.allocations.len();
+
+    //Kosaraju's algorithm, without recursion so that long chains do not overflow the stack. First, order the
+    //allocations by when their depth-first search finishes.
+    let mut visited = alloc::vec![false; len];
+    let mut order = Vec::with_capacity(len);
+    for root in 0..len {
+        if visited[root] {
+            continue;
+        }
+        visited[root] = true;
+        let mut stack = alloc::vec![(root, 0)];
+        while let Some((node, next)) = stack.last_mut() {
+            if let Some(&child) = graph.strong[*node].get(*next) {
+                *next += 1;
+                if !visited[child] {
+                    visited[child] = true;
+                    stack.push((child, 0));
+                }
+            } else {
+                order.push(*node);
+                stack.pop();
+            }
+        }
+    }
+
+    //Then, find the groups reachable from each allocation in the reversed graph, in the reverse order.
+    let mut reversed = alloc::vec![Vec::new(); len];
+    for (i, targets) in graph.strong.iter().enumerate() {
+        for &target in targets {
+            reversed[target].push(i);
+        }
+    }
+    let mut component = alloc::vec![usize::MAX; len];
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    for &root in order.iter().rev() {
+        if component[root] != usize::MAX {
+            continue;
+        }
+        component[root] = groups.len();
+        let mut group = Vec::new();
+        let mut stack = alloc::vec![root];
+        while let Some(node) = stack.pop() {
+            group.push(node);
+            for &parent in &reversed[node] {
+                if component[parent] == usize::MAX {
+                    component[parent] = groups.len();
+                    stack.push(parent);
+                }
+            }
+        }
+        groups.push(group);
+    }
+
+    //A single allocation is only a cycle if it refers to itself.
+    return groups
+        .into_iter()
+        .filter(|group| group.len() > 1 || graph.strong[group[0]].contains(&group[0]))
+        .map(|group| {
+            group
+                .into_iter()
+                .map(|i| graph.allocations[i].clone())
+                .collect()
+        })
+        .collect();
+}
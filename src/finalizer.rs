@@ -0,0 +1,224 @@
+//! Values with a finalizer, which runs when the last strong handle is dropped.
+
+use alloc::boxed::Box;
+use core::{
+    fmt::{self, Debug, Display},
+    ops::{Deref, DerefMut},
+};
+
+use crate::{SharedTrc, Trc};
+
+/// A value with a finalizer, which is run exactly once when the value is dropped, before the value itself.
+///
+/// Construct a `Trc<Finalized<T>>` with [`Trc::new_with_finalizer`] or a `SharedTrc<Finalized<T>>` with
+/// [`SharedTrc::new_with_finalizer`]. The finalizer runs when the last `Trc` or `SharedTrc` is dropped, on whichever
+/// thread that happens, even if `Weak`s are left. It can be removed with [`Trc::cancel_finalizer`] while the handle is
+/// unique.
+///
+/// # Examples
+#[cfg_attr(feature = "single-threaded", doc = "```ignore")]
+#[cfg_attr(not(feature = "single-threaded"), doc = "```")]
+/// use std::sync::Mutex;
+/// use trc::{SharedTrc, Trc};
+///
+/// static REGISTRY: Mutex<Vec<u32>> = Mutex::new(Vec::new());
+///
+/// let id = 7;
+/// REGISTRY.lock().unwrap().push(id);
+/// let resource = SharedTrc::new_with_finalizer(id, |id| {
+///     REGISTRY.lock().unwrap().retain(|registered| registered != id);
+/// });
+///
+/// let handle = std::thread::spawn(move || assert_eq!(**resource, 7));
+/// handle.join().unwrap();
+/// assert!(REGISTRY.lock().unwrap().is_empty());
+/// ```
+pub struct Finalized<T> {
+    value: T,
+    finalizer: Option<Finalizer<T>>,
+}
+
+type Finalizer<T> = Box<dyn FnOnce(&mut T) + Send>;
+
+//SAFETY: The finalizer is only accessed through `&mut Finalized<T>`, so sharing a `&Finalized<T>` only shares the value.
+unsafe impl<T: Sync> Sync for Finalized<T> {}
+
+impl<T> Finalized<T> {
+    /// Create a new `Finalized` holding `value`, which runs `finalizer` when it is dropped.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{Finalized, Trc};
+    ///
+    /// let finalized = Finalized::new(String::from("Trc"), |value| value.clear());
+    /// let trc = Trc::new(finalized);
+    /// assert_eq!(trc.len(), 3);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn new<F: FnOnce(&mut T) + Send + 'static>(value: T, finalizer: F) -> Self {
+        return Self {
+            value,
+            finalizer: Some(Box::new(finalizer)),
+        };
+    }
+
+    /// Remove the finalizer, returning whether there was one.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Finalized;
+    ///
+    /// let mut finalized = Finalized::new(100, |_| panic!("The finalizer was cancelled."));
+    /// assert!(Finalized::cancel(&mut finalized));
+    /// assert!(!Finalized::cancel(&mut finalized));
+    /// ```
+    #[inline]
+    pub fn cancel(this: &mut Self) -> bool {
+        return this.finalizer.take().is_some();
+    }
+
+    /// Return whether the finalizer has not been cancelled.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Finalized;
+    ///
+    /// let finalized = Finalized::new(100, |_| {});
+    /// assert!(Finalized::has_finalizer(&finalized));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn has_finalizer(this: &Self) -> bool {
+        return this.finalizer.is_some();
+    }
+
+    /// Return the value without running the finalizer.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Finalized;
+    ///
+    /// let finalized = Finalized::new(100, |_| panic!("The finalizer was cancelled."));
+    /// assert_eq!(Finalized::into_inner(finalized), 100);
+    /// ```
+    #[inline]
+    pub fn into_inner(mut this: Self) -> T {
+        Self::cancel(&mut this);
+        //SAFETY: The finalizer was dropped, and `this` is forgotten, so the value is moved out exactly once.
+        let value = unsafe { core::ptr::read(&this.value) };
+        core::mem::forget(this);
+        return value;
+    }
+}
+
+impl<T> Drop for Finalized<T> {
+    fn drop(&mut self) {
+        if let Some(finalizer) = self.finalizer.take() {
+            finalizer(&mut self.value);
+        }
+    }
+}
+
+impl<T> Deref for Finalized<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        return &self.value;
+    }
+}
+
+impl<T> DerefMut for Finalized<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        return &mut self.value;
+    }
+}
+
+impl<T: Debug> Debug for Finalized<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.value, f)
+    }
+}
+
+impl<T: Display> Display for Finalized<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.value, f)
+    }
+}
+
+impl<T> Trc<Finalized<T>> {
+    /// Creates a new `Trc` holding `value`, which runs `finalizer` exactly once when the last `Trc` or `SharedTrc` is
+    /// dropped, before the value is dropped. See [`Finalized`].
+    ///
+    /// # Examples
+    /// ```
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    /// use trc::Trc;
+    ///
+    /// static FINALIZED: AtomicBool = AtomicBool::new(false);
+    ///
+    /// let trc = Trc::new_with_finalizer(100, |_| FINALIZED.store(true, Ordering::Relaxed));
+    /// let trc2 = trc.clone();
+    /// drop(trc);
+    /// assert!(!FINALIZED.load(Ordering::Relaxed));
+    /// drop(trc2);
+    /// assert!(FINALIZED.load(Ordering::Relaxed));
+    /// ```
+    #[inline]
+    pub fn new_with_finalizer<F: FnOnce(&mut T) + Send + 'static>(value: T, finalizer: F) -> Self {
+        return Self::new(Finalized::new(value, finalizer));
+    }
+
+    /// Remove the finalizer if this is the only handle to the allocation, returning whether it was removed. Returns
+    /// `false` if there are other `Trc`s, `SharedTrc`s or `Weak`s, or if the finalizer was already cancelled.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let mut trc = Trc::new_with_finalizer(100, |_| panic!("The finalizer was cancelled."));
+    /// let trc2 = trc.clone();
+    /// assert!(!Trc::cancel_finalizer(&mut trc));
+    ///
+    /// drop(trc2);
+    /// assert!(Trc::cancel_finalizer(&mut trc));
+    /// ```
+    #[inline]
+    pub fn cancel_finalizer(this: &mut Self) -> bool {
+        return Self::get_mut(this).is_some_and(Finalized::cancel);
+    }
+}
+
+impl<T> SharedTrc<Finalized<T>> {
+    /// Creates a new `SharedTrc` holding `value`, which runs `finalizer` exactly once when the last `Trc` or
+    /// `SharedTrc` is dropped, before the value is dropped. See [`Finalized`].
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::SharedTrc;
+    ///
+    /// let shared = SharedTrc::new_with_finalizer(vec![1, 2, 3], |values| values.clear());
+    /// assert_eq!(shared.len(), 3);
+    /// ```
+    #[inline]
+    pub fn new_with_finalizer<F: FnOnce(&mut T) + Send + 'static>(value: T, finalizer: F) -> Self {
+        return Self::new(Finalized::new(value, finalizer));
+    }
+
+    /// Remove the finalizer if this is the only handle to the allocation, returning whether it was removed. See
+    /// [`Trc::cancel_finalizer`].
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::SharedTrc;
+    ///
+    /// let mut shared = SharedTrc::new_with_finalizer(100, |_| panic!("The finalizer was cancelled."));
+    /// assert!(SharedTrc::cancel_finalizer(&mut shared));
+    /// ```
+    #[inline]
+    pub fn cancel_finalizer(this: &mut Self) -> bool {
+        return Self::get_mut(this).is_some_and(Finalized::cancel);
+    }
+}
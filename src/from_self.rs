@@ -0,0 +1,158 @@
+//! Access to the `Trc` which owns a value from the value's own methods.
+
+use core::fmt::{self, Debug};
+
+use crate::{SharedTrc, Trc, Weak};
+
+/// A reference from a value to the allocation which holds it, for use with [`TrcFromSelf`]. It is a field of the value,
+/// created by [`Trc::new_with_self_ref`] or [`SharedTrc::new_with_self_ref`], or by [`SelfRef::new`] inside of
+/// [`Trc::new_cyclic`].
+///
+/// A `SelfRef` holds a [`Weak`], so it does not keep the value alive.
+pub struct SelfRef<T: ?Sized> {
+    weak: Weak<T>,
+}
+
+impl<T: ?Sized> SelfRef<T> {
+    /// Create a `SelfRef` from the `Weak` passed to the closure of [`Trc::new_cyclic`] or [`SharedTrc::new_cyclic`].
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{SelfRef, Trc};
+    ///
+    /// struct Node {
+    ///     self_ref: SelfRef<Node>,
+    /// }
+    ///
+    /// let node = Trc::new_cyclic(|weak| Node { self_ref: SelfRef::new(weak) });
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn new(weak: &Weak<T>) -> Self {
+        return Self { weak: weak.clone() };
+    }
+
+    /// Return a `Weak` to the allocation holding the value.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{SelfRef, Trc};
+    ///
+    /// struct Node {
+    ///     self_ref: SelfRef<Node>,
+    /// }
+    ///
+    /// let node = Trc::new_with_self_ref(|self_ref| Node { self_ref });
+    /// assert!(node.self_ref.weak().upgrade().is_some());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn weak(&self) -> &Weak<T> {
+        return &self.weak;
+    }
+}
+
+impl<T: ?Sized> Debug for SelfRef<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(SelfRef)")
+    }
+}
+
+/// A value which can return the `Trc` or `SharedTrc` it is held by, like C++'s `enable_shared_from_this`.
+///
+/// Implement it by returning a [`SelfRef`] field of the value, and create the value with [`Trc::new_with_self_ref`] or
+/// [`SharedTrc::new_with_self_ref`].
+///
+/// # Examples
+/// ```
+/// use std::cell::RefCell;
+/// use trc::{SelfRef, Trc, TrcFromSelf};
+///
+/// struct Button {
+///     self_ref: SelfRef<Button>,
+///     listeners: RefCell<Vec<Trc<Button>>>,
+/// }
+///
+/// impl TrcFromSelf for Button {
+///     fn self_ref(&self) -> &SelfRef<Self> {
+///         &self.self_ref
+///     }
+/// }
+///
+/// impl Button {
+///     fn register(&self, registry: &RefCell<Vec<Trc<Button>>>) {
+///         registry.borrow_mut().push(self.trc_from_self());
+///     }
+/// }
+///
+/// let registry = RefCell::new(Vec::new());
+/// let button = Trc::new_with_self_ref(|self_ref| Button {
+///     self_ref,
+///     listeners: RefCell::new(Vec::new()),
+/// });
+/// button.register(&registry);
+/// assert!(Trc::ptr_eq(&registry.borrow()[0], &button));
+/// ```
+pub trait TrcFromSelf {
+    /// Return the [`SelfRef`] field of the value.
+    fn self_ref(&self) -> &SelfRef<Self>;
+
+    /// Return a new `Trc` to the allocation holding the value.
+    ///
+    /// # Panics
+    /// Panics if the value is not held by a `Trc` or `SharedTrc`, which is the case while it is constructed or dropped.
+    #[must_use]
+    fn trc_from_self(&self) -> Trc<Self> {
+        return self
+            .self_ref()
+            .weak
+            .upgrade()
+            .expect("The value is not held by a `Trc`.");
+    }
+
+    /// Return a new `SharedTrc` to the allocation holding the value.
+    ///
+    /// # Panics
+    /// Panics if the value is not held by a `Trc` or `SharedTrc`, which is the case while it is constructed or dropped.
+    ///
+    /// # Examples
+    #[cfg_attr(feature = "single-threaded", doc = "```ignore")]
+    #[cfg_attr(not(feature = "single-threaded"), doc = "```")]
+    /// use trc::{SelfRef, SharedTrc, TrcFromSelf};
+    ///
+    /// struct Worker {
+    ///     self_ref: SelfRef<Worker>,
+    ///     id: usize,
+    /// }
+    ///
+    /// impl TrcFromSelf for Worker {
+    ///     fn self_ref(&self) -> &SelfRef<Self> {
+    ///         &self.self_ref
+    ///     }
+    /// }
+    ///
+    /// impl Worker {
+    ///     fn spawn(&self) -> std::thread::JoinHandle<usize> {
+    ///         let this = self.shared_from_self();
+    ///         std::thread::spawn(move || this.id)
+    ///     }
+    /// }
+    ///
+    /// let worker = SharedTrc::new_with_self_ref(|self_ref| Worker { self_ref, id: 7 });
+    /// assert_eq!(worker.spawn().join().unwrap(), 7);
+    /// ```
+    #[must_use]
+    fn shared_from_self(&self) -> SharedTrc<Self> {
+        return self
+            .self_ref()
+            .weak
+            .upgrade_shared()
+            .expect("The value is not held by a `Trc`.");
+    }
+
+    /// Return a new `Weak` to the allocation holding the value.
+    #[must_use]
+    fn weak_from_self(&self) -> Weak<Self> {
+        return self.self_ref().weak.clone();
+    }
+}
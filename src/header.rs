@@ -0,0 +1,167 @@
+//! A header and a dynamically sized tail stored in a single allocation.
+//!
+//! [`HeaderSlice`] allows a `Trc` to hold some fixed-size data alongside a slice without a second allocation, which is
+//! useful for interners and other structures where every entry carries metadata about its contents.
+
+use alloc::alloc::{handle_alloc_error, Layout};
+use core::{
+    marker::PhantomData,
+    mem::forget,
+    ptr::{self, addr_of_mut, slice_from_raw_parts_mut, write, NonNull},
+};
+
+use crate::{
+    internal_layout, new_threadref,
+    sync::{alloc, dealloc, AtomicUsize},
+    SharedTrcInternal, Trc,
+};
+
+/// A header of type `H` followed by a dynamically sized `slice`, stored inline.
+///
+/// Construct a `Trc<HeaderSlice<H, [T]>>` with [`Trc::from_header_and_iter`] or [`Trc::from_header_and_slice`].
+///
+/// # Examples
+/// ```
+/// use trc::{HeaderSlice, Trc};
+///
+/// let trc: Trc<HeaderSlice<&str, [u32]>> = Trc::from_header_and_slice("primes", &[2, 3, 5]);
+/// assert_eq!(trc.header, "primes");
+/// assert_eq!(trc.slice, [2, 3, 5]);
+/// ```
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(C)]
+pub struct HeaderSlice<H, T: ?Sized> {
+    /// The fixed-size header.
+    pub header: H,
+    /// The dynamically sized tail.
+    pub slice: T,
+}
+
+/// Owns a partially written `HeaderSlice` allocation. On drop, the header and the elements which were written are
+/// dropped, and the allocation is freed.
+struct HeaderSliceGuard<H, T> {
+    shared: NonNull<SharedTrcInternal<HeaderSlice<H, [T]>>>,
+    layout: Layout,
+    written: usize,
+}
+
+impl<H, T> HeaderSliceGuard<H, T> {
+    fn new(header: H, len: usize) -> Self {
+        let value_layout = Layout::new::<H>()
+            .extend(Layout::array::<T>(len).unwrap())
+            .unwrap()
+            .0
+            .pad_to_align();
+        let (layout, _) = internal_layout(value_layout).unwrap();
+
+        let ptr = unsafe { alloc(layout) };
+        if ptr.is_null() {
+            handle_alloc_error(layout);
+        }
+        #[cfg(feature = "stats")]
+        crate::stats::on_alloc(value_layout.size());
+        let res = slice_from_raw_parts_mut(ptr.cast::<T>(), len)
+            as *mut SharedTrcInternal<HeaderSlice<H, [T]>>;
+        unsafe { write(addr_of_mut!((*res).atomicref), AtomicUsize::new(1)) };
+        unsafe { write(addr_of_mut!((*res).weakcount), AtomicUsize::new(1)) };
+        unsafe { write(addr_of_mut!((*res).data.header), header) };
+
+        Self {
+            shared: unsafe { NonNull::new_unchecked(res) },
+            layout,
+            written: 0,
+        }
+    }
+
+    /// Write the next element. The caller must not write more elements than the length of the slice.
+    #[inline]
+    unsafe fn push(&mut self, value: T) {
+        let elems = addr_of_mut!((*self.shared.as_ptr()).data.slice).cast::<T>();
+        write(elems.add(self.written), value);
+        self.written += 1;
+    }
+
+    /// Finish construction. All elements of the slice must have been written.
+    fn finish(self) -> Trc<HeaderSlice<H, [T]>> {
+        let shared = self.shared;
+        forget(self);
+
+        return Trc {
+            threadref: new_threadref(),
+            shared,
+            phantom: PhantomData,
+        };
+    }
+}
+
+impl<H, T> Drop for HeaderSliceGuard<H, T> {
+    fn drop(&mut self) {
+        unsafe {
+            let data = addr_of_mut!((*self.shared.as_ptr()).data);
+            ptr::drop_in_place(addr_of_mut!((*data).header));
+            let elems = addr_of_mut!((*data).slice).cast::<T>();
+            ptr::drop_in_place(slice_from_raw_parts_mut(elems, self.written));
+
+            #[cfg(feature = "stats")]
+            crate::stats::on_dealloc(core::mem::size_of_val(&*data));
+            dealloc(self.shared.as_ptr().cast(), self.layout);
+        }
+    }
+}
+
+impl<H, T> Trc<HeaderSlice<H, [T]>> {
+    /// Constructs a new `Trc` holding `header` followed by the elements of an iterator with an exact size, all in one
+    /// allocation. The elements are written directly into the allocation.
+    ///
+    /// If the iterator panics, the header and the elements which were already written are dropped and the allocation
+    /// is freed.
+    ///
+    /// # Panics
+    /// Panics if the iterator yields fewer elements than reported by [`ExactSizeIterator::len`].
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let trc = Trc::from_header_and_iter(3usize, (1..4).map(|x| x * 10));
+    /// assert_eq!(trc.header, 3);
+    /// assert_eq!(trc.slice, [10, 20, 30]);
+    /// ```
+    pub fn from_header_and_iter<I>(header: H, iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let iter = iter.into_iter();
+        let len = iter.len();
+        let mut guard = HeaderSliceGuard::new(header, len);
+
+        for value in iter.take(len) {
+            unsafe { guard.push(value) };
+        }
+        assert_eq!(
+            guard.written, len,
+            "ExactSizeIterator reported an incorrect length."
+        );
+
+        guard.finish()
+    }
+
+    /// Constructs a new `Trc` holding `header` followed by clones of the elements of `slice`, all in one allocation.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let trc = Trc::from_header_and_slice('h', &["a", "b"]);
+    /// assert_eq!(trc.header, 'h');
+    /// assert_eq!(trc.slice, ["a", "b"]);
+    /// ```
+    #[inline]
+    pub fn from_header_and_slice(header: H, slice: &[T]) -> Self
+    where
+        T: Clone,
+    {
+        return Self::from_header_and_iter(header, slice.iter().cloned());
+    }
+}
@@ -0,0 +1,101 @@
+//! Global hooks which are called whenever a `Trc`, `SharedTrc` or `Weak` is cloned or dropped.
+//!
+//! Hooks allow custom instrumentation, such as sampling profilers or invariant checkers, to observe handle traffic.
+//! They are only available with the `hooks` feature. When no hook is set, each clone or drop only performs one extra relaxed load.
+//!
+//! # Examples
+//! ```
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//! use trc::hooks::{self, HandleKind};
+//! use trc::Trc;
+//!
+//! static CLONES: AtomicUsize = AtomicUsize::new(0);
+//!
+//! fn on_clone(_id: usize, kind: HandleKind) {
+//!     if kind == HandleKind::Trc {
+//!         CLONES.fetch_add(1, Ordering::Relaxed);
+//!     }
+//! }
+//!
+//! hooks::set_on_clone(Some(on_clone));
+//! let trc = Trc::new(100);
+//! let _trc2 = trc.clone();
+//! hooks::set_on_clone(None);
+//!
+//! assert_eq!(CLONES.load(Ordering::Relaxed), 1);
+//! ```
+
+use core::{mem, ptr};
+
+use crate::{
+    ordering::{Acquire, Relaxed, Release},
+    sync::StaticAtomicPtr as AtomicPtr,
+};
+
+/// The kind of handle passed to a [`Hook`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HandleKind {
+    /// A [`Trc`](crate::Trc).
+    Trc,
+    /// A [`SharedTrc`](crate::SharedTrc).
+    SharedTrc,
+    /// A [`Weak`](crate::Weak).
+    Weak,
+}
+
+/// A hook function. It receives an identifier of the allocation, which is its address and is shared by all handles to
+/// that allocation, and the kind of handle which was cloned or dropped.
+///
+/// Only [`Clone::clone`] and [`Drop::drop`] are reported; constructors and conversions are not. When the last strong
+/// handle is dropped, the release of their implicit weak reference is reported as a [`HandleKind::Weak`] drop.
+///
+/// Hooks may be called from any thread, and must not clone or drop handles themselves.
+pub type Hook = fn(alloc_id: usize, kind: HandleKind);
+
+static ON_CLONE: AtomicPtr<()> = AtomicPtr::new(ptr::null_mut());
+static ON_DROP: AtomicPtr<()> = AtomicPtr::new(ptr::null_mut());
+
+/// Set the hook which is called after a handle is cloned, replacing the previous hook.
+/// Passing `None` removes the hook.
+pub fn set_on_clone(hook: Option<Hook>) {
+    store(&ON_CLONE, hook);
+}
+
+/// Set the hook which is called before a handle is dropped, replacing the previous hook.
+/// Passing `None` removes the hook.
+pub fn set_on_drop(hook: Option<Hook>) {
+    store(&ON_DROP, hook);
+}
+
+fn store(slot: &AtomicPtr<()>, hook: Option<Hook>) {
+    let ptr = hook.map_or(ptr::null_mut(), |hook| hook as *mut ());
+    slot.store(ptr, Release);
+}
+
+#[inline(always)]
+fn call<T: ?Sized>(slot: &AtomicPtr<()>, alloc: *const T, kind: HandleKind) {
+    let ptr = slot.load(Relaxed);
+    if !ptr.is_null() {
+        call_slow(slot, alloc.addr(), kind);
+    }
+}
+
+#[cold]
+fn call_slow(slot: &AtomicPtr<()>, alloc_id: usize, kind: HandleKind) {
+    let ptr = slot.load(Acquire);
+    if !ptr.is_null() {
+        //SAFETY: Only `Hook`s are stored in the slots.
+        let hook = unsafe { mem::transmute::<*mut (), Hook>(ptr) };
+        hook(alloc_id, kind);
+    }
+}
+
+#[inline(always)]
+pub(crate) fn on_clone<T: ?Sized>(alloc: *const T, kind: HandleKind) {
+    call(&ON_CLONE, alloc, kind);
+}
+
+#[inline(always)]
+pub(crate) fn on_drop<T: ?Sized>(alloc: *const T, kind: HandleKind) {
+    call(&ON_DROP, alloc, kind);
+}
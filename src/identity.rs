@@ -0,0 +1,189 @@
+//! Serialization which preserves the sharing between `Trc`s.
+//!
+//! By default, a `Trc` is serialized as the data it points to, so a value shared by many `Trc`s is written once for each
+//! of them and deserialized into separate allocations. The functions in this module can be used with
+//! `#[serde(with = "trc::identity")]` to record the identity of each allocation instead. The first time an allocation is
+//! seen, it is written as `(id, Some(value))`, and every later occurrence is written as `(id, None)`. Deserializing
+//! resolves the ids back to clones of the same `Trc`.
+//!
+//! Ids are only shared between handles serialized or deserialized inside the same call to [`scope`]. Outside of a scope,
+//! every handle is written with its value. A `Trc` which contains itself, for example through a `RefCell`, cannot be
+//! represented and is reported as an error instead of recursing forever.
+//!
+//! This module is only available with the `serde` and `std` features.
+//!
+//! # Examples
+//! ```
+//! use serde::{Deserialize, Serialize};
+//! use trc::{identity, Trc};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Node {
+//!     #[serde(with = "identity")]
+//!     config: Trc<String>,
+//! }
+//!
+//! let config = Trc::new(String::from("shared"));
+//! let nodes = vec![
+//!     Node { config: config.clone() },
+//!     Node { config: config.clone() },
+//! ];
+//!
+//! let json = identity::scope(|| serde_json::to_string(&nodes)).unwrap();
+//! assert_eq!(json, r#"[{"config":[0,"shared"]},{"config":[0,null]}]"#);
+//!
+//! let nodes: Vec<Node> = identity::scope(|| serde_json::from_str(&json)).unwrap();
+//! assert!(Trc::ptr_eq(&nodes[0].config, &nodes[1].config));
+//! ```
+
+use alloc::boxed::Box;
+use core::{any::Any, cell::RefCell};
+use std::collections::HashMap;
+
+use serde::{
+    de::{self, Deserialize, Deserializer},
+    ser::{self, Serialize, SerializeTuple, Serializer},
+};
+
+use crate::Trc;
+
+#[derive(Default)]
+struct State {
+    //Allocation address -> (id, whether the value is still being serialized).
+    serialized: HashMap<usize, (u64, bool)>,
+    deserialized: HashMap<u64, Box<dyn Any>>,
+}
+
+std::thread_local! {
+    static STATE: RefCell<Option<State>> = const { RefCell::new(None) };
+}
+
+/// Clears the state when the outermost scope ends, even if it unwinds.
+struct ScopeGuard;
+
+impl Drop for ScopeGuard {
+    fn drop(&mut self) {
+        //Drop the deserialized handles after releasing the borrow, in case their destructors use this module.
+        let state = STATE.with(|state| state.borrow_mut().take());
+        drop(state);
+    }
+}
+
+/// Run `f` with identity tracking enabled on this thread, so that every `Trc` serialized or deserialized with this module
+/// inside `f` shares one table of ids. Nested calls use the table of the outermost scope.
+///
+/// # Examples
+/// ```
+/// use trc::{identity, Trc};
+///
+/// #[derive(serde::Serialize)]
+/// struct Pair(
+///     #[serde(with = "identity")] Trc<i32>,
+///     #[serde(with = "identity")] Trc<i32>,
+/// );
+///
+/// let trc = Trc::new(100);
+/// let pair = Pair(trc.clone(), trc);
+/// assert_eq!(serde_json::to_string(&pair).unwrap(), "[[0,100],[0,100]]");
+/// assert_eq!(identity::scope(|| serde_json::to_string(&pair)).unwrap(), "[[0,100],[0,null]]");
+/// ```
+pub fn scope<R>(f: impl FnOnce() -> R) -> R {
+    let outermost = STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        if state.is_some() {
+            return false;
+        }
+        *state = Some(State::default());
+        true
+    });
+    //`then_some` would create and immediately drop a guard for nested scopes, clearing the outer state.
+    let _guard = outermost.then(|| ScopeGuard);
+    f()
+}
+
+/// Serialize a `Trc`, writing its value only the first time its allocation is seen in the current [`scope`].
+///
+/// # Errors
+/// Returns an error if the value contains the `Trc` being serialized.
+pub fn serialize<T, S>(trc: &Trc<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: ?Sized + Serialize,
+    S: Serializer,
+{
+    scope(|| {
+        let addr = Trc::as_ptr(trc).addr();
+        let seen = STATE.with(|state| {
+            let mut state = state.borrow_mut();
+            let serialized = &mut state.as_mut().unwrap().serialized;
+            let next = serialized.len() as u64;
+            match serialized.get(&addr) {
+                Some(&(id, in_progress)) => Err((id, in_progress)),
+                None => {
+                    serialized.insert(addr, (next, true));
+                    Ok(next)
+                }
+            }
+        });
+
+        let mut tuple = serializer.serialize_tuple(2)?;
+        match seen {
+            Ok(id) => {
+                tuple.serialize_element(&id)?;
+                tuple.serialize_element(&Some(&**trc))?;
+                STATE.with(|state| {
+                    let mut state = state.borrow_mut();
+                    state.as_mut().unwrap().serialized.insert(addr, (id, false));
+                });
+            }
+            Err((_, true)) => {
+                return Err(ser::Error::custom(
+                    "Cannot serialize a `Trc` which contains itself.",
+                ));
+            }
+            Err((id, false)) => {
+                tuple.serialize_element(&id)?;
+                tuple.serialize_element(&None::<&T>)?;
+            }
+        }
+        tuple.end()
+    })
+}
+
+/// Deserialize a `Trc` written by [`serialize`], returning a clone of the same `Trc` for every occurrence of an id in the
+/// current [`scope`].
+///
+/// # Errors
+/// Returns an error if an id is referenced before its value, is defined twice, or is used for values of different types.
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Trc<T>, D::Error>
+where
+    T: Deserialize<'de> + 'static,
+    D: Deserializer<'de>,
+{
+    scope(|| {
+        let (id, value) = <(u64, Option<T>)>::deserialize(deserializer)?;
+        STATE.with(|state| {
+            let mut state = state.borrow_mut();
+            let deserialized = &mut state.as_mut().unwrap().deserialized;
+            match value {
+                Some(value) => {
+                    if deserialized.contains_key(&id) {
+                        return Err(de::Error::custom(format_args!(
+                            "Shared value {id} is defined more than once."
+                        )));
+                    }
+                    let trc = Trc::new(value);
+                    deserialized.insert(id, Box::new(trc.clone()));
+                    Ok(trc)
+                }
+                None => match deserialized.get(&id) {
+                    Some(trc) => trc.downcast_ref::<Trc<T>>().cloned().ok_or_else(|| {
+                        de::Error::custom(format_args!("Shared value {id} has a different type."))
+                    }),
+                    None => Err(de::Error::custom(format_args!(
+                        "Shared value {id} is referenced before it is defined."
+                    ))),
+                },
+            }
+        })
+    })
+}
@@ -0,0 +1,270 @@
+//! Backtraces of the live clones of `Trc`s and `SharedTrc`s, for finding what keeps a value alive.
+//!
+//! With the `leak-debug` feature, a backtrace is captured whenever a `Trc` or `SharedTrc` is cloned, and released when a
+//! handle is dropped. [`Trc::live_handle_traces`](crate::Trc::live_handle_traces) returns the backtraces of the clones of
+//! an allocation which are still alive, and [`report`] lists them for every allocation.
+//!
+//! The `Trc`s of a thread share one local count and cannot be told apart, so when one of them is dropped, the most recent
+//! backtrace of that thread's `Trc`s is released. Handles created by constructors and conversions, such as [`Trc::new`]
+//! or [`SharedTrc::from_trc`], are not recorded.
+//!
+//! Capturing backtraces is slow, so this feature is meant for debugging only.
+//!
+//! # Examples
+//! ```
+//! use trc::{leak_debug, Trc};
+//!
+//! //Print the handles which are still alive when `main` returns.
+//! let _report = leak_debug::report_on_exit();
+//!
+//! let trc = Trc::new(100);
+//! let leaked = trc.clone();
+//! std::mem::forget(leaked);
+//!
+//! assert_eq!(Trc::live_handle_traces(&trc).len(), 1);
+//! ```
+//!
+//! [`Trc::new`]: crate::Trc::new
+//! [`SharedTrc::from_trc`]: crate::SharedTrc::from_trc
+
+use alloc::{collections::BTreeMap, string::String, sync::Arc, vec::Vec};
+use core::{
+    fmt::{self, Debug, Display, Write},
+    ptr::NonNull,
+};
+use std::{backtrace::Backtrace, sync::Mutex};
+
+/// The backtrace of a live clone of a `Trc` or `SharedTrc`.
+#[derive(Clone)]
+pub struct HandleTrace {
+    shared: bool,
+    backtrace: Arc<Backtrace>,
+}
+
+impl HandleTrace {
+    /// Return whether the clone is a `SharedTrc`, rather than a `Trc`.
+    #[inline]
+    #[must_use]
+    pub fn is_shared(&self) -> bool {
+        return self.shared;
+    }
+
+    /// Return the backtrace of the clone.
+    #[inline]
+    pub fn backtrace(&self) -> &Backtrace {
+        return &self.backtrace;
+    }
+}
+
+impl Debug for HandleTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HandleTrace")
+            .field("shared", &self.shared)
+            .field("backtrace", &self.backtrace)
+            .finish()
+    }
+}
+
+impl Display for HandleTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = if self.shared { "SharedTrc" } else { "Trc" };
+        write!(f, "{kind} cloned at:\n{}", self.backtrace)
+    }
+}
+
+/// The local count a recorded `Trc` belongs to, or [`SHARED`] for a `SharedTrc`.
+type Group = usize;
+
+const SHARED: Group = 0;
+
+struct Record {
+    group: Group,
+    trace: HandleTrace,
+}
+
+struct Registry {
+    //The recorded clones of each allocation.
+    allocations: BTreeMap<usize, Vec<Record>>,
+    //The allocation of each local count with recorded clones.
+    groups: BTreeMap<Group, usize>,
+}
+
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry {
+    allocations: BTreeMap::new(),
+    groups: BTreeMap::new(),
+});
+
+fn with_registry<R>(f: impl FnOnce(&mut Registry) -> R) -> R {
+    let mut registry = REGISTRY
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    return f(&mut registry);
+}
+
+#[inline]
+fn group_of(threadref: Option<NonNull<usize>>) -> Group {
+    return threadref.map_or(SHARED, |threadref| threadref.as_ptr().addr());
+}
+
+/// Record a clone of the allocation at `alloc`, which is a `Trc` of the local count `threadref`, or a `SharedTrc` if it
+/// is `None`.
+pub(crate) fn record<T: ?Sized>(alloc: *const T, threadref: Option<NonNull<usize>>) {
+    let trace = HandleTrace {
+        shared: threadref.is_none(),
+        backtrace: Arc::new(Backtrace::force_capture()),
+    };
+    let alloc = alloc.addr();
+    let group = group_of(threadref);
+    with_registry(|registry| {
+        registry
+            .allocations
+            .entry(alloc)
+            .or_default()
+            .push(Record { group, trace });
+        if group != SHARED {
+            registry.groups.insert(group, alloc);
+        }
+    });
+}
+
+/// Release the most recent record of a handle to the allocation at `alloc` which was dropped. See [`record`].
+pub(crate) fn release<T: ?Sized>(alloc: *const T, threadref: Option<NonNull<usize>>) {
+    let alloc = alloc.addr();
+    let group = group_of(threadref);
+    let trace = with_registry(|registry| {
+        let records = registry.allocations.get_mut(&alloc)?;
+        let i = records.iter().rposition(|record| record.group == group)?;
+        let record = records.remove(i);
+        if records.is_empty() {
+            registry.allocations.remove(&alloc);
+        }
+        Some(record)
+    });
+    //Free the backtrace outside of the lock.
+    drop(trace);
+}
+
+/// Release the records of a local count which was freed, so that they are not attributed to a new local count at the
+/// same address.
+pub(crate) fn release_group(threadref: NonNull<usize>) {
+    let group = group_of(Some(threadref));
+    let records = with_registry(|registry| {
+        let alloc = registry.groups.remove(&group)?;
+        let records = registry.allocations.get_mut(&alloc)?;
+        let (released, kept) = records.drain(..).partition(|record| record.group == group);
+        *records = kept;
+        if records.is_empty() {
+            registry.allocations.remove(&alloc);
+        }
+        Some::<Vec<Record>>(released)
+    });
+    drop(records);
+}
+
+/// Release all records of the allocation at `alloc`, which was freed.
+pub(crate) fn release_all<T: ?Sized>(alloc: *const T) {
+    let alloc = alloc.addr();
+    let records = with_registry(|registry| {
+        let records = registry.allocations.remove(&alloc)?;
+        for record in &records {
+            if record.group != SHARED {
+                registry.groups.remove(&record.group);
+            }
+        }
+        Some(records)
+    });
+    drop(records);
+}
+
+/// Return the backtraces of the live clones of the allocation at `alloc`, from the oldest to the most recent.
+pub(crate) fn traces<T: ?Sized>(alloc: *const T) -> Vec<HandleTrace> {
+    let alloc = alloc.addr();
+    return with_registry(|registry| {
+        registry
+            .allocations
+            .get(&alloc)
+            .map_or_else(Vec::new, |records| {
+                records.iter().map(|record| record.trace.clone()).collect()
+            })
+    });
+}
+
+/// Return a report of the live clones of every allocation, or an empty string if there are none.
+///
+/// # Examples
+/// ```
+/// use trc::{leak_debug, Trc};
+///
+/// let trc = Trc::new(100);
+/// let trc2 = trc.clone();
+/// assert!(leak_debug::report().contains("1 live clone"));
+/// ```
+#[must_use]
+pub fn report() -> String {
+    let allocations: Vec<(usize, Vec<HandleTrace>)> = with_registry(|registry| {
+        registry
+            .allocations
+            .iter()
+            .map(|(&alloc, records)| {
+                let traces = records.iter().map(|record| record.trace.clone()).collect();
+                (alloc, traces)
+            })
+            .collect()
+    });
+
+    let mut report = String::new();
+    for (alloc, traces) in allocations {
+        let plural = if traces.len() == 1 { "" } else { "s" };
+        let _ = writeln!(
+            report,
+            "Allocation {alloc:#x} has {} live clone{plural}:",
+            traces.len()
+        );
+        for trace in traces {
+            let _ = writeln!(report, "{trace}");
+        }
+    }
+    return report;
+}
+
+/// Print the [`report`] to standard error, if there are any live clones.
+///
+/// # Examples
+/// ```
+/// use trc::leak_debug;
+///
+/// leak_debug::print_report();
+/// ```
+pub fn print_report() {
+    let report = report();
+    if !report.is_empty() {
+        std::eprint!("{report}");
+    }
+}
+
+/// Return a guard which calls [`print_report`] when it is dropped. Keep it in a variable at the start of `main` to print
+/// the handles which are still alive when the program exits.
+///
+/// # Examples
+/// ```
+/// use trc::leak_debug;
+///
+/// fn main() {
+///     let _report = leak_debug::report_on_exit();
+///     //...
+/// }
+/// ```
+#[must_use = "The report is printed when the guard is dropped."]
+pub fn report_on_exit() -> ReportOnExit {
+    return ReportOnExit(());
+}
+
+/// Prints the [`report`] when it is dropped. It is created by [`report_on_exit`].
+#[derive(Debug)]
+pub struct ReportOnExit(());
+
+impl Drop for ReportOnExit {
+    fn drop(&mut self) {
+        print_report();
+    }
+}
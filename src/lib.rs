@@ -19,63 +19,361 @@
 //! Because `Trc` is not part of the standard library,
 //! the `CoerceUnsized` and `Receiver` traits cannot currently be implemented by default.
 //! However, `Trc` provides `dyn_unstable` trait which enables the above traits for
-//! `Trc` and `SharedTrc` and must be used with nightly Rust (`cargo +nightly ...`).
+//! `Trc`, `SharedTrc` and `Weak` and must be used with nightly Rust (`cargo +nightly ...`).
+//! Similarly, the nightly-only `fn_traits` feature implements `Fn`, `FnMut` and `FnOnce` for `Trc<F>` and `SharedTrc<F>`,
+//! so that a shared closure can be passed anywhere a closure is expected.
+//! The nightly-only `allocator_api` feature makes `Trc`, `SharedTrc` and `Weak` generic over an `Allocator`, like `Arc`,
+//! and adds `new_in`, `try_new_in`, `new_uninit_in`, `pin_in` and `allocator`. The allocator is stored in the allocation,
+//! and the other methods are only available with the default `Global` allocator.
+//! The nightly-only `dropck_eyepatch` feature marks the value as `#[may_dangle]` in the `Drop` implementations of `Trc`,
+//! `SharedTrc` and `Weak`, like `Arc` does. A handle to a value which borrows from a local variable may then be declared
+//! before that variable, which the borrow checker otherwise rejects.
+//!
+//! ## `no_std` support
+//! `Trc` only requires `alloc` and atomics. The `std` feature is enabled by default, and disabling it (`default-features = false`)
+//! makes the crate `no_std`. Without `std`, the file descriptor and handle traits are not implemented, and the [`Error`]
+//! implementations only delegate [`source`](core::error::Error::source).
+//!
+//! On targets without native pointer-sized atomics, such as `thumbv6m-none-eabi` or AVR, the optional `portable-atomic`
+//! feature uses the atomics of [`portable-atomic`](https://docs.rs/portable-atomic) instead. These are usually
+//! implemented with critical sections, so one of its `critical-section` feature or `unsafe-assume-single-core` cfg must
+//! also be enabled. The conversions to and from [`Arc`](alloc::sync::Arc) are not available on such targets.
+//!
+//! ## Serialization
+//! The optional `serde` feature implements `Serialize` and `Deserialize` for `Trc` and `SharedTrc`, including the unsized
+//! `str`, `[T]` and `CStr` targets. Values are serialized as the data they point to, and deserializing always creates a new
+//! allocation. Strings are deserialized directly into the new allocation, and the [`as_bytes`] module serializes byte slices
+//! as byte buffers. With the `std` feature, the [`identity`] module can be used to preserve sharing between `Trc`s instead.
+//!
+//! The optional `rkyv` feature supports zero-copy archiving through the [`archive`] module.
+//!
+//! ## Collecting cycles
+//! With the optional `cycles` feature, values created with `Trc::new_traced` are tracked by a cycle collector, and
+//! `collect_cycles` reclaims the ones which are only kept alive by reference cycles. See the `cycles` module.
+//! The optional `debug-graph` feature adds the `debug` module, which dumps the graph of references between these values
+//! and finds the cycles in it.
+//!
+//! ## Finding leaks
+//! The optional `leak-debug` feature captures a backtrace whenever a `Trc` or `SharedTrc` is cloned, so that the handles
+//! keeping a value alive can be found with `Trc::live_handle_traces`. See the `leak_debug` module.
+//!
+//! The optional `stats` feature counts the live allocations and the bytes of their values in global atomics, which are
+//! returned by `stats()`, for monitoring leaks without a heap profiler.
+//!
+//! ## Immortal values
+//! With the optional `immortals` feature, `Trc::new_immortal` and `SharedTrc::new_immortal` create values which are never
+//! dropped. Cloning and dropping handles to them does not change their atomic reference count, which avoids contention
+//! on values shared by many threads. Enabling the feature adds a check to every atomic reference count update.
+//!
+//! ## Arena allocation
+//! The optional `arena` feature adds the `arena` module, whose `TrcArena` places many small values in large chunks
+//! instead of making an allocation for each of them. The chunks are freed once the arena and all handles to their values
+//! are gone.
+//!
+//! ## Reference count overflow
+//! A reference count can only exceed `isize::MAX` if handles are leaked, for example with [`mem::forget`]. As with `Arc`,
+//! the process is then aborted, because a panic could unwind after the count was incremented and let it wrap around.
+//! The optional `overflow-panic` feature panics instead, and the optional `overflow-saturate` feature pins the count at
+//! a value it never leaves, which leaks the value. If both are enabled, the count is saturated.
+//!
+//! ## Debugging memory orderings
+//! The optional `seqcst` feature makes every atomic operation and fence in the crate `SeqCst`. If a bug still happens
+//! with it, it is not caused by a weak memory ordering. This is only meant for debugging, as it slows down every update
+//! of the atomic counts.
+//!
+//! ## Single-threaded programs
+//! With the optional `single-threaded` feature, the atomic counts are replaced by plain cells, which avoids the cost of
+//! atomic operations on targets such as `wasm32-unknown-unknown`, where the program never spawns a thread. On the `wasm32`
+//! targets without the `atomics` target feature, no other thread can exist, so the API stays the same: the handles are
+//! still `Send` and `Sync`, and `StaticTrc`, `LazyTrc` and `OnceTrc` can be placed in `static`s.
+//!
+//! The feature may also be enabled on targets with threads, for programs which never share their handles between
+//! threads. There, the following are not available:
+//! - `Send` and `Sync` for `SharedTrc`, `Weak`, `ThinSharedTrc`, `ProjectedSharedTrc`, `UniqueTrc`, `TrcArena` and
+//!   `LazyTrc`, so these cannot be sent to other threads, and `LazyTrc` and `OnceTrc` cannot be placed in `static`s.
+//! - `StaticTrc`, with `Trc::from_static` and `SharedTrc::from_static`.
+//! - The `wake` module.
+//! - `Trc::share_scoped`.
+//! - The conversions of `Trc<[u8]>`, `SharedTrc<[u8]>` and `TrcBuf` into `Bytes`, with the `bytes` feature.
+//!
+//! The doctests, unit tests and benchmarks which use threads are skipped with this feature, and the rest of them run.
+//!
+//! ## Model checking with loom
+//! When built with `--cfg loom`, the atomic counts, fences and allocations of the crate come from
+//! [`loom`](https://docs.rs/loom), which runs the tests in `src/loom_tests.rs` under every interleaving of their threads
+//! and reports leaks and uses after free:
+//! ```text
+//! RUSTFLAGS="--cfg loom" cargo test --lib --release loom
+//! ```
+//! `StaticTrc` and the per-thread table of local counts are not available in this configuration.
+//!
+//! ## Padding the counts
+//! With the optional `padded` feature, the reference counts are placed on their own cache line, away from the data. This
+//! avoids false sharing between threads which clone and drop `SharedTrc`s and threads which read a small value, at the
+//! cost of up to 128 bytes per allocation. The `Shared churn Trc` benchmark compares both layouts.
 
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
+#![cfg_attr(feature = "dropck_eyepatch", feature(dropck_eyepatch))]
 #![cfg_attr(feature = "dyn_unstable", feature(unsize))]
 #![cfg_attr(feature = "dyn_unstable", feature(coerce_unsized))]
-#![cfg_attr(feature = "dyn_unstable", feature(receiver_trait))]
 #![cfg_attr(feature = "dyn_unstable", feature(dispatch_from_dyn))]
+#![cfg_attr(all(test, feature = "dyn_unstable"), feature(arbitrary_self_types))]
+#![cfg_attr(
+    feature = "fn_traits",
+    feature(fn_traits, unboxed_closures, tuple_trait)
+)]
+#![allow(clippy::needless_return)]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+/// Implement `Drop` for a handle. With the `dropck_eyepatch` feature, `T` is marked `#[may_dangle]` like in `Arc`, as
+/// the handles only ever drop their value while being dropped, and never read it.
+macro_rules! impl_drop {
+    (impl<$t:ident $(, $param:ident: $bound:path)*> Drop for $ty:ty { $($body:tt)* }) => {
+        #[cfg(not(feature = "dropck_eyepatch"))]
+        impl<$t: ?Sized $(, $param: $bound)*> Drop for $ty {
+            $($body)*
+        }
+
+        #[cfg(feature = "dropck_eyepatch")]
+        unsafe impl<#[may_dangle] $t: ?Sized $(, $param: $bound)*> Drop for $ty {
+            $($body)*
+        }
+    };
+}
 
+/// Keep items which need the shared handles to be `Send` and `Sync`, unless the `single-threaded` feature replaced the
+/// atomic counts with cells on a target which has threads. On targets without threads, such as `wasm32-unknown-unknown`,
+/// the handles stay `Send` and `Sync`, as no other thread can access the cells.
+macro_rules! if_thread_safe {
+    ($($item:item)*) => {
+        $(
+            #[cfg(any(
+                not(feature = "single-threaded"),
+                all(target_family = "wasm", not(target_feature = "atomics"))
+            ))]
+            $item
+        )*
+    };
+}
+
+#[cfg(all(test, loom))]
+mod loom_tests;
 #[deny(clippy::all)]
-#[cfg(test)]
+#[cfg(all(test, feature = "std", not(loom)))]
 mod tests;
 
-#[cfg(not(target_has_atomic = "ptr"))]
-compile_error!("Cannot use `Trc` on a system without atomics.");
+#[cfg(feature = "allocator_api")]
+mod allocator;
+#[cfg(feature = "rkyv")]
+pub mod archive;
+#[cfg(feature = "arena")]
+pub mod arena;
+#[cfg(feature = "serde")]
+pub mod as_bytes;
+mod atomic;
+#[cfg(feature = "bytes")]
+pub mod buf;
+mod by_ptr;
+mod cell;
+mod cow;
+#[cfg(feature = "cycles")]
+pub mod cycles;
+#[cfg(feature = "debug-graph")]
+pub mod debug;
+mod finalizer;
+mod from_self;
+mod header;
+#[cfg(feature = "hooks")]
+pub mod hooks;
+#[cfg(all(feature = "serde", feature = "std"))]
+pub mod identity;
+#[cfg(feature = "leak-debug")]
+pub mod leak_debug;
+#[cfg(all(feature = "std", not(loom)))]
+mod local_counts;
+#[cfg(feature = "std")]
+mod once;
+mod ordering;
+mod overflow;
+mod pool;
+mod projected;
+pub mod rope;
+if_thread_safe! {
+    #[cfg(feature = "std")]
+    mod scoped;
+    #[cfg(not(loom))]
+    mod static_trc;
+}
+#[cfg(feature = "stats")]
+mod stats;
+mod sync;
+mod thin;
+mod trc_bytes;
+mod trc_slice;
+mod trc_str;
+mod unique;
+#[cfg(feature = "std")]
+mod wait;
+if_thread_safe! {
+    pub mod wake;
+}
 
-use std::{
-    alloc::{alloc, Layout},
+pub use atomic::AtomicSharedTrc;
+pub use by_ptr::ByPtr;
+pub use cell::TrcCell;
+pub use cow::TrcCow;
+#[cfg(feature = "cycles")]
+pub use cycles::collect_cycles;
+pub use finalizer::Finalized;
+pub use from_self::{SelfRef, TrcFromSelf};
+pub use header::HeaderSlice;
+#[cfg(feature = "std")]
+pub use once::{LazyTrc, OnceTrc};
+pub use pool::TrcPool;
+pub use projected::{ProjectedSharedTrc, ProjectedTrc, ProjectedWeak};
+if_thread_safe! {
+    #[cfg(not(loom))]
+    pub use static_trc::StaticTrc;
+}
+#[cfg(feature = "stats")]
+pub use stats::{stats, TrcStats};
+pub use thin::{ThinSharedTrc, ThinTrc};
+pub use trc_bytes::TrcBytes;
+pub use trc_slice::TrcSlice;
+pub use trc_str::TrcStr;
+pub use unique::UniqueTrc;
+#[cfg(feature = "std")]
+pub use wait::{IntoInnerAsync, TimeoutError, WaitUnique};
+
+#[cfg(feature = "hooks")]
+use hooks::HandleKind;
+
+#[cfg(all(
+    not(target_has_atomic = "ptr"),
+    not(feature = "portable-atomic"),
+    not(feature = "single-threaded")
+))]
+compile_error!("Cannot use `Trc` on a system without atomics. Enable the `portable-atomic` feature to emulate them.");
+
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+use alloc::{
+    alloc::{handle_alloc_error, Layout, LayoutError},
+    borrow::Cow,
+    boxed::Box,
+    rc::Rc,
+    string::String,
+    vec::Vec,
+};
+use core::{
+    any::{type_name, Any, TypeId},
     borrow::Borrow,
     cmp,
+    convert::Infallible,
     error::Error,
+    ffi::CStr,
     fmt::{self, Debug, Display, Pointer},
     hash::{Hash, Hasher},
-    mem::{forget, ManuallyDrop, MaybeUninit},
-    ops::Deref,
-    panic::UnwindSafe,
+    marker::PhantomData,
+    mem::{self, forget, ManuallyDrop, MaybeUninit},
+    ops::{Bound, Deref, Range, RangeBounds},
+    panic::{RefUnwindSafe, UnwindSafe},
     pin::Pin,
     ptr::{self, addr_of, addr_of_mut, slice_from_raw_parts_mut, write, NonNull},
-    sync::atomic::{
-        fence, AtomicUsize,
-        Ordering::{self, AcqRel, Acquire, Relaxed, Release},
-    },
+    str::FromStr,
+    sync::atomic::Ordering,
 };
+use ordering::{AcqRel, Acquire, Relaxed, Release};
+use sync::{alloc, alloc_zeroed, dealloc, fence, AtomicUsize};
+
+#[cfg(feature = "std")]
+use std::io::{self, IoSlice, IoSliceMut, Read, Seek, SeekFrom, Write};
+#[cfg(feature = "std")]
+use std::path::{Path, PathBuf};
 
-#[cfg(not(target_os = "windows"))]
+#[cfg(all(feature = "std", any(unix, target_os = "wasi")))]
 use std::os::fd::{AsFd, AsRawFd};
 
-#[cfg(target_os = "windows")]
+#[cfg(all(feature = "std", target_os = "windows"))]
 use std::os::windows::io::{AsHandle, AsRawHandle, AsRawSocket, AsSocket};
 
+#[cfg(feature = "allocator_api")]
+use alloc::alloc::{Allocator, Global};
 #[cfg(feature = "dyn_unstable")]
-use std::any::Any;
-#[cfg(feature = "dyn_unstable")]
-use std::ops;
-
+use core::ops;
+
+#[cfg(feature = "arbitrary")]
+use arbitrary::{Arbitrary, MaxRecursionReached, Unstructured};
+#[cfg(feature = "proptest")]
+use proptest::{
+    arbitrary::{any_with, SMapped},
+    strategy::statics,
+};
+#[cfg(feature = "rayon")]
+use rayon::{
+    iter::{IntoParallelIterator, IntoParallelRefIterator},
+    slice::ParallelSlice,
+};
 #[cfg(feature = "serde")]
-use serde::{Deserialize, Serialize};
+use serde::{
+    de::{Error as DeError, Unexpected, Visitor},
+    Deserialize, Serialize,
+};
 #[cfg(feature = "stable_deref_trait")]
 use stable_deref_trait::{CloneStableDeref, StableDeref};
+#[cfg(feature = "unsize")]
+use unsize::CoerciblePtr;
 
 const MAX_REFCOUNT: usize = (isize::MAX) as usize;
 
+/// The atomic count of an immortal allocation, which is never changed.
+#[cfg(feature = "immortals")]
+const IMMORTAL: usize = usize::MAX;
+
+/// The address of a dangling `Weak` created by [`Weak::new`], which has no allocation.
+const DANGLING: usize = usize::MAX;
+
+#[inline(always)]
+fn is_dangling<T: ?Sized>(ptr: *const T) -> bool {
+    return ptr.cast::<()>().addr() == DANGLING;
+}
+
 #[repr(C)]
-struct SharedTrcInternal<T: ?Sized> {
+struct SharedTrcInternal<T: ?Sized, #[cfg(feature = "allocator_api")] A: Allocator = Global> {
     atomicref: AtomicUsize,
     weakcount: AtomicUsize,
+    //The allocator is stored in the allocation, so that every handle can free it.
+    #[cfg(feature = "allocator_api")]
+    alloc: A,
+    #[cfg(feature = "padded")]
+    padding: CachePadding,
     data: T,
 }
 
+/// An empty field aligned to a cache line, which places the data of a `SharedTrcInternal` on a different cache line than
+/// the counts with the `padded` feature. Where cache lines are prefetched in pairs, two lines are used.
+#[cfg(feature = "padded")]
+#[cfg_attr(
+    any(
+        target_arch = "x86_64",
+        target_arch = "aarch64",
+        target_arch = "powerpc64"
+    ),
+    repr(align(128))
+)]
+#[cfg_attr(
+    not(any(
+        target_arch = "x86_64",
+        target_arch = "aarch64",
+        target_arch = "powerpc64"
+    )),
+    repr(align(64))
+)]
+#[derive(Clone, Copy)]
+struct CachePadding;
+
 /// `Trc` is a performant heap-allocated smart pointer that implements thread reference counting.
 /// `Trc` stands for: Thread Reference Counted.
 /// `Trc` provides shared ownership of the data similar to `Arc<T>` and `Rc<T>`.
@@ -123,7 +421,8 @@ struct SharedTrcInternal<T: ?Sized> {
 /// ```
 ///
 /// Example with multiple threads:
-/// ```
+#[cfg_attr(feature = "single-threaded", doc = "```ignore")]
+#[cfg_attr(not(feature = "single-threaded"), doc = "```")]
 /// use std::thread;
 /// use trc::Trc;
 /// use trc::SharedTrc;
@@ -138,9 +437,17 @@ struct SharedTrcInternal<T: ?Sized> {
 /// assert_eq!(*trc, 100);
 /// ```
 ///
-pub struct Trc<T: ?Sized> {
+pub struct Trc<T: ?Sized, #[cfg(feature = "allocator_api")] A: Allocator = Global> {
+    #[cfg(not(feature = "allocator_api"))]
     shared: NonNull<SharedTrcInternal<T>>,
+    #[cfg(feature = "allocator_api")]
+    shared: NonNull<SharedTrcInternal<T, A>>,
     threadref: NonNull<usize>,
+    //Tells the drop checker that a `Trc` may drop a `T`.
+    #[cfg(not(feature = "allocator_api"))]
+    phantom: PhantomData<SharedTrcInternal<T>>,
+    #[cfg(feature = "allocator_api")]
+    phantom: PhantomData<SharedTrcInternal<T, A>>,
 }
 
 /// `SharedTrc` is a thread-safe wrapper used to send `Trc`s across threads.
@@ -187,8 +494,16 @@ pub struct Trc<T: ?Sized> {
 /// ```
 ///
 /// See [`Trc`] or [`Weak`] for an example with multiple threads.
-pub struct SharedTrc<T: ?Sized> {
+pub struct SharedTrc<T: ?Sized, #[cfg(feature = "allocator_api")] A: Allocator = Global> {
+    #[cfg(not(feature = "allocator_api"))]
     data: NonNull<SharedTrcInternal<T>>,
+    #[cfg(feature = "allocator_api")]
+    data: NonNull<SharedTrcInternal<T, A>>,
+    //Tells the drop checker that a `SharedTrc` may drop a `T`.
+    #[cfg(not(feature = "allocator_api"))]
+    phantom: PhantomData<SharedTrcInternal<T>>,
+    #[cfg(feature = "allocator_api")]
+    phantom: PhantomData<SharedTrcInternal<T, A>>,
 }
 
 /// `Weak` is a non-owning reference to `Trc`'s data. It is used to prevent cyclic references which cause memory to never be freed.
@@ -228,7 +543,8 @@ pub struct SharedTrc<T: ?Sized> {
 /// ```
 ///
 /// Example with multiple threads:
-/// ```
+#[cfg_attr(feature = "single-threaded", doc = "```ignore")]
+#[cfg_attr(not(feature = "single-threaded"), doc = "```")]
 /// use std::thread;
 /// use trc::Trc;
 /// use trc::SharedTrc;
@@ -243,8 +559,11 @@ pub struct SharedTrc<T: ?Sized> {
 /// assert_eq!(*trc, 100);
 /// ```
 ///
-pub struct Weak<T: ?Sized> {
+pub struct Weak<T: ?Sized, #[cfg(feature = "allocator_api")] A: Allocator = Global> {
+    #[cfg(not(feature = "allocator_api"))]
     data: NonNull<SharedTrcInternal<T>>,
+    #[cfg(feature = "allocator_api")]
+    data: NonNull<SharedTrcInternal<T, A>>,
 }
 
 impl<T: ?Sized> SharedTrc<T> {
@@ -263,18 +582,27 @@ impl<T: ?Sized> SharedTrc<T> {
     #[inline]
     #[must_use]
     pub fn from_trc(trc: &Trc<T>) -> Self {
-        let prev = sum_value(&unsafe { trc.shared.as_ref() }.atomicref, 1, Acquire);
-        assert!(
-            prev <= MAX_REFCOUNT,
-            "Overflow of maximum atomic reference count."
-        );
-        Self { data: trc.shared }
+        //A new reference is made from an existing one, which keeps the value alive, so no synchronization is needed.
+        let prev = sum_value(&unsafe { trc.shared.as_ref() }.atomicref, 1, Relaxed);
+        if prev > MAX_REFCOUNT {
+            overflow::atomic(
+                &unsafe { trc.shared.as_ref() }.atomicref,
+                "Overflow of maximum atomic reference count.",
+            );
+        }
+        Self {
+            data: trc.shared,
+            phantom: PhantomData,
+        }
     }
 
     /// Convert a `SharedTrc` to a `Trc`. To prevent memory leaks, this function takes
     /// ownership of the `SharedTrc`. Unlike [`Trc::upgrade`], this function will not fail as it
     /// prevents the data from being dropped.
     ///
+    /// If a `Trc` created by this function (or by [`Weak::upgrade`]) to the same value is still alive on this thread,
+    /// the new `Trc` shares its local thread reference count, and the atomic reference count is not incremented.
+    ///
     /// # Examples
     /// ```
     /// use trc::Trc;
@@ -284,14 +612,50 @@ impl<T: ?Sized> SharedTrc<T> {
     /// let shared = SharedTrc::from_trc(&trc);
     /// drop(trc);
     /// let trc2 = SharedTrc::to_trc(shared);
+    ///
+    /// let trc3 = SharedTrc::to_trc(SharedTrc::from_trc(&trc2));
+    /// assert_eq!(Trc::local_count(&trc3), 2);
+    /// assert_eq!(Trc::atomic_count(&trc3), 1);
     /// ```
     #[must_use]
     pub fn to_trc(this: Self) -> Trc<T> {
-        let tbx = Box::new(1);
+        //If this thread already has a `Trc` created this way, the new one joins its local count, and the atomic
+        //reference of `this` is released.
+        //This is a conversion, so neither a clone nor a drop is reported to the hooks.
+        #[cfg(all(feature = "std", not(loom)))]
+        if let Some(mut threadref) = local_counts::find(this.data.as_ptr()) {
+            let count = unsafe { threadref.as_mut() };
+            *count += 1;
+            if *count > MAX_REFCOUNT {
+                overflow::local(count, "Overflow of maximum local reference count.");
+            }
+            #[cfg(feature = "leak-debug")]
+            {
+                leak_debug::release(this.data.as_ptr(), None);
+                leak_debug::record(this.data.as_ptr(), Some(threadref));
+            }
+
+            //The local count already holds an atomic reference, so this is never the last one.
+            let prev = sub_value(&unsafe { this.data.as_ref() }.atomicref, 1, Release);
+            wait::on_release(prev, this.data.as_ptr());
+            let res = Trc {
+                shared: this.data,
+                threadref,
+                phantom: PhantomData,
+            };
+            forget(this);
+            return res;
+        }
+
+        #[cfg(feature = "leak-debug")]
+        leak_debug::release(this.data.as_ptr(), None);
         let res = Trc {
-            threadref: NonNull::from(Box::leak(tbx)),
+            threadref: new_threadref(),
             shared: this.data,
+            phantom: PhantomData,
         };
+        #[cfg(all(feature = "std", not(loom)))]
+        local_counts::register(res.shared.as_ptr(), res.threadref);
         forget(this);
         res
     }
@@ -299,7 +663,8 @@ impl<T: ?Sized> SharedTrc<T> {
     /// Return the atomic reference count of the object. This is how many threads are using the data referenced by this `SharedTrc`.
     ///
     /// # Examples
-    /// ```
+    #[cfg_attr(feature = "single-threaded", doc = "```ignore")]
+    #[cfg_attr(not(feature = "single-threaded"), doc = "```")]
     /// use std::thread;
     /// use trc::Trc;
     /// use trc::SharedTrc;
@@ -322,8 +687,26 @@ impl<T: ?Sized> SharedTrc<T> {
     }
 }
 
-#[cfg(feature = "dyn_unstable")]
 impl SharedTrc<dyn Any + Send + Sync> {
+    /// Creates a new `SharedTrc<dyn Any + Send + Sync>` holding `value`. This performs the unsizing coercion internally, so
+    /// it does not require the `dyn_unstable` feature.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::SharedTrc;
+    ///
+    /// let shared = SharedTrc::new_any(100i32);
+    /// assert!(shared.is::<i32>());
+    /// ```
+    #[must_use]
+    pub fn new_any<T: Any + Send + Sync>(value: T) -> Self {
+        let shared = ManuallyDrop::new(SharedTrc::new(value));
+        return Self {
+            data: shared.data,
+            phantom: PhantomData,
+        };
+    }
+
     /// Attempts to downcast a `SharedTrc<dyn Any + Send + Sync>` into a concrete type.
     ///
     /// # Examples
@@ -339,8 +722,8 @@ impl SharedTrc<dyn Any + Send + Sync> {
     /// }
     ///
     /// let my_string = "Hello World".to_string();
-    /// let a: Trc<dyn Any + Send + Sync> = Trc::new(my_string);
-    /// let b: Trc<dyn Any + Send + Sync> = Trc::new(0i8);
+    /// let a = Trc::new_any(my_string);
+    /// let b = Trc::new_any(0i8);
     /// print_if_string(SharedTrc::from_trc(&a));
     /// print_if_string(SharedTrc::from_trc(&b));
     /// ```
@@ -351,13 +734,43 @@ impl SharedTrc<dyn Any + Send + Sync> {
         if (*self).is::<T>() {
             let data = self.data.cast::<SharedTrcInternal<T>>();
             forget(self);
-            Ok(SharedTrc { data })
+            Ok(SharedTrc {
+                data,
+                phantom: PhantomData,
+            })
         } else {
             Err(self)
         }
     }
+
+    /// Attempts to downcast a `SharedTrc<dyn Any + Send + Sync>` into a concrete type. Unlike [`SharedTrc::downcast`],
+    /// a failed downcast returns a [`DowncastError`] which describes the expected and actual types, and still owns the `SharedTrc`.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::any::{Any, TypeId};
+    /// use trc::Trc;
+    /// use trc::SharedTrc;
+    ///
+    /// let a = Trc::new_any(0i8);
+    /// let err = SharedTrc::from_trc(&a).downcast_checked::<String>().unwrap_err();
+    /// assert_eq!(err.expected_type_id(), TypeId::of::<String>());
+    /// assert_eq!(err.actual_type_id(), TypeId::of::<i8>());
+    ///
+    /// let shared = err.into_inner();
+    /// assert_eq!(*shared.downcast_checked::<i8>().unwrap(), 0);
+    /// ```
+    pub fn downcast_checked<T>(self) -> Result<SharedTrc<T>, DowncastError<Self>>
+    where
+        T: Any + Send + Sync,
+    {
+        let actual = (*self).type_id();
+        self.downcast::<T>()
+            .map_err(|value| DowncastError::new::<T>(value, actual))
+    }
 }
 
+#[cfg(not(feature = "allocator_api"))]
 impl<T: ?Sized> Clone for SharedTrc<T> {
     /// Clone a `SharedTrc` (increment the atomic count).
     ///
@@ -373,25 +786,41 @@ impl<T: ?Sized> Clone for SharedTrc<T> {
     /// ```
     #[inline]
     fn clone(&self) -> Self {
-        let prev = sum_value(&unsafe { self.data.as_ref() }.atomicref, 1, AcqRel);
-        assert!(
-            prev <= MAX_REFCOUNT,
-            "Overflow of maximum atomic reference count."
-        );
-        Self { data: self.data }
+        //As in `Arc::clone`, the existing reference keeps the value alive, so the increment needs no synchronization.
+        let prev = sum_value(&unsafe { self.data.as_ref() }.atomicref, 1, Relaxed);
+        if prev > MAX_REFCOUNT {
+            overflow::atomic(
+                &unsafe { self.data.as_ref() }.atomicref,
+                "Overflow of maximum atomic reference count.",
+            );
+        }
+        #[cfg(feature = "hooks")]
+        hooks::on_clone(self.data.as_ptr(), HandleKind::SharedTrc);
+        #[cfg(feature = "leak-debug")]
+        leak_debug::record(self.data.as_ptr(), None);
+        Self {
+            data: self.data,
+            phantom: PhantomData,
+        }
     }
 }
 
-impl<T: ?Sized> Drop for SharedTrc<T> {
-    #[inline]
-    fn drop(&mut self) {
-        if sub_value(unsafe { &(*self.data.as_ptr()).atomicref }, 1, Release) != 1 {
-            return;
-        }
+#[cfg(not(feature = "allocator_api"))]
+impl_drop! {
+    impl<T> Drop for SharedTrc<T> {
+        #[inline]
+        fn drop(&mut self) {
+            #[cfg(feature = "hooks")]
+            hooks::on_drop(self.data.as_ptr(), HandleKind::SharedTrc);
+            #[cfg(feature = "leak-debug")]
+            leak_debug::release(self.data.as_ptr(), None);
+            let prev = sub_value(unsafe { &(*self.data.as_ptr()).atomicref }, 1, AcqRel);
+            #[cfg(feature = "std")]
+            wait::on_release(prev, self.data.as_ptr());
+            if prev != 1 {
+                return;
+            }
 
-        let weak = unsafe { &(*self.data.as_ptr()).weakcount }.load(Acquire);
-        if weak == 1 {
-            fence(Acquire);
             unsafe { ptr::drop_in_place(addr_of_mut!((*self.data.as_ptr()).data)) };
             Weak { data: self.data };
         }
@@ -403,6 +832,9 @@ impl<T: ?Sized> From<SharedTrc<T>> for Trc<T> {
     /// ownership of the `SharedTrc`. Unlike [`Weak::to_trc`], this function will not fail as it
     /// prevents the data from being dropped.
     ///
+    /// If a `Trc` created by this function (or by [`Weak::upgrade`]) to the same value is still alive on this thread,
+    /// the new `Trc` shares its local thread reference count, and the atomic reference count is not incremented.
+    ///
     /// # Examples
     /// ```
     /// use trc::Trc;
@@ -412,6 +844,10 @@ impl<T: ?Sized> From<SharedTrc<T>> for Trc<T> {
     /// let shared = SharedTrc::from_trc(&trc);
     /// drop(trc);
     /// let trc2 = SharedTrc::to_trc(shared);
+    ///
+    /// let trc3 = SharedTrc::to_trc(SharedTrc::from_trc(&trc2));
+    /// assert_eq!(Trc::local_count(&trc3), 2);
+    /// assert_eq!(Trc::atomic_count(&trc3), 1);
     /// ```
     fn from(value: SharedTrc<T>) -> Self {
         SharedTrc::to_trc(value)
@@ -437,26 +873,209 @@ impl<T: ?Sized> From<&Trc<T>> for SharedTrc<T> {
 }
 
 impl<T: ?Sized> From<Trc<T>> for SharedTrc<T> {
-    /// Convert a `Trc<T>` to a `SharedTrc<T>`, incrementing it's atomic reference count.
+    /// Convert a `Trc<T>` to a `SharedTrc<T>`, consuming the `Trc<T>`.
     /// While this `SharedTrc<T>` is alive, the data contained by `Trc<T>` will not be dropped, which is
     /// unlike a `Weak<T>`.
     ///
+    /// If this is the last `Trc` in the thread, its atomic reference is transferred to the `SharedTrc` and no atomic
+    /// operation is performed. Otherwise, the atomic reference count is incremented as with [`SharedTrc::from_trc`].
+    ///
     /// # Examples
     /// ```
     /// use trc::Trc;
     /// use trc::SharedTrc;
     ///
     /// let trc = Trc::new(100);
-    /// let shared = SharedTrc::from_trc(&trc);
+    /// let shared = SharedTrc::from(trc);
+    /// assert_eq!(SharedTrc::atomic_count(&shared), 1);
     /// ```
     fn from(value: Trc<T>) -> Self {
-        Self::from_trc(&value)
+        if *unsafe { value.threadref.as_ref() } != 1 {
+            return Self::from_trc(&value);
+        }
+
+        let value = ManuallyDrop::new(value);
+        unsafe { free_threadref(value.shared.as_ptr(), value.threadref) };
+        Self {
+            data: value.shared,
+            phantom: PhantomData,
+        }
     }
 }
 
 impl<T: ?Sized> SharedTrc<T> {
-    /// Return the weak count of the object. This is how many weak counts - across all threads - are pointing to the allocation inside of `SharedTrc`.
-    /// It includes the implicit weak reference held by all `Trc` or `SharedTrc` to themselves.
+    /// Upgrade a `Weak` directly to a `SharedTrc`, which can be sent to other threads. Because `Weak` does not own the
+    /// value, it may have been dropped already. If it has, a `None` is returned. Otherwise, only the atomic reference
+    /// count is incremented, and no local thread reference count is created as with [`Weak::upgrade`].
+    ///
+    /// # Examples
+    #[cfg_attr(feature = "single-threaded", doc = "```ignore")]
+    #[cfg_attr(not(feature = "single-threaded"), doc = "```")]
+    /// use trc::{SharedTrc, Trc};
+    ///
+    /// let trc = Trc::new(100);
+    /// let weak = Trc::downgrade(&trc);
+    ///
+    /// let shared = SharedTrc::from_weak(&weak).unwrap();
+    /// let handle = std::thread::spawn(move || *shared);
+    /// assert_eq!(handle.join().unwrap(), 100);
+    ///
+    /// drop(trc);
+    /// assert!(SharedTrc::from_weak(&weak).is_none());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn from_weak(weak: &Weak<T>) -> Option<Self> {
+        if is_dangling(weak.data.as_ptr()) {
+            return None;
+        }
+        #[cfg(feature = "immortals")]
+        if unsafe { weak.data.as_ref() }.atomicref.load(Relaxed) == IMMORTAL {
+            return Some(Self {
+                data: weak.data,
+                phantom: PhantomData,
+            });
+        }
+        unsafe { weak.data.as_ref() }
+            .atomicref
+            .fetch_update(Acquire, Relaxed, |n| {
+                // Any write of 0 we can observe leaves the field in permanently zero state.
+                if n == 0 {
+                    return None;
+                }
+                // See comments in `Trc::clone` for why we do this (for `mem::forget`).
+                if n > MAX_REFCOUNT {
+                    return Some(overflow::before_increment(
+                        n,
+                        "Overflow of maximum atomic reference count.",
+                    ));
+                }
+                Some(n + 1)
+            })
+            .ok()
+            .map(|_| Self {
+                data: weak.data,
+                phantom: PhantomData,
+            })
+    }
+
+    /// Create `n` clones of a `SharedTrc` with a single atomic increment, instead of one per clone. This is useful to
+    /// fan out one value to many worker threads.
+    ///
+    /// # Examples
+    #[cfg_attr(feature = "single-threaded", doc = "```ignore")]
+    #[cfg_attr(not(feature = "single-threaded"), doc = "```")]
+    /// use std::thread;
+    /// use trc::SharedTrc;
+    ///
+    /// let shared = SharedTrc::new(100);
+    /// let clones = SharedTrc::clone_many(&shared, 4);
+    /// assert_eq!(SharedTrc::atomic_count(&shared), 5);
+    ///
+    /// let handles: Vec<_> = clones
+    ///     .into_iter()
+    ///     .map(|shared| thread::spawn(move || *shared))
+    ///     .collect();
+    ///
+    /// for handle in handles {
+    ///     assert_eq!(handle.join().unwrap(), 100);
+    /// }
+    /// ```
+    #[must_use]
+    pub fn clone_many(this: &Self, n: usize) -> Vec<Self> {
+        assert!(
+            n <= MAX_REFCOUNT,
+            "Overflow of maximum atomic reference count."
+        );
+        let mut clones = Vec::with_capacity(n);
+        if n == 0 {
+            return clones;
+        }
+
+        let prev = sum_value(&unsafe { this.data.as_ref() }.atomicref, n, Relaxed);
+        if prev > MAX_REFCOUNT {
+            overflow::atomic(
+                &unsafe { this.data.as_ref() }.atomicref,
+                "Overflow of maximum atomic reference count.",
+            );
+        }
+        for _ in 0..n {
+            #[cfg(feature = "hooks")]
+            hooks::on_clone(this.data.as_ptr(), HandleKind::SharedTrc);
+            #[cfg(feature = "leak-debug")]
+            leak_debug::record(this.data.as_ptr(), None);
+            clones.push(Self {
+                data: this.data,
+                phantom: PhantomData,
+            });
+        }
+        return clones;
+    }
+
+    /// Get a &mut reference to the internal data if there are no other `SharedTrc`, [`Trc`] or [`Weak`] pointers to the same allocation.
+    /// Otherwise, return [`None`] because it would be unsafe to mutate a shared value.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::SharedTrc;
+    ///
+    /// let mut shared = SharedTrc::new(100);
+    /// *SharedTrc::get_mut(&mut shared).unwrap() = 300;
+    /// assert_eq!(*shared, 300);
+    ///
+    /// let _shared2 = shared.clone();
+    /// assert!(SharedTrc::get_mut(&mut shared).is_none());
+    /// ```
+    #[cfg(not(feature = "allocator_api"))]
+    #[inline]
+    pub fn get_mut(this: &mut Self) -> Option<&mut T> {
+        if Self::is_unique(this) {
+            Some(unsafe { &mut (*this.data.as_ptr()).data })
+        } else {
+            None
+        }
+    }
+
+    /// Return whether this is the only pointer to the allocation, so that there are no other `SharedTrc`, [`Trc`] or
+    /// [`Weak`] pointers. This is the condition under which [`SharedTrc::get_mut`] succeeds.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::SharedTrc;
+    ///
+    /// let shared = SharedTrc::new(100);
+    /// assert!(SharedTrc::is_unique(&shared));
+    ///
+    /// let _shared2 = shared.clone();
+    /// assert!(!SharedTrc::is_unique(&shared));
+    /// ```
+    #[cfg(not(feature = "allocator_api"))]
+    #[inline]
+    #[must_use]
+    pub fn is_unique(this: &Self) -> bool {
+        //Acquire the weakcount if it is == 1
+        let weakcount = unique_weak_count(&unsafe { this.data.as_ref() }.weakcount);
+        if unsafe { this.data.as_ref() }
+            .weakcount
+            .compare_exchange(weakcount, usize::MAX, Acquire, Relaxed)
+            .is_ok()
+        {
+            //Acquire the atomicref
+            let unique = unsafe { this.data.as_ref() }.atomicref.load(Acquire) == 1;
+
+            //Synchronize with the previous Acquire
+            unsafe { this.data.as_ref() }
+                .weakcount
+                .store(weakcount, Release);
+
+            unique
+        } else {
+            false
+        }
+    }
+
+    /// Return the weak count of the object. This is how many `Weak`s - across all threads - are pointing to the allocation inside of `SharedTrc`.
+    /// As with `Arc::weak_count`, the implicit weak reference held by all `Trc` or `SharedTrc` to themselves is not included.
     ///
     /// # Examples
     /// ```
@@ -470,12 +1089,12 @@ impl<T: ?Sized> SharedTrc<T> {
     /// let new_trc = Weak::upgrade(&weak).expect("Value was dropped");
     /// drop(weak);
     /// let shared: SharedTrc<_> = trc.into();
-    /// assert_eq!(SharedTrc::weak_count(&shared), 2);
+    /// assert_eq!(SharedTrc::weak_count(&shared), 1);
     /// ```
     #[inline]
     #[must_use]
     pub fn weak_count(this: &Self) -> usize {
-        return unsafe { this.data.as_ref() }.weakcount.load(Relaxed);
+        return strong_weak_count(unsafe { &this.data.as_ref().weakcount });
     }
 
     /// Checks if the other `SharedTrc` is equal to this one according to their internal pointers.
@@ -494,7 +1113,7 @@ impl<T: ?Sized> SharedTrc<T> {
     #[inline]
     #[must_use]
     pub fn ptr_eq(this: &Self, other: &Self) -> bool {
-        std::ptr::eq(this.data.as_ptr(), other.data.as_ptr())
+        core::ptr::eq(this.data.as_ptr(), other.data.as_ptr())
     }
 
     /// Gets the raw pointer to the most inner layer of `SharedTrc`.
@@ -514,6 +1133,24 @@ impl<T: ?Sized> SharedTrc<T> {
         unsafe { addr_of_mut!((*sharedptr).data) }
     }
 
+    /// Return the address of the value, without exposing the provenance of the pointer. This is the address of
+    /// [`SharedTrc::as_ptr`], for hashing, ordering or logging handles by identity.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{SharedTrc, Trc};
+    ///
+    /// let trc = Trc::new(100);
+    /// let shared = SharedTrc::from_trc(&trc);
+    /// assert_eq!(SharedTrc::addr(&shared), Trc::addr(&trc));
+    /// assert_eq!(SharedTrc::addr(&shared), SharedTrc::as_ptr(&shared).addr());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn addr(this: &Self) -> usize {
+        return Self::as_ptr(this).addr();
+    }
+
     /// Converts a `SharedTrc` into `*const T`, without freeing the allocation.
     /// To avoid a memory leak, be sure to call [`SharedTrc::from_raw`] to reclaim the allocation.
     ///
@@ -540,6 +1177,8 @@ impl<T: ?Sized> SharedTrc<T> {
 
 impl<T> SharedTrc<T> {
     /// Creates a new `SharedTrc` from the provided data.
+    /// Only the shared allocation is made, so this is cheaper than creating a `Trc` and converting it when the data
+    /// is only going to be used through `SharedTrc`s.
     ///
     /// # Examples
     /// ```
@@ -553,13 +1192,19 @@ impl<T> SharedTrc<T> {
         let shareddata = SharedTrcInternal {
             atomicref: AtomicUsize::new(1),
             weakcount: AtomicUsize::new(1),
+            #[cfg(feature = "allocator_api")]
+            alloc: Global,
+            #[cfg(feature = "padded")]
+            padding: CachePadding,
             data: value,
         };
 
-        let sharedbx = Box::new(shareddata);
+        #[cfg(feature = "stats")]
+        stats::on_alloc(mem::size_of::<T>());
 
         return Self {
-            data: NonNull::from(Box::leak(sharedbx)),
+            data: sync::leak_box(shareddata),
+            phantom: PhantomData,
         };
     }
 
@@ -568,15 +1213,12 @@ impl<T> SharedTrc<T> {
     /// # Examples
     /// ```
     /// use trc::SharedTrc;
-    /// use trc::Trc;
-    /// use std::mem::MaybeUninit;
     ///
-    /// let mut trc: Trc<MaybeUninit<i32>> = SharedTrc::new_uninit().into();
+    /// let mut shared = SharedTrc::<i32>::new_uninit();
     ///
-    /// Trc::<MaybeUninit<i32>>::get_mut(&mut trc).unwrap().write(5);
+    /// SharedTrc::get_mut(&mut shared).unwrap().write(5);
     ///
-    /// let trc: SharedTrc<MaybeUninit<i32>> = trc.into();
-    /// let five = unsafe { trc.assume_init() };
+    /// let five = unsafe { shared.assume_init() };
     ///
     /// assert_eq!(*five, 5);
     /// ```
@@ -586,16 +1228,89 @@ impl<T> SharedTrc<T> {
         let shareddata = SharedTrcInternal {
             atomicref: AtomicUsize::new(1),
             weakcount: AtomicUsize::new(1),
+            #[cfg(feature = "allocator_api")]
+            alloc: Global,
+            #[cfg(feature = "padded")]
+            padding: CachePadding,
             data: MaybeUninit::<T>::uninit(),
         };
 
-        let sharedbx = Box::new(shareddata);
+        #[cfg(feature = "stats")]
+        stats::on_alloc(mem::size_of::<T>());
 
         return SharedTrc {
-            data: NonNull::from(Box::leak(sharedbx)),
+            data: sync::leak_box(shareddata),
+            phantom: PhantomData,
         };
     }
 
+    /// Creates a new `SharedTrc` with uninitialized contents, with the memory being filled with `0` bytes.
+    /// The allocation is made with [`alloc_zeroed`], so large values can be backed by pages
+    /// which the operating system has already zeroed.
+    ///
+    /// See [`MaybeUninit::zeroed`] for examples of correct and incorrect usage of this method.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::SharedTrc;
+    ///
+    /// let zero = SharedTrc::<u32>::new_zeroed();
+    /// let zero = unsafe { zero.assume_init() };
+    ///
+    /// assert_eq!(*zero, 0);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn new_zeroed() -> SharedTrc<MaybeUninit<T>> {
+        SharedTrc {
+            data: new_zeroed_internal(),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Creates a new `SharedTrc` from the provided data, returning an error if the allocation fails.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::SharedTrc;
+    ///
+    /// let shared = SharedTrc::try_new(100)?;
+    /// assert_eq!(*shared, 100);
+    /// # Ok::<(), trc::AllocError>(())
+    /// ```
+    #[inline]
+    pub fn try_new(value: T) -> Result<Self, AllocError> {
+        Ok(Self {
+            data: try_alloc_internal(value)?,
+            phantom: PhantomData,
+        })
+    }
+
+    /// Creates a new uninitialized `SharedTrc`, returning an error if the allocation fails.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::SharedTrc;
+    /// use trc::Trc;
+    /// use std::mem::MaybeUninit;
+    ///
+    /// let mut trc: Trc<MaybeUninit<i32>> = SharedTrc::try_new_uninit()?.into();
+    ///
+    /// Trc::get_mut(&mut trc).unwrap().write(5);
+    ///
+    /// let five = unsafe { trc.assume_init() };
+    ///
+    /// assert_eq!(*five, 5);
+    /// # Ok::<(), trc::AllocError>(())
+    /// ```
+    #[inline]
+    pub fn try_new_uninit() -> Result<SharedTrc<MaybeUninit<T>>, AllocError> {
+        Ok(SharedTrc {
+            data: try_alloc_internal(MaybeUninit::<T>::uninit())?,
+            phantom: PhantomData,
+        })
+    }
+
     /// Creates a new cyclic `SharedTrc` from the provided data. It allows the storage of `Weak` which points the the allocation
     /// of `SharedTrc`inside of `T`. Holding a `SharedTrc` inside of `T` would cause a memory leak. This method works around this by
     /// providing a `Weak` during the construction of the `SharedTrc`, so that the `T` can store the `Weak` internally.
@@ -614,12 +1329,17 @@ impl<T> SharedTrc<T> {
     where
         F: FnOnce(&Weak<T>) -> T,
     {
-        let shareddata: NonNull<_> = Box::leak(Box::new(SharedTrcInternal {
+        #[cfg(feature = "stats")]
+        stats::on_alloc(mem::size_of::<T>());
+        let shareddata = sync::leak_box(SharedTrcInternal {
             atomicref: AtomicUsize::new(0),
             weakcount: AtomicUsize::new(1),
+            #[cfg(feature = "allocator_api")]
+            alloc: Global,
+            #[cfg(feature = "padded")]
+            padding: CachePadding,
             data: MaybeUninit::<T>::uninit(),
-        }))
-        .into();
+        });
 
         let init_ptr: NonNull<SharedTrcInternal<T>> = shareddata.cast();
 
@@ -631,23 +1351,94 @@ impl<T> SharedTrc<T> {
             let ptr = init_ptr.as_ptr();
             ptr::write(ptr::addr_of_mut!((*ptr).data), data);
 
-            let prev = sum_value(&init_ptr.as_ref().atomicref, 1, AcqRel);
-            assert!(
-                prev <= MAX_REFCOUNT,
-                "Overflow of maximum atomic reference count."
-            );
+            //Publish the data to `Weak`s upgraded on other threads, which acquire the count.
+            let prev = sum_value(&init_ptr.as_ref().atomicref, 1, Release);
+            if prev > MAX_REFCOUNT {
+                overflow::atomic(
+                    &init_ptr.as_ref().atomicref,
+                    "Overflow of maximum atomic reference count.",
+                );
+            }
         }
 
-        Self { data: init_ptr }
+        Self {
+            data: init_ptr,
+            phantom: PhantomData,
+        }
     }
-    /// Converts a `*const T` into `SharedTrc`. The caller must uphold the below safety constraints.
-    ///
-    /// # Safety
-    /// - The given pointer must be a valid pointer to `T` that came from [`SharedTrc::into_raw`].
-    /// - After `from_raw`, the pointer must not be accessed.
+
+    /// Creates a new `SharedTrc` of the value returned by `data_fn`, which receives the [`SelfRef`] of the allocation, so
+    /// that the value can implement [`TrcFromSelf`]. Like [`SharedTrc::new_cyclic`], the `SelfRef` cannot be upgraded
+    /// inside of the closure.
     ///
     /// # Examples
-    /// Example 1:
+    /// ```
+    /// use trc::{SelfRef, SharedTrc, TrcFromSelf};
+    ///
+    /// struct Service {
+    ///     self_ref: SelfRef<Service>,
+    /// }
+    ///
+    /// impl TrcFromSelf for Service {
+    ///     fn self_ref(&self) -> &SelfRef<Self> {
+    ///         &self.self_ref
+    ///     }
+    /// }
+    ///
+    /// let service = SharedTrc::new_with_self_ref(|self_ref| Service { self_ref });
+    /// assert!(SharedTrc::ptr_eq(&service.shared_from_self(), &service));
+    /// ```
+    #[inline]
+    pub fn new_with_self_ref<F>(data_fn: F) -> Self
+    where
+        F: FnOnce(SelfRef<T>) -> T,
+    {
+        return Self::new_cyclic(|weak| data_fn(SelfRef::new(weak)));
+    }
+
+    /// Creates `N` new cyclic `SharedTrc`s at once. Like [`SharedTrc::new_cyclic`], but the closure receives a `Weak` to every
+    /// allocation before any of them is initialized, so groups of values which refer to each other (such as a ring of nodes)
+    /// can be constructed without storing an `Option<Weak>` and patching it afterwards.
+    /// Calling [`Weak::upgrade`] on any of the `Weak`s inside of the closure will return `None`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::SharedTrc;
+    /// use trc::Weak;
+    ///
+    /// struct Node {
+    ///     value: usize,
+    ///     next: Weak<Node>,
+    /// }
+    ///
+    /// let nodes: [SharedTrc<Node>; 2] = SharedTrc::new_cyclic_multi(|weaks| {
+    ///     [
+    ///         Node { value: 0, next: weaks[1].clone() },
+    ///         Node { value: 1, next: weaks[0].clone() },
+    ///     ]
+    /// });
+    /// assert_eq!(nodes[0].next.upgrade().unwrap().value, 1);
+    /// assert_eq!(nodes[1].next.upgrade().unwrap().value, 0);
+    /// ```
+    #[inline]
+    pub fn new_cyclic_multi<const N: usize, F>(data_fn: F) -> [Self; N]
+    where
+        F: FnOnce(&[Weak<T>; N]) -> [T; N],
+    {
+        new_cyclic_multi_internal(data_fn).map(|data| Self {
+            data,
+            phantom: PhantomData,
+        })
+    }
+
+    /// Converts a `*const T` into `SharedTrc`. The caller must uphold the below safety constraints.
+    ///
+    /// # Safety
+    /// - The given pointer must be a valid pointer to `T` that came from [`SharedTrc::into_raw`].
+    /// - After `from_raw`, the pointer must not be accessed.
+    ///
+    /// # Examples
+    /// Example 1:
     /// ```
     /// use trc::Trc;
     /// use trc::SharedTrc;
@@ -683,13 +1474,9 @@ impl<T> SharedTrc<T> {
     /// SharedTrc::to_trc(unsafe { SharedTrc::from_raw(raw_2) });
     /// ```
     pub unsafe fn from_raw(ptr: *const T) -> Self {
-        let layout = Layout::new::<SharedTrcInternal<()>>();
-        let n = layout.size();
-
-        let data_ptr = ptr.cast::<u8>().sub(n) as *mut SharedTrcInternal<T>;
-
         Self {
-            data: NonNull::new_unchecked(data_ptr),
+            data: NonNull::new_unchecked(internal_from_data_ptr(ptr)),
+            phantom: PhantomData,
         }
     }
 
@@ -749,19 +1536,147 @@ impl<T> SharedTrc<T> {
     }
 }
 
+impl<T: Clone> SharedTrc<T> {
+    /// Convert a `SharedTrc` into an [`Arc`]. If the `SharedTrc` is the only strong reference, the value is moved out of
+    /// it. Otherwise, the value is cloned.
+    ///
+    /// # Examples
+    #[cfg_attr(feature = "single-threaded", doc = "```ignore")]
+    #[cfg_attr(not(feature = "single-threaded"), doc = "```")]
+    /// use std::sync::Arc;
+    /// use trc::SharedTrc;
+    ///
+    /// let shared = SharedTrc::new(vec![1, 2, 3]);
+    /// let handle = std::thread::spawn(move || SharedTrc::into_arc(shared));
+    /// let arc: Arc<Vec<i32>> = handle.join().unwrap();
+    /// assert_eq!(*arc, [1, 2, 3]);
+    /// ```
+    #[cfg(target_has_atomic = "ptr")]
+    #[inline]
+    #[must_use]
+    pub fn into_arc(this: Self) -> Arc<T> {
+        return Trc::into_arc(Self::to_trc(this));
+    }
+}
+
+#[cfg(feature = "leak-debug")]
+impl<T: ?Sized> SharedTrc<T> {
+    /// Return the backtraces of the live clones of the `Trc`s and `SharedTrc`s of this allocation, from the oldest to the
+    /// most recent. See [`Trc::live_handle_traces`].
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::SharedTrc;
+    ///
+    /// let shared = SharedTrc::new(100);
+    /// let shared2 = shared.clone();
+    /// let traces = SharedTrc::live_handle_traces(&shared);
+    /// assert_eq!(traces.len(), 1);
+    /// assert!(traces[0].is_shared());
+    /// ```
+    #[must_use]
+    pub fn live_handle_traces(this: &Self) -> Vec<leak_debug::HandleTrace> {
+        return leak_debug::traces(this.data.as_ptr());
+    }
+}
+
+#[cfg(feature = "immortals")]
+impl<T> SharedTrc<T> {
+    /// Creates a new immortal `SharedTrc` from the provided data. See [`Trc::new_immortal`].
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::SharedTrc;
+    ///
+    /// let shared = SharedTrc::new_immortal(100);
+    /// let shared2 = shared.clone();
+    /// assert_eq!(SharedTrc::atomic_count(&shared2), usize::MAX);
+    /// ```
+    #[must_use]
+    pub fn new_immortal(value: T) -> Self {
+        let shared = Self::new(value);
+        unsafe { shared.data.as_ref() }
+            .atomicref
+            .store(IMMORTAL, Relaxed);
+        return shared;
+    }
+}
+
+#[cfg(feature = "immortals")]
+impl<T: ?Sized> SharedTrc<T> {
+    /// Return whether the allocation is immortal. See [`Trc::is_immortal`].
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::SharedTrc;
+    ///
+    /// assert!(SharedTrc::is_immortal(&SharedTrc::new_immortal(100)));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn is_immortal(this: &Self) -> bool {
+        return unsafe { this.data.as_ref() }.atomicref.load(Relaxed) == IMMORTAL;
+    }
+}
+
+impl<T: Send + Sync + 'static> SharedTrc<T> {
+    /// Convert the `SharedTrc` into a [`ProjectedSharedTrc`] which dereferences to the part of the value selected by `f`,
+    /// such as a field. The `ProjectedSharedTrc` keeps the whole value alive.
+    ///
+    /// # Examples
+    #[cfg_attr(feature = "single-threaded", doc = "```ignore")]
+    #[cfg_attr(not(feature = "single-threaded"), doc = "```")]
+    /// use trc::{ProjectedSharedTrc, SharedTrc};
+    ///
+    /// let shared = SharedTrc::new((String::from("key"), 100));
+    /// let value: ProjectedSharedTrc<i32> = SharedTrc::map(shared, |pair| &pair.1);
+    /// let handle = std::thread::spawn(move || *value);
+    /// assert_eq!(handle.join().unwrap(), 100);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn map<U: ?Sized, F: FnOnce(&T) -> &U>(this: Self, f: F) -> ProjectedSharedTrc<U> {
+        return match ProjectedSharedTrc::try_new(this, |value| Some(f(value))) {
+            Ok(projected) => projected,
+            Err(_) => unreachable!(),
+        };
+    }
+
+    /// Convert the `SharedTrc` into a [`ProjectedSharedTrc`] which dereferences to the part of the value selected by
+    /// `f`. If `f` returns [`None`], the `SharedTrc` is returned in the [`Err`].
+    ///
+    /// # Errors
+    /// Returns `this` if `f` returns [`None`].
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::SharedTrc;
+    ///
+    /// let shared = SharedTrc::new(vec![1, 2, 3]);
+    /// let shared = SharedTrc::try_map(shared, |vec| vec.get(3)).unwrap_err();
+    /// let second = SharedTrc::try_map(shared, |vec| vec.get(1)).unwrap();
+    /// assert_eq!(*second, 2);
+    /// ```
+    #[inline]
+    pub fn try_map<U: ?Sized, F: FnOnce(&T) -> Option<&U>>(
+        this: Self,
+        f: F,
+    ) -> Result<ProjectedSharedTrc<U>, Self> {
+        return ProjectedSharedTrc::try_new(this, f);
+    }
+}
+
 impl<T> SharedTrc<[T]> {
     /// Constructs a new `SharedTrc` slice with uninitialized contents.
     ///
     /// # Examples
     /// ```
     /// use trc::SharedTrc;
-    /// use trc::Trc;
-    /// use std::mem::MaybeUninit;
     ///
-    /// let mut values: Trc<[MaybeUninit<u32>]> = SharedTrc::<[u32]>::new_uninit_slice(3).into();
+    /// let mut values = SharedTrc::<[u32]>::new_uninit_slice(3);
     ///
     /// // Deferred initialization:
-    /// let data = Trc::get_mut(&mut values).unwrap();
+    /// let data = SharedTrc::get_mut(&mut values).unwrap();
     /// data[0].write(1);
     /// data[1].write(2);
     /// data[2].write(3);
@@ -772,27 +1687,73 @@ impl<T> SharedTrc<[T]> {
     /// ```
     #[must_use]
     pub fn new_uninit_slice(len: usize) -> SharedTrc<[MaybeUninit<T>]> {
-        let value_layout = Layout::array::<T>(len).unwrap();
-        let layout = Layout::new::<SharedTrcInternal<()>>()
-            .extend(value_layout)
-            .unwrap()
-            .0
-            .pad_to_align();
-
-        let res = slice_from_raw_parts_mut(unsafe { alloc(layout) }.cast::<T>(), len)
-            as *mut SharedTrcInternal<[MaybeUninit<T>]>;
-        unsafe { write(&mut (*res).atomicref, AtomicUsize::new(1)) };
-        unsafe { write(&mut (*res).weakcount, AtomicUsize::new(1)) };
-
-        let elems = unsafe { addr_of_mut!((*res).data) }.cast::<std::mem::MaybeUninit<T>>();
-        for i in 0..len {
-            unsafe {
-                write(elems.add(i), MaybeUninit::<T>::uninit());
-            }
+        SharedTrc {
+            data: alloc_slice_internal(len),
+            phantom: PhantomData,
         }
+    }
+
+    /// Constructs a new `SharedTrc` slice with uninitialized contents, with the memory being filled with `0` bytes.
+    /// The allocation is made with [`alloc_zeroed`], so large buffers can be backed by pages
+    /// which the operating system has already zeroed.
+    ///
+    /// See [`MaybeUninit::zeroed`] for examples of correct and incorrect usage of this method.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::SharedTrc;
+    ///
+    /// let values = SharedTrc::<[u32]>::new_zeroed_slice(3);
+    /// let values = unsafe { values.assume_init() };
+    ///
+    /// assert_eq!(*values, [0, 0, 0])
+    /// ```
+    #[must_use]
+    pub fn new_zeroed_slice(len: usize) -> SharedTrc<[MaybeUninit<T>]> {
+        SharedTrc {
+            data: new_zeroed_slice_internal(len),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Constructs a new `SharedTrc` slice with uninitialized contents, returning an error if the allocation fails or if
+    /// the size of the slice overflows.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::SharedTrc;
+    ///
+    /// let values = SharedTrc::<[u32]>::try_new_uninit_slice(3)?;
+    /// assert_eq!(values.len(), 3);
+    /// assert!(SharedTrc::<[u32]>::try_new_uninit_slice(usize::MAX).is_err());
+    /// # Ok::<(), trc::AllocError>(())
+    /// ```
+    pub fn try_new_uninit_slice(len: usize) -> Result<SharedTrc<[MaybeUninit<T>]>, AllocError> {
+        Ok(SharedTrc {
+            data: try_alloc_slice_internal(len)?,
+            phantom: PhantomData,
+        })
+    }
+}
 
+impl<T, const N: usize> SharedTrc<[T; N]> {
+    /// Convert a `SharedTrc<[T; N]>` into a `SharedTrc<[T]>` without changing any counts. This performs the same unsizing
+    /// as the `dyn_unstable` coercion, but is available on stable Rust.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::SharedTrc;
+    ///
+    /// let shared = SharedTrc::new([1, 2, 3]);
+    /// let slice: SharedTrc<[i32]> = SharedTrc::unsize_array(shared);
+    /// assert_eq!(*slice, [1, 2, 3]);
+    /// ```
+    #[must_use]
+    pub fn unsize_array(this: Self) -> SharedTrc<[T]> {
+        let this = ManuallyDrop::new(this);
         SharedTrc {
-            data: unsafe { NonNull::new_unchecked(res) },
+            data: unsize_array_ptr(this.data),
+            phantom: PhantomData,
         }
     }
 }
@@ -806,22 +1767,22 @@ impl<T> SharedTrc<MaybeUninit<T>> {
     ///
     /// # Examples
     /// ```
-    /// use trc::Trc;
     /// use trc::SharedTrc;
-    /// use std::mem::MaybeUninit;
     ///
-    /// let mut trc: Trc<MaybeUninit<i32>> = SharedTrc::new_uninit().into();
+    /// let mut shared = SharedTrc::<i32>::new_uninit();
     ///
-    /// Trc::<MaybeUninit<i32>>::get_mut(&mut trc).unwrap().write(5);
+    /// SharedTrc::get_mut(&mut shared).unwrap().write(5);
     ///
-    /// let five = unsafe { trc.assume_init() };
+    /// let five = unsafe { shared.assume_init() };
     ///
     /// assert_eq!(*five, 5);
     /// ```
+    #[cfg(not(feature = "allocator_api"))]
     #[must_use]
     pub unsafe fn assume_init(self) -> SharedTrc<T> {
         SharedTrc {
             data: NonNull::new_unchecked(ManuallyDrop::new(self).data.as_ptr().cast()),
+            phantom: PhantomData,
         }
     }
 }
@@ -835,19 +1796,17 @@ impl<T> SharedTrc<[MaybeUninit<T>]> {
     ///
     /// # Examples
     /// ```
-    /// use trc::Trc;
     /// use trc::SharedTrc;
-    /// use std::mem::MaybeUninit;
     ///
-    /// let mut values: Trc<[MaybeUninit<u32>]> = SharedTrc::<[u32]>::new_uninit_slice(3).into();
+    /// let mut values = SharedTrc::<[u32]>::new_uninit_slice(3);
     ///
     /// // Deferred initialization:
-    /// let data = Trc::get_mut(&mut values).unwrap();
+    /// let data = SharedTrc::get_mut(&mut values).unwrap();
     /// data[0].write(1);
     /// data[1].write(2);
     /// data[2].write(3);
     ///
-    /// let values = unsafe { SharedTrc::from_trc(&values).assume_init() };
+    /// let values = unsafe { values.assume_init() };
     ///
     /// assert_eq!(*values, [1, 2, 3])
     /// ```
@@ -857,10 +1816,12 @@ impl<T> SharedTrc<[MaybeUninit<T>]> {
             data: NonNull::new_unchecked(
                 ManuallyDrop::new(self).data.as_ptr() as *mut SharedTrcInternal<[T]>
             ),
+            phantom: PhantomData,
         }
     }
 }
 
+#[cfg(not(feature = "allocator_api"))]
 impl<T: ?Sized> Deref for SharedTrc<T> {
     type Target = T;
 
@@ -882,32 +1843,428 @@ impl<T: ?Sized> Deref for SharedTrc<T> {
     }
 }
 
+/// The error returned by the fallible constructors (such as [`Trc::try_new`]) when the allocator fails to allocate memory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AllocError;
+
+impl Display for AllocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Memory allocation failed.")
+    }
+}
+
+impl Error for AllocError {}
+
+/// Allocate a `SharedTrcInternal` containing `value` with an atomic and weak count of 1, returning an error instead of aborting.
+fn try_alloc_internal<T>(value: T) -> Result<NonNull<SharedTrcInternal<T>>, AllocError> {
+    let layout = Layout::new::<SharedTrcInternal<T>>();
+    let res =
+        NonNull::new(unsafe { alloc(layout) }.cast::<SharedTrcInternal<T>>()).ok_or(AllocError)?;
+    #[cfg(feature = "stats")]
+    stats::on_alloc(mem::size_of::<T>());
+    unsafe {
+        write(
+            res.as_ptr(),
+            SharedTrcInternal {
+                atomicref: AtomicUsize::new(1),
+                weakcount: AtomicUsize::new(1),
+                #[cfg(feature = "allocator_api")]
+                alloc: Global,
+                #[cfg(feature = "padded")]
+                padding: CachePadding,
+                data: value,
+            },
+        )
+    };
+    Ok(res)
+}
+
+/// Allocate an uninitialized `SharedTrcInternal` slice with an atomic and weak count of 1, returning an error instead of aborting.
+fn try_alloc_slice_internal<T>(
+    len: usize,
+) -> Result<NonNull<SharedTrcInternal<[MaybeUninit<T>]>>, AllocError> {
+    return alloc_slice_internal_with::<T>(len, alloc);
+}
+
+/// Allocate a `SharedTrcInternal` slice with `allocate` and an atomic and weak count of 1, returning an error instead of aborting.
+/// All slice allocations go through here, so that the layout and the header are only set up in one place.
+fn alloc_slice_internal_with<T>(
+    len: usize,
+    allocate: unsafe fn(Layout) -> *mut u8,
+) -> Result<NonNull<SharedTrcInternal<[MaybeUninit<T>]>>, AllocError> {
+    let (layout, _) = slice_layout::<T>(len).map_err(|_| AllocError)?;
+
+    let ptr = unsafe { allocate(layout) };
+    if ptr.is_null() {
+        return Err(AllocError);
+    }
+    #[cfg(feature = "stats")]
+    stats::on_alloc(mem::size_of::<T>() * len);
+    let res =
+        slice_from_raw_parts_mut(ptr.cast::<T>(), len) as *mut SharedTrcInternal<[MaybeUninit<T>]>;
+    unsafe { write(addr_of_mut!((*res).atomicref), AtomicUsize::new(1)) };
+    unsafe { write(addr_of_mut!((*res).weakcount), AtomicUsize::new(1)) };
+    Ok(unsafe { NonNull::new_unchecked(res) })
+}
+
+/// Abort on a failed slice allocation of `len` elements, panicking instead if its layout overflows.
+#[cold]
+fn slice_alloc_error<T>(len: usize) -> ! {
+    let (layout, _) = slice_layout::<T>(len).unwrap();
+    handle_alloc_error(layout);
+}
+
+/// Recover the pointer to a `SharedTrcInternal` from a pointer to its data, keeping the metadata (length or vtable).
+///
+/// # Safety
+/// `ptr` must point to the data of a live `SharedTrcInternal<T>` allocation, although the data itself may have been dropped.
+unsafe fn internal_from_data_ptr<T: ?Sized>(ptr: *const T) -> *mut SharedTrcInternal<T> {
+    //The data is placed after the counts, aligned to its own alignment.
+    let align = mem::align_of_val(&*ptr);
+    let (_, offset) = internal_layout(Layout::from_size_align_unchecked(0, align)).unwrap();
+    ptr.byte_sub(offset).cast_mut() as *mut SharedTrcInternal<T>
+}
+
+/// Allocate a local thread reference count of 1, reusing a freed one from this thread if possible.
+#[inline]
+fn try_new_threadref() -> Option<NonNull<usize>> {
+    #[cfg(all(feature = "std", not(loom)))]
+    let cached = local_counts::pop_free();
+    #[cfg(any(not(feature = "std"), loom))]
+    let cached = None;
+
+    let threadref = match cached {
+        Some(threadref) => threadref,
+        None => NonNull::new(unsafe { alloc(Layout::new::<usize>()) }.cast::<usize>())?,
+    };
+    unsafe { write(threadref.as_ptr(), 1) };
+    Some(threadref)
+}
+
+/// Allocate a local thread reference count of 1, aborting if the allocation fails.
+#[inline]
+fn new_threadref() -> NonNull<usize> {
+    match try_new_threadref() {
+        Some(threadref) => threadref,
+        None => handle_alloc_error(Layout::new::<usize>()),
+    }
+}
+
+/// Free a local thread reference count of `alloc`, keeping it for reuse by this thread if there is space.
+///
+/// # Safety
+/// `threadref` must have been created by [`new_threadref`] or [`try_new_threadref`], and must not be used afterwards.
+#[inline]
+unsafe fn free_threadref<T: ?Sized>(alloc: *const T, threadref: NonNull<usize>) {
+    #[cfg(feature = "leak-debug")]
+    leak_debug::release_group(threadref);
+    #[cfg(all(feature = "std", not(loom)))]
+    local_counts::unregister(alloc, threadref);
+    #[cfg(any(not(feature = "std"), loom))]
+    let _ = alloc;
+
+    #[cfg(all(feature = "std", not(loom)))]
+    let cached = local_counts::push_free(threadref);
+    #[cfg(any(not(feature = "std"), loom))]
+    let cached = false;
+
+    if !cached {
+        dealloc(threadref.as_ptr().cast(), Layout::new::<usize>());
+    }
+}
+
+/// Allocate the local thread reference count of a new `Trc` for an allocation which holds one atomic reference.
+/// If this fails, that atomic reference is released.
+fn try_into_trc<T: ?Sized>(shared: NonNull<SharedTrcInternal<T>>) -> Result<Trc<T>, AllocError> {
+    match try_new_threadref() {
+        Some(threadref) => Ok(Trc {
+            shared,
+            threadref,
+            phantom: PhantomData,
+        }),
+        None => {
+            drop(SharedTrc {
+                data: shared,
+                phantom: PhantomData,
+            });
+            Err(AllocError)
+        }
+    }
+}
+
+/// Return the layout of a `SharedTrcInternal` whose value has `value_layout`, and the offset of the value in it.
+#[inline]
+fn internal_layout(value_layout: Layout) -> Result<(Layout, usize), LayoutError> {
+    let (layout, offset) = Layout::new::<SharedTrcInternal<()>>().extend(value_layout)?;
+    return Ok((layout.pad_to_align(), offset));
+}
+
+/// Return the layout of a `SharedTrcInternal<[T]>` of `len` elements, and the offset of the elements in it.
+#[inline]
+fn slice_layout<T>(len: usize) -> Result<(Layout, usize), LayoutError> {
+    return internal_layout(Layout::array::<T>(len)?);
+}
+
+/// Allocate an uninitialized `SharedTrcInternal` slice with an atomic and weak count of 1.
+fn alloc_slice_internal<T>(len: usize) -> NonNull<SharedTrcInternal<[MaybeUninit<T>]>> {
+    return try_alloc_slice_internal::<T>(len).unwrap_or_else(|_| slice_alloc_error::<T>(len));
+}
+
+/// Move the elements of a `Vec` into a new `SharedTrcInternal` slice with an atomic and weak count of 1,
+/// and free the buffer of the `Vec` without dropping the elements.
+fn vec_into_internal<T>(mut value: Vec<T>) -> NonNull<SharedTrcInternal<[T]>> {
+    let len = value.len();
+    let res = alloc_slice_internal::<T>(len);
+    unsafe {
+        ptr::copy_nonoverlapping(
+            value.as_ptr(),
+            addr_of_mut!((*res.as_ptr()).data).cast::<T>(),
+            len,
+        );
+        //The elements were moved, so only the buffer is freed.
+        value.set_len(0);
+        NonNull::new_unchecked(res.as_ptr() as *mut SharedTrcInternal<[T]>)
+    }
+}
+
+/// Move the (possibly unsized) value out of a `Box` into a new `SharedTrcInternal` with an atomic and weak count of 1,
+/// and free the `Box` without dropping the value.
+fn box_into_internal<T: ?Sized>(value: Box<T>) -> NonNull<SharedTrcInternal<T>> {
+    let src = Box::into_raw(value);
+    unsafe {
+        let res = move_into_internal(src);
+        //Free the `Box` without dropping the value, which was moved.
+        drop(Box::from_raw(src as *mut ManuallyDrop<T>));
+        res
+    }
+}
+
+/// Move the (possibly unsized) value at `src` into a new `SharedTrcInternal` with an atomic and weak count of 1.
+///
+/// # Safety
+/// `src` must point to a valid value, which is not used or dropped afterwards.
+unsafe fn move_into_internal<T: ?Sized>(src: *mut T) -> NonNull<SharedTrcInternal<T>> {
+    let value_layout = Layout::for_value(unsafe { &*src });
+    let (layout, _) = internal_layout(value_layout).unwrap();
+
+    let mem = unsafe { alloc(layout) };
+    if mem.is_null() {
+        handle_alloc_error(layout);
+    }
+    #[cfg(feature = "stats")]
+    stats::on_alloc(value_layout.size());
+
+    //Keep the metadata (length or vtable) of the value and point it at the new allocation.
+    let res = unsafe { set_data_ptr(src as *mut SharedTrcInternal<T>, mem) };
+    unsafe {
+        write(addr_of_mut!((*res).atomicref), AtomicUsize::new(1));
+        write(addr_of_mut!((*res).weakcount), AtomicUsize::new(1));
+        ptr::copy_nonoverlapping(
+            src.cast::<u8>(),
+            addr_of_mut!((*res).data).cast::<u8>(),
+            value_layout.size(),
+        );
+        NonNull::new_unchecked(res)
+    }
+}
+
+/// Replace the address of a (possibly fat) pointer, keeping its metadata. The provenance is taken from `data`, as the
+/// pointer itself is written, not its address.
+///
+/// # Safety
+/// The metadata of `ptr` must be valid for the value at `data`.
+unsafe fn set_data_ptr<T: ?Sized, U>(mut ptr: *mut T, data: *mut U) -> *mut T {
+    write(addr_of_mut!(ptr).cast::<*mut u8>(), data.cast::<u8>());
+    ptr
+}
+
+/// Resolve `range` against a slice of length `len`.
+///
+/// # Panics
+/// Panics if the start of the range is greater than its end, or if its end is greater than `len`.
+fn slice_range<R: RangeBounds<usize>>(range: R, len: usize) -> Range<usize> {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start
+            .checked_add(1)
+            .expect("Range start overflows `usize`."),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end.checked_add(1).expect("Range end overflows `usize`."),
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+    assert!(start <= end, "Range start is greater than range end.");
+    assert!(end <= len, "Range end is out of bounds.");
+    return start..end;
+}
+
+/// Allocate an uninitialized `SharedTrcInternal` with an atomic and weak count of 1, without constructing it on the stack.
+fn alloc_uninit_internal<T>() -> NonNull<SharedTrcInternal<MaybeUninit<T>>> {
+    let layout = Layout::new::<SharedTrcInternal<MaybeUninit<T>>>();
+    let res = unsafe { alloc(layout) }.cast::<SharedTrcInternal<MaybeUninit<T>>>();
+    if res.is_null() {
+        handle_alloc_error(layout);
+    }
+    #[cfg(feature = "stats")]
+    stats::on_alloc(mem::size_of::<T>());
+    unsafe { write(addr_of_mut!((*res).atomicref), AtomicUsize::new(1)) };
+    unsafe { write(addr_of_mut!((*res).weakcount), AtomicUsize::new(1)) };
+    unsafe { NonNull::new_unchecked(res) }
+}
+
+/// Allocate a zeroed `SharedTrcInternal` with an atomic and weak count of 1.
+fn new_zeroed_internal<T>() -> NonNull<SharedTrcInternal<MaybeUninit<T>>> {
+    let layout = Layout::new::<SharedTrcInternal<MaybeUninit<T>>>();
+    let res = unsafe { alloc_zeroed(layout) }.cast::<SharedTrcInternal<MaybeUninit<T>>>();
+    if res.is_null() {
+        handle_alloc_error(layout);
+    }
+    #[cfg(feature = "stats")]
+    stats::on_alloc(mem::size_of::<T>());
+    unsafe { write(addr_of_mut!((*res).atomicref), AtomicUsize::new(1)) };
+    unsafe { write(addr_of_mut!((*res).weakcount), AtomicUsize::new(1)) };
+    unsafe { NonNull::new_unchecked(res) }
+}
+
+/// Allocate a zeroed `SharedTrcInternal` slice with an atomic and weak count of 1.
+fn new_zeroed_slice_internal<T>(len: usize) -> NonNull<SharedTrcInternal<[MaybeUninit<T>]>> {
+    return alloc_slice_internal_with::<T>(len, alloc_zeroed)
+        .unwrap_or_else(|_| slice_alloc_error::<T>(len));
+}
+
+/// Rewrite the pointer metadata of an array allocation into that of a slice allocation with the same length.
+/// `SharedTrcInternal` is `repr(C)`, so both have the same layout.
+#[inline]
+fn unsize_array_ptr<T, const N: usize>(
+    ptr: NonNull<SharedTrcInternal<[T; N]>>,
+) -> NonNull<SharedTrcInternal<[T]>> {
+    let res = slice_from_raw_parts_mut(ptr.as_ptr().cast::<T>(), N) as *mut SharedTrcInternal<[T]>;
+    unsafe { NonNull::new_unchecked(res) }
+}
+
+//Memory orderings of the atomic counts, following `Arc`:
+//- Making a reference from an existing one (`SharedTrc::clone`, `SharedTrc::from_trc`, `Weak::clone`) is `Relaxed`, as
+//  the existing reference keeps the allocation alive and nothing is read through the new one yet.
+//- Releasing a reference is `Release`, and the release of the last one is followed by an `Acquire` fence, so that every
+//  access through the other references happens before the value is dropped or the allocation is freed.
+//- Upgrading a `Weak` acquires the atomic count, which pairs with the `Release` increment that publishes the value in
+//  `new_cyclic`.
+//- Checking for uniqueness (`is_unique`, `get_mut`, `try_into_box`) acquires the counts, so that the accesses through
+//  the dropped references happen before the mutable access. The weak count is locked with an `Acquire` exchange and
+//  unlocked with a `Release` store, and `Trc::downgrade` waits for the lock with an `Acquire` exchange.
+//- Counts which are only reported (`atomic_count`, `Weak::is_dead`) are `Relaxed`.
+
+/// Add `offset` to an atomic reference count, returning the previous count. The count of an immortal allocation is not
+/// changed, and is reported as a single reference.
 #[inline(always)]
 fn sum_value(value: &AtomicUsize, offset: usize, ordering: Ordering) -> usize {
-    #[cfg(immortals)]
-    if value.load(Acquire) != usize::MAX {
-        value.fetch_add(offset, ordering)
-    } else {
-        usize::MAX
+    #[cfg(feature = "immortals")]
+    if value.load(Relaxed) == IMMORTAL {
+        return 1;
     }
 
-    #[cfg(not(immortals))]
     return value.fetch_add(offset, ordering);
 }
 
+/// Subtract `offset` from an atomic reference count, returning the previous count. The count of an immortal allocation is
+/// not changed, so it never appears to drop its last reference.
 #[inline(always)]
 fn sub_value(value: &AtomicUsize, offset: usize, ordering: Ordering) -> usize {
-    #[cfg(immortals)]
-    if value.load(Acquire) != usize::MAX {
-        value.fetch_sub(offset, ordering)
-    } else {
-        usize::MAX
+    #[cfg(feature = "immortals")]
+    if value.load(Relaxed) == IMMORTAL {
+        return IMMORTAL;
     }
 
-    #[cfg(not(immortals))]
     return value.fetch_sub(offset, ordering);
 }
 
+/// The maximum weak count. With the `arena` feature, it is far below the mark of values in an arena.
+#[cfg(feature = "arena")]
+const MAX_WEAKCOUNT: usize = MAX_REFCOUNT / 2;
+#[cfg(not(feature = "arena"))]
+const MAX_WEAKCOUNT: usize = MAX_REFCOUNT;
+
+/// Return the weak count `count` without the mark of a value in an arena.
+#[inline(always)]
+fn weak_count_value(count: usize) -> usize {
+    #[cfg(feature = "arena")]
+    return count & !arena::ARENA_FLAG;
+    #[cfg(not(feature = "arena"))]
+    return count;
+}
+
+/// Return the weak count of an allocation without `Weak`s, which `is_unique` locks. It keeps the mark of a value in an
+/// arena.
+#[inline(always)]
+fn unique_weak_count(weakcount: &AtomicUsize) -> usize {
+    #[cfg(feature = "arena")]
+    return (weakcount.load(Relaxed) & arena::ARENA_FLAG) | 1;
+    #[cfg(not(feature = "arena"))]
+    {
+        let _ = weakcount;
+        return 1;
+    }
+}
+
+/// Return the number of `Weak`s for an allocation, given its weak count while a strong reference is held.
+#[inline]
+fn strong_weak_count(weakcount: &AtomicUsize) -> usize {
+    let count = weakcount.load(Relaxed);
+    //The weak count is locked by `get_mut`, which means that there are no `Weak`s.
+    if count == usize::MAX {
+        return 0;
+    }
+    return weak_count_value(count) - 1;
+}
+
+/// Allocate `N` uninitialized allocations, pass a `Weak` to each of them to `data_fn`, and then initialize them.
+/// Each returned pointer holds one atomic reference and the implicit weak reference.
+fn new_cyclic_multi_internal<T, const N: usize, F>(data_fn: F) -> [NonNull<SharedTrcInternal<T>>; N]
+where
+    F: FnOnce(&[Weak<T>; N]) -> [T; N],
+{
+    let weaks: [Weak<T>; N] = core::array::from_fn(|_| {
+        #[cfg(feature = "stats")]
+        stats::on_alloc(mem::size_of::<T>());
+        let shareddata = sync::leak_box(SharedTrcInternal {
+            atomicref: AtomicUsize::new(0),
+            weakcount: AtomicUsize::new(1),
+            #[cfg(feature = "allocator_api")]
+            alloc: Global,
+            #[cfg(feature = "padded")]
+            padding: CachePadding,
+            data: MaybeUninit::<T>::uninit(),
+        });
+        Weak {
+            data: shareddata.cast(),
+        }
+    });
+
+    //If `data_fn` panics, the `Weak`s free the allocations.
+    let data = data_fn(&weaks);
+    let weaks = ManuallyDrop::new(weaks);
+
+    for (weak, value) in weaks.iter().zip(data) {
+        unsafe {
+            let ptr = weak.data.as_ptr();
+            ptr::write(ptr::addr_of_mut!((*ptr).data), value);
+
+            //Publish the data to `Weak`s upgraded on other threads, which acquire the count.
+            let prev = sum_value(&weak.data.as_ref().atomicref, 1, Release);
+            if prev > MAX_REFCOUNT {
+                overflow::atomic(
+                    &weak.data.as_ref().atomicref,
+                    "Overflow of maximum atomic reference count.",
+                );
+            }
+        }
+    }
+
+    core::array::from_fn(|i| weaks[i].data)
+}
+
 impl<T> Trc<T> {
     /// Creates a new `Trc` from the provided data.
     ///
@@ -923,16 +2280,20 @@ impl<T> Trc<T> {
         let shareddata = SharedTrcInternal {
             atomicref: AtomicUsize::new(1),
             weakcount: AtomicUsize::new(1),
+            #[cfg(feature = "allocator_api")]
+            alloc: Global,
+            #[cfg(feature = "padded")]
+            padding: CachePadding,
             data: value,
         };
 
-        let sharedbx = Box::new(shareddata);
-
-        let threadbx = Box::new(1);
+        #[cfg(feature = "stats")]
+        stats::on_alloc(mem::size_of::<T>());
 
         return Self {
-            threadref: NonNull::from(Box::leak(threadbx)),
-            shared: NonNull::from(Box::leak(sharedbx)),
+            threadref: new_threadref(),
+            shared: sync::leak_box(shareddata),
+            phantom: PhantomData,
         };
     }
 
@@ -956,43 +2317,163 @@ impl<T> Trc<T> {
         let shareddata = SharedTrcInternal {
             atomicref: AtomicUsize::new(1),
             weakcount: AtomicUsize::new(1),
+            #[cfg(feature = "allocator_api")]
+            alloc: Global,
+            #[cfg(feature = "padded")]
+            padding: CachePadding,
             data: MaybeUninit::<T>::uninit(),
         };
 
-        let sharedbx = Box::new(shareddata);
-
-        let threadbx = Box::new(1);
+        #[cfg(feature = "stats")]
+        stats::on_alloc(mem::size_of::<T>());
 
         return Trc {
-            threadref: NonNull::from(Box::leak(threadbx)),
-            shared: NonNull::from(Box::leak(sharedbx)),
+            threadref: new_threadref(),
+            shared: sync::leak_box(shareddata),
+            phantom: PhantomData,
         };
     }
 
-    /// Creates a new cyclic `Trc` from the provided data. It allows the storage of `Weak` which points the the allocation
-    /// of `Trc`inside of `T`. Holding a `Trc` inside of `T` would cause a memory leak. This method works around this by
-    /// providing a `Weak` during the construction of the `Trc`, so that the `T` can store the `Weak` internally.
+    /// Creates a new `Trc` by initializing the value in place inside of the heap allocation. The closure receives the uninitialized
+    /// value, so large values never need to be constructed on the stack and then moved into the allocation.
+    ///
+    /// If the closure panics, the allocation is freed without dropping the value.
+    ///
+    /// # Safety
+    /// The closure must fully initialize the value before returning, as with [`MaybeUninit::assume_init`].
     ///
     /// # Examples
     /// ```
     /// use trc::Trc;
-    /// use trc::Weak;
     ///
-    /// struct T(Weak<T>);
+    /// const LEN: usize = 1 << 20;
     ///
-    /// let trc = Trc::new_cyclic(|x| T(x.clone()));
+    /// let trc = unsafe {
+    ///     Trc::<[u8; LEN]>::new_with(|slot| {
+    ///         slot.as_mut_ptr().cast::<u8>().write_bytes(7, LEN);
+    ///     })
+    /// };
+    /// assert!(trc.iter().all(|x| *x == 7));
+    /// ```
+    #[inline]
+    pub unsafe fn new_with<F>(init: F) -> Self
+    where
+        F: FnOnce(&mut MaybeUninit<T>),
+    {
+        let trc = Trc {
+            threadref: new_threadref(),
+            shared: alloc_uninit_internal::<T>(),
+            phantom: PhantomData,
+        };
+        init(&mut (*trc.shared.as_ptr()).data);
+        trc.assume_init()
+    }
+
+    /// Creates a new `Trc` with uninitialized contents, with the memory being filled with `0` bytes.
+    /// The allocation is made with [`alloc_zeroed`], so large values can be backed by pages
+    /// which the operating system has already zeroed.
+    ///
+    /// See [`MaybeUninit::zeroed`] for examples of correct and incorrect usage of this method.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let zero = Trc::<u32>::new_zeroed();
+    /// let zero = unsafe { zero.assume_init() };
+    ///
+    /// assert_eq!(*zero, 0);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn new_zeroed() -> Trc<MaybeUninit<T>> {
+        return Trc {
+            threadref: new_threadref(),
+            shared: new_zeroed_internal(),
+            phantom: PhantomData,
+        };
+    }
+
+    /// Creates a new `Trc` from the provided data, returning an error if the allocation fails.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let trc = Trc::try_new(100)?;
+    /// assert_eq!(*trc, 100);
+    /// # Ok::<(), trc::AllocError>(())
+    /// ```
+    #[inline]
+    pub fn try_new(value: T) -> Result<Self, AllocError> {
+        try_into_trc(try_alloc_internal(value)?)
+    }
+
+    /// Creates a new uninitialized `Trc`, returning an error if the allocation fails.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let mut trc = Trc::try_new_uninit()?;
+    ///
+    /// Trc::get_mut(&mut trc).unwrap().write(5);
+    ///
+    /// let five = unsafe { trc.assume_init() };
+    ///
+    /// assert_eq!(*five, 5);
+    /// # Ok::<(), trc::AllocError>(())
+    /// ```
+    #[inline]
+    pub fn try_new_uninit() -> Result<Trc<MaybeUninit<T>>, AllocError> {
+        try_into_trc(try_alloc_internal(MaybeUninit::<T>::uninit())?)
+    }
+
+    /// Creates a new pinned `Trc`, returning an error if the allocation fails.
+    /// If `T` does not implement [`Unpin`], then the data will be pinned in memory and unable to be moved.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let pinned = Trc::try_pin(100)?;
+    /// assert_eq!(*pinned, 100);
+    /// # Ok::<(), trc::AllocError>(())
+    /// ```
+    #[inline]
+    pub fn try_pin(data: T) -> Result<Pin<Self>, AllocError> {
+        Ok(unsafe { Pin::new_unchecked(Self::try_new(data)?) })
+    }
+
+    /// Creates a new cyclic `Trc` from the provided data. It allows the storage of `Weak` which points the the allocation
+    /// of `Trc`inside of `T`. Holding a `Trc` inside of `T` would cause a memory leak. This method works around this by
+    /// providing a `Weak` during the construction of the `Trc`, so that the `T` can store the `Weak` internally.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    /// use trc::Weak;
+    ///
+    /// struct T(Weak<T>);
+    ///
+    /// let trc = Trc::new_cyclic(|x| T(x.clone()));
     /// ```
     #[inline]
     pub fn new_cyclic<F>(data_fn: F) -> Self
     where
         F: FnOnce(&Weak<T>) -> T,
     {
-        let shareddata: NonNull<_> = Box::leak(Box::new(SharedTrcInternal {
+        #[cfg(feature = "stats")]
+        stats::on_alloc(mem::size_of::<T>());
+        let shareddata = sync::leak_box(SharedTrcInternal {
             atomicref: AtomicUsize::new(0),
             weakcount: AtomicUsize::new(1),
+            #[cfg(feature = "allocator_api")]
+            alloc: Global,
+            #[cfg(feature = "padded")]
+            padding: CachePadding,
             data: MaybeUninit::<T>::uninit(),
-        }))
-        .into();
+        });
 
         let init_ptr: NonNull<SharedTrcInternal<T>> = shareddata.cast();
 
@@ -1004,27 +2485,126 @@ impl<T> Trc<T> {
             let ptr = init_ptr.as_ptr();
             ptr::write(ptr::addr_of_mut!((*ptr).data), data);
 
-            let prev = sum_value(&init_ptr.as_ref().atomicref, 1, AcqRel);
-            assert!(
-                prev <= MAX_REFCOUNT,
-                "Overflow of maximum atomic reference count."
-            )
+            //Publish the data to `Weak`s upgraded on other threads, which acquire the count.
+            let prev = sum_value(&init_ptr.as_ref().atomicref, 1, Release);
+            if prev > MAX_REFCOUNT {
+                overflow::atomic(
+                    &init_ptr.as_ref().atomicref,
+                    "Overflow of maximum atomic reference count.",
+                );
+            }
         };
 
-        let tbx = Box::new(1);
-
         return Self {
-            threadref: NonNull::from(Box::leak(tbx)),
+            threadref: new_threadref(),
             shared: init_ptr,
+            phantom: PhantomData,
         };
     }
 
+    /// Creates a new `Trc` of the value returned by `data_fn`, which receives the [`SelfRef`] of the allocation, so that
+    /// the value can implement [`TrcFromSelf`]. Like [`Trc::new_cyclic`], the `SelfRef` cannot be upgraded inside of the
+    /// closure.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{SelfRef, Trc, TrcFromSelf};
+    ///
+    /// struct Node {
+    ///     self_ref: SelfRef<Node>,
+    /// }
+    ///
+    /// impl TrcFromSelf for Node {
+    ///     fn self_ref(&self) -> &SelfRef<Self> {
+    ///         &self.self_ref
+    ///     }
+    /// }
+    ///
+    /// let node = Trc::new_with_self_ref(|self_ref| Node { self_ref });
+    /// assert!(Trc::ptr_eq(&node.trc_from_self(), &node));
+    /// ```
+    #[inline]
+    pub fn new_with_self_ref<F>(data_fn: F) -> Self
+    where
+        F: FnOnce(SelfRef<T>) -> T,
+    {
+        return Self::new_cyclic(|weak| data_fn(SelfRef::new(weak)));
+    }
+
+    /// Creates `N` new cyclic `Trc`s at once. Like [`Trc::new_cyclic`], but the closure receives a `Weak` to every
+    /// allocation before any of them is initialized, so groups of values which refer to each other (such as a ring of nodes)
+    /// can be constructed without storing an `Option<Weak>` and patching it afterwards.
+    /// Calling [`Weak::upgrade`] on any of the `Weak`s inside of the closure will return `None`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    /// use trc::Weak;
+    ///
+    /// struct Node {
+    ///     value: usize,
+    ///     next: Weak<Node>,
+    ///     prev: Weak<Node>,
+    /// }
+    ///
+    /// let ring: [Trc<Node>; 3] = Trc::new_cyclic_multi(|weaks| {
+    ///     std::array::from_fn(|i| Node {
+    ///         value: i,
+    ///         next: weaks[(i + 1) % 3].clone(),
+    ///         prev: weaks[(i + 2) % 3].clone(),
+    ///     })
+    /// });
+    /// assert_eq!(ring[0].next.upgrade().unwrap().value, 1);
+    /// assert_eq!(ring[0].prev.upgrade().unwrap().value, 2);
+    /// ```
+    #[inline]
+    pub fn new_cyclic_multi<const N: usize, F>(data_fn: F) -> [Self; N]
+    where
+        F: FnOnce(&[Weak<T>; N]) -> [T; N],
+    {
+        new_cyclic_multi_internal(data_fn).map(|shared| Self {
+            threadref: new_threadref(),
+            shared,
+            phantom: PhantomData,
+        })
+    }
+
     /// Creates a new pinned `Trc`. If `T` does not implement [`Unpin`], then the data will be pinned in memory and unable to be moved.
     #[inline]
     pub fn pin(data: T) -> Pin<Self> {
         unsafe { Pin::new_unchecked(Self::new(data)) }
     }
 
+    /// Creates a new pinned cyclic `Trc`. Like [`Trc::new_cyclic`], but the closure receives a [`PinnedWeak`], which
+    /// can only be upgraded to a `Pin<Trc<T>>`. This keeps the data pinned even through handles created from the `Weak`,
+    /// so self-referential values such as intrusive list nodes can be constructed soundly.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::marker::PhantomPinned;
+    /// use trc::{PinnedWeak, Trc};
+    ///
+    /// struct Node {
+    ///     this: PinnedWeak<Node>,
+    ///     _pin: PhantomPinned,
+    /// }
+    ///
+    /// let node = Trc::pin_cyclic(|weak| Node {
+    ///     this: weak.clone(),
+    ///     _pin: PhantomPinned,
+    /// });
+    /// let node2 = node.this.upgrade().unwrap();
+    /// assert!(std::ptr::eq(&*node, &*node2));
+    /// ```
+    #[inline]
+    pub fn pin_cyclic<F>(data_fn: F) -> Pin<Self>
+    where
+        F: FnOnce(&PinnedWeak<T>) -> T,
+    {
+        //SAFETY: The `Weak` is only exposed as a `PinnedWeak`, which never hands out an unpinned `Trc`.
+        unsafe { Pin::new_unchecked(Self::new_cyclic(|weak| data_fn(PinnedWeak::from_ref(weak)))) }
+    }
+
     /// Returns the inner value if the `Trc` has exactly one atomic and local reference.
     /// Otherwise, an [`Err`] is returned with the same `Trc` that was passed in.
     /// This will succeed even if there are outstanding weak references.
@@ -1050,7 +2630,8 @@ impl<T> Trc<T> {
     /// ```
     #[inline]
     pub fn try_unwrap(mut this: Self) -> Result<T, Self> {
-        if unsafe { this.shared.as_ref() }.atomicref.load(Acquire) != 1
+        //The fence below synchronizes with the other handles' drops once the value is taken.
+        if unsafe { this.shared.as_ref() }.atomicref.load(Relaxed) != 1
             || *unsafe { this.threadref.as_ref() } != 1
         {
             return Err(this);
@@ -1061,7 +2642,7 @@ impl<T> Trc<T> {
 
         unsafe {
             let elem = ptr::read(&this.shared.as_ref().data);
-            drop(Box::from_raw(this.threadref.as_ptr()));
+            free_threadref(this.shared.as_ptr(), this.threadref);
 
             //Clean up implicit self-reference
             drop(Weak { data: this.shared });
@@ -1078,7 +2659,8 @@ impl<T> Trc<T> {
     /// This means the inner value is not dropped. The similar expression `Trc::try_unwrap(this).ok` does not offer such a guarantee.
     ///
     /// # Examples
-    /// ```
+    #[cfg_attr(feature = "single-threaded", doc = "```ignore")]
+    #[cfg_attr(not(feature = "single-threaded"), doc = "```")]
     /// use trc::Trc;
     /// use trc::SharedTrc;
     ///
@@ -1107,17 +2689,22 @@ impl<T> Trc<T> {
     pub fn into_inner(this: Self) -> Option<T> {
         let this = ManuallyDrop::new(this);
 
-        if sub_value(&unsafe { this.shared.as_ref() }.atomicref, 1, Release) != 1
-            || *unsafe { this.threadref.as_ref() } != 1
-        {
-            drop(unsafe { Box::from_raw(this.threadref.as_ptr()) });
+        //The atomic reference is held by the local count, which may be shared with other `Trc`s on this thread (see
+        //`SharedTrc::to_trc`), so it is only released with the last of them.
+        *unsafe { &mut *this.threadref.as_ptr() } -= 1;
+        if *unsafe { this.threadref.as_ref() } != 0 {
             return None;
         }
+        unsafe { free_threadref(this.shared.as_ptr(), this.threadref) };
 
-        fence(Acquire);
+        let prev = sub_value(&unsafe { this.shared.as_ref() }.atomicref, 1, AcqRel);
+        #[cfg(feature = "std")]
+        wait::on_release(prev, this.shared.as_ptr());
+        if prev != 1 {
+            return None;
+        }
 
         let elem = unsafe { ptr::read(addr_of_mut!((*this.shared.as_ptr()).data)) };
-        drop(unsafe { Box::from_raw(this.threadref.as_ptr()) });
 
         //Clean up implicit self-reference
         drop(Weak { data: this.shared });
@@ -1126,1113 +2713,3504 @@ impl<T> Trc<T> {
     }
 }
 
-impl<T> Trc<[T]> {
-    /// Constructs a new `Trc` slice with uninitialized contents.
+#[cfg(feature = "cycles")]
+impl<T: cycles::Trace + 'static> Trc<T> {
+    /// Creates a new `Trc` from the provided data, and registers it with the cycle collector of the current thread, so that
+    /// it can be reclaimed by [`collect_cycles`] if it becomes part of an unreachable cycle.
     ///
     /// # Examples
     /// ```
-    /// use trc::Trc;
-    ///
-    /// let mut values = Trc::<[u32]>::new_uninit_slice(3);
+    /// use std::cell::RefCell;
+    /// use trc::{cycles::{Trace, Tracer}, Trc};
     ///
-    /// // Deferred initialization:
-    /// let data = Trc::get_mut(&mut values).unwrap();
-    /// data[0].write(1);
-    /// data[1].write(2);
-    /// data[2].write(3);
+    /// struct Node(RefCell<Vec<Trc<Node>>>);
     ///
-    /// let values = unsafe { values.assume_init() };
+    /// unsafe impl Trace for Node {
+    ///     fn trace(&self, tracer: &mut Tracer) {
+    ///         self.0.trace(tracer);
+    ///     }
+    /// }
     ///
-    /// assert_eq!(*values, [1, 2, 3])
+    /// let node = Trc::new_traced(Node(RefCell::new(Vec::new())));
+    /// node.0.borrow_mut().push(node.clone());
+    /// drop(node);
+    /// assert_eq!(trc::collect_cycles(), 1);
     /// ```
     #[must_use]
-    pub fn new_uninit_slice(len: usize) -> Trc<[MaybeUninit<T>]> {
-        let value_layout = Layout::array::<T>(len).unwrap();
-        let layout = Layout::new::<SharedTrcInternal<()>>()
-            .extend(value_layout)
-            .unwrap()
-            .0
-            .pad_to_align();
-
-        let res = slice_from_raw_parts_mut(unsafe { alloc(layout) }.cast::<T>(), len)
-            as *mut SharedTrcInternal<[MaybeUninit<T>]>;
-        unsafe { write(&mut (*res).atomicref, AtomicUsize::new(1)) };
-        unsafe { write(&mut (*res).weakcount, AtomicUsize::new(1)) };
-
-        let elems = unsafe { addr_of_mut!((*res).data) }.cast::<std::mem::MaybeUninit<T>>();
-        for i in 0..len {
-            unsafe {
-                write(elems.add(i), MaybeUninit::<T>::uninit());
-            }
-        }
-        let tbx = Box::new(1);
-
-        return Trc {
-            threadref: NonNull::from(Box::leak(tbx)),
-            shared: unsafe { NonNull::new_unchecked(res) },
-        };
+    pub fn new_traced(value: T) -> Self {
+        let trc = Self::new(value);
+        cycles::register(&trc);
+        return trc;
     }
 }
 
-impl<T> Trc<MaybeUninit<T>> {
-    /// Assume that `Trc<MaybeUninit<T>>` is initialized, converting it to `Trc<T>`.
-    ///
-    /// # Safety
-    /// As with `MaybeUninit::assume_init`, it is up to the caller to guarantee that the inner value really is in an initialized state.
-    /// Calling this when the content is not yet fully initialized causes immediate undefined behavior.
+#[cfg(feature = "leak-debug")]
+impl<T: ?Sized> Trc<T> {
+    /// Return the backtraces of the live clones of the `Trc`s and `SharedTrc`s of this allocation, from the oldest to the
+    /// most recent. See the [`leak_debug`] module.
     ///
     /// # Examples
     /// ```
     /// use trc::Trc;
     ///
-    /// let mut trc = Trc::new_uninit();
-    ///
-    /// Trc::get_mut(&mut trc).unwrap().write(5);
-    ///
-    /// let five = unsafe { trc.assume_init() };
+    /// let trc = Trc::new(100);
+    /// assert!(Trc::live_handle_traces(&trc).is_empty());
     ///
-    /// assert_eq!(*five, 5);
+    /// let trc2 = trc.clone();
+    /// let traces = Trc::live_handle_traces(&trc);
+    /// assert_eq!(traces.len(), 1);
+    /// assert!(!traces[0].is_shared());
+    /// println!("{}", traces[0]);
     /// ```
     #[must_use]
-    pub unsafe fn assume_init(self) -> Trc<T> {
-        let threadref = self.threadref;
-        Trc {
-            shared: NonNull::new_unchecked(ManuallyDrop::new(self).shared.as_ptr().cast()),
-            threadref,
-        }
+    pub fn live_handle_traces(this: &Self) -> Vec<leak_debug::HandleTrace> {
+        return leak_debug::traces(this.shared.as_ptr());
     }
 }
 
-impl<T> Trc<[MaybeUninit<T>]> {
-    /// Assume that all elements in `Trc<[MaybeUninit<T>]>` are initialized, converting it to `Trc<[T]>`.
-    ///
-    /// # Safety
-    /// As with `MaybeUninit::assume_init`, it is up to the caller to guarantee that the inner value really is in an initialized state.
-    /// Calling this when the content is not yet fully initialized causes immediate undefined behavior.
+#[cfg(feature = "immortals")]
+impl<T> Trc<T> {
+    /// Creates a new immortal `Trc` from the provided data. The atomic reference count of an immortal allocation is never
+    /// changed, so `Trc`s and `SharedTrc`s of it can be shared between threads without atomic operations, and the value is
+    /// never dropped or deallocated. The local counts of `Trc`s are still tracked and freed as usual.
     ///
     /// # Examples
     /// ```
-    /// use trc::Trc;
-    ///
-    /// let mut values = Trc::<[u32]>::new_uninit_slice(3);
-    ///
-    /// // Deferred initialization:
-    /// let data = Trc::get_mut(&mut values).unwrap();
-    /// data[0].write(1);
-    /// data[1].write(2);
-    /// data[2].write(3);
+    /// use trc::{SharedTrc, Trc};
     ///
-    /// let values = unsafe { values.assume_init() };
+    /// let trc = Trc::new_immortal(100);
+    /// let shared = SharedTrc::from_trc(&trc);
+    /// drop(trc);
     ///
-    /// assert_eq!(*values, [1, 2, 3])
+    /// let handle = std::thread::spawn(move || *shared);
+    /// assert_eq!(handle.join().unwrap(), 100);
     /// ```
     #[must_use]
-    pub unsafe fn assume_init(self) -> Trc<[T]> {
-        let threadref = self.threadref;
-        Trc {
-            shared: NonNull::new_unchecked(
-                ManuallyDrop::new(self).shared.as_ptr() as *mut SharedTrcInternal<[T]>
-            ),
-            threadref,
-        }
+    pub fn new_immortal(value: T) -> Self {
+        let trc = Self::new(value);
+        unsafe { trc.shared.as_ref() }
+            .atomicref
+            .store(IMMORTAL, Relaxed);
+        return trc;
     }
 }
 
+#[cfg(feature = "immortals")]
 impl<T: ?Sized> Trc<T> {
-    /// Return the local thread reference count of the object, which is how many `Trc`s in this thread point to the data referenced by this `Trc`.
+    /// Return whether the allocation is immortal. The [`Trc::atomic_count`] of an immortal allocation is `usize::MAX`.
     ///
     /// # Examples
     /// ```
     /// use trc::Trc;
     ///
-    /// let trc = Trc::new(100);
-    /// assert!(Trc::local_count(&trc) == 1)
+    /// assert!(Trc::is_immortal(&Trc::new_immortal(100)));
+    /// assert!(!Trc::is_immortal(&Trc::new(100)));
     /// ```
     #[inline]
     #[must_use]
-    pub fn local_count(this: &Self) -> usize {
-        return *unsafe { this.threadref.as_ref() };
+    pub fn is_immortal(this: &Self) -> bool {
+        return unsafe { this.shared.as_ref() }.atomicref.load(Relaxed) == IMMORTAL;
     }
+}
 
-    /// Return the atomic reference count of the object. This is how many threads are using the data referenced by this `Trc`.
+impl<T> Trc<[T]> {
+    /// Constructs a new `Trc` slice with uninitialized contents.
+    ///
+    /// # Examples
     /// ```
-    /// use std::thread;
     /// use trc::Trc;
-    /// use trc::SharedTrc;
     ///
-    /// let trc = Trc::new(100);
-    /// let shared = SharedTrc::from_trc(&trc);
+    /// let mut values = Trc::<[u32]>::new_uninit_slice(3);
     ///
-    /// let handle = thread::spawn(move || {
-    ///     let mut trc = SharedTrc::to_trc(shared);
-    ///     assert_eq!(Trc::atomic_count(&trc), 2);
-    /// });
+    /// // Deferred initialization:
+    /// let data = Trc::get_mut(&mut values).unwrap();
+    /// data[0].write(1);
+    /// data[1].write(2);
+    /// data[2].write(3);
     ///
-    /// handle.join().unwrap();
-    /// assert_eq!(Trc::atomic_count(&trc), 1);
-    /// assert_eq!(*trc, 100);
+    /// let values = unsafe { values.assume_init() };
+    ///
+    /// assert_eq!(*values, [1, 2, 3])
     /// ```
-    #[inline]
     #[must_use]
-    pub fn atomic_count(this: &Self) -> usize {
-        return unsafe { this.shared.as_ref() }.atomicref.load(Relaxed);
+    pub fn new_uninit_slice(len: usize) -> Trc<[MaybeUninit<T>]> {
+        return Trc {
+            threadref: new_threadref(),
+            shared: alloc_slice_internal(len),
+            phantom: PhantomData,
+        };
     }
 
-    /// Return the weak count of the object. This is how many weak counts - across all threads - are pointing to the allocation inside of `Trc`.
-    /// It includes the implicit weak reference held by all `Trc` or `SharedTrc` to themselves.
+    /// Constructs a new `Trc` slice from an iterator with an exact size. Unlike [`FromIterator`], the elements are written
+    /// directly into the new allocation in a single pass, without collecting them into a `Vec` first.
+    ///
+    /// If the iterator panics, the elements which were already written are dropped and the allocation is freed.
+    ///
+    /// # Panics
+    /// Panics if the iterator yields fewer elements than reported by [`ExactSizeIterator::len`].
     ///
     /// # Examples
     /// ```
     /// use trc::Trc;
-    /// use trc::Weak;
     ///
-    /// let trc = Trc::new(100i32);
-    /// let weak = Trc::downgrade(&trc);
-    /// let weak2 = Trc::downgrade(&trc);
-    /// let new_trc = Weak::upgrade(&weak).expect("Value was dropped");
-    /// drop(weak);
-    /// assert_eq!(Trc::weak_count(&new_trc), 2);
+    /// let trc = Trc::<[i32]>::from_exact_iter((1..4).map(|x| x * 2));
+    /// assert_eq!(*trc, [2, 4, 6]);
     /// ```
-    #[inline]
-    #[must_use]
-    pub fn weak_count(this: &Self) -> usize {
-        return unsafe { this.shared.as_ref() }.weakcount.load(Relaxed);
+    pub fn from_exact_iter<I>(iter: I) -> Trc<[T]>
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        <Self as TrcFromIter<T>>::from_iter(iter.into_iter())
     }
 
-    /// Checks if the other `Trc` is equal to this one according to their internal pointers.
+    /// Constructs a new `Trc` slice of length `len`, calling `f` with the index of each element to produce it. The
+    /// elements are written directly into the new allocation.
+    ///
+    /// If `f` panics, the elements which were already written are dropped and the allocation is freed.
     ///
     /// # Examples
     /// ```
     /// use trc::Trc;
     ///
-    /// let trc1 = Trc::new(100);
-    /// let trc2 = trc1.clone();
-    /// assert!(Trc::ptr_eq(&trc1, &trc2));
+    /// let squares = Trc::<[usize]>::new_slice_with(4, |i| i * i);
+    /// assert_eq!(*squares, [0, 1, 4, 9]);
     /// ```
     #[inline]
-    #[must_use]
-    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
-        std::ptr::eq(this.shared.as_ptr(), other.shared.as_ptr())
+    pub fn new_slice_with<F>(len: usize, f: F) -> Trc<[T]>
+    where
+        F: FnMut(usize) -> T,
+    {
+        <Self as TrcFromIter<T>>::from_iter((0..len).map(f))
     }
 
-    /// Gets the raw pointer to the most inner layer of `Trc`. This is only valid if there are at least some atomic references.
+    /// Moves the elements into a [`Vec`] if this is the only strong reference, including the local one.
+    /// Otherwise, an [`Err`] is returned with the same `Trc` that was passed in.
+    /// This will succeed even if there are outstanding weak references, which will no longer be able to upgrade.
     ///
     /// # Examples
     /// ```
     /// use trc::Trc;
     ///
-    /// let trc = Trc::new(100);
-    /// println!("{}", Trc::as_ptr(&trc) as usize)
+    /// let trc = Trc::<[String]>::from(vec![String::from("a"), String::from("b")]);
+    /// let vec = Trc::try_unwrap_into_vec(trc).ok().unwrap();
+    /// assert_eq!(vec, ["a", "b"]);
     /// ```
     #[inline]
-    #[must_use]
-    pub fn as_ptr(this: &Self) -> *const T {
-        let sharedptr = NonNull::as_ptr(this.shared);
-        unsafe { addr_of_mut!((*sharedptr).data) }
+    pub fn try_unwrap_into_vec(this: Self) -> Result<Vec<T>, Self> {
+        return Trc::try_into_box(this).map(Vec::from);
     }
 
-    /// Get a &mut reference to the internal data if there are no other `Trc`, [`SharedTrc`] or [`Weak`] pointers to the same allocation.
-    /// Otherwise, return [`None`] because it would be unsafe to mutate a shared value.
+    /// Constructs a new `Trc` slice of `len` copies of `value`, the shared equivalent of `vec![value; len]`. The
+    /// elements are cloned directly into the new allocation, and `value` itself is moved in as the last element.
     ///
     /// # Examples
     /// ```
     /// use trc::Trc;
-    /// use std::ops::DerefMut;
     ///
-    /// let mut trc = Trc::new(100);
-    /// let mutref = Trc::get_mut(&mut trc).unwrap();
-    /// *mutref = 300;
-    /// assert_eq!(*trc, 300);
+    /// let trc = Trc::<[String]>::filled(String::from("a"), 3);
+    /// assert_eq!(*trc, ["a", "a", "a"]);
     /// ```
-    #[inline]
-    pub fn get_mut(this: &mut Self) -> Option<&mut T> {
-        //Acquire the weakcount if it is == 1
-        if unsafe { this.shared.as_ref() }
-            .weakcount
-            .compare_exchange(1, usize::MAX, Acquire, Relaxed)
-            .is_ok()
-        {
-            //Acquire the atomicref
-            let unique = unsafe { this.shared.as_ref() }.atomicref.load(Acquire) == 1;
-
-            //Synchronize with the previous Acquire
-            unsafe { this.shared.as_ref() }.weakcount.store(1, Release);
-
-            if unique && *unsafe { this.threadref.as_ref() } == 1 {
-                Some(unsafe { &mut (*this.shared.as_ptr()).data })
-            } else {
-                None
+    pub fn filled(value: T, len: usize) -> Trc<[T]>
+    where
+        T: Clone,
+    {
+        let mut guard = SliceGuard::<T>::new(len);
+        if len > 0 {
+            for _ in 1..len {
+                unsafe { guard.push(value.clone()) };
             }
-        } else {
-            None
+            unsafe { guard.push(value) };
         }
+
+        return Trc {
+            threadref: new_threadref(),
+            shared: guard.finish(),
+            phantom: PhantomData,
+        };
     }
-}
 
-impl<T: Clone> Trc<T> {
-    /// If we have the only strong and local reference to `T`, then unwrap it. Otherwise, clone `T` and return the clone.
-    /// If `trc_t` is of type `Trc<T>`, this function is functionally equivalent to `(*trc_t).clone()`, but will avoid cloning the inner
-    /// value where possible.
+    /// Constructs a new `Trc` slice with uninitialized contents, with the memory being filled with `0` bytes.
+    /// The allocation is made with [`alloc_zeroed`], so large buffers can be backed by pages
+    /// which the operating system has already zeroed.
+    ///
+    /// See [`MaybeUninit::zeroed`] for examples of correct and incorrect usage of this method.
     ///
     /// # Examples
     /// ```
     /// use trc::Trc;
     ///
-    /// let inner = String::from("Trc");
-    /// let ptr = inner.as_ptr();
-    ///
-    /// let trc = Trc::new(inner);
-    /// let trc2 = trc.clone();
-    /// let inner = Trc::unwrap_or_clone(trc);
-    /// assert!(!std::ptr::eq(ptr, inner.as_ptr()));
+    /// let values = Trc::<[u32]>::new_zeroed_slice(3);
+    /// let values = unsafe { values.assume_init() };
     ///
-    /// let inner = Trc::unwrap_or_clone(trc2);
-    /// assert!(std::ptr::eq(ptr, inner.as_ptr()));
+    /// assert_eq!(*values, [0, 0, 0])
     /// ```
-    #[inline]
     #[must_use]
-    pub fn unwrap_or_clone(this: Self) -> T {
-        Self::try_unwrap(this).unwrap_or_else(|trc| (*trc).clone())
+    pub fn new_zeroed_slice(len: usize) -> Trc<[MaybeUninit<T>]> {
+        return Trc {
+            threadref: new_threadref(),
+            shared: new_zeroed_slice_internal(len),
+            phantom: PhantomData,
+        };
     }
-}
 
-#[cfg(feature = "dyn_unstable")]
-impl Trc<dyn Any + Send + Sync> {
-    /// Attempts to downcast a `Trc<dyn Any + Send + Sync>` into a concrete type.
+    /// Constructs a new `Trc` slice with uninitialized contents, returning an error if the allocation fails or if
+    /// the size of the slice overflows.
     ///
     /// # Examples
     /// ```
-    /// use std::any::Any;
     /// use trc::Trc;
     ///
-    /// fn print_if_string(value: Trc<dyn Any + Send + Sync>) {
-    ///     if let Ok(string) = value.downcast::<String>() {
-    ///         println!("String ({}): {}", string.len(), string);
-    ///     }
-    /// }
+    /// let mut values = Trc::<[u32]>::try_new_uninit_slice(3)?;
     ///
-    /// let my_string = "Hello World".to_string();
-    /// print_if_string(Trc::new(my_string));
-    /// print_if_string(Trc::new(0i8));
+    /// // Deferred initialization:
+    /// let data = Trc::get_mut(&mut values).unwrap();
+    /// data[0].write(1);
+    /// data[1].write(2);
+    /// data[2].write(3);
+    ///
+    /// let values = unsafe { values.assume_init() };
+    ///
+    /// assert_eq!(*values, [1, 2, 3]);
+    /// assert!(Trc::<[u32]>::try_new_uninit_slice(usize::MAX).is_err());
+    /// # Ok::<(), trc::AllocError>(())
     /// ```
-    pub fn downcast<T>(self) -> Result<Trc<T>, Self>
-    where
-        T: Any + Send + Sync,
-    {
-        if (*self).is::<T>() {
-            let shared = self.shared.cast::<SharedTrcInternal<T>>();
-            let threadref = self.threadref;
-            forget(self);
-            Ok(Trc { shared, threadref })
-        } else {
-            Err(self)
-        }
+    pub fn try_new_uninit_slice(len: usize) -> Result<Trc<[MaybeUninit<T>]>, AllocError> {
+        try_into_trc(try_alloc_slice_internal(len)?)
     }
-}
 
-impl<T: ?Sized> Trc<T> {
-    /// Downgrade a `Trc` to a `Weak`. This increments the weak count.
+    /// Return a [`TrcSlice`] of the elements in `range`, which shares this allocation instead of copying the elements.
+    ///
+    /// # Panics
+    /// Panics if `range` is out of bounds.
     ///
     /// # Examples
     /// ```
-    /// use trc::Trc;
-    /// use trc::Weak;
+    /// use trc::{Trc, TrcSlice};
     ///
-    /// let trc = Trc::new(100);
-    /// let weak = Trc::downgrade(&trc);
+    /// //Two columns of three rows, stored one after the other.
+    /// let table: Trc<[f64]> = Trc::from(vec![1.0, 2.0, 3.0, 10.0, 20.0, 30.0]);
+    /// let x: TrcSlice<f64> = Trc::slice(&table, ..3);
+    /// let y: TrcSlice<f64> = Trc::slice(&table, 3..);
+    ///
+    /// assert_eq!(*x, [1.0, 2.0, 3.0]);
+    /// assert_eq!(*y, [10.0, 20.0, 30.0]);
+    /// assert_eq!(Trc::local_count(&table), 3);
     /// ```
-    #[inline]
     #[must_use]
-    pub fn downgrade(trc: &Self) -> Weak<T> {
-        let prev = sum_value(&unsafe { trc.shared.as_ref() }.weakcount, 1, Acquire);
-        assert!(
-            prev <= MAX_REFCOUNT,
-            "Overflow of maximum weak reference count."
-        );
-        Weak { data: trc.shared }
+    pub fn slice<R: RangeBounds<usize>>(this: &Self, range: R) -> TrcSlice<T> {
+        return TrcSlice::new(this.clone()).slice(range);
     }
 }
 
-impl<T: ?Sized> Deref for Trc<T> {
-    type Target = T;
-
-    /// Get an immutable reference to the internal data.
+impl<T, const N: usize> Trc<[T; N]> {
+    /// Convert a `Trc<[T; N]>` into a `Trc<[T]>` without changing any counts. This performs the same unsizing
+    /// as the `dyn_unstable` coercion, but is available on stable Rust.
     ///
     /// # Examples
     /// ```
     /// use trc::Trc;
-    /// use std::ops::Deref;
     ///
-    /// let mut trc = Trc::new(100i32);
-    /// assert_eq!(*trc, 100i32);
-    /// assert_eq!(trc.deref(), &100i32);
+    /// let trc = Trc::new([1, 2, 3]);
+    /// let slice: Trc<[i32]> = Trc::unsize_array(trc);
+    /// assert_eq!(*slice, [1, 2, 3]);
     /// ```
-    #[inline]
-    fn deref(&self) -> &Self::Target {
-        return &unsafe { self.shared.as_ref() }.data;
+    #[must_use]
+    pub fn unsize_array(this: Self) -> Trc<[T]> {
+        let this = ManuallyDrop::new(this);
+        Trc {
+            shared: unsize_array_ptr(this.shared),
+            threadref: this.threadref,
+            phantom: PhantomData,
+        }
     }
 }
 
-impl<T: ?Sized> Drop for Trc<T> {
-    #[cfg(immortals)]
-    #[inline]
-    fn drop(&mut self) {
-        if unsafe { self.shared.as_ref() }.atomicref.load(Acquire) != usize::MAX {
-            //If it is not immortal
-            *unsafe { self.threadref.as_mut() } -= 1;
-            if *unsafe { self.threadref.as_ref() } == 0 {
-                drop(unsafe { Box::from_raw(self.threadref.as_ptr()) });
-                if sub_value(&unsafe { self.shared.as_ref() }.atomicref, 1, Release) != 1 {
-                    return;
-                }
-
-                sync::atomic::fence(Acquire);
-                unsafe { ptr::drop_in_place(addr_of_mut!((*self.shared.as_ptr()).data)) };
-                Weak { data: self.shared };
-            }
+impl<T> Trc<MaybeUninit<T>> {
+    /// Assume that `Trc<MaybeUninit<T>>` is initialized, converting it to `Trc<T>`.
+    ///
+    /// # Safety
+    /// As with `MaybeUninit::assume_init`, it is up to the caller to guarantee that the inner value really is in an initialized state.
+    /// Calling this when the content is not yet fully initialized causes immediate undefined behavior.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let mut trc = Trc::new_uninit();
+    ///
+    /// Trc::get_mut(&mut trc).unwrap().write(5);
+    ///
+    /// let five = unsafe { trc.assume_init() };
+    ///
+    /// assert_eq!(*five, 5);
+    /// ```
+    #[cfg(not(feature = "allocator_api"))]
+    #[must_use]
+    pub unsafe fn assume_init(self) -> Trc<T> {
+        let threadref = self.threadref;
+        Trc {
+            shared: NonNull::new_unchecked(ManuallyDrop::new(self).shared.as_ptr().cast()),
+            threadref,
+            phantom: PhantomData,
         }
     }
 
-    #[cfg(not(immortals))]
-    #[inline]
-    fn drop(&mut self) {
-        *unsafe { self.threadref.as_mut() } -= 1;
-        if *unsafe { self.threadref.as_ref() } == 0 {
-            drop(unsafe { Box::from_raw(self.threadref.as_ptr()) });
-            if sub_value(&unsafe { self.shared.as_ref() }.atomicref, 1, Release) != 1 {
-                return;
-            }
+    /// Initialize the value in place and convert the `Trc` to a `Trc<T>`. Unlike writing through [`Trc::get_mut`], this
+    /// succeeds while there are outstanding [`Weak`] references. While the value is being written, those `Weak`s cannot
+    /// be upgraded.
+    ///
+    /// # Panics
+    /// Panics if there are other `Trc` or [`SharedTrc`] references to the allocation.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let trc = Trc::new_uninit();
+    /// let weak = Trc::downgrade(&trc);
+    ///
+    /// let five = Trc::write(trc, 5);
+    /// assert_eq!(*five, 5);
+    /// ```
+    pub fn write(this: Self, value: T) -> Trc<T> {
+        let unique = *unsafe { this.threadref.as_ref() } == 1
+            //Prevent `Weak`s from upgrading while the value is written
+            && unsafe { this.shared.as_ref() }
+                .atomicref
+                .compare_exchange(1, 0, Acquire, Relaxed)
+                .is_ok();
+        assert!(unique, "Cannot write to a shared `Trc`.");
 
-            fence(Acquire);
-            unsafe { ptr::drop_in_place(addr_of_mut!((*self.shared.as_ptr()).data)) };
-            Weak { data: self.shared };
+        unsafe {
+            write(
+                addr_of_mut!((*this.shared.as_ptr()).data).cast::<T>(),
+                value,
+            );
+            this.shared.as_ref().atomicref.store(1, Release);
+            this.assume_init()
         }
     }
-}
 
-impl<T: ?Sized> Clone for Trc<T> {
-    /// Clone a `Trc` (increment it's local reference count).
-    /// It will panic if the local reference count overflows.
+    /// Get a reference to the inner value, assuming that it is initialized.
+    ///
+    /// # Safety
+    /// As with `MaybeUninit::assume_init_ref`, it is up to the caller to guarantee that the inner value really is in an
+    /// initialized state.
+    ///
+    /// # Examples
     /// ```
     /// use trc::Trc;
     ///
-    /// let trc = Trc::new(100);
-    /// let trc2 = trc.clone();
-    /// assert_eq!(Trc::local_count(&trc), Trc::local_count(&trc2));
+    /// let mut trc = Trc::new_uninit();
+    /// Trc::get_mut(&mut trc).unwrap().write(5);
+    ///
+    /// assert_eq!(unsafe { *Trc::assume_init_ref(&trc) }, 5);
     /// ```
-    #[inline(always)]
-    fn clone(&self) -> Self {
-        #[cfg(immortals)]
-        if value.load(Acquire) == usize::MAX {
-            //Is immortal
-            return Trc {
-                shared: self.shared,
-                threadref: self.threadref,
-            };
-        }
-
-        unsafe { *self.threadref.as_ptr() += 1 };
-        assert!(
-            unsafe { *self.threadref.as_ptr() } <= MAX_REFCOUNT,
-            "Overflow of maximum atomic reference count."
-        );
-
-        Self {
-            shared: self.shared,
-            threadref: self.threadref,
-        }
+    #[inline]
+    #[must_use]
+    pub unsafe fn assume_init_ref(this: &Self) -> &T {
+        return (**this).assume_init_ref();
     }
-}
 
-impl<T: ?Sized> AsRef<T> for Trc<T> {
-    fn as_ref(&self) -> &T {
-        return Self::deref(self);
+    /// Get a mutable reference to the inner value, assuming that it is initialized.
+    ///
+    /// # Safety
+    /// As with `MaybeUninit::assume_init_mut`, it is up to the caller to guarantee that the inner value really is in an
+    /// initialized state. Additionally, no other `Trc`, [`SharedTrc`] or upgraded [`Weak`] may access the value for the
+    /// duration of the returned borrow.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let mut trc = Trc::new_uninit();
+    /// Trc::get_mut(&mut trc).unwrap().write(5);
+    ///
+    /// unsafe { *Trc::assume_init_mut(&mut trc) += 1 };
+    /// let six = unsafe { trc.assume_init() };
+    /// assert_eq!(*six, 6);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub unsafe fn assume_init_mut(this: &mut Self) -> &mut T {
+        return (*addr_of_mut!((*this.shared.as_ptr()).data)).assume_init_mut();
     }
 }
 
-impl<T: ?Sized> AsRef<T> for SharedTrc<T> {
-    fn as_ref(&self) -> &T {
-        return Self::deref(self);
+impl<T> Trc<[MaybeUninit<T>]> {
+    /// Assume that all elements in `Trc<[MaybeUninit<T>]>` are initialized, converting it to `Trc<[T]>`.
+    ///
+    /// # Safety
+    /// As with `MaybeUninit::assume_init`, it is up to the caller to guarantee that the inner value really is in an initialized state.
+    /// Calling this when the content is not yet fully initialized causes immediate undefined behavior.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let mut values = Trc::<[u32]>::new_uninit_slice(3);
+    ///
+    /// // Deferred initialization:
+    /// let data = Trc::get_mut(&mut values).unwrap();
+    /// data[0].write(1);
+    /// data[1].write(2);
+    /// data[2].write(3);
+    ///
+    /// let values = unsafe { values.assume_init() };
+    ///
+    /// assert_eq!(*values, [1, 2, 3])
+    /// ```
+    #[must_use]
+    pub unsafe fn assume_init(self) -> Trc<[T]> {
+        let threadref = self.threadref;
+        Trc {
+            shared: NonNull::new_unchecked(
+                ManuallyDrop::new(self).shared.as_ptr() as *mut SharedTrcInternal<[T]>
+            ),
+            threadref,
+            phantom: PhantomData,
+        }
     }
 }
 
-impl<T: ?Sized> Borrow<T> for Trc<T> {
-    fn borrow(&self) -> &T {
-        return self.as_ref();
+impl<T: ?Sized> Trc<T> {
+    /// Return the local thread reference count of the object, which is how many `Trc`s in this thread point to the data referenced by this `Trc`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let trc = Trc::new(100);
+    /// assert!(Trc::local_count(&trc) == 1)
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn local_count(this: &Self) -> usize {
+        return *unsafe { this.threadref.as_ref() };
     }
-}
 
-impl<T: ?Sized> Borrow<T> for SharedTrc<T> {
-    fn borrow(&self) -> &T {
-        return self.as_ref();
-    }
-}
-
-impl<T: ?Sized + Default> Default for Trc<T> {
-    fn default() -> Self {
-        Self::new(Default::default())
-    }
-}
-
-impl<T: ?Sized + Default> Default for SharedTrc<T> {
-    fn default() -> Self {
-        Self::from_trc(&Trc::new(Default::default()))
-    }
-}
-
-impl<T: Display> Display for Trc<T> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt::Display::fmt(&*(*self), f)
-    }
-}
-
-impl<T: Display> Display for SharedTrc<T> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt::Display::fmt(&*(*self), f)
-    }
-}
-
-impl<T: Debug> Debug for Trc<T> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt::Debug::fmt(&*(*self), f)
-    }
-}
-
-impl<T: Debug> Debug for SharedTrc<T> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt::Debug::fmt(&*(*self), f)
-    }
-}
-
-impl<T: ?Sized> Pointer for Trc<T> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt::Pointer::fmt(&addr_of!(unsafe { self.shared.as_ref() }.data), f)
-    }
-}
-
-impl<T: ?Sized> Pointer for SharedTrc<T> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt::Pointer::fmt(&addr_of!(unsafe { self.data.as_ref() }.data), f)
-    }
-}
-
-impl<T> From<T> for Trc<T> {
-    /// Create a new `Trc` from the provided data. This is equivalent to calling `Trc::new` on the same data.
-    ///
-    /// # Examples
-    /// ```
+    /// Return the atomic reference count of the object. This is how many threads are using the data referenced by this `Trc`.
+    #[cfg_attr(feature = "single-threaded", doc = "```ignore")]
+    #[cfg_attr(not(feature = "single-threaded"), doc = "```")]
+    /// use std::thread;
     /// use trc::Trc;
+    /// use trc::SharedTrc;
     ///
-    /// let trc = Trc::from(100);
+    /// let trc = Trc::new(100);
+    /// let shared = SharedTrc::from_trc(&trc);
+    ///
+    /// let handle = thread::spawn(move || {
+    ///     let mut trc = SharedTrc::to_trc(shared);
+    ///     assert_eq!(Trc::atomic_count(&trc), 2);
+    /// });
+    ///
+    /// handle.join().unwrap();
+    /// assert_eq!(Trc::atomic_count(&trc), 1);
     /// assert_eq!(*trc, 100);
     /// ```
-    fn from(value: T) -> Self {
-        Self::new(value)
-    }
-}
-
-impl<T: Hash> Hash for Trc<T> {
-    /// Pass the data contained in this `Trc` to the provided hasher.
-    #[inline]
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.deref().hash(state);
-    }
-}
-
-impl<T: Hash> Hash for SharedTrc<T> {
-    /// Pass the data contained in this `SharedTrc` to the provided hasher.
     #[inline]
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.deref().hash(state);
+    #[must_use]
+    pub fn atomic_count(this: &Self) -> usize {
+        return unsafe { this.shared.as_ref() }.atomicref.load(Relaxed);
     }
-}
 
-impl<T: PartialOrd> PartialOrd for Trc<T> {
-    /// "Greater than or equal to" comparison for two `Trc`s.
-    ///
-    /// Calls `.ge` on the data.
+    /// Return the weak count of the object. This is how many `Weak`s - across all threads - are pointing to the allocation inside of `Trc`.
+    /// As with `Arc::weak_count`, the implicit weak reference held by all `Trc` or `SharedTrc` to themselves is not included.
     ///
     /// # Examples
     /// ```
     /// use trc::Trc;
+    /// use trc::Weak;
     ///
-    /// let trc1 = Trc::from(100);
-    /// let trc2 = Trc::from(100);
-    /// assert!(trc1>=trc2);
+    /// let trc = Trc::new(100i32);
+    /// let weak = Trc::downgrade(&trc);
+    /// let weak2 = Trc::downgrade(&trc);
+    /// let new_trc = Weak::upgrade(&weak).expect("Value was dropped");
+    /// drop(weak);
+    /// assert_eq!(Trc::weak_count(&new_trc), 1);
     /// ```
     #[inline]
-    fn ge(&self, other: &Self) -> bool {
-        return self.deref().ge(&**other);
+    #[must_use]
+    pub fn weak_count(this: &Self) -> usize {
+        return strong_weak_count(unsafe { &this.shared.as_ref().weakcount });
     }
 
-    /// "Less than or equal to" comparison for two `Trc`s.
-    ///
-    /// Calls `.le` on the data.
+    /// Checks if the other `Trc` is equal to this one according to their internal pointers.
     ///
     /// # Examples
     /// ```
     /// use trc::Trc;
     ///
-    /// let trc1 = Trc::from(100);
-    /// let trc2 = Trc::from(100);
-    /// assert!(trc1<=trc2);
+    /// let trc1 = Trc::new(100);
+    /// let trc2 = trc1.clone();
+    /// assert!(Trc::ptr_eq(&trc1, &trc2));
     /// ```
     #[inline]
-    fn le(&self, other: &Self) -> bool {
-        return self.deref().ge(&**other);
+    #[must_use]
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        core::ptr::eq(this.shared.as_ptr(), other.shared.as_ptr())
     }
 
-    /// "Greater than" comparison for two `Trc`s.
-    ///
-    /// Calls `.gt` on the data.
+    /// Gets the raw pointer to the most inner layer of `Trc`. This is only valid if there are at least some atomic references.
     ///
     /// # Examples
     /// ```
     /// use trc::Trc;
     ///
-    /// let trc1 = Trc::from(200);
-    /// let trc2 = Trc::from(100);
-    /// assert!(trc1>trc2);
+    /// let trc = Trc::new(100);
+    /// println!("{:p}", Trc::as_ptr(&trc))
     /// ```
     #[inline]
-    fn gt(&self, other: &Self) -> bool {
-        return self.deref().gt(&**other);
+    #[must_use]
+    pub fn as_ptr(this: &Self) -> *const T {
+        let sharedptr = NonNull::as_ptr(this.shared);
+        unsafe { addr_of_mut!((*sharedptr).data) }
     }
 
-    /// "Less than" comparison for two `Trc`s.
-    ///
-    /// Calls `.lt` on the data.
+    /// Return the address of the value, without exposing the provenance of the pointer. This is the address of
+    /// [`Trc::as_ptr`], for hashing, ordering or logging handles by identity.
     ///
     /// # Examples
     /// ```
     /// use trc::Trc;
     ///
-    /// let trc1 = Trc::from(100);
-    /// let trc2 = Trc::from(200);
-    /// assert!(trc1<trc2);
+    /// let trc = Trc::new(100);
+    /// assert_eq!(Trc::addr(&trc), Trc::addr(&trc.clone()));
+    /// assert_eq!(Trc::addr(&trc), Trc::as_ptr(&trc).addr());
     /// ```
     #[inline]
-    fn lt(&self, other: &Self) -> bool {
-        return self.deref().lt(&**other);
+    #[must_use]
+    pub fn addr(this: &Self) -> usize {
+        return Self::as_ptr(this).addr();
     }
 
-    /// Partial comparison for two `Trc`s.
-    ///
-    /// Calls `.partial_cmp` on the data.
+    /// Get a &mut reference to the internal data if there are no other `Trc`, [`SharedTrc`] or [`Weak`] pointers to the same allocation.
+    /// Otherwise, return [`None`] because it would be unsafe to mutate a shared value.
     ///
     /// # Examples
     /// ```
     /// use trc::Trc;
-    /// use std::cmp::Ordering;
+    /// use std::ops::DerefMut;
     ///
-    /// let trc1 = Trc::from(100);
-    /// let trc2 = Trc::from(200);
-    /// assert_eq!(Some(Ordering::Less), trc1.partial_cmp(&trc2));
+    /// let mut trc = Trc::new(100);
+    /// let mutref = Trc::get_mut(&mut trc).unwrap();
+    /// *mutref = 300;
+    /// assert_eq!(*trc, 300);
     /// ```
+    #[cfg(not(feature = "allocator_api"))]
     #[inline]
-    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
-        return self.deref().partial_cmp(&**other);
+    pub fn get_mut(this: &mut Self) -> Option<&mut T> {
+        if Self::is_unique(this) {
+            Some(unsafe { &mut (*this.shared.as_ptr()).data })
+        } else {
+            None
+        }
     }
-}
 
-impl<T: PartialOrd> PartialOrd for SharedTrc<T> {
-    /// "Greater than or equal to" comparison for two `SharedTrc`s.
-    ///
-    /// Calls `.ge` on the data.
+    /// Return whether this is the only pointer to the allocation, so that there are no other `Trc` (in any thread),
+    /// [`SharedTrc`] or [`Weak`] pointers. This is the condition under which [`Trc::get_mut`] succeeds.
     ///
     /// # Examples
     /// ```
     /// use trc::Trc;
-    /// use trc::SharedTrc;
     ///
-    /// let shared1: SharedTrc<_> = Trc::from(100).into();
-    /// let shared2 = shared1.clone();
-    /// assert!(shared1>=shared2);
+    /// let trc = Trc::new(100);
+    /// assert!(Trc::is_unique(&trc));
+    ///
+    /// let _weak = Trc::downgrade(&trc);
+    /// assert!(!Trc::is_unique(&trc));
     /// ```
+    #[cfg(not(feature = "allocator_api"))]
     #[inline]
-    fn ge(&self, other: &Self) -> bool {
-        return self.deref().ge(&**other);
+    #[must_use]
+    pub fn is_unique(this: &Self) -> bool {
+        if *unsafe { this.threadref.as_ref() } != 1 {
+            return false;
+        }
+
+        //Acquire the weakcount if it is == 1
+        let weakcount = unique_weak_count(&unsafe { this.shared.as_ref() }.weakcount);
+        if unsafe { this.shared.as_ref() }
+            .weakcount
+            .compare_exchange(weakcount, usize::MAX, Acquire, Relaxed)
+            .is_ok()
+        {
+            //Acquire the atomicref
+            let unique = unsafe { this.shared.as_ref() }.atomicref.load(Acquire) == 1;
+
+            //Synchronize with the previous Acquire
+            unsafe { this.shared.as_ref() }
+                .weakcount
+                .store(weakcount, Release);
+
+            unique
+        } else {
+            false
+        }
     }
 
-    /// "Less than or equal to" comparison for two `SharedTrc`s.
+    /// Moves the inner value into a [`Box`] if this is the only strong reference, including the local one.
+    /// Otherwise, an [`Err`] is returned with the same `Trc` that was passed in.
+    /// This will succeed even if there are outstanding weak references, which will no longer be able to upgrade.
     ///
-    /// Calls `.le` on the data.
+    /// This also works for unsized values, so a `Trc<dyn Trait>` can be turned into a `Box<dyn Trait>`.
     ///
     /// # Examples
     /// ```
     /// use trc::Trc;
-    /// use trc::SharedTrc;
     ///
-    /// let shared1: SharedTrc<_> = Trc::from(100).into();
-    /// let shared2 = shared1.clone();
-    /// assert!(shared1<=shared2);
+    /// let trc = Trc::<[i32]>::from(vec![1, 2, 3]);
+    /// let boxed: Box<[i32]> = Trc::try_into_box(trc).ok().unwrap();
+    /// assert_eq!(*boxed, [1, 2, 3]);
+    ///
+    /// let trc = Trc::new(4);
+    /// let _trc2 = trc.clone();
+    /// assert_eq!(*Trc::try_into_box(trc).unwrap_err(), 4);
     /// ```
-    #[inline]
-    fn le(&self, other: &Self) -> bool {
-        return self.deref().ge(&**other);
+    pub fn try_into_box(this: Self) -> Result<Box<T>, Self> {
+        if *unsafe { this.threadref.as_ref() } != 1
+            || unsafe { this.shared.as_ref() }
+                .atomicref
+                .compare_exchange(1, 0, Acquire, Relaxed)
+                .is_err()
+        {
+            return Err(this);
+        }
+
+        let this = ManuallyDrop::new(this);
+        unsafe {
+            let src = addr_of_mut!((*this.shared.as_ptr()).data);
+            let layout = Layout::for_value(&*src);
+            let mem = if layout.size() == 0 {
+                ptr::without_provenance_mut::<u8>(layout.align())
+            } else {
+                let mem = alloc::alloc::alloc(layout);
+                if mem.is_null() {
+                    handle_alloc_error(layout);
+                }
+                mem
+            };
+            ptr::copy_nonoverlapping(src.cast::<u8>(), mem, layout.size());
+            //Keep the metadata (length or vtable) of the data and point it at the new allocation.
+            let res = Box::from_raw(set_data_ptr(src, mem));
+
+            free_threadref(this.shared.as_ptr(), this.threadref);
+            //Clean up implicit self-reference
+            drop(Weak { data: this.shared });
+
+            Ok(res)
+        }
     }
 
-    /// "Greater than" comparison for two `SharedTrc`s.
-    ///
-    /// Calls `.gt` on the data.
+    /// Moves the inner value into an [`Arc`] if this is the only strong reference, including the local one.
+    /// Otherwise, an [`Err`] is returned with the same `Trc` that was passed in. Like [`Trc::try_into_box`], this also
+    /// works for unsized values.
     ///
     /// # Examples
     /// ```
+    /// use std::sync::Arc;
     /// use trc::Trc;
-    /// use trc::SharedTrc;
     ///
-    /// let shared1: SharedTrc<_> = Trc::from(200).into();
-    /// let shared2: SharedTrc<_> = Trc::from(100).into();
-    /// assert!(shared1>shared2);
+    /// let trc = Trc::<str>::from("Trc");
+    /// let arc: Arc<str> = Trc::try_into_arc(trc).ok().unwrap();
+    /// assert_eq!(&*arc, "Trc");
+    ///
+    /// let trc = Trc::<[i32]>::from(vec![1, 2]);
+    /// let _trc2 = trc.clone();
+    /// assert!(Trc::try_into_arc(trc).is_err());
     /// ```
+    #[cfg(target_has_atomic = "ptr")]
     #[inline]
-    fn gt(&self, other: &Self) -> bool {
-        return self.deref().gt(&**other);
+    pub fn try_into_arc(this: Self) -> Result<Arc<T>, Self> {
+        return Self::try_into_box(this).map(Arc::from);
     }
+}
 
-    /// "Less than" comparison for two `SharedTrc`s.
-    ///
-    /// Calls `.lt` on the data.
+impl<T: Clone> Trc<T> {
+    /// If we have the only strong and local reference to `T`, then unwrap it. Otherwise, clone `T` and return the clone.
+    /// If `trc_t` is of type `Trc<T>`, this function is functionally equivalent to `(*trc_t).clone()`, but will avoid cloning the inner
+    /// value where possible.
     ///
     /// # Examples
     /// ```
     /// use trc::Trc;
-    /// use trc::SharedTrc;
     ///
-    /// let shared1: SharedTrc<_> = Trc::from(100).into();
-    /// let shared2: SharedTrc<_> = Trc::from(200).into();
-    /// assert!(shared1<shared2);
+    /// let inner = String::from("Trc");
+    /// let ptr = inner.as_ptr();
+    ///
+    /// let trc = Trc::new(inner);
+    /// let trc2 = trc.clone();
+    /// let inner = Trc::unwrap_or_clone(trc);
+    /// assert!(!std::ptr::eq(ptr, inner.as_ptr()));
+    ///
+    /// let inner = Trc::unwrap_or_clone(trc2);
+    /// assert!(std::ptr::eq(ptr, inner.as_ptr()));
     /// ```
     #[inline]
-    fn lt(&self, other: &Self) -> bool {
-        return self.deref().lt(&**other);
+    #[must_use]
+    pub fn unwrap_or_clone(this: Self) -> T {
+        Self::try_unwrap(this).unwrap_or_else(|trc| (*trc).clone())
     }
 
-    /// Partial comparison for two `SharedTrc`s.
+    /// Return a mutable reference to the value, cloning it into a new allocation first if this is not the only pointer to
+    /// it. After this, the `Trc` is unique, and other pointers to the previous allocation are unaffected.
     ///
-    /// Calls `.partial_cmp` on the data.
+    /// If the only other pointers are `Weak`s, the value is moved into a new allocation instead of being cloned, and the
+    /// `Weak`s can no longer be upgraded, as with `Arc::make_mut`.
     ///
     /// # Examples
     /// ```
     /// use trc::Trc;
-    /// use trc::SharedTrc;
-    /// use std::cmp::Ordering;
     ///
-    /// let shared1: SharedTrc<_> = Trc::from(100).into();
-    /// let shared2: SharedTrc<_> = Trc::from(200).into();
-    /// assert_eq!(Some(Ordering::Less), shared1.partial_cmp(&shared2));
-    /// ```
+    /// let mut trc = Trc::new(vec![1]);
+    /// let other = trc.clone();
+    ///
+    /// Trc::make_mut(&mut trc).push(2);
+    /// assert_eq!(*trc, [1, 2]);
+    /// assert_eq!(*other, [1]);
+    ///
+    /// //`trc` is now unique, so this does not clone.
+    /// let ptr = Trc::as_ptr(&trc);
+    /// Trc::make_mut(&mut trc).push(3);
+    /// assert_eq!(Trc::as_ptr(&trc), ptr);
+    ///
+    /// //The value is moved away from `Weak`s.
+    /// let weak = Trc::downgrade(&trc);
+    /// Trc::make_mut(&mut trc).push(4);
+    /// assert_eq!(*trc, [1, 2, 3, 4]);
+    /// assert!(weak.upgrade().is_none());
+    /// ```
+    #[inline]
+    pub fn make_mut(this: &mut Self) -> &mut T {
+        //Setting the atomic count to 0 prevents `Weak`s from upgrading while the value is moved out, as in `try_take`.
+        if *unsafe { this.threadref.as_ref() } != 1
+            || unsafe { this.shared.as_ref() }
+                .atomicref
+                .compare_exchange(1, 0, Acquire, Relaxed)
+                .is_err()
+        {
+            *this = Self::new((**this).clone());
+        } else if weak_count_value(unsafe { this.shared.as_ref() }.weakcount.load(Relaxed)) != 1 {
+            let value = unsafe { ptr::read(addr_of!((*this.shared.as_ptr()).data)) };
+            let old = ManuallyDrop::new(mem::replace(this, Self::new(value)));
+            unsafe { free_threadref(old.shared.as_ptr(), old.threadref) };
+            //Clean up implicit self-reference
+            drop(Weak { data: old.shared });
+        } else {
+            //There are no other pointers, so the value stays in place.
+            unsafe { this.shared.as_ref() }.atomicref.store(1, Release);
+        }
+        return unsafe { &mut (*this.shared.as_ptr()).data };
+    }
+
+    /// Create a `Trc` from an [`Arc`]. If the `Arc` is the only strong reference, the value is moved out of it. Otherwise,
+    /// the value is cloned.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::sync::Arc;
+    /// use trc::Trc;
+    ///
+    /// let arc = Arc::new(String::from("Trc"));
+    /// let ptr = arc.as_ptr();
+    ///
+    /// let trc = Trc::from_arc(arc.clone());
+    /// assert_eq!(*trc, "Trc");
+    ///
+    /// let trc = Trc::from_arc(arc);
+    /// assert_eq!(trc.as_ptr(), ptr);
+    /// ```
+    #[cfg(target_has_atomic = "ptr")]
+    #[inline]
+    #[must_use]
+    pub fn from_arc(arc: Arc<T>) -> Self {
+        return Self::new(Arc::unwrap_or_clone(arc));
+    }
+
+    /// Create a `Trc` from an [`Rc`]. If the `Rc` is the only strong reference, the value is moved out of it. Otherwise,
+    /// the value is cloned.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::rc::Rc;
+    /// use trc::Trc;
+    ///
+    /// let rc = Rc::new(vec![1, 2, 3]);
+    /// let trc = Trc::from_rc(rc.clone());
+    /// assert_eq!(*trc, *rc);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn from_rc(rc: Rc<T>) -> Self {
+        return Self::new(Rc::unwrap_or_clone(rc));
+    }
+
+    /// Convert a `Trc` into an [`Arc`]. If the `Trc` is the only strong reference, including the local one, the value is
+    /// moved out of it. Otherwise, the value is cloned.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::sync::Arc;
+    /// use trc::Trc;
+    ///
+    /// let trc = Trc::new(String::from("Trc"));
+    /// let ptr = trc.as_ptr();
+    ///
+    /// let arc: Arc<String> = Trc::into_arc(trc);
+    /// assert_eq!(arc.as_ptr(), ptr);
+    /// ```
+    #[cfg(target_has_atomic = "ptr")]
+    #[inline]
+    #[must_use]
+    pub fn into_arc(this: Self) -> Arc<T> {
+        return Arc::new(Self::unwrap_or_clone(this));
+    }
+
+    /// Convert a `Trc` into an [`Rc`]. If the `Trc` is the only strong reference, including the local one, the value is
+    /// moved out of it. Otherwise, the value is cloned.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::rc::Rc;
+    /// use trc::Trc;
+    ///
+    /// let trc = Trc::new(100);
+    /// let rc: Rc<i32> = Trc::into_rc(trc.clone());
+    /// assert_eq!(*rc, *trc);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn into_rc(this: Self) -> Rc<T> {
+        return Rc::new(Self::unwrap_or_clone(this));
+    }
+}
+
+impl<T: 'static> Trc<T> {
+    /// Convert the `Trc` into a [`ProjectedTrc`] which dereferences to the part of the value selected by `f`, such as a
+    /// field. The `ProjectedTrc` keeps the whole value alive, and shares the local count of this `Trc`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{ProjectedTrc, Trc};
+    ///
+    /// struct Node {
+    ///     name: String,
+    ///     children: Vec<Node>,
+    /// }
+    ///
+    /// let node = Trc::new(Node { name: String::from("root"), children: Vec::new() });
+    /// let name: ProjectedTrc<String> = Trc::map(node.clone(), |node| &node.name);
+    /// assert_eq!(*name, "root");
+    /// assert_eq!(Trc::local_count(&node), 2);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn map<U: ?Sized, F: FnOnce(&T) -> &U>(this: Self, f: F) -> ProjectedTrc<U> {
+        return match ProjectedTrc::try_new(this, |value| Some(f(value))) {
+            Ok(projected) => projected,
+            Err(_) => unreachable!(),
+        };
+    }
+
+    /// Convert the `Trc` into a [`ProjectedTrc`] which dereferences to the part of the value selected by `f`. If `f`
+    /// returns [`None`], the `Trc` is returned in the [`Err`].
+    ///
+    /// # Errors
+    /// Returns `this` if `f` returns [`None`].
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let trc = Trc::new(vec![1, 2, 3]);
+    /// let trc = Trc::try_map(trc, |vec| vec.get(3)).unwrap_err();
+    /// let second = Trc::try_map(trc, |vec| vec.get(1)).unwrap();
+    /// assert_eq!(*second, 2);
+    /// ```
+    #[inline]
+    pub fn try_map<U: ?Sized, F: FnOnce(&T) -> Option<&U>>(
+        this: Self,
+        f: F,
+    ) -> Result<ProjectedTrc<U>, Self> {
+        return ProjectedTrc::try_new(this, f);
+    }
+}
+
+impl Trc<dyn Any + Send + Sync> {
+    /// Creates a new `Trc<dyn Any + Send + Sync>` holding `value`. This performs the unsizing coercion internally, so it
+    /// does not require the `dyn_unstable` feature.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let trc = Trc::new_any(String::from("Trc"));
+    /// assert_eq!(trc.downcast_ref::<String>().unwrap(), "Trc");
+    /// ```
+    #[must_use]
+    pub fn new_any<T: Any + Send + Sync>(value: T) -> Self {
+        let trc = ManuallyDrop::new(Trc::new(value));
+        return Self {
+            shared: trc.shared,
+            threadref: trc.threadref,
+            phantom: PhantomData,
+        };
+    }
+
+    /// Attempts to downcast a `Trc<dyn Any + Send + Sync>` into a concrete type.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::any::Any;
+    /// use trc::Trc;
+    ///
+    /// fn print_if_string(value: Trc<dyn Any + Send + Sync>) {
+    ///     if let Ok(string) = value.downcast::<String>() {
+    ///         println!("String ({}): {}", string.len(), string);
+    ///     }
+    /// }
+    ///
+    /// let my_string = "Hello World".to_string();
+    /// print_if_string(Trc::new_any(my_string));
+    /// print_if_string(Trc::new_any(0i8));
+    /// ```
+    pub fn downcast<T>(self) -> Result<Trc<T>, Self>
+    where
+        T: Any + Send + Sync,
+    {
+        if (*self).is::<T>() {
+            let shared = self.shared.cast::<SharedTrcInternal<T>>();
+            let threadref = self.threadref;
+            forget(self);
+            Ok(Trc {
+                shared,
+                threadref,
+                phantom: PhantomData,
+            })
+        } else {
+            Err(self)
+        }
+    }
+
+    /// Attempts to downcast a `Trc<dyn Any + Send + Sync>` into a concrete type. Unlike [`Trc::downcast`],
+    /// a failed downcast returns a [`DowncastError`] which describes the expected and actual types, and still owns the `Trc`.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::any::{Any, TypeId};
+    /// use trc::Trc;
+    ///
+    /// let value = Trc::new_any(0i8);
+    /// let err = value.downcast_checked::<String>().unwrap_err();
+    /// assert_eq!(err.expected_type_name(), "alloc::string::String");
+    /// assert_eq!(err.actual_type_id(), TypeId::of::<i8>());
+    /// println!("{err}");
+    ///
+    /// let value = err.into_inner();
+    /// assert_eq!(*value.downcast_checked::<i8>().unwrap(), 0);
+    /// ```
+    pub fn downcast_checked<T>(self) -> Result<Trc<T>, DowncastError<Self>>
+    where
+        T: Any + Send + Sync,
+    {
+        let actual = (*self).type_id();
+        self.downcast::<T>()
+            .map_err(|value| DowncastError::new::<T>(value, actual))
+    }
+}
+
+impl Trc<dyn Any> {
+    /// Creates a new `Trc<dyn Any>` holding `value`. Because a `Trc` is never sent to another thread, the value does not
+    /// need to be `Send` or `Sync`.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::rc::Rc;
+    /// use trc::Trc;
+    ///
+    /// let trc = Trc::new_local_any(Rc::new(100));
+    /// assert!(trc.is::<Rc<i32>>());
+    /// ```
+    #[must_use]
+    pub fn new_local_any<T: Any>(value: T) -> Self {
+        let trc = ManuallyDrop::new(Trc::new(value));
+        return Self {
+            shared: trc.shared,
+            threadref: trc.threadref,
+            phantom: PhantomData,
+        };
+    }
+
+    /// Attempts to downcast a `Trc<dyn Any>` into a concrete type.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::cell::Cell;
+    /// use trc::Trc;
+    ///
+    /// let trc = Trc::new_local_any(Cell::new(1));
+    /// let trc = trc.downcast::<String>().unwrap_err();
+    /// let cell = trc.downcast::<Cell<i32>>().unwrap();
+    /// cell.set(2);
+    /// assert_eq!(cell.get(), 2);
+    /// ```
+    pub fn downcast<T: Any>(self) -> Result<Trc<T>, Self> {
+        if (*self).is::<T>() {
+            let shared = self.shared.cast::<SharedTrcInternal<T>>();
+            let threadref = self.threadref;
+            forget(self);
+            Ok(Trc {
+                shared,
+                threadref,
+                phantom: PhantomData,
+            })
+        } else {
+            Err(self)
+        }
+    }
+
+    /// Attempts to downcast a `Trc<dyn Any>` into a concrete type. Unlike [`Trc::downcast`], a failed downcast returns
+    /// a [`DowncastError`] which describes the expected and actual types, and still owns the `Trc`.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::any::TypeId;
+    /// use trc::Trc;
+    ///
+    /// let value = Trc::new_local_any(0u8);
+    /// let err = value.downcast_checked::<i8>().unwrap_err();
+    /// assert_eq!(err.actual_type_id(), TypeId::of::<u8>());
+    /// ```
+    pub fn downcast_checked<T: Any>(self) -> Result<Trc<T>, DowncastError<Self>> {
+        let actual = (*self).type_id();
+        self.downcast::<T>()
+            .map_err(|value| DowncastError::new::<T>(value, actual))
+    }
+}
+
+impl From<Trc<dyn Any + Send + Sync>> for Trc<dyn Any> {
+    /// Convert a `Trc<dyn Any + Send + Sync>` into a `Trc<dyn Any>`, which can be downcast in the same way.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::any::Any;
+    /// use trc::Trc;
+    ///
+    /// let trc: Trc<dyn Any> = Trc::new_any(100i32).into();
+    /// assert_eq!(*trc.downcast::<i32>().unwrap(), 100);
+    /// ```
+    #[inline]
+    fn from(value: Trc<dyn Any + Send + Sync>) -> Self {
+        let trc = ManuallyDrop::new(value);
+        return Self {
+            shared: trc.shared,
+            threadref: trc.threadref,
+            phantom: PhantomData,
+        };
+    }
+}
+
+/// The error returned by `downcast_checked` when the value is not of the requested type.
+/// It describes the expected and actual types, and owns the handle that failed to downcast so it can be recovered with
+/// [`DowncastError::into_inner`].
+///
+/// Only the [`TypeId`] of the actual type is available, because a `dyn Any` does not record its type name.
+pub struct DowncastError<P> {
+    value: P,
+    expected: TypeId,
+    expected_name: &'static str,
+    actual: TypeId,
+}
+
+impl<P> DowncastError<P> {
+    fn new<T: Any>(value: P, actual: TypeId) -> Self {
+        Self {
+            value,
+            expected: TypeId::of::<T>(),
+            expected_name: type_name::<T>(),
+            actual,
+        }
+    }
+
+    /// Return the handle which failed to downcast.
+    #[must_use]
+    pub fn into_inner(self) -> P {
+        self.value
+    }
+
+    /// Return the [`TypeId`] of the type that was requested.
+    #[must_use]
+    pub fn expected_type_id(&self) -> TypeId {
+        self.expected
+    }
+
+    /// Return the name of the type that was requested, as given by [`type_name`].
+    #[must_use]
+    pub fn expected_type_name(&self) -> &'static str {
+        self.expected_name
+    }
+
+    /// Return the [`TypeId`] of the value's actual type.
+    #[must_use]
+    pub fn actual_type_id(&self) -> TypeId {
+        self.actual
+    }
+}
+
+impl<P> Debug for DowncastError<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DowncastError")
+            .field("expected", &self.expected_name)
+            .field("expected_type_id", &self.expected)
+            .field("actual_type_id", &self.actual)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<P> Display for DowncastError<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Cannot downcast to `{}` ({:?}), the value has type {:?}.",
+            self.expected_name, self.expected, self.actual
+        )
+    }
+}
+
+impl<P> Error for DowncastError<P> {}
+
+impl<T: ?Sized> Trc<T> {
+    /// Downgrade a `Trc` to a `Weak`. This increments the weak count.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    /// use trc::Weak;
+    ///
+    /// let trc = Trc::new(100);
+    /// let weak = Trc::downgrade(&trc);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn downgrade(trc: &Self) -> Weak<T> {
+        let weakcount = &unsafe { trc.shared.as_ref() }.weakcount;
+        let mut cur = weakcount.load(Relaxed);
+        loop {
+            //The weak count is locked by `is_unique` on a `Trc` or `SharedTrc` on another thread, so wait for it to be
+            //unlocked instead of wrapping it around.
+            if cur == usize::MAX {
+                sync::spin_loop();
+                cur = weakcount.load(Relaxed);
+                continue;
+            }
+            let new = if weak_count_value(cur) > MAX_WEAKCOUNT {
+                overflow::before_increment(cur, "Overflow of maximum weak reference count.")
+            } else {
+                cur + 1
+            };
+            //Acquire synchronizes with the Release unlock in `is_unique`, as in `Arc::downgrade`.
+            match weakcount.compare_exchange_weak(cur, new, Acquire, Relaxed) {
+                Ok(_) => return Weak { data: trc.shared },
+                Err(old) => cur = old,
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "allocator_api"))]
+impl<T: ?Sized> Deref for Trc<T> {
+    type Target = T;
+
+    /// Get an immutable reference to the internal data.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    /// use std::ops::Deref;
+    ///
+    /// let mut trc = Trc::new(100i32);
+    /// assert_eq!(*trc, 100i32);
+    /// assert_eq!(trc.deref(), &100i32);
+    /// ```
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        return &unsafe { self.shared.as_ref() }.data;
+    }
+}
+
+#[cfg(not(feature = "allocator_api"))]
+impl_drop! {
+    impl<T> Drop for Trc<T> {
+        #[inline]
+        fn drop(&mut self) {
+            #[cfg(feature = "hooks")]
+            hooks::on_drop(self.shared.as_ptr(), HandleKind::Trc);
+            #[cfg(feature = "leak-debug")]
+            leak_debug::release(self.shared.as_ptr(), Some(self.threadref));
+            *unsafe { self.threadref.as_mut() } -= 1;
+            if *unsafe { self.threadref.as_ref() } == 0 {
+                unsafe { free_threadref(self.shared.as_ptr(), self.threadref) };
+                let prev = sub_value(&unsafe { self.shared.as_ref() }.atomicref, 1, AcqRel);
+                #[cfg(feature = "std")]
+                wait::on_release(prev, self.shared.as_ptr());
+                if prev != 1 {
+                    return;
+                }
+
+                unsafe { ptr::drop_in_place(addr_of_mut!((*self.shared.as_ptr()).data)) };
+                Weak { data: self.shared };
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "allocator_api"))]
+impl<T: ?Sized> Clone for Trc<T> {
+    /// Clone a `Trc` (increment it's local reference count).
+    /// The process is aborted if the local reference count overflows, see [overflow](crate#reference-count-overflow).
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let trc = Trc::new(100);
+    /// let trc2 = trc.clone();
+    /// assert_eq!(Trc::local_count(&trc), Trc::local_count(&trc2));
+    /// ```
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        let threadref = unsafe { &mut *self.threadref.as_ptr() };
+        *threadref += 1;
+        if *threadref > MAX_REFCOUNT {
+            overflow::local(threadref, "Overflow of maximum local reference count.");
+        }
+        #[cfg(feature = "hooks")]
+        hooks::on_clone(self.shared.as_ptr(), HandleKind::Trc);
+        #[cfg(feature = "leak-debug")]
+        leak_debug::record(self.shared.as_ptr(), Some(self.threadref));
+
+        Self {
+            shared: self.shared,
+            threadref: self.threadref,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized> AsRef<T> for Trc<T> {
+    fn as_ref(&self) -> &T {
+        return Self::deref(self);
+    }
+}
+
+impl<T: ?Sized> AsRef<T> for SharedTrc<T> {
+    fn as_ref(&self) -> &T {
+        return Self::deref(self);
+    }
+}
+
+impl<T: ?Sized> Borrow<T> for Trc<T> {
+    fn borrow(&self) -> &T {
+        return self.as_ref();
+    }
+}
+
+impl<T: ?Sized> Borrow<T> for SharedTrc<T> {
+    fn borrow(&self) -> &T {
+        return self.as_ref();
+    }
+}
+
+impl Borrow<str> for Trc<String> {
+    /// Borrow the wrapped `String` as a `str`, so that a `Trc<String>` can be looked up by `&str`.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::collections::HashSet;
+    /// use trc::Trc;
+    ///
+    /// let mut set = HashSet::new();
+    /// set.insert(Trc::new(String::from("Trc")));
+    /// assert!(set.contains("Trc"));
+    /// ```
+    fn borrow(&self) -> &str {
+        return self.as_str();
+    }
+}
+
+impl<T> Borrow<[T]> for Trc<Vec<T>> {
+    /// Borrow the wrapped `Vec<T>` as a `[T]`, so that a `Trc<Vec<T>>` can be looked up by `&[T]`.
+    fn borrow(&self) -> &[T] {
+        return self.as_slice();
+    }
+}
+
+#[cfg(feature = "std")]
+impl Borrow<Path> for Trc<PathBuf> {
+    /// Borrow the wrapped `PathBuf` as a `Path`, so that a `Trc<PathBuf>` can be looked up by `&Path`.
+    fn borrow(&self) -> &Path {
+        return self.as_path();
+    }
+}
+
+#[cfg(feature = "std")]
+impl AsRef<Path> for Trc<PathBuf> {
+    /// Get a reference to the wrapped `PathBuf` as a `Path`.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::path::{Path, PathBuf};
+    /// use trc::Trc;
+    ///
+    /// let path = Trc::new(PathBuf::from("/tmp"));
+    /// assert!(Path::new("/tmp/trc").starts_with(&path));
+    /// ```
+    fn as_ref(&self) -> &Path {
+        return self.as_path();
+    }
+}
+
+impl Borrow<str> for SharedTrc<String> {
+    /// Borrow the wrapped `String` as a `str`, so that a `SharedTrc<String>` can be looked up by `&str`.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::collections::HashSet;
+    /// use trc::SharedTrc;
+    ///
+    /// let mut set = HashSet::new();
+    /// set.insert(SharedTrc::new(String::from("Trc")));
+    /// assert!(set.contains("Trc"));
+    /// ```
+    fn borrow(&self) -> &str {
+        return self.as_str();
+    }
+}
+
+impl<T> Borrow<[T]> for SharedTrc<Vec<T>> {
+    /// Borrow the wrapped `Vec<T>` as a `[T]`, so that a `SharedTrc<Vec<T>>` can be looked up by `&[T]`.
+    fn borrow(&self) -> &[T] {
+        return self.as_slice();
+    }
+}
+
+#[cfg(feature = "std")]
+impl Borrow<Path> for SharedTrc<PathBuf> {
+    /// Borrow the wrapped `PathBuf` as a `Path`, so that a `SharedTrc<PathBuf>` can be looked up by `&Path`.
+    fn borrow(&self) -> &Path {
+        return self.as_path();
+    }
+}
+
+#[cfg(feature = "std")]
+impl AsRef<Path> for SharedTrc<PathBuf> {
+    /// Get a reference to the wrapped `PathBuf` as a `Path`.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::path::{Path, PathBuf};
+    /// use trc::SharedTrc;
+    ///
+    /// let path = SharedTrc::new(PathBuf::from("/tmp"));
+    /// assert!(Path::new("/tmp/trc").starts_with(&path));
+    /// ```
+    fn as_ref(&self) -> &Path {
+        return self.as_path();
+    }
+}
+
+impl<T: Default> Default for Trc<T> {
+    fn default() -> Self {
+        Self::new(Default::default())
+    }
+}
+
+impl<T: Default> Default for SharedTrc<T> {
+    fn default() -> Self {
+        Self::new(Default::default())
+    }
+}
+
+impl Default for Trc<str> {
+    /// Create an empty `Trc<str>`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let trc = Trc::<str>::default();
+    /// assert!(trc.is_empty());
+    /// ```
+    #[inline]
+    fn default() -> Self {
+        return Self::from("");
+    }
+}
+
+impl<T> Default for Trc<[T]> {
+    /// Create an empty `Trc<[T]>`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let trc = Trc::<[i32]>::default();
+    /// assert!(trc.is_empty());
+    /// ```
+    #[inline]
+    fn default() -> Self {
+        return Self::from(Vec::new());
+    }
+}
+
+impl Default for Trc<CStr> {
+    /// Create an empty `Trc<CStr>`, which only contains the nul terminator.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::ffi::CStr;
+    /// use trc::Trc;
+    ///
+    /// let trc = Trc::<CStr>::default();
+    /// assert!(trc.is_empty());
+    /// ```
+    #[inline]
+    fn default() -> Self {
+        let bytes = ManuallyDrop::new(Trc::<[u8]>::from(&b"\0"[..]));
+        //SAFETY: `CStr` is a transparent wrapper around a nul-terminated byte slice.
+        return Trc {
+            shared: unsafe {
+                NonNull::new_unchecked(bytes.shared.as_ptr() as *mut SharedTrcInternal<CStr>)
+            },
+            threadref: bytes.threadref,
+            phantom: PhantomData,
+        };
+    }
+}
+
+impl<T: ?Sized + Display> Display for Trc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&*(*self), f)
+    }
+}
+
+impl<T: ?Sized + Display> Display for SharedTrc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&*(*self), f)
+    }
+}
+
+impl<T: ?Sized + Debug> Debug for Trc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*(*self), f)
+    }
+}
+
+impl<T: ?Sized + Debug> Debug for SharedTrc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*(*self), f)
+    }
+}
+
+impl<T: ?Sized> Debug for Weak<T> {
+    /// Format a `Weak` as `(Weak)`, without accessing the value.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let trc = Trc::new(100);
+    /// let weak = Trc::downgrade(&trc);
+    /// assert_eq!(format!("{weak:?}"), "(Weak)");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(Weak)")
+    }
+}
+
+impl<T: ?Sized> Pointer for Trc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Pointer::fmt(&addr_of!(unsafe { self.shared.as_ref() }.data), f)
+    }
+}
+
+impl<T: ?Sized> Pointer for SharedTrc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Pointer::fmt(&addr_of!(unsafe { self.data.as_ref() }.data), f)
+    }
+}
+
+impl<T> From<T> for Trc<T> {
+    /// Create a new `Trc` from the provided data. This is equivalent to calling `Trc::new` on the same data.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let trc = Trc::from(100);
+    /// assert_eq!(*trc, 100);
+    /// ```
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: ?Sized> From<Box<T>> for Trc<T> {
+    /// Move a boxed value into a new `Trc`. This works for unsized values such as `Box<[T]>`, `Box<str>` and
+    /// `Box<dyn Trait>`, and is how a `Trc<dyn Trait>` can be created on stable Rust.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::fmt::Display;
+    /// use trc::Trc;
+    ///
+    /// let boxed: Box<dyn Display> = Box::new(100);
+    /// let trc: Trc<dyn Display> = Trc::from(boxed);
+    /// assert_eq!(trc.to_string(), "100");
+    ///
+    /// let trc: Trc<str> = Trc::from(Box::<str>::from("Trc"));
+    /// assert_eq!(&*trc, "Trc");
+    /// ```
+    fn from(value: Box<T>) -> Self {
+        let shared = box_into_internal(value);
+        return Self {
+            threadref: new_threadref(),
+            shared,
+            phantom: PhantomData,
+        };
+    }
+}
+
+impl<T: ?Sized + Hash> Hash for Trc<T> {
+    /// Pass the data contained in this `Trc` to the provided hasher.
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.deref().hash(state);
+    }
+}
+
+impl<T: ?Sized + Hash> Hash for SharedTrc<T> {
+    /// Pass the data contained in this `SharedTrc` to the provided hasher.
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.deref().hash(state);
+    }
+}
+
+impl<T: ?Sized + PartialOrd> PartialOrd for Trc<T> {
+    /// "Greater than or equal to" comparison for two `Trc`s.
+    ///
+    /// Calls `.ge` on the data.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let trc1 = Trc::from(100);
+    /// let trc2 = Trc::from(100);
+    /// assert!(trc1>=trc2);
+    /// ```
+    #[inline]
+    fn ge(&self, other: &Self) -> bool {
+        return self.deref().ge(&**other);
+    }
+
+    /// "Less than or equal to" comparison for two `Trc`s.
+    ///
+    /// Calls `.le` on the data.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let trc1 = Trc::from(100);
+    /// let trc2 = Trc::from(100);
+    /// assert!(trc1<=trc2);
+    /// ```
+    #[inline]
+    fn le(&self, other: &Self) -> bool {
+        return self.deref().ge(&**other);
+    }
+
+    /// "Greater than" comparison for two `Trc`s.
+    ///
+    /// Calls `.gt` on the data.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let trc1 = Trc::from(200);
+    /// let trc2 = Trc::from(100);
+    /// assert!(trc1>trc2);
+    /// ```
+    #[inline]
+    fn gt(&self, other: &Self) -> bool {
+        return self.deref().gt(&**other);
+    }
+
+    /// "Less than" comparison for two `Trc`s.
+    ///
+    /// Calls `.lt` on the data.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let trc1 = Trc::from(100);
+    /// let trc2 = Trc::from(200);
+    /// assert!(trc1<trc2);
+    /// ```
+    #[inline]
+    fn lt(&self, other: &Self) -> bool {
+        return self.deref().lt(&**other);
+    }
+
+    /// Partial comparison for two `Trc`s.
+    ///
+    /// Calls `.partial_cmp` on the data.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    /// use std::cmp::Ordering;
+    ///
+    /// let trc1 = Trc::from(100);
+    /// let trc2 = Trc::from(200);
+    /// assert_eq!(Some(Ordering::Less), trc1.partial_cmp(&trc2));
+    /// ```
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        return self.deref().partial_cmp(&**other);
+    }
+}
+
+impl<T: ?Sized + PartialOrd> PartialOrd for SharedTrc<T> {
+    /// "Greater than or equal to" comparison for two `SharedTrc`s.
+    ///
+    /// Calls `.ge` on the data.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    /// use trc::SharedTrc;
+    ///
+    /// let shared1: SharedTrc<_> = Trc::from(100).into();
+    /// let shared2 = shared1.clone();
+    /// assert!(shared1>=shared2);
+    /// ```
+    #[inline]
+    fn ge(&self, other: &Self) -> bool {
+        return self.deref().ge(&**other);
+    }
+
+    /// "Less than or equal to" comparison for two `SharedTrc`s.
+    ///
+    /// Calls `.le` on the data.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    /// use trc::SharedTrc;
+    ///
+    /// let shared1: SharedTrc<_> = Trc::from(100).into();
+    /// let shared2 = shared1.clone();
+    /// assert!(shared1<=shared2);
+    /// ```
+    #[inline]
+    fn le(&self, other: &Self) -> bool {
+        return self.deref().ge(&**other);
+    }
+
+    /// "Greater than" comparison for two `SharedTrc`s.
+    ///
+    /// Calls `.gt` on the data.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    /// use trc::SharedTrc;
+    ///
+    /// let shared1: SharedTrc<_> = Trc::from(200).into();
+    /// let shared2: SharedTrc<_> = Trc::from(100).into();
+    /// assert!(shared1>shared2);
+    /// ```
+    #[inline]
+    fn gt(&self, other: &Self) -> bool {
+        return self.deref().gt(&**other);
+    }
+
+    /// "Less than" comparison for two `SharedTrc`s.
+    ///
+    /// Calls `.lt` on the data.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    /// use trc::SharedTrc;
+    ///
+    /// let shared1: SharedTrc<_> = Trc::from(100).into();
+    /// let shared2: SharedTrc<_> = Trc::from(200).into();
+    /// assert!(shared1<shared2);
+    /// ```
+    #[inline]
+    fn lt(&self, other: &Self) -> bool {
+        return self.deref().lt(&**other);
+    }
+
+    /// Partial comparison for two `SharedTrc`s.
+    ///
+    /// Calls `.partial_cmp` on the data.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    /// use trc::SharedTrc;
+    /// use std::cmp::Ordering;
+    ///
+    /// let shared1: SharedTrc<_> = Trc::from(100).into();
+    /// let shared2: SharedTrc<_> = Trc::from(200).into();
+    /// assert_eq!(Some(Ordering::Less), shared1.partial_cmp(&shared2));
+    /// ```
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        return self.deref().partial_cmp(&**other);
+    }
+}
+
+impl<T: ?Sized + Ord> Ord for Trc<T> {
+    /// Comparison for two `Trc`s. The two are compared by calling `.cmp` on the inner values.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    /// use std::cmp::Ordering;
+    ///
+    /// let trc1 = Trc::from(100);
+    /// let trc2 = Trc::from(200);
+    /// assert_eq!(Ordering::Less, trc1.cmp(&trc2));
+    /// ```
+    #[inline]
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        return self.deref().cmp(&**other);
+    }
+}
+
+impl<T: ?Sized + Ord> Ord for SharedTrc<T> {
+    /// Comparison for two `SharedTrc`s. The two are compared by calling `.cmp` on the inner values.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    /// use trc::SharedTrc;
+    /// use std::cmp::Ordering;
+    ///
+    /// let shared1: SharedTrc<_> = Trc::from(100).into();
+    /// let shared2: SharedTrc<_> = Trc::from(200).into();
+    /// assert_eq!(Ordering::Less, shared1.cmp(&shared2));
+    /// ```
+    #[inline]
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        return self.deref().cmp(&**other);
+    }
+}
+
+impl<T: ?Sized + Eq> Eq for Trc<T> {}
+
+impl<T: ?Sized + Eq> Eq for SharedTrc<T> {}
+
+impl<T: ?Sized + PartialEq> PartialEq for Trc<T> {
+    /// Equality by value comparison for two `Trc`s, even if the data is in different allocoations.
+    ///
+    /// Calls `.eq` on the data.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let trc1 = Trc::from(100);
+    /// let trc2 = Trc::from(100);
+    /// assert!(trc1==trc2);
+    /// ```
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        return self.deref().eq(&**other);
+    }
+
+    /// Inequality by value comparison for two `Trc`s, even if the data is in different allocoations.
+    ///
+    /// Calls `.ne` on the data.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let trc1 = Trc::from(100);
+    /// let trc2 = Trc::from(200);
+    /// assert!(trc1!=trc2);
+    /// ```
+    #[allow(clippy::partialeq_ne_impl)]
+    #[inline]
+    fn ne(&self, other: &Self) -> bool {
+        return self.deref().ne(&**other);
+    }
+}
+
+impl<T: ?Sized + PartialEq> PartialEq for SharedTrc<T> {
+    /// Equality by value comparison for two `SharedTrc`s, even if the data is in different allocoations.
+    ///
+    /// Calls `.eq` on the data.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    /// use trc::SharedTrc;
+    ///
+    /// let shared1: SharedTrc<_> = Trc::from(100).into();
+    /// let shared2: SharedTrc<_> = Trc::from(100).into();
+    /// assert!(shared1==shared2);
+    /// ```
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        return self.deref().eq(&**other);
+    }
+
+    /// Inequality by value comparison for two `SharedTrc`s, even if the data is in different allocoations.
+    ///
+    /// Calls `.ne` on the data.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    /// use trc::SharedTrc;
+    ///
+    /// let shared1: SharedTrc<_> = Trc::from(100).into();
+    /// let shared2: SharedTrc<_> = Trc::from(200).into();
+    /// assert!(shared1!=shared2);
+    /// ```
+    #[allow(clippy::partialeq_ne_impl)]
+    #[inline]
+    fn ne(&self, other: &Self) -> bool {
+        return self.deref().ne(&**other);
+    }
+}
+
+impl<T: ?Sized + PartialEq> PartialEq<SharedTrc<T>> for Trc<T> {
+    /// Equality by value comparison for a `Trc` and a `SharedTrc`, even if the data is in different allocoations.
+    ///
+    /// Calls `.eq` on the data.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    /// use trc::SharedTrc;
+    ///
+    /// let trc = Trc::from(100);
+    /// let shared = SharedTrc::from_trc(&trc);
+    /// assert!(trc == shared);
+    /// ```
+    #[inline]
+    fn eq(&self, other: &SharedTrc<T>) -> bool {
+        return self.deref().eq(&**other);
+    }
+}
+
+impl<T: ?Sized + PartialEq> PartialEq<Trc<T>> for SharedTrc<T> {
+    /// Equality by value comparison for a `SharedTrc` and a `Trc`, even if the data is in different allocoations.
+    ///
+    /// Calls `.eq` on the data.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    /// use trc::SharedTrc;
+    ///
+    /// let shared: SharedTrc<_> = Trc::from(100).into();
+    /// let trc = Trc::from(200);
+    /// assert!(shared != trc);
+    /// ```
+    #[inline]
+    fn eq(&self, other: &Trc<T>) -> bool {
+        return self.deref().eq(&**other);
+    }
+}
+
+impl<T: ?Sized + PartialOrd> PartialOrd<SharedTrc<T>> for Trc<T> {
+    /// Partial comparison for a `Trc` and a `SharedTrc`.
+    ///
+    /// Calls `.partial_cmp` on the data.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    /// use trc::SharedTrc;
+    /// use std::cmp::Ordering;
+    ///
+    /// let trc = Trc::from(100);
+    /// let shared: SharedTrc<_> = Trc::from(200).into();
+    /// assert_eq!(Some(Ordering::Less), trc.partial_cmp(&shared));
+    /// assert!(trc < shared);
+    /// ```
+    #[inline]
+    fn partial_cmp(&self, other: &SharedTrc<T>) -> Option<cmp::Ordering> {
+        return self.deref().partial_cmp(&**other);
+    }
+}
+
+impl<T: ?Sized + PartialOrd> PartialOrd<Trc<T>> for SharedTrc<T> {
+    /// Partial comparison for a `SharedTrc` and a `Trc`.
+    ///
+    /// Calls `.partial_cmp` on the data.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    /// use trc::SharedTrc;
+    /// use std::cmp::Ordering;
+    ///
+    /// let shared: SharedTrc<_> = Trc::from(200).into();
+    /// let trc = Trc::from(100);
+    /// assert_eq!(Some(Ordering::Greater), shared.partial_cmp(&trc));
+    /// assert!(shared >= trc);
+    /// ```
+    #[inline]
+    fn partial_cmp(&self, other: &Trc<T>) -> Option<cmp::Ordering> {
+        return self.deref().partial_cmp(&**other);
+    }
+}
+
+impl PartialEq<str> for Trc<str> {
+    /// Compare the string contained in a `Trc` with a `str`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let trc = Trc::<str>::from("abc");
+    /// assert!(trc == *"abc");
+    /// ```
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        return **self == *other;
+    }
+}
+
+impl<'a> PartialEq<&'a str> for Trc<str> {
+    /// Compare the string contained in a `Trc` with a `&str`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let trc = Trc::<str>::from("abc");
+    /// assert_eq!(trc, "abc");
+    /// ```
+    #[inline]
+    fn eq(&self, other: &&'a str) -> bool {
+        return **self == **other;
+    }
+}
+
+impl PartialEq<String> for Trc<str> {
+    /// Compare the string contained in a `Trc` with a `String`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let trc = Trc::<str>::from("abc");
+    /// assert_eq!(trc, String::from("abc"));
+    /// ```
+    #[inline]
+    fn eq(&self, other: &String) -> bool {
+        return **self == **other;
+    }
+}
+
+impl PartialEq<Trc<str>> for &str {
+    /// Compare a `&str` with the string contained in a `Trc`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let trc = Trc::<str>::from("abc");
+    /// assert_eq!("abc", trc);
+    /// ```
+    #[inline]
+    fn eq(&self, other: &Trc<str>) -> bool {
+        return **self == **other;
+    }
+}
+
+impl PartialEq<Trc<str>> for String {
+    /// Compare a `String` with the string contained in a `Trc`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let trc = Trc::<str>::from("abc");
+    /// assert_eq!(String::from("abc"), trc);
+    /// ```
+    #[inline]
+    fn eq(&self, other: &Trc<str>) -> bool {
+        return **self == **other;
+    }
+}
+
+impl<T: PartialEq<U>, U> PartialEq<[U]> for Trc<[T]> {
+    /// Compare the slice contained in a `Trc` with a slice.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let trc = Trc::<[i32]>::from(vec![1, 2, 3]);
+    /// assert!(trc == [1, 2, 3][..]);
+    /// ```
+    #[inline]
+    fn eq(&self, other: &[U]) -> bool {
+        return **self == *other;
+    }
+}
+
+impl<'a, T: PartialEq<U>, U> PartialEq<&'a [U]> for Trc<[T]> {
+    /// Compare the slice contained in a `Trc` with a `&[T]`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let trc = Trc::<[i32]>::from(vec![1, 2, 3]);
+    /// assert_eq!(trc, &[1, 2, 3][..]);
+    /// ```
+    #[inline]
+    fn eq(&self, other: &&'a [U]) -> bool {
+        return **self == **other;
+    }
+}
+
+impl<T: PartialEq<U>, U, const N: usize> PartialEq<[U; N]> for Trc<[T]> {
+    /// Compare the slice contained in a `Trc` with an array.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let trc = Trc::<[i32]>::from(vec![1, 2, 3]);
+    /// assert_eq!(trc, [1, 2, 3]);
+    /// ```
+    #[inline]
+    fn eq(&self, other: &[U; N]) -> bool {
+        return **self == other[..];
+    }
+}
+
+impl<T: PartialEq<U>, U> PartialEq<Vec<U>> for Trc<[T]> {
+    /// Compare the slice contained in a `Trc` with a `Vec`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let trc = Trc::<[i32]>::from(vec![1, 2, 3]);
+    /// assert_eq!(trc, vec![1, 2, 3]);
+    /// ```
+    #[inline]
+    fn eq(&self, other: &Vec<U>) -> bool {
+        return **self == **other;
+    }
+}
+
+#[cfg(all(feature = "std", any(unix, target_os = "wasi")))]
+impl<T: AsFd> AsFd for Trc<T> {
+    fn as_fd(&self) -> std::os::fd::BorrowedFd<'_> {
+        return (**self).as_fd();
+    }
+}
+
+#[cfg(all(feature = "std", any(unix, target_os = "wasi")))]
+impl<T: AsFd> AsFd for SharedTrc<T> {
+    fn as_fd(&self) -> std::os::fd::BorrowedFd<'_> {
+        return (**self).as_fd();
+    }
+}
+
+#[cfg(all(feature = "std", target_os = "windows"))]
+impl<T: AsRawHandle> AsRawHandle for Trc<T> {
+    fn as_raw_handle(&self) -> std::os::windows::io::RawHandle {
+        (**self).as_raw_handle()
+    }
+}
+
+#[cfg(all(feature = "std", target_os = "windows"))]
+impl<T: AsRawHandle> AsRawHandle for SharedTrc<T> {
+    fn as_raw_handle(&self) -> std::os::windows::io::RawHandle {
+        (**self).as_raw_handle()
+    }
+}
+
+#[cfg(all(feature = "std", target_os = "windows"))]
+impl<T: AsHandle> AsHandle for Trc<T> {
+    fn as_handle(&self) -> std::os::windows::io::BorrowedHandle<'_> {
+        (**self).as_handle()
+    }
+}
+
+#[cfg(all(feature = "std", target_os = "windows"))]
+impl<T: AsHandle> AsHandle for SharedTrc<T> {
+    fn as_handle(&self) -> std::os::windows::io::BorrowedHandle<'_> {
+        (**self).as_handle()
+    }
+}
+
+#[cfg(all(feature = "std", any(unix, target_os = "wasi")))]
+impl<T: AsRawFd> AsRawFd for Trc<T> {
+    fn as_raw_fd(&self) -> std::os::fd::RawFd {
+        (**self).as_raw_fd()
+    }
+}
+
+#[cfg(all(feature = "std", any(unix, target_os = "wasi")))]
+impl<T: AsRawFd> AsRawFd for SharedTrc<T> {
+    fn as_raw_fd(&self) -> std::os::fd::RawFd {
+        (**self).as_raw_fd()
+    }
+}
+
+#[cfg(all(feature = "std", target_os = "windows"))]
+impl<T: AsRawSocket> AsRawSocket for Trc<T> {
+    fn as_raw_socket(&self) -> std::os::windows::io::RawSocket {
+        (**self).as_raw_socket()
+    }
+}
+
+#[cfg(all(feature = "std", target_os = "windows"))]
+impl<T: AsRawSocket> AsRawSocket for SharedTrc<T> {
+    fn as_raw_socket(&self) -> std::os::windows::io::RawSocket {
+        (**self).as_raw_socket()
+    }
+}
+
+#[cfg(all(feature = "std", target_os = "windows"))]
+impl<T: AsSocket> AsSocket for Trc<T> {
+    fn as_socket(&self) -> std::os::windows::io::BorrowedSocket<'_> {
+        (**self).as_socket()
+    }
+}
+
+#[cfg(all(feature = "std", target_os = "windows"))]
+impl<T: AsSocket> AsSocket for SharedTrc<T> {
+    fn as_socket(&self) -> std::os::windows::io::BorrowedSocket<'_> {
+        (**self).as_socket()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized> Read for Trc<T>
+where
+    for<'a> &'a T: Read,
+{
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        return (&**self).read(buf);
+    }
+
+    #[inline]
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        return (&**self).read_vectored(bufs);
+    }
+
+    #[inline]
+    fn read_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
+        return (&**self).read_to_end(buf);
+    }
+
+    #[inline]
+    fn read_to_string(&mut self, buf: &mut String) -> io::Result<usize> {
+        return (&**self).read_to_string(buf);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized> Write for Trc<T>
+where
+    for<'a> &'a T: Write,
+{
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        return (&**self).write(buf);
+    }
+
+    #[inline]
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        return (&**self).write_vectored(bufs);
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        return (&**self).flush();
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized> Seek for Trc<T>
+where
+    for<'a> &'a T: Seek,
+{
+    #[inline]
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        return (&**self).seek(pos);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized> Read for SharedTrc<T>
+where
+    for<'a> &'a T: Read,
+{
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        return (&**self).read(buf);
+    }
+
+    #[inline]
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        return (&**self).read_vectored(bufs);
+    }
+
+    #[inline]
+    fn read_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
+        return (&**self).read_to_end(buf);
+    }
+
+    #[inline]
+    fn read_to_string(&mut self, buf: &mut String) -> io::Result<usize> {
+        return (&**self).read_to_string(buf);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized> Write for SharedTrc<T>
+where
+    for<'a> &'a T: Write,
+{
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        return (&**self).write(buf);
+    }
+
+    #[inline]
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        return (&**self).write_vectored(bufs);
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        return (&**self).flush();
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized> Seek for SharedTrc<T>
+where
+    for<'a> &'a T: Seek,
+{
+    #[inline]
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        return (&**self).seek(pos);
+    }
+}
+
+#[allow(deprecated)]
+impl<T: ?Sized + Error> Error for Trc<T> {
+    #[cfg(feature = "std")]
+    fn cause(&self) -> Option<&dyn Error> {
+        return (**self).cause();
+    }
+    #[cfg(feature = "std")]
+    fn description(&self) -> &str {
+        return (**self).description();
+    }
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        return (**self).source();
+    }
+}
+
+#[allow(deprecated)]
+impl<T: ?Sized + Error> Error for SharedTrc<T> {
+    #[cfg(feature = "std")]
+    fn cause(&self) -> Option<&dyn Error> {
+        return (**self).cause();
+    }
+    #[cfg(feature = "std")]
+    fn description(&self) -> &str {
+        return (**self).description();
+    }
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        return (**self).source();
+    }
+}
+
+impl<'a> Trc<dyn Error + Send + Sync + 'a> {
+    /// Move an error into a new `Trc<dyn Error + Send + Sync>`, which can be cheaply cloned and sent to other threads
+    /// through a [`SharedTrc`].
+    ///
+    /// A blanket `From<E: Error>` conversion would conflict with `From<Box<T>>`, so use this with `map_err` instead.
+    /// Errors which are already boxed convert with `?` through `From<Box<T>>`.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::error::Error;
+    /// use trc::Trc;
+    ///
+    /// fn parse(value: &str) -> Result<i32, Trc<dyn Error + Send + Sync>> {
+    ///     value.parse::<i32>().map_err(Trc::from_error)
+    /// }
+    ///
+    /// assert_eq!(parse("100").unwrap(), 100);
+    /// let err = parse("Trc").unwrap_err();
+    /// let err2 = err.clone();
+    /// assert_eq!(err.to_string(), err2.to_string());
+    /// ```
+    pub fn from_error<E: Error + Send + Sync + 'a>(error: E) -> Self {
+        let trc = ManuallyDrop::new(Trc::new(error));
+        return Self {
+            shared: trc.shared,
+            threadref: trc.threadref,
+            phantom: PhantomData,
+        };
+    }
+}
+
+/// An error which only holds a message, used to convert strings into `Trc<dyn Error>`.
+struct StringError(String);
+
+impl Debug for StringError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+
+impl Display for StringError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl Error for StringError {}
+
+impl From<String> for Trc<dyn Error + Send + Sync> {
+    /// Create a `Trc<dyn Error + Send + Sync>` which holds a message.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::error::Error;
+    /// use trc::Trc;
+    ///
+    /// let err: Trc<dyn Error + Send + Sync> = Trc::from(String::from("Something went wrong."));
+    /// assert_eq!(err.to_string(), "Something went wrong.");
+    /// ```
+    fn from(value: String) -> Self {
+        return Self::from_error(StringError(value));
+    }
+}
+
+impl From<&str> for Trc<dyn Error + Send + Sync> {
+    /// Create a `Trc<dyn Error + Send + Sync>` which holds a copy of a message.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::error::Error;
+    /// use trc::Trc;
+    ///
+    /// fn fail() -> Result<(), Trc<dyn Error + Send + Sync>> {
+    ///     Err("Something went wrong.")?
+    /// }
+    ///
+    /// assert_eq!(fail().unwrap_err().to_string(), "Something went wrong.");
+    /// ```
+    fn from(value: &str) -> Self {
+        return Self::from(String::from(value));
+    }
+}
+
+impl From<String> for Trc<dyn Error> {
+    /// Create a `Trc<dyn Error>` which holds a message.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::error::Error;
+    /// use trc::Trc;
+    ///
+    /// let err: Trc<dyn Error> = Trc::from(String::from("Something went wrong."));
+    /// assert_eq!(err.to_string(), "Something went wrong.");
+    /// ```
+    fn from(value: String) -> Self {
+        let trc = ManuallyDrop::new(Trc::new(StringError(value)));
+        return Self {
+            shared: trc.shared,
+            threadref: trc.threadref,
+            phantom: PhantomData,
+        };
+    }
+}
+
+impl From<&str> for Trc<dyn Error> {
+    /// Create a `Trc<dyn Error>` which holds a copy of a message.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::error::Error;
+    /// use trc::Trc;
+    ///
+    /// let err: Trc<dyn Error> = Trc::from("Something went wrong.");
+    /// assert_eq!(err.to_string(), "Something went wrong.");
+    /// ```
+    fn from(value: &str) -> Self {
+        return Self::from(String::from(value));
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Trc<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Trc<T>, D::Error>
+    where
+        D: ::serde::de::Deserializer<'de>,
+    {
+        T::deserialize(deserializer).map(Trc::new)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: ?Sized + Serialize> Serialize for Trc<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ::serde::ser::Serializer,
+    {
+        (**self).serialize(serializer)
+    }
+}
+
+/// Deserializes a string directly into the allocation of a `Trc<str>` or `SharedTrc<str>`, without first building a
+/// `String` when the input is borrowed.
+#[cfg(feature = "serde")]
+struct StrVisitor<P>(PhantomData<P>);
+
+#[cfg(feature = "serde")]
+impl<'de, P: for<'a> From<Cow<'a, str>>> Visitor<'de> for StrVisitor<P> {
+    type Value = P;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a string")
+    }
+
+    fn visit_str<E: DeError>(self, v: &str) -> Result<P, E> {
+        Ok(P::from(Cow::Borrowed(v)))
+    }
+
+    fn visit_string<E: DeError>(self, v: String) -> Result<P, E> {
+        Ok(P::from(Cow::Owned(v)))
+    }
+
+    fn visit_bytes<E: DeError>(self, v: &[u8]) -> Result<P, E> {
+        match core::str::from_utf8(v) {
+            Ok(v) => self.visit_str(v),
+            Err(_) => Err(E::invalid_value(Unexpected::Bytes(v), &self)),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Trc<str> {
+    fn deserialize<D>(deserializer: D) -> Result<Trc<str>, D::Error>
+    where
+        D: ::serde::de::Deserializer<'de>,
+    {
+        deserializer.deserialize_str(StrVisitor(PhantomData))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Trc<[T]> {
+    fn deserialize<D>(deserializer: D) -> Result<Trc<[T]>, D::Error>
+    where
+        D: ::serde::de::Deserializer<'de>,
+    {
+        Vec::<T>::deserialize(deserializer).map(Trc::from)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Trc<CStr> {
+    fn deserialize<D>(deserializer: D) -> Result<Trc<CStr>, D::Error>
+    where
+        D: ::serde::de::Deserializer<'de>,
+    {
+        Box::<CStr>::deserialize(deserializer).map(Trc::from)
+    }
+}
+
+#[cfg(feature = "stable_deref_trait")]
+unsafe impl<T: ?Sized> StableDeref for Trc<T> {}
+#[cfg(feature = "stable_deref_trait")]
+unsafe impl<T: ?Sized> CloneStableDeref for Trc<T> {}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for SharedTrc<T> {
+    fn deserialize<D>(deserializer: D) -> Result<SharedTrc<T>, D::Error>
+    where
+        D: ::serde::de::Deserializer<'de>,
+    {
+        T::deserialize(deserializer).map(SharedTrc::new)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: ?Sized + Serialize> Serialize for SharedTrc<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ::serde::ser::Serializer,
+    {
+        (**self).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for SharedTrc<str> {
+    fn deserialize<D>(deserializer: D) -> Result<SharedTrc<str>, D::Error>
+    where
+        D: ::serde::de::Deserializer<'de>,
+    {
+        deserializer.deserialize_str(StrVisitor(PhantomData))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for SharedTrc<[T]> {
+    fn deserialize<D>(deserializer: D) -> Result<SharedTrc<[T]>, D::Error>
+    where
+        D: ::serde::de::Deserializer<'de>,
+    {
+        Vec::<T>::deserialize(deserializer).map(SharedTrc::from)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for SharedTrc<CStr> {
+    fn deserialize<D>(deserializer: D) -> Result<SharedTrc<CStr>, D::Error>
+    where
+        D: ::serde::de::Deserializer<'de>,
+    {
+        Box::<CStr>::deserialize(deserializer).map(SharedTrc::from)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T: Arbitrary<'a>> Arbitrary<'a> for Trc<T> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        T::arbitrary(u).map(Trc::new)
+    }
+
+    #[inline]
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        T::size_hint(depth)
+    }
+
+    #[inline]
+    fn try_size_hint(depth: usize) -> Result<(usize, Option<usize>), MaxRecursionReached> {
+        T::try_size_hint(depth)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T: Arbitrary<'a>> Arbitrary<'a> for Trc<[T]> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter()?.collect()
+    }
+
+    #[inline]
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> Arbitrary<'a> for Trc<str> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        <&str>::arbitrary(u).map(|value| Trc::from(Cow::Borrowed(value)))
+    }
+
+    #[inline]
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        <&str>::size_hint(depth)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T: Arbitrary<'a>> Arbitrary<'a> for SharedTrc<T> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        T::arbitrary(u).map(SharedTrc::new)
+    }
+
+    #[inline]
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        T::size_hint(depth)
+    }
+
     #[inline]
-    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
-        return self.deref().partial_cmp(&**other);
+    fn try_size_hint(depth: usize) -> Result<(usize, Option<usize>), MaxRecursionReached> {
+        T::try_size_hint(depth)
     }
 }
 
-impl<T: Ord> Ord for Trc<T> {
-    /// Comparison for two `Trc`s. The two are compared by calling `.cmp` on the inner values.
-    ///
-    /// # Examples
-    /// ```
-    /// use trc::Trc;
-    /// use std::cmp::Ordering;
-    ///
-    /// let trc1 = Trc::from(100);
-    /// let trc2 = Trc::from(200);
-    /// assert_eq!(Ordering::Less, trc1.cmp(&trc2));
-    /// ```
+#[cfg(feature = "arbitrary")]
+impl<'a, T: Arbitrary<'a>> Arbitrary<'a> for SharedTrc<[T]> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_iter()?.collect()
+    }
+
+    fn arbitrary_take_rest(u: Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.arbitrary_take_rest_iter()?.collect()
+    }
+
     #[inline]
-    fn cmp(&self, other: &Self) -> cmp::Ordering {
-        return self.deref().cmp(&**other);
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (0, None)
     }
 }
 
-impl<T: Ord> Ord for SharedTrc<T> {
-    /// Comparison for two `SharedTrc`s. The two are compared by calling `.cmp` on the inner values.
-    ///
-    /// # Examples
-    /// ```
-    /// use trc::Trc;
-    /// use trc::SharedTrc;
-    /// use std::cmp::Ordering;
-    ///
-    /// let shared1: SharedTrc<_> = Trc::from(100).into();
-    /// let shared2: SharedTrc<_> = Trc::from(200).into();
-    /// assert_eq!(Ordering::Less, shared1.cmp(&shared2));
-    /// ```
+#[cfg(feature = "arbitrary")]
+impl<'a> Arbitrary<'a> for SharedTrc<str> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        <&str>::arbitrary(u).map(|value| SharedTrc::from(Cow::Borrowed(value)))
+    }
+
     #[inline]
-    fn cmp(&self, other: &Self) -> cmp::Ordering {
-        return self.deref().cmp(&**other);
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        <&str>::size_hint(depth)
     }
 }
 
-impl<T: Eq> Eq for Trc<T> {}
+/// Generates either a dangling `Weak` or a `Weak` whose value has already been dropped. A `Weak` which can be upgraded
+/// cannot be generated, because nothing would own the value.
+#[cfg(feature = "arbitrary")]
+impl<'a, T: Arbitrary<'a>> Arbitrary<'a> for Weak<T> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(match Option::<T>::arbitrary(u)? {
+            Some(value) => Trc::downgrade(&Trc::new(value)),
+            None => Weak::new(),
+        })
+    }
 
-impl<T: Eq> Eq for SharedTrc<T> {}
+    #[inline]
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        Option::<T>::size_hint(depth)
+    }
 
-impl<T: PartialEq> PartialEq for Trc<T> {
-    /// Equality by value comparison for two `Trc`s, even if the data is in different allocoations.
-    ///
-    /// Calls `.eq` on the data.
-    ///
-    /// # Examples
-    /// ```
-    /// use trc::Trc;
-    ///
-    /// let trc1 = Trc::from(100);
-    /// let trc2 = Trc::from(100);
-    /// assert!(trc1==trc2);
-    /// ```
     #[inline]
-    fn eq(&self, other: &Self) -> bool {
-        return self.deref().eq(&**other);
+    fn try_size_hint(depth: usize) -> Result<(usize, Option<usize>), MaxRecursionReached> {
+        Option::<T>::try_size_hint(depth)
     }
+}
 
-    /// Inequality by value comparison for two `Trc`s, even if the data is in different allocoations.
-    ///
-    /// Calls `.ne` on the data.
+#[cfg(feature = "proptest")]
+impl<T: proptest::arbitrary::Arbitrary> proptest::arbitrary::Arbitrary for Trc<T> {
+    type Parameters = T::Parameters;
+    type Strategy = SMapped<T, Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        statics::Map::new(any_with::<T>(args), Trc::new)
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T: proptest::arbitrary::Arbitrary> proptest::arbitrary::Arbitrary for SharedTrc<T> {
+    type Parameters = T::Parameters;
+    type Strategy = SMapped<T, Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        statics::Map::new(any_with::<T>(args), SharedTrc::new)
+    }
+}
+
+/// Generates either a dangling `Weak` or a `Weak` whose value has already been dropped. A `Weak` which can be upgraded
+/// cannot be generated, because nothing would own the value.
+#[cfg(feature = "proptest")]
+impl<T: proptest::arbitrary::Arbitrary> proptest::arbitrary::Arbitrary for Weak<T> {
+    type Parameters = <Option<T> as proptest::arbitrary::Arbitrary>::Parameters;
+    type Strategy = SMapped<Option<T>, Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        statics::Map::new(any_with::<Option<T>>(args), |value| match value {
+            Some(value) => Trc::downgrade(&Trc::new(value)),
+            None => Weak::new(),
+        })
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<'a, T: Sync + 'a> IntoParallelIterator for &'a SharedTrc<[T]> {
+    type Item = &'a T;
+    type Iter = rayon::slice::Iter<'a, T>;
+
+    /// Iterate over the elements of the slice in parallel, without copying them.
     ///
     /// # Examples
     /// ```
-    /// use trc::Trc;
+    /// use rayon::prelude::*;
+    /// use trc::SharedTrc;
     ///
-    /// let trc1 = Trc::from(100);
-    /// let trc2 = Trc::from(200);
-    /// assert!(trc1!=trc2);
+    /// let shared: SharedTrc<[u64]> = (1..=100).collect();
+    /// assert_eq!(shared.par_iter().sum::<u64>(), 5050);
     /// ```
-    #[allow(clippy::partialeq_ne_impl)]
     #[inline]
-    fn ne(&self, other: &Self) -> bool {
-        return self.deref().ne(&**other);
+    fn into_par_iter(self) -> Self::Iter {
+        return (**self).par_iter();
     }
 }
 
-impl<T: PartialEq> PartialEq for SharedTrc<T> {
-    /// Equality by value comparison for two `SharedTrc`s, even if the data is in different allocoations.
-    ///
-    /// Calls `.eq` on the data.
+#[cfg(feature = "rayon")]
+impl<'a, T: Sync + 'a> IntoParallelIterator for &'a Trc<[T]> {
+    type Item = &'a T;
+    type Iter = rayon::slice::Iter<'a, T>;
+
+    /// Iterate over the elements of the slice in parallel, without copying them. Only the elements are shared with
+    /// other threads, so this does not require the `Trc` itself to be sent.
     ///
     /// # Examples
     /// ```
+    /// use rayon::prelude::*;
     /// use trc::Trc;
-    /// use trc::SharedTrc;
     ///
-    /// let shared1: SharedTrc<_> = Trc::from(100).into();
-    /// let shared2: SharedTrc<_> = Trc::from(100).into();
-    /// assert!(shared1==shared2);
+    /// let trc: Trc<[u64]> = (1..=100).collect();
+    /// assert_eq!(trc.par_iter().map(|x| x * 2).sum::<u64>(), 10100);
     /// ```
     #[inline]
-    fn eq(&self, other: &Self) -> bool {
-        return self.deref().eq(&**other);
+    fn into_par_iter(self) -> Self::Iter {
+        return (**self).par_iter();
     }
+}
 
-    /// Inequality by value comparison for two `SharedTrc`s, even if the data is in different allocoations.
-    ///
-    /// Calls `.ne` on the data.
+#[cfg(feature = "rayon")]
+impl<T: Sync> ParallelSlice<T> for SharedTrc<[T]> {
+    /// Return the slice, which gives access to the methods of [`ParallelSlice`].
     ///
     /// # Examples
     /// ```
-    /// use trc::Trc;
+    /// use rayon::prelude::*;
     /// use trc::SharedTrc;
     ///
-    /// let shared1: SharedTrc<_> = Trc::from(100).into();
-    /// let shared2: SharedTrc<_> = Trc::from(200).into();
-    /// assert!(shared1!=shared2);
+    /// let shared: SharedTrc<[u32]> = (0..10).collect();
+    /// let sums: Vec<u32> = shared.par_chunks(5).map(|chunk| chunk.iter().sum()).collect();
+    /// assert_eq!(sums, [10, 35]);
     /// ```
-    #[allow(clippy::partialeq_ne_impl)]
     #[inline]
-    fn ne(&self, other: &Self) -> bool {
-        return self.deref().ne(&**other);
+    fn as_parallel_slice(&self) -> &[T] {
+        return self;
     }
 }
 
-#[cfg(not(target_os = "windows"))]
-impl<T: AsFd> AsFd for Trc<T> {
-    fn as_fd(&self) -> std::os::fd::BorrowedFd<'_> {
-        return (**self).as_fd();
-    }
+#[cfg(feature = "stable_deref_trait")]
+unsafe impl<T: ?Sized> StableDeref for SharedTrc<T> {}
+#[cfg(feature = "stable_deref_trait")]
+unsafe impl<T: ?Sized> CloneStableDeref for SharedTrc<T> {}
+
+impl<T: ?Sized> Unpin for Trc<T> {}
+impl<T: ?Sized> UnwindSafe for Trc<T> {}
+impl<T: ?Sized + RefUnwindSafe> RefUnwindSafe for Trc<T> {}
+
+impl<T: ?Sized> Unpin for SharedTrc<T> {}
+impl<T: ?Sized> UnwindSafe for SharedTrc<T> {}
+impl<T: ?Sized + RefUnwindSafe> RefUnwindSafe for SharedTrc<T> {}
+
+impl<T: ?Sized + RefUnwindSafe> UnwindSafe for Weak<T> {}
+impl<T: ?Sized + RefUnwindSafe> RefUnwindSafe for Weak<T> {}
+
+if_thread_safe! {
+    #[cfg(not(feature = "allocator_api"))]
+    unsafe impl<T: ?Sized + Sync + Send> Send for SharedTrc<T> {}
+    #[cfg(not(feature = "allocator_api"))]
+    unsafe impl<T: ?Sized + Sync + Send> Sync for SharedTrc<T> {}
+
+    #[cfg(not(feature = "allocator_api"))]
+    unsafe impl<T: ?Sized + Sync + Send> Send for Weak<T> {}
+    #[cfg(not(feature = "allocator_api"))]
+    unsafe impl<T: ?Sized + Sync + Send> Sync for Weak<T> {}
 }
 
-#[cfg(not(target_os = "windows"))]
-impl<T: AsFd> AsFd for SharedTrc<T> {
-    fn as_fd(&self) -> std::os::fd::BorrowedFd<'_> {
-        return (**self).as_fd();
-    }
+/// Owns a slice allocation while its elements are being written. If construction panics, the elements which were
+/// already written are dropped and the allocation is freed.
+struct SliceGuard<T> {
+    shared: NonNull<SharedTrcInternal<[MaybeUninit<T>]>>,
+    written: usize,
 }
 
-#[cfg(target_os = "windows")]
-impl<T: AsRawHandle> AsRawHandle for Trc<T> {
-    fn as_raw_handle(&self) -> std::os::windows::io::RawHandle {
-        (**self).as_raw_handle()
+impl<T> SliceGuard<T> {
+    fn new(len: usize) -> Self {
+        Self {
+            shared: alloc_slice_internal::<T>(len),
+            written: 0,
+        }
     }
-}
 
-#[cfg(target_os = "windows")]
-impl<T: AsRawHandle> AsRawHandle for SharedTrc<T> {
-    fn as_raw_handle(&self) -> std::os::windows::io::RawHandle {
-        (**self).as_raw_handle()
+    /// Write the next element. The caller must not write more elements than the length of the slice.
+    #[inline]
+    unsafe fn push(&mut self, value: T) {
+        let elems = addr_of_mut!((*self.shared.as_ptr()).data).cast::<T>();
+        write(elems.add(self.written), value);
+        self.written += 1;
     }
-}
 
-#[cfg(target_os = "windows")]
-impl<T: AsHandle> AsHandle for Trc<T> {
-    fn as_handle(&self) -> std::os::windows::io::BorrowedHandle<'_> {
-        (**self).as_handle()
+    /// Finish construction. All elements of the slice must have been written.
+    fn finish(self) -> NonNull<SharedTrcInternal<[T]>> {
+        let this = ManuallyDrop::new(self);
+        unsafe { NonNull::new_unchecked(this.shared.as_ptr() as *mut SharedTrcInternal<[T]>) }
     }
 }
 
-#[cfg(target_os = "windows")]
-impl<T: AsHandle> AsHandle for SharedTrc<T> {
-    fn as_handle(&self) -> std::os::windows::io::BorrowedHandle<'_> {
-        (**self).as_handle()
+impl<T> Drop for SliceGuard<T> {
+    fn drop(&mut self) {
+        unsafe {
+            let elems = addr_of_mut!((*self.shared.as_ptr()).data).cast::<T>();
+            ptr::drop_in_place(slice_from_raw_parts_mut(elems, self.written));
+
+            #[cfg(feature = "stats")]
+            stats::on_dealloc(mem::size_of_val(&(*self.shared.as_ptr()).data));
+            let layout = Layout::for_value(&*self.shared.as_ptr());
+            dealloc(self.shared.as_ptr().cast(), layout);
+        }
     }
 }
 
-#[cfg(not(target_os = "windows"))]
-impl<T: AsRawFd> AsRawFd for Trc<T> {
-    fn as_raw_fd(&self) -> std::os::fd::RawFd {
-        (**self).as_raw_fd()
+/// Write the elements of `iterator` directly into a new slice allocation. This panics if the iterator yields fewer
+/// elements than it reported, and ignores any extra elements.
+fn create_from_iterator_exact<T>(
+    iterator: impl ExactSizeIterator<Item = T>,
+) -> *mut SharedTrcInternal<[T]> {
+    let len = iterator.len();
+    let mut guard = SliceGuard::<T>::new(len);
+
+    for value in iterator.take(len) {
+        unsafe { guard.push(value) };
     }
+    assert_eq!(
+        guard.written, len,
+        "ExactSizeIterator reported an incorrect length."
+    );
+
+    guard.finish().as_ptr()
 }
 
-#[cfg(not(target_os = "windows"))]
-impl<T: AsRawFd> AsRawFd for SharedTrc<T> {
-    fn as_raw_fd(&self) -> std::os::fd::RawFd {
-        (**self).as_raw_fd()
-    }
+trait TrcFromIter<T> {
+    fn from_iter(slice: impl ExactSizeIterator<Item = T>) -> Self;
 }
 
-#[cfg(target_os = "windows")]
-impl<T: AsRawSocket> AsRawSocket for Trc<T> {
-    fn as_raw_socket(&self) -> std::os::windows::io::RawSocket {
-        (**self).as_raw_socket()
+impl<T> TrcFromIter<T> for Trc<[T]> {
+    fn from_iter(slice: impl ExactSizeIterator<Item = T>) -> Self {
+        let shared = create_from_iterator_exact(slice);
+        return Self {
+            threadref: new_threadref(),
+            shared: unsafe { NonNull::new_unchecked(shared) },
+            phantom: PhantomData,
+        };
     }
 }
 
-#[cfg(target_os = "windows")]
-impl<T: AsRawSocket> AsRawSocket for SharedTrc<T> {
-    fn as_raw_socket(&self) -> std::os::windows::io::RawSocket {
-        (**self).as_raw_socket()
+impl<T: Clone> From<&[T]> for Trc<[T]> {
+    /// From conversion from a reference to a slice of type `T` (`&[T]`) to a `Trc<[T]>`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let vec = (1..100).collect::<Vec<i32>>();
+    /// let slice = &vec[2..5];
+    /// let trc = Trc::<[i32]>::from(slice);
+    /// assert_eq!(&*trc, slice);
+    /// ```
+    fn from(value: &[T]) -> Self {
+        return <Self as TrcFromIter<T>>::from_iter(value.iter().cloned());
     }
 }
 
-#[cfg(target_os = "windows")]
-impl<T: AsSocket> AsSocket for Trc<T> {
-    fn as_socket(&self) -> std::os::windows::io::BorrowedSocket<'_> {
-        (**self).as_socket()
+impl<T> FromIterator<T> for Trc<[T]> {
+    /// From conversion from an iterator (`impl IntoIterator<Item = T>`) to `Trc<[T]>`. The elements are moved out of the iterator,
+    /// so `T` does not need to implement `Clone`. Due to Rust's unstable trait specialization feature,
+    /// there is no special case for iterators that implement [`ExactSizeIterator`].
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let trc = Trc::<[i32]>::from_iter(vec![1,2,3]);
+    /// assert_eq!(&*trc, vec![1,2,3]);
+    ///
+    /// struct NotClone(i32);
+    ///
+    /// let trc: Trc<[NotClone]> = (0..3).map(NotClone).collect();
+    /// assert_eq!(trc[2].0, 2);
+    /// ```
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from(iter.into_iter().collect::<Vec<_>>())
     }
 }
 
-#[cfg(target_os = "windows")]
-impl<T: AsSocket> AsSocket for SharedTrc<T> {
-    fn as_socket(&self) -> std::os::windows::io::BorrowedSocket<'_> {
-        (**self).as_socket()
+impl<T> From<Vec<T>> for Trc<[T]> {
+    /// Move the elements of a `Vec<T>` into a new `Trc<[T]>`. Unlike the conversion from `&[T]`, this does not require `T: Clone`.
+    /// A `Box<[T]>` can be converted in the same way with [`Trc::from`].
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// struct NotClone(i32);
+    ///
+    /// let trc = Trc::<[NotClone]>::from(vec![NotClone(1), NotClone(2)]);
+    /// assert_eq!(trc[1].0, 2);
+    ///
+    /// let trc = Trc::<[i32]>::from(vec![1, 2, 3].into_boxed_slice());
+    /// assert_eq!(&*trc, [1, 2, 3]);
+    /// ```
+    fn from(value: Vec<T>) -> Self {
+        let shared = vec_into_internal(value);
+        return Self {
+            threadref: new_threadref(),
+            shared,
+            phantom: PhantomData,
+        };
     }
 }
 
-#[allow(deprecated)]
-impl<T: Error> Error for Trc<T> {
-    fn cause(&self) -> Option<&dyn Error> {
-        return (**self).cause();
-    }
-    fn description(&self) -> &str {
-        return (**self).description();
-    }
-    fn source(&self) -> Option<&(dyn Error + 'static)> {
-        return (**self).source();
+impl<T: ?Sized> From<Box<T>> for SharedTrc<T> {
+    /// Move a boxed value into a new `SharedTrc`. This works for unsized values such as `Box<[T]>`, `Box<str>` and
+    /// `Box<dyn Trait + Send + Sync>`.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::fmt::Display;
+    /// use trc::SharedTrc;
+    ///
+    /// let boxed: Box<dyn Display + Send + Sync> = Box::new(100);
+    /// let shared: SharedTrc<dyn Display + Send + Sync> = SharedTrc::from(boxed);
+    /// assert_eq!(shared.to_string(), "100");
+    ///
+    /// let shared: SharedTrc<str> = SharedTrc::from(Box::<str>::from("Trc"));
+    /// assert_eq!(&*shared, "Trc");
+    /// ```
+    fn from(value: Box<T>) -> Self {
+        return Self {
+            data: box_into_internal(value),
+            phantom: PhantomData,
+        };
     }
 }
 
-#[allow(deprecated)]
-impl<T: Error> Error for SharedTrc<T> {
-    fn cause(&self) -> Option<&dyn Error> {
-        return (**self).cause();
-    }
-    fn description(&self) -> &str {
-        return (**self).description();
+impl<T: Clone> From<&[T]> for SharedTrc<[T]> {
+    /// From conversion from a reference to a slice of type `T` (`&[T]`) to a `SharedTrc<[T]>`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::SharedTrc;
+    ///
+    /// let vec = (1..100).collect::<Vec<i32>>();
+    /// let slice = &vec[2..5];
+    /// let shared = SharedTrc::<[i32]>::from(slice);
+    /// assert_eq!(&*shared, slice);
+    /// ```
+    fn from(value: &[T]) -> Self {
+        return Self {
+            data: unsafe {
+                NonNull::new_unchecked(create_from_iterator_exact(value.iter().cloned()))
+            },
+            phantom: PhantomData,
+        };
     }
-    fn source(&self) -> Option<&(dyn Error + 'static)> {
-        return (**self).source();
+}
+
+impl<T> FromIterator<T> for SharedTrc<[T]> {
+    /// From conversion from an iterator (`impl IntoIterator<Item = T>`) to `SharedTrc<[T]>`. The elements are moved out of
+    /// the iterator, so `T` does not need to implement `Clone`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::SharedTrc;
+    ///
+    /// let shared: SharedTrc<[i32]> = (1..4).map(|x| x * 2).collect();
+    /// assert_eq!(*shared, [2, 4, 6]);
+    /// ```
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from(iter.into_iter().collect::<Vec<_>>())
     }
 }
 
-#[cfg(feature = "serde")]
-impl<'de, T: Deserialize<'de>> Deserialize<'de> for Trc<T> {
-    fn deserialize<D>(deserializer: D) -> Result<Trc<T>, D::Error>
-    where
-        D: ::serde::de::Deserializer<'de>,
-    {
-        T::deserialize(deserializer).map(Trc::new)
+impl<T> From<Vec<T>> for SharedTrc<[T]> {
+    /// Move the elements of a `Vec<T>` into a new `SharedTrc<[T]>`. Unlike the conversion from `&[T]`, this does not require
+    /// `T: Clone`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::SharedTrc;
+    ///
+    /// struct NotClone(i32);
+    ///
+    /// let shared = SharedTrc::<[NotClone]>::from(vec![NotClone(1), NotClone(2)]);
+    /// assert_eq!(shared[1].0, 2);
+    /// ```
+    fn from(value: Vec<T>) -> Self {
+        return Self {
+            data: vec_into_internal(value),
+            phantom: PhantomData,
+        };
     }
 }
 
-#[cfg(feature = "serde")]
-impl<T: Serialize> Serialize for Trc<T> {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: ::serde::ser::Serializer,
-    {
-        (**self).serialize(serializer)
+impl From<&str> for Trc<str> {
+    /// From conversion from a string slice (`&str`) to a `Trc<str>`. The bytes of the string are copied into the new allocation.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let trc = Trc::<str>::from("Trc");
+    /// assert_eq!(&*trc, "Trc");
+    /// ```
+    fn from(value: &str) -> Self {
+        let bytes = Trc::<[u8]>::from(value.as_bytes());
+        unsafe { Trc::from_utf8_unchecked(bytes) }
     }
 }
 
-#[cfg(feature = "stable_deref_trait")]
-unsafe impl<T: ?Sized> StableDeref for Trc<T> {}
-#[cfg(feature = "stable_deref_trait")]
-unsafe impl<T: ?Sized> CloneStableDeref for Trc<T> {}
+impl FromStr for Trc<str> {
+    type Err = Infallible;
 
-#[cfg(feature = "serde")]
-impl<'de, T: Deserialize<'de>> Deserialize<'de> for SharedTrc<T> {
-    fn deserialize<D>(deserializer: D) -> Result<SharedTrc<T>, D::Error>
-    where
-        D: ::serde::de::Deserializer<'de>,
-    {
-        T::deserialize(deserializer).map(SharedTrc::new)
+    /// Parse a `Trc<str>` from a string slice, which always succeeds. This allows `Trc<str>` to be used with
+    /// [`str::parse`] and other code which is generic over [`FromStr`].
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let trc: Trc<str> = "Trc".parse().unwrap();
+    /// assert_eq!(&*trc, "Trc");
+    /// ```
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return Ok(Self::from(s));
     }
 }
 
-#[cfg(feature = "serde")]
-impl<T: Serialize> Serialize for SharedTrc<T> {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: ::serde::ser::Serializer,
-    {
-        (**self).serialize(serializer)
+impl<'a, T: Clone> From<Cow<'a, [T]>> for Trc<[T]> {
+    /// Create a `Trc<[T]>` from a `Cow<[T]>`. Borrowed slices are cloned into the new allocation, while the elements of
+    /// owned vectors are moved.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::borrow::Cow;
+    /// use trc::Trc;
+    ///
+    /// let borrowed: Cow<[i32]> = Cow::Borrowed(&[1, 2, 3]);
+    /// let owned: Cow<[i32]> = Cow::Owned(vec![1, 2, 3]);
+    /// let borrowed: Trc<[i32]> = Trc::from(borrowed);
+    /// let owned: Trc<[i32]> = Trc::from(owned);
+    /// assert_eq!(*borrowed, *owned);
+    /// ```
+    fn from(value: Cow<'a, [T]>) -> Self {
+        match value {
+            Cow::Borrowed(value) => Self::from(value),
+            Cow::Owned(value) => Self::from(value),
+        }
     }
 }
 
-#[cfg(feature = "stable_deref_trait")]
-unsafe impl<T: ?Sized> StableDeref for SharedTrc<T> {}
-#[cfg(feature = "stable_deref_trait")]
-unsafe impl<T: ?Sized> CloneStableDeref for SharedTrc<T> {}
-
-impl<T: ?Sized> Unpin for Trc<T> {}
-impl<T: ?Sized> UnwindSafe for Trc<T> {}
-
-impl<T: ?Sized> Unpin for SharedTrc<T> {}
-impl<T: ?Sized> UnwindSafe for SharedTrc<T> {}
-
-unsafe impl<T: Sync + Send> Send for SharedTrc<T> {}
-unsafe impl<T: Sync + Send> Sync for SharedTrc<T> {}
-
-unsafe impl<T: Sync + Send> Send for Weak<T> {}
-unsafe impl<T: Sync + Send> Sync for Weak<T> {}
-
-fn create_from_iterator_exact<T>(
-    iterator: impl Iterator<Item = T> + ExactSizeIterator,
-) -> *mut SharedTrcInternal<[T]> {
-    let value_layout = Layout::array::<T>(iterator.len()).unwrap();
-    let layout = Layout::new::<SharedTrcInternal<()>>()
-        .extend(value_layout)
-        .unwrap()
-        .0
-        .pad_to_align();
-
-    let res = slice_from_raw_parts_mut(unsafe { alloc(layout) }.cast::<T>(), iterator.len())
-        as *mut SharedTrcInternal<[T]>;
-    unsafe { write(&mut (*res).atomicref, AtomicUsize::new(1)) };
-    unsafe { write(&mut (*res).weakcount, AtomicUsize::new(1)) };
-
-    let elems = unsafe { addr_of_mut!((*res).data) }.cast::<T>();
-    for (n, i) in iterator.enumerate() {
-        unsafe {
-            write(elems.add(n), i);
+impl<'a> From<Cow<'a, str>> for Trc<str> {
+    /// Create a `Trc<str>` from a `Cow<str>`. Borrowed strings are copied into the new allocation, while the bytes of
+    /// owned strings are moved.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::borrow::Cow;
+    /// use trc::Trc;
+    ///
+    /// let trc = Trc::<str>::from(String::from_utf8_lossy(b"Trc"));
+    /// assert_eq!(&*trc, "Trc");
+    /// ```
+    fn from(value: Cow<'a, str>) -> Self {
+        match value {
+            Cow::Borrowed(value) => Self::from(value),
+            Cow::Owned(value) => unsafe { Trc::from_utf8_unchecked(Trc::from(value.into_bytes())) },
         }
     }
-    res
 }
 
-trait TrcFromIter<T> {
-    fn from_iter(slice: impl Iterator<Item = T> + ExactSizeIterator) -> Self;
+impl<'a, T: Clone> From<Cow<'a, [T]>> for SharedTrc<[T]> {
+    /// Create a `SharedTrc<[T]>` from a `Cow<[T]>`. Borrowed slices are cloned into the new allocation, while the elements of
+    /// owned vectors are moved.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::borrow::Cow;
+    /// use trc::SharedTrc;
+    ///
+    /// let shared = SharedTrc::from(Cow::Borrowed(&[1, 2, 3][..]));
+    /// assert_eq!(*shared, [1, 2, 3]);
+    /// ```
+    fn from(value: Cow<'a, [T]>) -> Self {
+        let data = match value {
+            Cow::Borrowed(value) => unsafe {
+                NonNull::new_unchecked(create_from_iterator_exact(value.iter().cloned()))
+            },
+            Cow::Owned(value) => vec_into_internal(value),
+        };
+        Self {
+            data,
+            phantom: PhantomData,
+        }
+    }
 }
 
-impl<T: Clone + ?Sized> TrcFromIter<T> for Trc<[T]> {
-    fn from_iter(slice: impl Iterator<Item = T> + ExactSizeIterator) -> Self {
-        let shared = create_from_iterator_exact(slice);
-        let tbx = Box::new(1);
-
-        return Self {
-            threadref: NonNull::from(Box::leak(tbx)),
-            shared: unsafe { NonNull::new_unchecked(shared) },
-        };
+impl<'a> From<Cow<'a, str>> for SharedTrc<str> {
+    /// Create a `SharedTrc<str>` from a `Cow<str>`. Borrowed strings are copied into the new allocation, while the bytes of
+    /// owned strings are moved.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::borrow::Cow;
+    /// use trc::SharedTrc;
+    ///
+    /// let shared = SharedTrc::<str>::from(Cow::Owned(String::from("Trc")));
+    /// assert_eq!(&*shared, "Trc");
+    /// ```
+    fn from(value: Cow<'a, str>) -> Self {
+        let bytes = SharedTrc::<[u8]>::from(match value {
+            Cow::Borrowed(value) => Cow::Borrowed(value.as_bytes()),
+            Cow::Owned(value) => Cow::Owned(value.into_bytes()),
+        });
+        let bytes = ManuallyDrop::new(bytes);
+        Self {
+            data: unsafe {
+                NonNull::new_unchecked(bytes.data.as_ptr() as *mut SharedTrcInternal<str>)
+            },
+            phantom: PhantomData,
+        }
     }
 }
 
-impl<T: Clone + ?Sized> From<&[T]> for Trc<[T]> {
-    /// From conversion from a reference to a slice of type `T` (`&[T]`) to a `Trc<[T]>`.
+impl Trc<str> {
+    /// Converts a slice of bytes to a `Trc<str>`, including invalid characters.
+    /// If the bytes are valid UTF-8, they are copied directly into the new allocation. Otherwise, each invalid sequence
+    /// is replaced with [`U+FFFD REPLACEMENT CHARACTER`](char::REPLACEMENT_CHARACTER), as with [`String::from_utf8_lossy`].
     ///
     /// # Examples
     /// ```
     /// use trc::Trc;
     ///
-    /// let vec = (1..100).collect::<Vec<i32>>();
-    /// let slice = &vec[2..5];
-    /// let trc = Trc::<[i32]>::from(slice);
-    /// assert_eq!(&*trc, slice);
+    /// let trc = Trc::<str>::from_utf8_lossy(b"Hello World");
+    /// assert_eq!(&*trc, "Hello World");
+    ///
+    /// let trc = Trc::<str>::from_utf8_lossy(b"Hello \xF0\x90\x80World");
+    /// assert_eq!(&*trc, "Hello \u{FFFD}World");
     /// ```
-    fn from(value: &[T]) -> Self {
-        return <Self as TrcFromIter<T>>::from_iter(value.iter().cloned());
+    #[must_use]
+    pub fn from_utf8_lossy(v: &[u8]) -> Trc<str> {
+        Trc::<str>::from(&*String::from_utf8_lossy(v))
     }
-}
 
-impl<T: Clone + ?Sized> FromIterator<T> for Trc<[T]> {
-    /// From conversion from an iterator (`impl IntoIterator<Item = T>`) to `Trc<[T]>`. Due to Rust's unstable trait specialization feature,
-    /// there is no special case for iterators that implement [`ExactSizeIterator`].
+    /// Converts a `Trc<[u8]>` to a `Trc<str>`, including invalid characters.
+    /// If the bytes are valid UTF-8, the allocation is reused and no bytes are copied. Otherwise, a new allocation is made
+    /// where each invalid sequence is replaced with [`U+FFFD REPLACEMENT CHARACTER`](char::REPLACEMENT_CHARACTER).
     ///
     /// # Examples
     /// ```
     /// use trc::Trc;
     ///
-    /// let trc = Trc::<[i32]>::from_iter(vec![1,2,3]);
-    /// assert_eq!(&*trc, vec![1,2,3]);
+    /// let bytes = Trc::<[u8]>::from(&b"Hello World"[..]);
+    /// let ptr = Trc::as_ptr(&bytes).cast::<u8>();
+    /// let trc = Trc::<str>::from_utf8_lossy_owned(bytes);
+    /// assert_eq!(&*trc, "Hello World");
+    /// assert_eq!(Trc::as_ptr(&trc).cast::<u8>(), ptr);
+    ///
+    /// let bytes = Trc::<[u8]>::from(&b"Hello \xF0\x90\x80World"[..]);
+    /// let trc = Trc::<str>::from_utf8_lossy_owned(bytes);
+    /// assert_eq!(&*trc, "Hello \u{FFFD}World");
     /// ```
-    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
-        Self::from(&*iter.into_iter().collect::<Vec<_>>())
+    #[must_use]
+    pub fn from_utf8_lossy_owned(v: Trc<[u8]>) -> Trc<str> {
+        if core::str::from_utf8(&v).is_ok() {
+            unsafe { Trc::from_utf8_unchecked(v) }
+        } else {
+            Self::from_utf8_lossy(&v)
+        }
+    }
+
+    /// Converts a `Trc<[u8]>` to a `Trc<str>` without checking that the bytes are valid UTF-8.
+    ///
+    /// # Safety
+    /// The bytes must be valid UTF-8.
+    unsafe fn from_utf8_unchecked(v: Trc<[u8]>) -> Trc<str> {
+        let v = ManuallyDrop::new(v);
+        Trc {
+            shared: NonNull::new_unchecked(v.shared.as_ptr() as *mut SharedTrcInternal<str>),
+            threadref: v.threadref,
+            phantom: PhantomData,
+        }
     }
 }
 
 //TODO: Integration with standard library for both, or use lib & conditional for just CoerceUnsized
 #[cfg(feature = "dyn_unstable")]
-impl<T: ?Sized + std::marker::Unsize<U>, U: ?Sized> std::ops::CoerceUnsized<Trc<U>> for Trc<T> {}
+impl<T: ?Sized + core::marker::Unsize<U>, U: ?Sized> core::ops::CoerceUnsized<Trc<U>> for Trc<T> {}
 
-#[cfg(feature = "dyn_unstable")]
-impl<T: ?Sized> std::ops::Receiver for Trc<T> {}
+//`Receiver` is implemented for all `Deref` types, so `fn _(&self)` works without an impl here.
 //Because Trc is !DispatchFromDyn, fn _(self: Trc<Self>) cannot be implemented.
 
 #[cfg(feature = "dyn_unstable")]
-impl<T: ?Sized + std::marker::Unsize<U>, U: ?Sized> std::ops::CoerceUnsized<SharedTrc<U>>
+impl<T: ?Sized + core::marker::Unsize<U>, U: ?Sized> core::ops::CoerceUnsized<SharedTrc<U>>
     for SharedTrc<T>
 {
 }
 
-#[cfg(feature = "dyn_unstable")]
-impl<T: ?Sized> std::ops::Receiver for SharedTrc<T> {}
-
 #[cfg(feature = "dyn_unstable")]
 impl<T: ?Sized, U: ?Sized> ops::DispatchFromDyn<SharedTrc<U>> for SharedTrc<T> where
-    T: std::marker::Unsize<U>
+    T: core::marker::Unsize<U>
 {
 }
 //Because SharedTrc is !DispatchFromDyn, fn _(self: SharedTrc<Self>) cannot be implemented.
 
-impl<T: ?Sized> Drop for Weak<T> {
+#[cfg(feature = "dyn_unstable")]
+impl<T: ?Sized + core::marker::Unsize<U>, U: ?Sized> core::ops::CoerceUnsized<Weak<U>> for Weak<T> {}
+
+#[cfg(feature = "dyn_unstable")]
+impl<T: ?Sized, U: ?Sized> ops::DispatchFromDyn<Weak<U>> for Weak<T> where T: core::marker::Unsize<U>
+{}
+
+//The address passed to the coercion is never dereferenced: only the metadata of the result is used, and it is reattached
+//to the original allocation. This also covers dangling `Weak`s.
+#[cfg(feature = "unsize")]
+unsafe impl<T, U: ?Sized> CoerciblePtr<U> for Trc<T> {
+    type Pointee = T;
+    type Output = Trc<U>;
+
+    /// Return the pointer which is coerced by [`CoerceUnsize::unsize`](unsize::CoerceUnsize::unsize).
+    ///
+    /// # Examples
+    /// ```
+    /// use std::fmt::Display;
+    /// use trc::Trc;
+    /// use unsize::{CoerceUnsize, Coercion};
+    ///
+    /// let trc: Trc<dyn Display> = Trc::new(100).unsize(Coercion!(to dyn Display));
+    /// assert_eq!(trc.to_string(), "100");
+    ///
+    /// let trc: Trc<[i32]> = Trc::new([1, 2, 3]).unsize(Coercion::to_slice());
+    /// assert_eq!(*trc, [1, 2, 3]);
+    /// ```
     #[inline]
-    fn drop(&mut self) {
-        if sub_value(unsafe { &(*self.data.as_ptr()).weakcount }, 1, Release) != 1 {
-            return;
+    fn as_sized_ptr(&mut self) -> *mut T {
+        return self.shared.as_ptr().cast();
+    }
+
+    #[inline]
+    unsafe fn replace_ptr(self, new: *mut U) -> Trc<U> {
+        let this = ManuallyDrop::new(self);
+        let shared = set_data_ptr(new as *mut SharedTrcInternal<U>, this.shared.as_ptr());
+        return Trc {
+            shared: NonNull::new_unchecked(shared),
+            threadref: this.threadref,
+            phantom: PhantomData,
+        };
+    }
+}
+
+#[cfg(feature = "unsize")]
+unsafe impl<T, U: ?Sized> CoerciblePtr<U> for SharedTrc<T> {
+    type Pointee = T;
+    type Output = SharedTrc<U>;
+
+    /// Return the pointer which is coerced by [`CoerceUnsize::unsize`](unsize::CoerceUnsize::unsize).
+    ///
+    /// # Examples
+    /// ```
+    /// use std::any::Any;
+    /// use trc::SharedTrc;
+    /// use unsize::{CoerceUnsize, Coercion};
+    ///
+    /// let shared: SharedTrc<dyn Any + Send + Sync> =
+    ///     SharedTrc::new(100).unsize(Coercion!(to dyn Any + Send + Sync));
+    /// assert_eq!(*shared.downcast::<i32>().unwrap(), 100);
+    /// ```
+    #[inline]
+    fn as_sized_ptr(&mut self) -> *mut T {
+        return self.data.as_ptr().cast();
+    }
+
+    #[inline]
+    unsafe fn replace_ptr(self, new: *mut U) -> SharedTrc<U> {
+        let this = ManuallyDrop::new(self);
+        let data = set_data_ptr(new as *mut SharedTrcInternal<U>, this.data.as_ptr());
+        return SharedTrc {
+            data: NonNull::new_unchecked(data),
+            phantom: PhantomData,
+        };
+    }
+}
+
+#[cfg(feature = "unsize")]
+unsafe impl<T, U: ?Sized> CoerciblePtr<U> for Weak<T> {
+    type Pointee = T;
+    type Output = Weak<U>;
+
+    /// Return the pointer which is coerced by [`CoerceUnsize::unsize`](unsize::CoerceUnsize::unsize).
+    ///
+    /// # Examples
+    /// ```
+    /// use std::fmt::Debug;
+    /// use trc::{Trc, Weak};
+    /// use unsize::{CoerceUnsize, Coercion};
+    ///
+    /// let trc = Trc::new(100);
+    /// let weak: Weak<dyn Debug> = Trc::downgrade(&trc).unsize(Coercion!(to dyn Debug));
+    /// assert_eq!(format!("{:?}", weak.upgrade().unwrap()), "100");
+    /// ```
+    #[inline]
+    fn as_sized_ptr(&mut self) -> *mut T {
+        return self.data.as_ptr().cast();
+    }
+
+    #[inline]
+    unsafe fn replace_ptr(self, new: *mut U) -> Weak<U> {
+        let this = ManuallyDrop::new(self);
+        let data = set_data_ptr(new as *mut SharedTrcInternal<U>, this.data.as_ptr());
+        return Weak {
+            data: NonNull::new_unchecked(data),
+        };
+    }
+}
+
+//Calling a `Trc<F>` already works through `Deref`, but these impls allow it to be passed where an `Fn` is expected.
+#[cfg(feature = "fn_traits")]
+impl<Args: core::marker::Tuple, F: ?Sized + Fn<Args>> FnOnce<Args> for Trc<F> {
+    type Output = F::Output;
+
+    #[inline]
+    extern "rust-call" fn call_once(self, args: Args) -> Self::Output {
+        return (*self).call(args);
+    }
+}
+
+#[cfg(feature = "fn_traits")]
+impl<Args: core::marker::Tuple, F: ?Sized + Fn<Args>> FnMut<Args> for Trc<F> {
+    #[inline]
+    extern "rust-call" fn call_mut(&mut self, args: Args) -> Self::Output {
+        return (**self).call(args);
+    }
+}
+
+#[cfg(feature = "fn_traits")]
+impl<Args: core::marker::Tuple, F: ?Sized + Fn<Args>> Fn<Args> for Trc<F> {
+    #[inline]
+    extern "rust-call" fn call(&self, args: Args) -> Self::Output {
+        return (**self).call(args);
+    }
+}
+
+#[cfg(feature = "fn_traits")]
+impl<Args: core::marker::Tuple, F: ?Sized + Fn<Args>> FnOnce<Args> for SharedTrc<F> {
+    type Output = F::Output;
+
+    #[inline]
+    extern "rust-call" fn call_once(self, args: Args) -> Self::Output {
+        return (*self).call(args);
+    }
+}
+
+#[cfg(feature = "fn_traits")]
+impl<Args: core::marker::Tuple, F: ?Sized + Fn<Args>> FnMut<Args> for SharedTrc<F> {
+    #[inline]
+    extern "rust-call" fn call_mut(&mut self, args: Args) -> Self::Output {
+        return (**self).call(args);
+    }
+}
+
+#[cfg(feature = "fn_traits")]
+impl<Args: core::marker::Tuple, F: ?Sized + Fn<Args>> Fn<Args> for SharedTrc<F> {
+    #[inline]
+    extern "rust-call" fn call(&self, args: Args) -> Self::Output {
+        return (**self).call(args);
+    }
+}
+
+#[cfg(feature = "immortals")]
+impl<T: ?Sized> Weak<T> {
+    /// Return whether the allocation is immortal, in which case [`Weak::upgrade`] always succeeds. See
+    /// [`Trc::is_immortal`].
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    /// use trc::Weak;
+    ///
+    /// let weak = Trc::downgrade(&Trc::new_immortal(100));
+    /// assert!(Weak::is_immortal(&weak));
+    /// assert_eq!(*weak.upgrade().unwrap(), 100);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn is_immortal(this: &Self) -> bool {
+        if is_dangling(this.data.as_ptr()) {
+            return false;
         }
+        return unsafe { this.data.as_ref() }.atomicref.load(Relaxed) == IMMORTAL;
+    }
+}
 
-        fence(Acquire);
+#[cfg(not(feature = "allocator_api"))]
+impl_drop! {
+    impl<T> Drop for Weak<T> {
+        #[inline]
+        fn drop(&mut self) {
+            if is_dangling(self.data.as_ptr()) {
+                return;
+            }
+            #[cfg(feature = "hooks")]
+            hooks::on_drop(self.data.as_ptr(), HandleKind::Weak);
+            let prev = unsafe { self.data.as_ref() }
+                .weakcount
+                .fetch_sub(1, AcqRel);
+            let weakcount = weak_count_value(prev);
+            #[cfg(feature = "std")]
+            wait::on_release(weakcount, self.data.as_ptr());
+            if weakcount != 1 {
+                return;
+            }
+            //The last `Weak` to a value in an arena releases it from its chunk.
+            #[cfg(feature = "arena")]
+            if prev & arena::ARENA_FLAG != 0 {
+                unsafe { arena::release(self.data) };
+                return;
+            }
 
-        let layout = Layout::for_value(unsafe { &*self.data.as_ptr() });
-        unsafe {
-            std::alloc::dealloc(self.data.as_ptr().cast(), layout);
+            #[cfg(feature = "leak-debug")]
+            leak_debug::release_all(self.data.as_ptr());
+            #[cfg(feature = "stats")]
+            stats::on_dealloc(mem::size_of_val(unsafe { &(*self.data.as_ptr()).data }));
+            let layout = Layout::for_value(unsafe { &*self.data.as_ptr() });
+            unsafe {
+                dealloc(self.data.as_ptr().cast(), layout);
+            }
         }
     }
 }
 
 impl<T: ?Sized> Weak<T> {
     /// Upgrade a `Weak` to a `Trc`. Because `Weak` does not own the value, it may have been dropped already. If it has, a `None` is returned.
-    /// If the value has not been dropped, then this function increments the atomic reference count of the object.
+    /// If the value has not been dropped, then this function increments the atomic reference count of the object,
+    /// unless this thread already holds an upgraded `Trc` to it, whose local count is shared instead (see
+    /// [`SharedTrc::to_trc`]).
     ///
     /// # Examples
     /// ```
@@ -2247,38 +6225,57 @@ impl<T: ?Sized> Weak<T> {
     #[inline]
     #[must_use]
     pub fn upgrade(&self) -> Option<Trc<T>> {
-        #[cfg(immortals)]
-        if value.load(Acquire) == usize::MAX {
-            //SAFETY: The data is guaranteed to not be dropped.
-            let tbx = Box::new(1);
-            Trc {
-                threadref: NonNull::from(Box::leak(tbx)),
-                shared: self.data,
-            }
-        }
+        SharedTrc::from_weak(self).map(SharedTrc::to_trc)
+    }
 
-        unsafe { self.data.as_ref() }
-            .atomicref
-            .fetch_update(Acquire, Relaxed, |n| {
-                // Any write of 0 we can observe leaves the field in permanently zero state.
-                if n == 0 {
-                    return None;
-                }
-                // See comments in `Trc::clone` for why we do this (for `mem::forget`).
-                assert!(
-                    n <= MAX_REFCOUNT,
-                    "Overflow of maximum atomic reference count."
-                );
-                Some(n + 1)
-            })
-            .ok()
-            .map(|_| {
-                let tbx = Box::new(1);
-                return Trc {
-                    threadref: NonNull::from(Box::leak(tbx)),
-                    shared: self.data,
-                };
-            })
+    /// Return whether the value has been dropped, so that [`Weak::upgrade`] will return `None`. This is also the case for a
+    /// dangling `Weak` created by [`Weak::new`]. Unlike attempting an upgrade, this only performs a single load.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{Trc, Weak};
+    ///
+    /// #[derive(Default)]
+    /// struct Child {
+    ///     parent: Weak<i32>,
+    /// }
+    ///
+    /// let mut child = Child::default();
+    /// assert!(Weak::is_dead(&child.parent));
+    ///
+    /// let parent = Trc::new(100);
+    /// child.parent = Trc::downgrade(&parent);
+    /// assert!(!Weak::is_dead(&child.parent));
+    ///
+    /// drop(parent);
+    /// assert!(Weak::is_dead(&child.parent));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn is_dead(this: &Self) -> bool {
+        if is_dangling(this.data.as_ptr()) {
+            return true;
+        }
+        return unsafe { this.data.as_ref() }.atomicref.load(Relaxed) == 0;
+    }
+
+    /// Upgrade a `Weak` to a [`SharedTrc`], which can be sent to other threads. Because `Weak` does not own the value, it may
+    /// have been dropped already. If it has, a `None` is returned. Unlike [`Weak::upgrade`], only the atomic reference
+    /// count is incremented and no local thread reference count is created. This is equivalent to [`SharedTrc::from_weak`].
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    ///
+    /// let trc = Trc::new(100i32);
+    /// let weak = Trc::downgrade(&trc);
+    /// let shared = weak.upgrade_shared().expect("Value was dropped");
+    /// assert_eq!(*shared, 100i32);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn upgrade_shared(&self) -> Option<SharedTrc<T>> {
+        SharedTrc::from_weak(self)
     }
 
     /// Gets the raw pointer to the most inner layer of `Weak`. The data is only valid (not dropped) if there are at least some atomic references.
@@ -2290,15 +6287,37 @@ impl<T: ?Sized> Weak<T> {
     ///
     /// let trc = Trc::new(100);
     /// let weak = Trc::downgrade(&trc);
-    /// println!("{}", Trc::as_ptr(&trc) as usize)
+    /// assert_eq!(Weak::as_ptr(&weak), Trc::as_ptr(&trc));
     /// ```
     #[inline]
     #[must_use]
     pub fn as_ptr(this: &Self) -> *const T {
         let sharedptr = NonNull::as_ptr(this.data);
+        if is_dangling(sharedptr) {
+            //A dangling `Weak` has no allocation to offset into.
+            return sharedptr as *const T;
+        }
         unsafe { addr_of_mut!((*sharedptr).data) }
     }
 
+    /// Return the address of the value, without exposing the provenance of the pointer. This is the address of
+    /// [`Weak::as_ptr`], for hashing, ordering or logging handles by identity.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{Trc, Weak};
+    ///
+    /// let trc = Trc::new(100);
+    /// let weak = Trc::downgrade(&trc);
+    /// assert_eq!(Weak::addr(&weak), Trc::addr(&trc));
+    /// assert_eq!(Weak::addr(&weak), Weak::as_ptr(&weak).addr());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn addr(this: &Self) -> usize {
+        return Self::as_ptr(this).addr();
+    }
+
     /// Converts a `Weak` into `*const T`, without freeing the allocation.
     /// To avoid a memory leak, be sure to call [`Weak::from_raw`] to reclaim the allocation.
     ///
@@ -2322,13 +6341,12 @@ impl<T: ?Sized> Weak<T> {
         forget(this);
         ptr
     }
-}
 
-impl<T> Weak<T> {
     /// Converts a `*const T` into `Weak`. The caller must uphold the below safety constraints.
     ///
     /// # Safety
-    /// - The given pointer must be a valid pointer to `T` that came from [`Weak::into_raw`].
+    /// - The given pointer must be a valid pointer to `T` that came from [`Weak::into_raw`]. The pointer may be to an unsized
+    ///   type such as `[T]`, `str` or `dyn Trait`, as long as it has the same type as the one returned by `into_raw`.
     /// - After `from_raw`, the pointer must not be accessed.
     ///
     /// # Examples
@@ -2354,59 +6372,89 @@ impl<T> Weak<T> {
     /// let raw_1 = Weak::into_raw(Trc::downgrade(&strong));
     /// let raw_2 = Weak::into_raw(Trc::downgrade(&strong));
     ///
-    /// assert_eq!(3, Trc::weak_count(&strong));
+    /// assert_eq!(2, Trc::weak_count(&strong));
     ///
     /// assert_eq!("hello", &*Weak::upgrade(unsafe { &Weak::from_raw(raw_1) }).unwrap());
-    /// assert_eq!(2, Trc::weak_count(&strong));
+    /// assert_eq!(1, Trc::weak_count(&strong));
     ///
     /// drop(strong);
     ///
     /// // Decrement the last weak count.
     /// assert!( Weak::upgrade(unsafe {& Weak::from_raw(raw_2) }).is_none());
     /// ```
+    /// Example 3:
+    /// ```
+    /// use std::fmt::Display;
+    /// use trc::Trc;
+    /// use trc::Weak;
+    ///
+    /// let trc: Trc<dyn Display> = Trc::from(Box::new(100) as Box<dyn Display>);
+    /// let ptr: *const dyn Display = Weak::into_raw(Trc::downgrade(&trc));
+    ///
+    /// let weak = unsafe { Weak::from_raw(ptr) };
+    /// assert_eq!(weak.upgrade().unwrap().to_string(), "100");
+    /// ```
     pub unsafe fn from_raw(ptr: *const T) -> Self {
-        let layout = Layout::new::<SharedTrcInternal<()>>();
-        let n = layout.size();
-
-        let data_ptr = ptr.cast::<u8>().sub(n) as *mut SharedTrcInternal<T>;
+        if is_dangling(ptr) {
+            //Keep the metadata of the dangling pointer.
+            return Self {
+                data: NonNull::new_unchecked(ptr as *mut SharedTrcInternal<T>),
+            };
+        }
 
         Self {
-            data: NonNull::new_unchecked(data_ptr),
+            data: NonNull::new_unchecked(internal_from_data_ptr(ptr)),
+        }
+    }
+}
+
+impl<T, const N: usize> Weak<[T; N]> {
+    /// Convert a `Weak<[T; N]>` into a `Weak<[T]>` without changing any counts. This performs the same unsizing
+    /// as the `dyn_unstable` coercion, but is available on stable Rust.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    /// use trc::Weak;
+    ///
+    /// let trc = Trc::new([1, 2, 3]);
+    /// let weak: Weak<[i32]> = Weak::unsize_array(Trc::downgrade(&trc));
+    /// assert_eq!(*weak.upgrade().unwrap(), [1, 2, 3]);
+    /// ```
+    #[must_use]
+    pub fn unsize_array(this: Self) -> Weak<[T]> {
+        let this = ManuallyDrop::new(this);
+        Weak {
+            data: unsize_array_ptr(this.data),
         }
     }
+}
 
-    /// Create a new, uninitialized `Weak`. Calling [`Weak::upgrade`] on this will always return `None.
+impl<T> Weak<T> {
+    /// Create a new dangling `Weak` without allocating any memory. Calling [`Weak::upgrade`] on this will always return `None`,
+    /// and its atomic and weak counts are both 0.
     ///
     /// # Examples
     /// ```
     /// use trc::Weak;
-    /// use core::mem::MaybeUninit;
     ///
-    /// let weak: Weak<MaybeUninit<i32>> = Weak::new();
+    /// let weak: Weak<i32> = Weak::new();
     ///
     /// assert!(Weak::upgrade(&weak).is_none());
     /// ```
+    #[inline]
     #[must_use]
-    pub fn new() -> Weak<MaybeUninit<T>> {
-        let data = MaybeUninit::<T>::uninit();
-
-        let shareddata = SharedTrcInternal {
-            atomicref: AtomicUsize::new(0),
-            weakcount: AtomicUsize::new(1),
-            data,
-        };
-
-        let sbx = Box::new(shareddata);
-
+    pub const fn new() -> Weak<T> {
         return Weak {
-            data: NonNull::from(Box::leak(sbx)),
+            data: unsafe { NonNull::new_unchecked(ptr::without_provenance_mut(DANGLING)) },
         };
     }
 
     /// Return the atomic reference count of the object. This is how many threads are using the data referenced by this `Weak`.
     ///
     /// # Examples
-    /// ```
+    #[cfg_attr(feature = "single-threaded", doc = "```ignore")]
+    #[cfg_attr(not(feature = "single-threaded"), doc = "```")]
     /// use std::thread;
     /// use trc::Trc;
     /// use trc::SharedTrc;
@@ -2427,33 +6475,62 @@ impl<T> Weak<T> {
     #[inline]
     #[must_use]
     pub fn atomic_count(this: &Self) -> usize {
+        if is_dangling(this.data.as_ptr()) {
+            return 0;
+        }
         return unsafe { this.data.as_ref() }.atomicref.load(Relaxed);
     }
 
-    /// Return the weak count of the object. This is how many weak counts - across all threads - are pointing to the allocation inside of the `Weak`.
-    /// It includes the implicit weak reference held by all `SharedTrc` or `Trc` to themselves.
+    /// Return the weak count of the object. This is how many `Weak`s - across all threads - are pointing to the allocation inside of the `Weak`.
+    /// As with `std::sync::Weak::weak_count`, this does not include the implicit weak reference held by all `SharedTrc` or
+    /// `Trc` to themselves, and is 0 once the value has been dropped.
     ///
     /// # Examples
     /// ```
     /// use trc::Trc;
-    /// use trc::SharedTrc;
     /// use trc::Weak;
     ///
     /// let trc = Trc::new(100i32);
     /// let weak = Trc::downgrade(&trc);
     /// let weak2 = Trc::downgrade(&trc);
-    /// let new_trc = Weak::upgrade(&weak).expect("Value was dropped");
-    /// drop(weak);
-    /// let shared: SharedTrc<_> = trc.into();
-    /// assert_eq!(SharedTrc::weak_count(&shared), 2);
+    /// assert_eq!(Weak::weak_count(&weak), 2);
+    ///
+    /// drop(trc);
+    /// assert_eq!(Weak::weak_count(&weak), 0);
     /// ```
     #[inline]
     #[must_use]
     pub fn weak_count(this: &Self) -> usize {
-        return unsafe { this.data.as_ref() }.weakcount.load(Relaxed);
+        if is_dangling(this.data.as_ptr()) {
+            return 0;
+        }
+        let inner = unsafe { this.data.as_ref() };
+        let weak = inner.weakcount.load(Acquire);
+        if inner.atomicref.load(Relaxed) == 0 {
+            return 0;
+        }
+        //The value was alive when the weak count was loaded, so it includes the implicit weak reference.
+        return weak_count_value(weak) - 1;
+    }
+}
+
+impl<T> Default for Weak<T> {
+    /// Create a new dangling `Weak`, as with [`Weak::new`].
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Weak;
+    ///
+    /// let weak: Weak<i32> = Default::default();
+    /// assert!(weak.upgrade().is_none());
+    /// ```
+    #[inline]
+    fn default() -> Self {
+        return Self::new();
     }
 }
 
+#[cfg(not(feature = "allocator_api"))]
 impl<T: ?Sized> Clone for Weak<T> {
     /// Clone a `Weak` (increment the weak count).
     ///
@@ -2465,19 +6542,347 @@ impl<T: ?Sized> Clone for Weak<T> {
     /// let trc = Trc::new(100);
     /// let weak1 = Trc::downgrade(&trc);
     /// let weak2 = weak1.clone();
-    /// assert_eq!(Trc::weak_count(&trc), 3);
+    /// assert_eq!(Trc::weak_count(&trc), 2);
     /// ```
     #[inline]
     fn clone(&self) -> Self {
-        let prev = sum_value(&unsafe { self.data.as_ref() }.weakcount, 1, Relaxed);
+        if is_dangling(self.data.as_ptr()) {
+            return Self { data: self.data };
+        }
+        let prev = unsafe { self.data.as_ref() }
+            .weakcount
+            .fetch_add(1, Relaxed);
 
         //If an absurd number of threads are created, and then they are aborted before this, UB can
         //occur if the refcount wraps around.
-        assert!(
-            prev <= MAX_REFCOUNT,
-            "Overflow of maximum weak reference count."
-        );
+        if weak_count_value(prev) > MAX_WEAKCOUNT {
+            overflow::atomic(
+                &unsafe { self.data.as_ref() }.weakcount,
+                "Overflow of maximum weak reference count.",
+            );
+        }
+        #[cfg(feature = "hooks")]
+        hooks::on_clone(self.data.as_ptr(), HandleKind::Weak);
 
         Self { data: self.data }
     }
 }
+
+/// A [`Weak`] to pinned data, which can only be upgraded to a `Pin<Trc<T>>`.
+///
+/// Upgrading a plain `Weak` produces an unpinned `Trc`, which would allow the data to be moved out with
+/// [`Trc::try_unwrap`] once the pinned handles are gone. A `PinnedWeak` is created by [`Trc::pin_cyclic`] or
+/// [`PinnedWeak::downgrade`] instead, so the pinning guarantee holds for every handle to the allocation.
+///
+/// # Examples
+/// ```
+/// use trc::{PinnedWeak, Trc};
+///
+/// let pinned = Trc::pin(100);
+/// let weak = PinnedWeak::downgrade(&pinned);
+/// assert_eq!(*weak.upgrade().unwrap(), 100);
+/// ```
+#[repr(transparent)]
+pub struct PinnedWeak<T: ?Sized> {
+    weak: Weak<T>,
+}
+
+impl<T: ?Sized> PinnedWeak<T> {
+    #[inline]
+    fn from_ref(weak: &Weak<T>) -> &Self {
+        //SAFETY: `PinnedWeak` is a transparent wrapper around `Weak`.
+        unsafe { &*(weak as *const Weak<T> as *const Self) }
+    }
+
+    /// Downgrade a pinned `Trc` to a `PinnedWeak`. This increments the weak count.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{PinnedWeak, Trc};
+    ///
+    /// let pinned = Trc::pin(100);
+    /// let weak = PinnedWeak::downgrade(&pinned);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn downgrade(trc: &Pin<Trc<T>>) -> Self {
+        //SAFETY: `Pin` is a transparent wrapper around the pointer, and the `Trc` is only used to create a `Weak`.
+        let trc = unsafe { &*(trc as *const Pin<Trc<T>> as *const Trc<T>) };
+        return Self {
+            weak: Trc::downgrade(trc),
+        };
+    }
+
+    /// Upgrade the `PinnedWeak` to a pinned `Trc`, returning [`None`] if the data has already been dropped.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{PinnedWeak, Trc};
+    ///
+    /// let pinned = Trc::pin(100);
+    /// let weak = PinnedWeak::downgrade(&pinned);
+    /// assert!(weak.upgrade().is_some());
+    /// drop(pinned);
+    /// assert!(weak.upgrade().is_none());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn upgrade(&self) -> Option<Pin<Trc<T>>> {
+        //SAFETY: The data was pinned when this `PinnedWeak` was created.
+        return self
+            .weak
+            .upgrade()
+            .map(|trc| unsafe { Pin::new_unchecked(trc) });
+    }
+}
+
+impl<T: ?Sized> Clone for PinnedWeak<T> {
+    /// Clone a `PinnedWeak` (increment the weak count).
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{PinnedWeak, Trc};
+    ///
+    /// let pinned = Trc::pin(100);
+    /// let weak1 = PinnedWeak::downgrade(&pinned);
+    /// let weak2 = weak1.clone();
+    /// ```
+    #[inline]
+    fn clone(&self) -> Self {
+        return Self {
+            weak: self.weak.clone(),
+        };
+    }
+}
+
+/// A non-atomic [`Weak`] which can only be used on the thread that created it.
+///
+/// Like `Trc` splits the strong count into a local and an atomic count, `LocalWeak` keeps a local weak count and holds
+/// a single atomic weak reference for all of its clones on this thread. Cloning and dropping a `LocalWeak` therefore
+/// only updates the local count, and the atomic weak count is only updated when the first `LocalWeak` is created and
+/// the last one is dropped. `LocalWeak` is `!Send` and `!Sync`; convert it to a [`Weak`] to send it to another thread.
+///
+/// # Examples
+/// ```
+/// use trc::{LocalWeak, Trc};
+///
+/// let trc = Trc::new(100);
+/// let weak = LocalWeak::downgrade(&trc);
+/// let weak2 = weak.clone();
+/// assert_eq!(Trc::weak_count(&trc), 1);
+/// assert_eq!(*weak2.upgrade().unwrap(), 100);
+/// ```
+pub struct LocalWeak<T: ?Sized> {
+    weak: ManuallyDrop<Weak<T>>,
+    localweak: NonNull<usize>,
+}
+
+impl<T: ?Sized> LocalWeak<T> {
+    /// Downgrade a `Trc` to a `LocalWeak`. This increments the weak count.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{LocalWeak, Trc};
+    ///
+    /// let trc = Trc::new(100);
+    /// let weak = LocalWeak::downgrade(&trc);
+    /// assert_eq!(Trc::weak_count(&trc), 1);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn downgrade(trc: &Trc<T>) -> Self {
+        return Self::from_weak(Trc::downgrade(trc));
+    }
+
+    /// Create a `LocalWeak` from a `Weak`, which becomes the atomic weak reference shared by all clones of the
+    /// `LocalWeak` on this thread.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{LocalWeak, Trc};
+    ///
+    /// let trc = Trc::new(100);
+    /// let weak = LocalWeak::from_weak(Trc::downgrade(&trc));
+    /// assert_eq!(*weak.upgrade().unwrap(), 100);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn from_weak(weak: Weak<T>) -> Self {
+        return Self {
+            weak: ManuallyDrop::new(weak),
+            localweak: new_threadref(),
+        };
+    }
+
+    /// Create a `Weak` to the same allocation, which can be sent to other threads. This increments the atomic weak
+    /// count.
+    ///
+    /// # Examples
+    #[cfg_attr(feature = "single-threaded", doc = "```ignore")]
+    #[cfg_attr(not(feature = "single-threaded"), doc = "```")]
+    /// use std::thread;
+    /// use trc::{LocalWeak, Trc};
+    ///
+    /// let trc = Trc::new(100);
+    /// let local = LocalWeak::downgrade(&trc);
+    /// let weak = LocalWeak::to_weak(&local);
+    /// thread::spawn(move || {
+    ///     drop(weak);
+    /// })
+    /// .join()
+    /// .unwrap();
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn to_weak(this: &Self) -> Weak<T> {
+        return Weak::clone(&this.weak);
+    }
+
+    /// Upgrade the `LocalWeak` to a `Trc`, returning [`None`] if the data has already been dropped.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{LocalWeak, Trc};
+    ///
+    /// let trc = Trc::new(100);
+    /// let weak = LocalWeak::downgrade(&trc);
+    /// assert!(weak.upgrade().is_some());
+    /// drop(trc);
+    /// assert!(weak.upgrade().is_none());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn upgrade(&self) -> Option<Trc<T>> {
+        return self.weak.upgrade();
+    }
+
+    /// Return the local weak count of the object, which is the number of `LocalWeak`s sharing this atomic weak
+    /// reference on this thread.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{LocalWeak, Trc};
+    ///
+    /// let trc = Trc::new(100);
+    /// let weak = LocalWeak::downgrade(&trc);
+    /// let weak2 = weak.clone();
+    /// assert_eq!(LocalWeak::local_count(&weak), 2);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn local_count(this: &Self) -> usize {
+        return unsafe { *this.localweak.as_ptr() };
+    }
+}
+
+impl<T: ?Sized> Clone for LocalWeak<T> {
+    /// Clone a `LocalWeak` (increment the local weak count).
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{LocalWeak, Trc};
+    ///
+    /// let trc = Trc::new(100);
+    /// let weak1 = LocalWeak::downgrade(&trc);
+    /// let weak2 = weak1.clone();
+    /// assert_eq!(LocalWeak::local_count(&weak1), 2);
+    /// ```
+    #[inline]
+    fn clone(&self) -> Self {
+        let localweak = unsafe { &mut *self.localweak.as_ptr() };
+        *localweak += 1;
+        if *localweak > MAX_REFCOUNT {
+            overflow::local(localweak, "Overflow of maximum local weak reference count.");
+        }
+
+        #[cfg(feature = "hooks")]
+        if !is_dangling(self.weak.data.as_ptr()) {
+            hooks::on_clone(self.weak.data.as_ptr(), HandleKind::Weak);
+        }
+
+        return Self {
+            weak: ManuallyDrop::new(Weak {
+                data: self.weak.data,
+            }),
+            localweak: self.localweak,
+        };
+    }
+}
+
+impl<T: ?Sized> Drop for LocalWeak<T> {
+    #[inline]
+    fn drop(&mut self) {
+        let localweak = unsafe { &mut *self.localweak.as_ptr() };
+        *localweak -= 1;
+        if *localweak == 0 {
+            unsafe { free_threadref(self.weak.data.as_ptr(), self.localweak) };
+            //SAFETY: This is the last `LocalWeak` using the atomic weak reference.
+            unsafe { ManuallyDrop::drop(&mut self.weak) };
+            return;
+        }
+
+        #[cfg(feature = "hooks")]
+        if !is_dangling(self.weak.data.as_ptr()) {
+            hooks::on_drop(self.weak.data.as_ptr(), HandleKind::Weak);
+        }
+    }
+}
+
+impl<T: ?Sized> From<Weak<T>> for LocalWeak<T> {
+    /// Create a `LocalWeak` from a `Weak`. See [`LocalWeak::from_weak`].
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{LocalWeak, Trc};
+    ///
+    /// let trc = Trc::new(100);
+    /// let weak: LocalWeak<_> = Trc::downgrade(&trc).into();
+    /// assert_eq!(*weak.upgrade().unwrap(), 100);
+    /// ```
+    #[inline]
+    fn from(value: Weak<T>) -> Self {
+        return Self::from_weak(value);
+    }
+}
+
+impl<T: ?Sized> From<LocalWeak<T>> for Weak<T> {
+    /// Convert a `LocalWeak` into a `Weak`. If it is the last `LocalWeak` on this thread, its atomic weak reference is
+    /// reused, otherwise the atomic weak count is incremented.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{LocalWeak, Trc, Weak};
+    ///
+    /// let trc = Trc::new(100);
+    /// let local = LocalWeak::downgrade(&trc);
+    /// let weak: Weak<_> = local.into();
+    /// assert_eq!(Trc::weak_count(&trc), 1);
+    /// ```
+    #[inline]
+    fn from(value: LocalWeak<T>) -> Self {
+        if LocalWeak::local_count(&value) != 1 {
+            return LocalWeak::to_weak(&value);
+        }
+
+        let mut value = ManuallyDrop::new(value);
+        unsafe { free_threadref(value.weak.data.as_ptr(), value.localweak) };
+        //SAFETY: `value` is the only `LocalWeak` using the atomic weak reference, and it is not dropped.
+        return unsafe { ManuallyDrop::take(&mut value.weak) };
+    }
+}
+
+impl<T: ?Sized> Debug for LocalWeak<T> {
+    /// Format a `LocalWeak` as `(Weak)`, without accessing the value.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{LocalWeak, Trc};
+    ///
+    /// let trc = Trc::new(100);
+    /// let weak = LocalWeak::downgrade(&trc);
+    /// assert_eq!(format!("{weak:?}"), "(Weak)");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(Weak)")
+    }
+}
@@ -0,0 +1,137 @@
+//! The local thread reference counts of each thread, kept in a per-thread table.
+//!
+//! Freed counts are kept on a free list and reused, so that creating a `Trc` does not need a heap allocation in the
+//! common case. The counts created by [`SharedTrc::to_trc`] (and so by [`Weak::upgrade`]) are also registered by the
+//! address of their allocation, so that moving the same value to a thread which already holds a `Trc` to it joins the
+//! existing local count instead of creating another one.
+//!
+//! [`SharedTrc::to_trc`]: crate::SharedTrc::to_trc
+//! [`Weak::upgrade`]: crate::Weak::upgrade
+
+use alloc::alloc::{dealloc, Layout};
+use core::{
+    cell::RefCell,
+    hash::{BuildHasherDefault, Hasher},
+    ptr::NonNull,
+};
+use std::collections::HashMap;
+
+/// The maximum number of freed local thread reference counts which are kept per thread for reuse.
+const FREE_LIST_SIZE: usize = 64;
+
+/// A hasher for allocation addresses, which are already unique and only need their low bits mixed.
+#[derive(Default)]
+struct AddressHasher(u64);
+
+impl Hasher for AddressHasher {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.write_u8(*byte);
+        }
+    }
+
+    #[inline]
+    fn write_u8(&mut self, value: u8) {
+        self.write_usize(value as usize);
+    }
+
+    #[inline]
+    fn write_usize(&mut self, value: usize) {
+        self.0 = (self.0 ^ value as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        return self.0 ^ (self.0 >> 32);
+    }
+}
+
+struct LocalCounts {
+    //Freed counts, which are reused for new `Trc`s.
+    free: Vec<NonNull<usize>>,
+    //The registered counts in use on this thread, by the address of their allocation.
+    groups: HashMap<usize, NonNull<usize>, BuildHasherDefault<AddressHasher>>,
+}
+
+impl Drop for LocalCounts {
+    fn drop(&mut self) {
+        //Registered counts are still used by their `Trc`s, which free them on their own.
+        for threadref in self.free.drain(..) {
+            unsafe { dealloc(threadref.as_ptr().cast(), Layout::new::<usize>()) };
+        }
+    }
+}
+
+std::thread_local! {
+    static LOCAL_COUNTS: RefCell<LocalCounts> = const {
+        RefCell::new(LocalCounts {
+            free: Vec::new(),
+            groups: HashMap::with_hasher(BuildHasherDefault::new()),
+        })
+    };
+}
+
+#[inline]
+fn key<T: ?Sized>(alloc: *const T) -> usize {
+    return alloc.addr();
+}
+
+/// Take a freed local count of this thread for reuse, if there is one.
+#[inline]
+pub(crate) fn pop_free() -> Option<NonNull<usize>> {
+    return LOCAL_COUNTS
+        .try_with(|counts| counts.borrow_mut().free.pop())
+        .ok()
+        .flatten();
+}
+
+/// Keep a freed local count for reuse by this thread, returning whether there was space for it.
+#[inline]
+pub(crate) fn push_free(threadref: NonNull<usize>) -> bool {
+    return LOCAL_COUNTS
+        .try_with(|counts| {
+            let free = &mut counts.borrow_mut().free;
+            if free.len() < FREE_LIST_SIZE {
+                free.push(threadref);
+                return true;
+            }
+            return false;
+        })
+        .unwrap_or(false);
+}
+
+/// Return the registered local count of `alloc` on this thread, if there is one.
+#[inline]
+pub(crate) fn find<T: ?Sized>(alloc: *const T) -> Option<NonNull<usize>> {
+    return LOCAL_COUNTS
+        .try_with(|counts| counts.borrow().groups.get(&key(alloc)).copied())
+        .ok()
+        .flatten();
+}
+
+/// Register `threadref` as the local count of `alloc` on this thread, unless another one is already registered.
+#[inline]
+pub(crate) fn register<T: ?Sized>(alloc: *const T, threadref: NonNull<usize>) {
+    let _ = LOCAL_COUNTS.try_with(|counts| {
+        counts
+            .borrow_mut()
+            .groups
+            .entry(key(alloc))
+            .or_insert(threadref);
+    });
+}
+
+/// Remove the registration of `threadref` for `alloc`, before the count is freed.
+#[inline]
+pub(crate) fn unregister<T: ?Sized>(alloc: *const T, threadref: NonNull<usize>) {
+    let _ = LOCAL_COUNTS.try_with(|counts| {
+        let groups = &mut counts.borrow_mut().groups;
+        if groups.is_empty() {
+            return;
+        }
+        if groups.get(&key(alloc)) == Some(&threadref) {
+            groups.remove(&key(alloc));
+        }
+    });
+}
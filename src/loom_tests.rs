@@ -0,0 +1,83 @@
+//! Model checks of the reference counts, run with `RUSTFLAGS="--cfg loom" cargo test --lib --release loom`.
+
+use loom::thread;
+
+use crate::{SharedTrc, Trc, Weak};
+
+#[test]
+fn loom_shared_clone_drop() {
+    loom::model(|| {
+        let trc = Trc::new(100);
+        let shared = SharedTrc::from_trc(&trc);
+        let handle = thread::spawn(move || {
+            let clone = shared.clone();
+            assert_eq!(*clone, 100);
+            drop(shared);
+            drop(clone);
+        });
+        assert_eq!(*trc, 100);
+        drop(trc);
+        handle.join().unwrap();
+    });
+}
+
+#[test]
+fn loom_last_drop_on_either_thread() {
+    loom::model(|| {
+        let trc = Trc::new(100);
+        let shared = SharedTrc::from_trc(&trc);
+        drop(trc);
+        let other = shared.clone();
+        let handle = thread::spawn(move || drop(other));
+        drop(shared);
+        handle.join().unwrap();
+    });
+}
+
+#[test]
+fn loom_upgrade_races_last_drop() {
+    loom::model(|| {
+        let trc = Trc::new(100);
+        let weak = Trc::downgrade(&trc);
+        let shared = SharedTrc::from_trc(&trc);
+        drop(trc);
+        let handle = thread::spawn(move || {
+            if let Some(upgraded) = weak.upgrade() {
+                assert_eq!(*upgraded, 100);
+            }
+        });
+        drop(shared);
+        handle.join().unwrap();
+    });
+}
+
+#[test]
+fn loom_get_mut_races_downgrade() {
+    loom::model(|| {
+        let mut trc = Trc::new(100);
+        let shared = SharedTrc::from_trc(&trc);
+        let handle = thread::spawn(move || {
+            let trc = SharedTrc::to_trc(shared);
+            let weak = Trc::downgrade(&trc);
+            drop(trc);
+            drop(weak);
+        });
+        if let Some(value) = Trc::get_mut(&mut trc) {
+            *value += 1;
+        }
+        handle.join().unwrap();
+        assert!(Trc::is_unique(&trc));
+        assert!(*trc == 100 || *trc == 101);
+    });
+}
+
+#[test]
+fn loom_weak_drop_races_last_drop() {
+    loom::model(|| {
+        let trc = Trc::new(100);
+        let weak: Weak<i32> = Trc::downgrade(&trc);
+        let handle = thread::spawn(move || drop(weak));
+        drop(trc);
+        handle.join().unwrap();
+    });
+}
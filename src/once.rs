@@ -0,0 +1,355 @@
+//! Lazily initialized shared values.
+//!
+//! [`OnceTrc`] is written at most once and then hands out handles to the value, and [`LazyTrc`] initializes itself on
+//! first access. Both can be used in `static`s, and both store the `SharedTrc` directly, so there is no extra allocation
+//! or indirection compared to a `SharedTrc` created eagerly.
+
+use core::{
+    cell::UnsafeCell,
+    fmt::{self, Debug},
+    ops::Deref,
+    panic::{RefUnwindSafe, UnwindSafe},
+};
+use std::sync::OnceLock;
+
+use crate::{SharedTrc, Trc};
+
+/// A cell holding a [`SharedTrc<T>`] which can be written only once. After it has been written, it hands out `SharedTrc`s
+/// and `Trc`s to the value.
+///
+/// # Examples
+#[cfg_attr(feature = "single-threaded", doc = "```ignore")]
+#[cfg_attr(not(feature = "single-threaded"), doc = "```")]
+/// use trc::OnceTrc;
+///
+/// static CONFIG: OnceTrc<String> = OnceTrc::new();
+///
+/// assert!(CONFIG.get().is_none());
+/// CONFIG.set(String::from("config")).unwrap();
+/// assert!(CONFIG.set(String::from("other")).is_err());
+///
+/// let trc = CONFIG.get_trc().unwrap();
+/// assert_eq!(*trc, "config");
+/// ```
+pub struct OnceTrc<T> {
+    inner: OnceLock<SharedTrc<T>>,
+}
+
+impl<T> OnceTrc<T> {
+    /// Create a new, empty `OnceTrc`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::OnceTrc;
+    ///
+    /// let once = OnceTrc::<i32>::new();
+    /// assert!(once.get().is_none());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self {
+        return Self {
+            inner: OnceLock::new(),
+        };
+    }
+
+    /// Return the `SharedTrc` in the cell, or [`None`] if it has not been written yet.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::OnceTrc;
+    ///
+    /// let once = OnceTrc::new();
+    /// once.set(100).unwrap();
+    /// assert_eq!(**once.get().unwrap(), 100);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn get(&self) -> Option<&SharedTrc<T>> {
+        return self.inner.get();
+    }
+
+    /// Return a new `Trc` pointing to the value in the cell, or [`None`] if it has not been written yet.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{OnceTrc, Trc};
+    ///
+    /// let once = OnceTrc::new();
+    /// once.set(100).unwrap();
+    /// let trc = once.get_trc().unwrap();
+    /// assert_eq!(Trc::atomic_count(&trc), 2);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn get_trc(&self) -> Option<Trc<T>> {
+        return self.get().map(|shared| SharedTrc::to_trc(shared.clone()));
+    }
+
+    /// Write `value` to the cell. If the cell has already been written, `value` is returned in the [`Err`].
+    ///
+    /// # Errors
+    /// Returns `value` if the cell has already been written.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::OnceTrc;
+    ///
+    /// let once = OnceTrc::new();
+    /// assert_eq!(once.set(1), Ok(()));
+    /// assert_eq!(once.set(2), Err(2));
+    /// ```
+    #[inline]
+    pub fn set(&self, value: T) -> Result<(), T> {
+        let mut value = Some(value);
+        self.inner
+            .get_or_init(|| SharedTrc::new(value.take().unwrap()));
+        return match value {
+            Some(value) => Err(value),
+            None => Ok(()),
+        };
+    }
+
+    /// Write an existing `SharedTrc` to the cell, so that the cell shares its allocation. If the cell has already been
+    /// written, `value` is returned in the [`Err`].
+    ///
+    /// # Errors
+    /// Returns `value` if the cell has already been written.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{OnceTrc, SharedTrc};
+    ///
+    /// let shared = SharedTrc::new(100);
+    /// let once = OnceTrc::new();
+    /// once.set_shared(shared.clone()).unwrap();
+    /// assert!(SharedTrc::ptr_eq(once.get().unwrap(), &shared));
+    /// ```
+    #[inline]
+    pub fn set_shared(&self, value: SharedTrc<T>) -> Result<(), SharedTrc<T>> {
+        return self.inner.set(value);
+    }
+
+    /// Return the `SharedTrc` in the cell, initializing it with `f` if it has not been written yet. If several threads
+    /// call this at once, only one of them runs `f` and the others wait for it.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::OnceTrc;
+    ///
+    /// let once = OnceTrc::new();
+    /// assert_eq!(**once.get_or_init(|| 1), 1);
+    /// assert_eq!(**once.get_or_init(|| 2), 1);
+    /// ```
+    #[inline]
+    pub fn get_or_init<F: FnOnce() -> T>(&self, f: F) -> &SharedTrc<T> {
+        return self.inner.get_or_init(|| SharedTrc::new(f()));
+    }
+
+    /// Consume the cell, returning the `SharedTrc` it holds, or [`None`] if it has not been written.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::OnceTrc;
+    ///
+    /// let once = OnceTrc::new();
+    /// once.set(100).unwrap();
+    /// assert_eq!(*once.into_inner().unwrap(), 100);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn into_inner(self) -> Option<SharedTrc<T>> {
+        return self.inner.into_inner();
+    }
+
+    /// Take the `SharedTrc` out of the cell, leaving it empty so that it can be written again.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::OnceTrc;
+    ///
+    /// let mut once = OnceTrc::new();
+    /// once.set(1).unwrap();
+    /// assert_eq!(*once.take().unwrap(), 1);
+    /// assert!(once.set(2).is_ok());
+    /// ```
+    #[inline]
+    pub fn take(&mut self) -> Option<SharedTrc<T>> {
+        return self.inner.take();
+    }
+}
+
+impl<T> Default for OnceTrc<T> {
+    /// Create a new, empty `OnceTrc`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::OnceTrc;
+    ///
+    /// let once = OnceTrc::<i32>::default();
+    /// assert!(once.get().is_none());
+    /// ```
+    #[inline]
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+impl<T> From<T> for OnceTrc<T> {
+    /// Create a new `OnceTrc` which has already been written with `value`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::OnceTrc;
+    ///
+    /// let once = OnceTrc::from(100);
+    /// assert_eq!(**once.get().unwrap(), 100);
+    /// ```
+    #[inline]
+    fn from(value: T) -> Self {
+        return Self {
+            inner: OnceLock::from(SharedTrc::new(value)),
+        };
+    }
+}
+
+impl<T: Debug> Debug for OnceTrc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut tuple = f.debug_tuple("OnceTrc");
+        match self.get() {
+            Some(value) => tuple.field(value),
+            None => tuple.field(&format_args!("<uninit>")),
+        };
+        tuple.finish()
+    }
+}
+
+/// A [`SharedTrc<T>`] which is initialized with `F` the first time it is accessed. It dereferences to the value, and
+/// [`LazyTrc::force`] gives access to the `SharedTrc` itself.
+///
+/// # Examples
+#[cfg_attr(feature = "single-threaded", doc = "```ignore")]
+#[cfg_attr(not(feature = "single-threaded"), doc = "```")]
+/// use std::collections::HashMap;
+/// use trc::{LazyTrc, Trc};
+///
+/// static TABLE: LazyTrc<HashMap<u32, &str>> = LazyTrc::new(|| HashMap::from([(1, "one"), (2, "two")]));
+///
+/// assert_eq!(TABLE[&1], "one");
+///
+/// let trc: Trc<HashMap<u32, &str>> = LazyTrc::trc(&TABLE);
+/// assert_eq!(trc[&2], "two");
+/// ```
+pub struct LazyTrc<T, F = fn() -> T> {
+    once: OnceTrc<T>,
+    init: UnsafeCell<Option<F>>,
+}
+
+//`init` is only accessed by the one thread which initializes `once`.
+if_thread_safe! {
+    unsafe impl<T: Send + Sync, F: Send> Sync for LazyTrc<T, F> {}
+}
+
+//A panicking initializer is never called again, so no broken state can be observed.
+impl<T: RefUnwindSafe + UnwindSafe, F: UnwindSafe> RefUnwindSafe for LazyTrc<T, F> {}
+
+impl<T, F: FnOnce() -> T> LazyTrc<T, F> {
+    /// Create a new `LazyTrc` which is initialized with `f`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::LazyTrc;
+    ///
+    /// let lazy = LazyTrc::new(|| 100);
+    /// assert_eq!(*lazy, 100);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn new(f: F) -> Self {
+        return Self {
+            once: OnceTrc::new(),
+            init: UnsafeCell::new(Some(f)),
+        };
+    }
+
+    /// Initialize the value if needed, and return the `SharedTrc` holding it.
+    ///
+    /// # Panics
+    /// Panics if the initializer panicked during an earlier access.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{LazyTrc, SharedTrc};
+    ///
+    /// let lazy = LazyTrc::new(|| 100);
+    /// let shared: SharedTrc<i32> = LazyTrc::force(&lazy).clone();
+    /// assert_eq!(SharedTrc::atomic_count(&shared), 2);
+    /// ```
+    #[inline]
+    pub fn force(this: &Self) -> &SharedTrc<T> {
+        return this.once.get_or_init(|| {
+            let init = unsafe { (*this.init.get()).take() };
+            init.expect("The initializer of a `LazyTrc` panicked during an earlier access.")()
+        });
+    }
+
+    /// Initialize the value if needed, and return a new `Trc` pointing to it.
+    ///
+    /// # Panics
+    /// Panics if the initializer panicked during an earlier access.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{LazyTrc, Trc};
+    ///
+    /// let lazy = LazyTrc::new(|| String::from("Trc"));
+    /// let trc = LazyTrc::trc(&lazy);
+    /// assert_eq!(*trc, "Trc");
+    /// assert_eq!(Trc::atomic_count(&trc), 2);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn trc(this: &Self) -> Trc<T> {
+        return SharedTrc::to_trc(Self::force(this).clone());
+    }
+}
+
+impl<T, F: FnOnce() -> T> Deref for LazyTrc<T, F> {
+    type Target = T;
+
+    /// Initialize the value if needed, and return a reference to it.
+    ///
+    /// # Panics
+    /// Panics if the initializer panicked during an earlier access.
+    #[inline]
+    fn deref(&self) -> &T {
+        return Self::force(self);
+    }
+}
+
+impl<T: Default> Default for LazyTrc<T> {
+    /// Create a new `LazyTrc` which is initialized with the default value of `T`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::LazyTrc;
+    ///
+    /// let lazy = LazyTrc::<Vec<i32>>::default();
+    /// assert!(lazy.is_empty());
+    /// ```
+    #[inline]
+    fn default() -> Self {
+        return Self::new(T::default);
+    }
+}
+
+impl<T: Debug, F> Debug for LazyTrc<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut tuple = f.debug_tuple("LazyTrc");
+        match self.once.get() {
+            Some(value) => tuple.field(value),
+            None => tuple.field(&format_args!("<uninit>")),
+        };
+        tuple.finish()
+    }
+}
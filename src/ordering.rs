@@ -0,0 +1,19 @@
+//! The memory orderings of the atomics in the crate. With the `seqcst` feature, every ordering is `SeqCst`, so that a
+//! suspected ordering bug can be told apart from a logic bug by whether it still happens.
+#![allow(non_upper_case_globals)]
+
+#[cfg(feature = "seqcst")]
+use core::sync::atomic::Ordering;
+
+pub(crate) use core::sync::atomic::Ordering::SeqCst;
+#[cfg(not(feature = "seqcst"))]
+pub(crate) use core::sync::atomic::Ordering::{AcqRel, Acquire, Relaxed, Release};
+
+#[cfg(feature = "seqcst")]
+pub(crate) const Relaxed: Ordering = Ordering::SeqCst;
+#[cfg(feature = "seqcst")]
+pub(crate) const Acquire: Ordering = Ordering::SeqCst;
+#[cfg(feature = "seqcst")]
+pub(crate) const Release: Ordering = Ordering::SeqCst;
+#[cfg(feature = "seqcst")]
+pub(crate) const AcqRel: Ordering = Ordering::SeqCst;
@@ -0,0 +1,91 @@
+//! The policy for reference counts which are incremented past `MAX_REFCOUNT`, which only happens if handles are leaked,
+//! for example with `mem::forget`.
+//!
+//! By default, the process is aborted like with `Arc`, because a panic may unwind after the count was incremented and
+//! leave it to wrap around. The `overflow-panic` feature panics instead, and the `overflow-saturate` feature (which takes
+//! precedence) sets the count to a value it can never leave, so the value is leaked instead.
+
+use crate::sync::AtomicUsize;
+#[cfg(feature = "overflow-saturate")]
+use crate::MAX_REFCOUNT;
+
+#[cfg(feature = "overflow-saturate")]
+use crate::ordering::Relaxed;
+
+/// The count of a saturated allocation. It is halfway between `MAX_REFCOUNT` and `usize::MAX`, so no number of handles
+/// which can exist at once brings it to zero or makes it wrap around, and it is far from the weak count lock.
+#[cfg(feature = "overflow-saturate")]
+const SATURATED: usize = MAX_REFCOUNT + (usize::MAX - MAX_REFCOUNT) / 2;
+
+/// Handle an atomic count which was incremented past `MAX_REFCOUNT`.
+#[cold]
+#[inline(never)]
+pub(crate) fn atomic(count: &AtomicUsize, message: &'static str) {
+    #[cfg(feature = "overflow-saturate")]
+    {
+        let _ = message;
+        count.store(SATURATED, Relaxed);
+    }
+    #[cfg(not(feature = "overflow-saturate"))]
+    {
+        let _ = count;
+        fail(message);
+    }
+}
+
+/// Handle a local count which was incremented past `MAX_REFCOUNT`.
+#[cold]
+#[inline(never)]
+pub(crate) fn local(count: &mut usize, message: &'static str) {
+    #[cfg(feature = "overflow-saturate")]
+    {
+        let _ = message;
+        *count = SATURATED;
+    }
+    #[cfg(not(feature = "overflow-saturate"))]
+    {
+        let _ = count;
+        fail(message);
+    }
+}
+
+/// Handle a count which is about to be incremented past `MAX_REFCOUNT`, returning the count to store instead.
+#[cold]
+#[inline(never)]
+pub(crate) fn before_increment(count: usize, message: &'static str) -> usize {
+    #[cfg(feature = "overflow-saturate")]
+    {
+        let _ = (count, message);
+        return SATURATED;
+    }
+    #[cfg(not(feature = "overflow-saturate"))]
+    {
+        let _ = count;
+        fail(message);
+    }
+}
+
+#[cfg(not(feature = "overflow-saturate"))]
+fn fail(message: &'static str) -> ! {
+    #[cfg(feature = "overflow-panic")]
+    panic!("{message}");
+
+    #[cfg(all(not(feature = "overflow-panic"), feature = "std"))]
+    {
+        let _ = message;
+        std::process::abort();
+    }
+
+    //Without `std`, a panic while panicking aborts.
+    #[cfg(all(not(feature = "overflow-panic"), not(feature = "std")))]
+    {
+        struct Abort;
+        impl Drop for Abort {
+            fn drop(&mut self) {
+                panic!("Aborting after a reference count overflow.");
+            }
+        }
+        let _abort = Abort;
+        panic!("{message}");
+    }
+}
@@ -0,0 +1,246 @@
+//! A pool which reuses the allocations of `Trc`s.
+
+use alloc::vec::Vec;
+use core::{
+    cell::{Cell, RefCell},
+    fmt::{self, Debug},
+    marker::PhantomData,
+    ptr::{addr_of_mut, write},
+};
+
+use crate::{
+    new_threadref,
+    ordering::{Acquire, Relaxed, Release},
+    Trc, Weak,
+};
+
+/// The number of allocations kept by [`TrcPool::new`].
+const DEFAULT_CAPACITY: usize = 64;
+
+/// A pool of `Trc<T>` allocations, for short-lived values which would otherwise be allocated and freed repeatedly.
+///
+/// The pool keeps a [`Weak`] to each allocation it hands out. When the last `Trc` or `SharedTrc` to a value is dropped,
+/// the value is dropped as usual, but the allocation is kept alive by the pool, and [`TrcPool::get_or`] reuses it for a
+/// new value instead of allocating. Values from the pool may be sent to and dropped on other threads. The allocations
+/// are freed when the pool is dropped, or when the last handle is dropped if the pool is gone.
+///
+/// # Examples
+/// ```
+/// use trc::{Trc, TrcPool};
+///
+/// let pool = TrcPool::new();
+///
+/// let first = pool.get_or(|| vec![1, 2, 3]);
+/// let ptr = Trc::as_ptr(&first);
+/// drop(first);
+///
+/// //The allocation of the first value is reused.
+/// let second = pool.get_or(Vec::new);
+/// assert_eq!(Trc::as_ptr(&second), ptr);
+/// ```
+pub struct TrcPool<T> {
+    slots: RefCell<Vec<Weak<T>>>,
+    //Where the next search for a free allocation starts, so that allocations in use are not scanned every time.
+    cursor: Cell<usize>,
+    capacity: usize,
+}
+
+impl<T> TrcPool<T> {
+    /// Create a new, empty pool which keeps up to 64 allocations.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::TrcPool;
+    ///
+    /// let pool = TrcPool::<i32>::new();
+    /// assert_eq!(pool.capacity(), 64);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self {
+        return Self::with_capacity(DEFAULT_CAPACITY);
+    }
+
+    /// Create a new, empty pool which keeps up to `capacity` allocations. Values created when the pool is full are
+    /// allocated and freed as usual.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::TrcPool;
+    ///
+    /// let pool = TrcPool::with_capacity(1);
+    /// let first = pool.get_or(|| 1);
+    /// let second = pool.get_or(|| 2);
+    /// assert_eq!(pool.len(), 1);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn with_capacity(capacity: usize) -> Self {
+        return Self {
+            slots: RefCell::new(Vec::new()),
+            cursor: Cell::new(0),
+            capacity,
+        };
+    }
+
+    /// Return a new `Trc` holding the value returned by `f`. If an allocation in the pool is no longer used, the value is
+    /// placed in it. Otherwise, a new allocation is made, and kept by the pool if it is not full.
+    ///
+    /// `f` is always called, and may use the pool itself.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{Trc, TrcPool};
+    ///
+    /// let pool = TrcPool::new();
+    /// let buffers: Vec<Trc<Vec<u8>>> = (0..4).map(|_| pool.get_or(|| Vec::with_capacity(16))).collect();
+    /// assert_eq!(pool.len(), 4);
+    /// assert_eq!(pool.available(), 0);
+    ///
+    /// drop(buffers);
+    /// assert_eq!(pool.available(), 4);
+    /// ```
+    pub fn get_or<F: FnOnce() -> T>(&self, f: F) -> Trc<T> {
+        let value = f();
+        let mut slots = self.slots.borrow_mut();
+
+        let len = slots.len();
+        for i in 0..len {
+            let index = (self.cursor.get() + i) % len;
+            let shared = slots[index].data;
+            if !Self::is_free(&slots[index]) {
+                continue;
+            }
+
+            self.cursor.set(index + 1);
+            //Only the pool's `Weak` is left, so nothing else can access the allocation.
+            unsafe {
+                write(addr_of_mut!((*shared.as_ptr()).data), value);
+                (*shared.as_ptr()).weakcount.store(2, Relaxed);
+                (*shared.as_ptr()).atomicref.store(1, Release);
+            }
+            return Trc {
+                shared,
+                threadref: new_threadref(),
+                phantom: PhantomData,
+            };
+        }
+
+        let trc = Trc::new(value);
+        if len < self.capacity {
+            slots.push(Trc::downgrade(&trc));
+        }
+        return trc;
+    }
+
+    /// Return the maximum number of allocations the pool keeps.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::TrcPool;
+    ///
+    /// let pool = TrcPool::<i32>::with_capacity(8);
+    /// assert_eq!(pool.capacity(), 8);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn capacity(&self) -> usize {
+        return self.capacity;
+    }
+
+    /// Return the number of allocations the pool keeps, including the ones which are in use.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::TrcPool;
+    ///
+    /// let pool = TrcPool::new();
+    /// let value = pool.get_or(|| 1);
+    /// assert_eq!(pool.len(), 1);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        return self.slots.borrow().len();
+    }
+
+    /// Return whether the pool keeps no allocations.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::TrcPool;
+    ///
+    /// let pool = TrcPool::<i32>::new();
+    /// assert!(pool.is_empty());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        return self.slots.borrow().is_empty();
+    }
+
+    /// Return the number of allocations in the pool which are not in use, and can be reused by [`TrcPool::get_or`].
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::TrcPool;
+    ///
+    /// let pool = TrcPool::new();
+    /// let value = pool.get_or(|| 1);
+    /// assert_eq!(pool.available(), 0);
+    /// drop(value);
+    /// assert_eq!(pool.available(), 1);
+    /// ```
+    #[must_use]
+    pub fn available(&self) -> usize {
+        return self
+            .slots
+            .borrow()
+            .iter()
+            .filter(|weak| Self::is_free(weak))
+            .count();
+    }
+
+    /// Free the allocations which are not in use.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::TrcPool;
+    ///
+    /// let pool = TrcPool::new();
+    /// let first = pool.get_or(|| 1);
+    /// drop(pool.get_or(|| 2));
+    /// pool.shrink();
+    /// assert_eq!(pool.len(), 1);
+    /// ```
+    pub fn shrink(&self) {
+        self.slots.borrow_mut().retain(|weak| !Self::is_free(weak));
+        self.cursor.set(0);
+    }
+
+    /// Return whether only the pool's `Weak` to an allocation is left. The value has been dropped before the implicit
+    /// `Weak` of the strong handles was released, so it can be overwritten.
+    #[inline]
+    fn is_free(weak: &Weak<T>) -> bool {
+        let inner = unsafe { weak.data.as_ref() };
+        return inner.atomicref.load(Relaxed) == 0 && inner.weakcount.load(Acquire) == 1;
+    }
+}
+
+impl<T> Default for TrcPool<T> {
+    /// Create a new, empty pool. See [`TrcPool::new`].
+    #[inline]
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+impl<T> Debug for TrcPool<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TrcPool")
+            .field("len", &self.len())
+            .field("available", &self.available())
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
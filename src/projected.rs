@@ -0,0 +1,608 @@
+//! Handles which keep a whole allocation alive but dereference to a part of it.
+//!
+//! [`Trc::map`] and [`SharedTrc::map`] turn a handle to a value into a handle to one of its fields, like the aliasing
+//! constructor of C++'s `shared_ptr`. The type of the value which owns the field is erased, so a `ProjectedTrc<str>` can
+//! point into any kind of struct.
+
+use core::{
+    fmt::{self, Debug, Display},
+    marker::PhantomData,
+    mem::{forget, ManuallyDrop},
+    ops::Deref,
+    ptr::NonNull,
+};
+
+use crate::{new_threadref, SharedTrc, Trc, Weak};
+
+/// The operations on the handle to the owning allocation, whose type is erased.
+struct OwnerVTable {
+    clone_trc: unsafe fn(NonNull<u8>, NonNull<usize>),
+    drop_trc: unsafe fn(NonNull<u8>, NonNull<usize>),
+    clone_shared: unsafe fn(NonNull<u8>),
+    drop_shared: unsafe fn(NonNull<u8>),
+    downgrade: unsafe fn(NonNull<u8>, NonNull<usize>),
+    clone_weak: unsafe fn(NonNull<u8>),
+    drop_weak: unsafe fn(NonNull<u8>),
+    //Take an atomic reference if the value has not been dropped.
+    upgrade: unsafe fn(NonNull<u8>) -> bool,
+}
+
+struct Owner<T>(PhantomData<T>);
+
+impl<T: 'static> Owner<T> {
+    const VTABLE: OwnerVTable = OwnerVTable {
+        clone_trc: Self::clone_trc,
+        drop_trc: Self::drop_trc,
+        clone_shared: Self::clone_shared,
+        drop_shared: Self::drop_shared,
+        downgrade: Self::downgrade,
+        clone_weak: Self::clone_weak,
+        drop_weak: Self::drop_weak,
+        upgrade: Self::upgrade,
+    };
+
+    unsafe fn clone_trc(shared: NonNull<u8>, threadref: NonNull<usize>) {
+        let trc = ManuallyDrop::new(Trc::<T> {
+            shared: shared.cast(),
+            threadref,
+            phantom: PhantomData,
+        });
+        forget(Trc::clone(&trc));
+    }
+
+    unsafe fn drop_trc(shared: NonNull<u8>, threadref: NonNull<usize>) {
+        drop(Trc::<T> {
+            shared: shared.cast(),
+            threadref,
+            phantom: PhantomData,
+        });
+    }
+
+    unsafe fn clone_shared(data: NonNull<u8>) {
+        let shared = ManuallyDrop::new(SharedTrc::<T> {
+            data: data.cast(),
+            phantom: PhantomData,
+        });
+        forget(SharedTrc::clone(&shared));
+    }
+
+    unsafe fn drop_shared(data: NonNull<u8>) {
+        drop(SharedTrc::<T> {
+            data: data.cast(),
+            phantom: PhantomData,
+        });
+    }
+
+    unsafe fn downgrade(shared: NonNull<u8>, threadref: NonNull<usize>) {
+        let trc = ManuallyDrop::new(Trc::<T> {
+            shared: shared.cast(),
+            threadref,
+            phantom: PhantomData,
+        });
+        forget(Trc::downgrade(&trc));
+    }
+
+    unsafe fn clone_weak(data: NonNull<u8>) {
+        let weak = ManuallyDrop::new(Weak::<T> { data: data.cast() });
+        forget(Weak::clone(&weak));
+    }
+
+    unsafe fn drop_weak(data: NonNull<u8>) {
+        drop(Weak::<T> { data: data.cast() });
+    }
+
+    unsafe fn upgrade(data: NonNull<u8>) -> bool {
+        let weak = ManuallyDrop::new(Weak::<T> { data: data.cast() });
+        return match weak.upgrade_shared() {
+            Some(shared) => {
+                forget(shared);
+                true
+            }
+            None => false,
+        };
+    }
+}
+
+/// A [`Trc`] which dereferences to a part of the value it points to, such as a field. It is created with [`Trc::map`].
+///
+/// The whole value is kept alive while any `ProjectedTrc` to a part of it exists. Cloning a `ProjectedTrc` increments the
+/// local count of the `Trc` it was created from, and it cannot be sent to other threads.
+///
+/// # Examples
+/// ```
+/// use trc::{ProjectedTrc, Trc};
+///
+/// struct User {
+///     id: u64,
+///     name: String,
+/// }
+///
+/// let user = Trc::new(User { id: 1, name: String::from("Ferris") });
+/// let name: ProjectedTrc<str> = Trc::map(user.clone(), |user| user.name.as_str());
+/// drop(user);
+///
+/// assert_eq!(&*name, "Ferris");
+/// ```
+pub struct ProjectedTrc<U: ?Sized> {
+    shared: NonNull<u8>,
+    threadref: NonNull<usize>,
+    vtable: &'static OwnerVTable,
+    value: NonNull<U>,
+}
+
+impl<U: ?Sized> ProjectedTrc<U> {
+    /// Create a `ProjectedTrc` to the part of the value of `trc` selected by `f`, or return `trc` if `f` returns [`None`].
+    #[inline]
+    pub(crate) fn try_new<T: 'static, F: FnOnce(&T) -> Option<&U>>(
+        trc: Trc<T>,
+        f: F,
+    ) -> Result<Self, Trc<T>> {
+        let value = match f(&trc) {
+            Some(value) => NonNull::from(value),
+            None => return Err(trc),
+        };
+        let trc = ManuallyDrop::new(trc);
+        return Ok(Self {
+            shared: trc.shared.cast(),
+            threadref: trc.threadref,
+            vtable: &Owner::<T>::VTABLE,
+            value,
+        });
+    }
+
+    /// Project this handle further, to the part of the value selected by `f`. The new handle keeps the same allocation
+    /// alive.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{ProjectedTrc, Trc};
+    ///
+    /// let trc = Trc::new((String::from("Trc"), 1));
+    /// let string = Trc::map(trc, |pair| &pair.0);
+    /// let first: ProjectedTrc<u8> = ProjectedTrc::map(string, |string| &string.as_bytes()[0]);
+    /// assert_eq!(*first, b'T');
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn map<V: ?Sized, F: FnOnce(&U) -> &V>(this: Self, f: F) -> ProjectedTrc<V> {
+        let value = NonNull::from(f(&this));
+        let this = ManuallyDrop::new(this);
+        return ProjectedTrc {
+            shared: this.shared,
+            threadref: this.threadref,
+            vtable: this.vtable,
+            value,
+        };
+    }
+
+    /// Project this handle further, to the part of the value selected by `f`. If `f` returns [`None`], this handle is
+    /// returned in the [`Err`].
+    ///
+    /// # Errors
+    /// Returns `this` if `f` returns [`None`].
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{ProjectedTrc, Trc};
+    ///
+    /// let trc = Trc::new(vec![1, 2, 3]);
+    /// let slice: ProjectedTrc<[i32]> = Trc::map(trc, |vec| vec.as_slice());
+    /// let slice = ProjectedTrc::try_map(slice, |slice| slice.get(5)).unwrap_err();
+    /// let last = ProjectedTrc::try_map(slice, |slice| slice.last()).unwrap();
+    /// assert_eq!(*last, 3);
+    /// ```
+    #[inline]
+    pub fn try_map<V: ?Sized, F: FnOnce(&U) -> Option<&V>>(
+        this: Self,
+        f: F,
+    ) -> Result<ProjectedTrc<V>, Self> {
+        let value = match f(&this) {
+            Some(value) => NonNull::from(value),
+            None => return Err(this),
+        };
+        let this = ManuallyDrop::new(this);
+        return Ok(ProjectedTrc {
+            shared: this.shared,
+            threadref: this.threadref,
+            vtable: this.vtable,
+            value,
+        });
+    }
+
+    /// Create a [`ProjectedWeak`] to the same part of the value, which does not keep the value alive.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{ProjectedTrc, Trc};
+    ///
+    /// let trc = Trc::new((String::from("Trc"), 1));
+    /// let string = Trc::map(trc, |pair| &pair.0);
+    /// let weak = ProjectedTrc::downgrade(&string);
+    /// assert_eq!(*weak.upgrade().unwrap(), "Trc");
+    ///
+    /// drop(string);
+    /// assert!(weak.upgrade().is_none());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn downgrade(this: &Self) -> ProjectedWeak<U> {
+        unsafe { (this.vtable.downgrade)(this.shared, this.threadref) };
+        return ProjectedWeak {
+            data: this.shared,
+            vtable: this.vtable,
+            value: this.value,
+        };
+    }
+
+    /// Return a raw pointer to the part of the value this handle dereferences to.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{ProjectedTrc, Trc};
+    ///
+    /// let trc = Trc::new((1, 2));
+    /// let second = Trc::map(trc.clone(), |pair| &pair.1);
+    /// assert_eq!(ProjectedTrc::as_ptr(&second), &trc.1 as *const i32);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn as_ptr(this: &Self) -> *const U {
+        return this.value.as_ptr();
+    }
+}
+
+impl<U: ?Sized> Clone for ProjectedTrc<U> {
+    /// Clone a `ProjectedTrc` (increment the local count of the `Trc` it was created from).
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{ProjectedTrc, Trc};
+    ///
+    /// let trc = Trc::new((1, 2));
+    /// let first = Trc::map(trc.clone(), |pair| &pair.0);
+    /// let first2 = first.clone();
+    /// assert_eq!(Trc::local_count(&trc), 3);
+    /// ```
+    #[inline]
+    fn clone(&self) -> Self {
+        unsafe { (self.vtable.clone_trc)(self.shared, self.threadref) };
+        return Self {
+            shared: self.shared,
+            threadref: self.threadref,
+            vtable: self.vtable,
+            value: self.value,
+        };
+    }
+}
+
+impl<U: ?Sized> Drop for ProjectedTrc<U> {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe { (self.vtable.drop_trc)(self.shared, self.threadref) };
+    }
+}
+
+impl<U: ?Sized> Deref for ProjectedTrc<U> {
+    type Target = U;
+
+    #[inline]
+    fn deref(&self) -> &U {
+        return unsafe { self.value.as_ref() };
+    }
+}
+
+impl<T: 'static> From<Trc<T>> for ProjectedTrc<T> {
+    /// Convert a `Trc` into a `ProjectedTrc` to the whole value.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{ProjectedTrc, Trc};
+    ///
+    /// let projected = ProjectedTrc::from(Trc::new(100));
+    /// assert_eq!(*projected, 100);
+    /// ```
+    #[inline]
+    fn from(value: Trc<T>) -> Self {
+        return Trc::map(value, |value| value);
+    }
+}
+
+impl<U: ?Sized + Debug> Debug for ProjectedTrc<U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<U: ?Sized + Display> Display for ProjectedTrc<U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&**self, f)
+    }
+}
+
+/// A [`SharedTrc`] which dereferences to a part of the value it points to, such as a field. It is created with
+/// [`SharedTrc::map`].
+///
+/// The whole value is kept alive while any `ProjectedSharedTrc` to a part of it exists. Cloning a `ProjectedSharedTrc`
+/// increments the atomic count of the allocation, and it can be sent to other threads if the part of the value is
+/// `Send + Sync`.
+///
+/// # Examples
+#[cfg_attr(feature = "single-threaded", doc = "```ignore")]
+#[cfg_attr(not(feature = "single-threaded"), doc = "```")]
+/// use std::thread;
+/// use trc::{ProjectedSharedTrc, SharedTrc};
+///
+/// struct Request {
+///     path: String,
+///     body: Vec<u8>,
+/// }
+///
+/// let request = SharedTrc::new(Request { path: String::from("/"), body: vec![1, 2, 3] });
+/// let body: ProjectedSharedTrc<[u8]> = SharedTrc::map(request, |request| request.body.as_slice());
+///
+/// let handle = thread::spawn(move || body.len());
+/// assert_eq!(handle.join().unwrap(), 3);
+/// ```
+pub struct ProjectedSharedTrc<U: ?Sized> {
+    data: NonNull<u8>,
+    vtable: &'static OwnerVTable,
+    value: NonNull<U>,
+}
+
+//The owning value is required to be `Send + Sync` when the handle is created.
+if_thread_safe! {
+    unsafe impl<U: ?Sized + Sync + Send> Send for ProjectedSharedTrc<U> {}
+    unsafe impl<U: ?Sized + Sync + Send> Sync for ProjectedSharedTrc<U> {}
+}
+
+impl<U: ?Sized> ProjectedSharedTrc<U> {
+    /// Create a `ProjectedSharedTrc` to the part of the value of `shared` selected by `f`, or return `shared` if `f`
+    /// returns [`None`].
+    #[inline]
+    pub(crate) fn try_new<T: Send + Sync + 'static, F: FnOnce(&T) -> Option<&U>>(
+        shared: SharedTrc<T>,
+        f: F,
+    ) -> Result<Self, SharedTrc<T>> {
+        let value = match f(&shared) {
+            Some(value) => NonNull::from(value),
+            None => return Err(shared),
+        };
+        let shared = ManuallyDrop::new(shared);
+        return Ok(Self {
+            data: shared.data.cast(),
+            vtable: &Owner::<T>::VTABLE,
+            value,
+        });
+    }
+
+    /// Project this handle further, to the part of the value selected by `f`. The new handle keeps the same allocation
+    /// alive.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{ProjectedSharedTrc, SharedTrc};
+    ///
+    /// let shared = SharedTrc::new((String::from("Trc"), 1));
+    /// let string = SharedTrc::map(shared, |pair| &pair.0);
+    /// let first: ProjectedSharedTrc<u8> = ProjectedSharedTrc::map(string, |string| &string.as_bytes()[0]);
+    /// assert_eq!(*first, b'T');
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn map<V: ?Sized, F: FnOnce(&U) -> &V>(this: Self, f: F) -> ProjectedSharedTrc<V> {
+        let value = NonNull::from(f(&this));
+        let this = ManuallyDrop::new(this);
+        return ProjectedSharedTrc {
+            data: this.data,
+            vtable: this.vtable,
+            value,
+        };
+    }
+
+    /// Project this handle further, to the part of the value selected by `f`. If `f` returns [`None`], this handle is
+    /// returned in the [`Err`].
+    ///
+    /// # Errors
+    /// Returns `this` if `f` returns [`None`].
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{ProjectedSharedTrc, SharedTrc};
+    ///
+    /// let shared = SharedTrc::new(vec![1, 2, 3]);
+    /// let slice: ProjectedSharedTrc<[i32]> = SharedTrc::map(shared, |vec| vec.as_slice());
+    /// let first = ProjectedSharedTrc::try_map(slice, |slice| slice.first()).unwrap();
+    /// assert_eq!(*first, 1);
+    /// ```
+    #[inline]
+    pub fn try_map<V: ?Sized, F: FnOnce(&U) -> Option<&V>>(
+        this: Self,
+        f: F,
+    ) -> Result<ProjectedSharedTrc<V>, Self> {
+        let value = match f(&this) {
+            Some(value) => NonNull::from(value),
+            None => return Err(this),
+        };
+        let this = ManuallyDrop::new(this);
+        return Ok(ProjectedSharedTrc {
+            data: this.data,
+            vtable: this.vtable,
+            value,
+        });
+    }
+
+    /// Return a raw pointer to the part of the value this handle dereferences to.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{ProjectedSharedTrc, SharedTrc};
+    ///
+    /// let shared = SharedTrc::new((1, 2));
+    /// let second = SharedTrc::map(shared.clone(), |pair| &pair.1);
+    /// assert_eq!(ProjectedSharedTrc::as_ptr(&second), &shared.1 as *const i32);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn as_ptr(this: &Self) -> *const U {
+        return this.value.as_ptr();
+    }
+}
+
+impl<U: ?Sized> Clone for ProjectedSharedTrc<U> {
+    /// Clone a `ProjectedSharedTrc` (increment the atomic count of the allocation).
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{ProjectedSharedTrc, SharedTrc};
+    ///
+    /// let shared = SharedTrc::new((1, 2));
+    /// let first = SharedTrc::map(shared.clone(), |pair| &pair.0);
+    /// let first2 = first.clone();
+    /// assert_eq!(SharedTrc::atomic_count(&shared), 3);
+    /// ```
+    #[inline]
+    fn clone(&self) -> Self {
+        unsafe { (self.vtable.clone_shared)(self.data) };
+        return Self {
+            data: self.data,
+            vtable: self.vtable,
+            value: self.value,
+        };
+    }
+}
+
+impl<U: ?Sized> Drop for ProjectedSharedTrc<U> {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe { (self.vtable.drop_shared)(self.data) };
+    }
+}
+
+impl<U: ?Sized> Deref for ProjectedSharedTrc<U> {
+    type Target = U;
+
+    #[inline]
+    fn deref(&self) -> &U {
+        return unsafe { self.value.as_ref() };
+    }
+}
+
+impl<T: Send + Sync + 'static> From<SharedTrc<T>> for ProjectedSharedTrc<T> {
+    /// Convert a `SharedTrc` into a `ProjectedSharedTrc` to the whole value.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{ProjectedSharedTrc, SharedTrc};
+    ///
+    /// let projected = ProjectedSharedTrc::from(SharedTrc::new(100));
+    /// assert_eq!(*projected, 100);
+    /// ```
+    #[inline]
+    fn from(value: SharedTrc<T>) -> Self {
+        return SharedTrc::map(value, |value| value);
+    }
+}
+
+impl<U: ?Sized + Debug> Debug for ProjectedSharedTrc<U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<U: ?Sized + Display> Display for ProjectedSharedTrc<U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&**self, f)
+    }
+}
+
+/// A non-owning handle to a part of a value, created from a [`ProjectedTrc`] with [`ProjectedTrc::downgrade`].
+///
+/// Like a [`Weak`], it keeps the allocation but not the value alive, and it must be upgraded to access the part of the
+/// value it points to.
+///
+/// # Examples
+/// ```
+/// use trc::{ProjectedTrc, ProjectedWeak, Trc};
+///
+/// struct Node {
+///     name: String,
+///     children: Vec<Trc<Node>>,
+/// }
+///
+/// let child = Trc::new(Node { name: String::from("child"), children: Vec::new() });
+/// let name = Trc::map(child, |node| node.name.as_str());
+/// let observer: ProjectedWeak<str> = ProjectedTrc::downgrade(&name);
+/// assert_eq!(&*observer.upgrade().unwrap(), "child");
+///
+/// drop(name);
+/// assert!(observer.upgrade().is_none());
+/// ```
+pub struct ProjectedWeak<U: ?Sized> {
+    data: NonNull<u8>,
+    vtable: &'static OwnerVTable,
+    value: NonNull<U>,
+}
+
+impl<U: ?Sized> ProjectedWeak<U> {
+    /// Upgrade the `ProjectedWeak` to a [`ProjectedTrc`]. If the value has been dropped, [`None`] is returned.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{ProjectedTrc, Trc};
+    ///
+    /// let trc = Trc::new((1, 2));
+    /// let second = Trc::map(trc, |pair| &pair.1);
+    /// let weak = ProjectedTrc::downgrade(&second);
+    /// assert_eq!(*weak.upgrade().unwrap(), 2);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn upgrade(&self) -> Option<ProjectedTrc<U>> {
+        if !unsafe { (self.vtable.upgrade)(self.data) } {
+            return None;
+        }
+        return Some(ProjectedTrc {
+            shared: self.data,
+            threadref: new_threadref(),
+            vtable: self.vtable,
+            value: self.value,
+        });
+    }
+}
+
+impl<U: ?Sized> Clone for ProjectedWeak<U> {
+    /// Clone a `ProjectedWeak` (increment the weak count of the allocation).
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{ProjectedTrc, Trc};
+    ///
+    /// let trc = Trc::new((1, 2));
+    /// let first = Trc::map(trc.clone(), |pair| &pair.0);
+    /// let weak = ProjectedTrc::downgrade(&first);
+    /// let weak2 = weak.clone();
+    /// assert_eq!(Trc::weak_count(&trc), 2);
+    /// ```
+    #[inline]
+    fn clone(&self) -> Self {
+        unsafe { (self.vtable.clone_weak)(self.data) };
+        return Self {
+            data: self.data,
+            vtable: self.vtable,
+            value: self.value,
+        };
+    }
+}
+
+impl<U: ?Sized> Drop for ProjectedWeak<U> {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe { (self.vtable.drop_weak)(self.data) };
+    }
+}
+
+impl<U: ?Sized> Debug for ProjectedWeak<U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(ProjectedWeak)")
+    }
+}
@@ -0,0 +1,494 @@
+//! A persistent rope whose chunks are shared `Trc<str>` segments.
+//!
+//! [`Rope`] stores text as a balanced binary tree of [`Trc`] nodes. Leaves refer to a byte range of a `Trc<str>`, so
+//! concatenating, slicing, inserting and removing only create `O(log n)` new nodes and never copy the text itself.
+//! Cloning a `Rope` only increments the local reference count of the root.
+
+use alloc::{string::String, vec::Vec};
+use core::{
+    fmt::{self, Debug, Display},
+    ops::{Bound, RangeBounds},
+};
+
+use crate::{SharedTrc, Trc};
+
+enum Node {
+    Leaf {
+        text: Trc<str>,
+        start: usize,
+        end: usize,
+    },
+    Branch {
+        left: Trc<Node>,
+        right: Trc<Node>,
+        len: usize,
+        height: usize,
+    },
+}
+
+impl Node {
+    #[inline]
+    fn len(&self) -> usize {
+        match self {
+            Node::Leaf { start, end, .. } => end - start,
+            Node::Branch { len, .. } => *len,
+        }
+    }
+
+    #[inline]
+    fn height(&self) -> usize {
+        match self {
+            Node::Leaf { .. } => 1,
+            Node::Branch { height, .. } => *height,
+        }
+    }
+}
+
+fn leaf(text: Trc<str>, start: usize, end: usize) -> Trc<Node> {
+    Trc::new(Node::Leaf { text, start, end })
+}
+
+fn branch(left: Trc<Node>, right: Trc<Node>) -> Trc<Node> {
+    Trc::new(Node::Branch {
+        len: left.len() + right.len(),
+        height: left.height().max(right.height()) + 1,
+        left,
+        right,
+    })
+}
+
+fn children(node: &Trc<Node>) -> (Trc<Node>, Trc<Node>) {
+    match &**node {
+        Node::Branch { left, right, .. } => (left.clone(), right.clone()),
+        Node::Leaf { .. } => unreachable!("A leaf has no children."),
+    }
+}
+
+/// Build a branch from two subtrees whose heights differ by at most 2, rotating to restore the AVL invariant.
+fn balance(left: Trc<Node>, right: Trc<Node>) -> Trc<Node> {
+    if left.height() > right.height() + 1 {
+        let (ll, lr) = children(&left);
+        if ll.height() >= lr.height() {
+            branch(ll, branch(lr, right))
+        } else {
+            let (lrl, lrr) = children(&lr);
+            branch(branch(ll, lrl), branch(lrr, right))
+        }
+    } else if right.height() > left.height() + 1 {
+        let (rl, rr) = children(&right);
+        if rr.height() >= rl.height() {
+            branch(branch(left, rl), rr)
+        } else {
+            let (rll, rlr) = children(&rl);
+            branch(branch(left, rll), branch(rlr, rr))
+        }
+    } else {
+        branch(left, right)
+    }
+}
+
+fn join(left: Trc<Node>, right: Trc<Node>) -> Trc<Node> {
+    if left.height() > right.height() + 1 {
+        let (ll, lr) = children(&left);
+        balance(ll, join(lr, right))
+    } else if right.height() > left.height() + 1 {
+        let (rl, rr) = children(&right);
+        balance(join(left, rl), rr)
+    } else {
+        branch(left, right)
+    }
+}
+
+fn join_opt(left: Option<Trc<Node>>, right: Option<Trc<Node>>) -> Option<Trc<Node>> {
+    match (left, right) {
+        (Some(left), Some(right)) => Some(join(left, right)),
+        (left, None) => left,
+        (None, right) => right,
+    }
+}
+
+fn split(node: &Trc<Node>, at: usize) -> (Option<Trc<Node>>, Option<Trc<Node>>) {
+    if at == 0 {
+        return (None, Some(node.clone()));
+    }
+    if at == node.len() {
+        return (Some(node.clone()), None);
+    }
+
+    match &**node {
+        Node::Leaf { text, start, end } => {
+            let mid = start + at;
+            assert!(
+                text.is_char_boundary(mid),
+                "Byte index {at} is not a char boundary."
+            );
+            (
+                Some(leaf(text.clone(), *start, mid)),
+                Some(leaf(text.clone(), mid, *end)),
+            )
+        }
+        Node::Branch { left, right, .. } => {
+            if at <= left.len() {
+                let (a, b) = split(left, at);
+                (a, join_opt(b, Some(right.clone())))
+            } else {
+                let (a, b) = split(right, at - left.len());
+                (join_opt(Some(left.clone()), a), b)
+            }
+        }
+    }
+}
+
+/// `Rope` is a persistent string built from shared [`Trc<str>`](Trc) chunks.
+///
+/// Every operation that produces a new `Rope` (such as [`Rope::concat`], [`Rope::slice`], [`Rope::insert`] or
+/// [`Rope::remove`]) shares all untouched chunks and nodes with the original, and runs in `O(log n)` time in the number of chunks.
+/// All indices are byte offsets, and as with [`str`], they must lie on `char` boundaries.
+///
+/// Because the nodes are held by `Trc`, a `Rope` is `!Send` and `!Sync`. Chunks can be sent between threads as
+/// [`SharedTrc<str>`](SharedTrc) and converted into a `Rope` on the receiving thread.
+///
+/// # Examples
+/// ```
+/// use trc::rope::Rope;
+///
+/// let hello = Rope::from("Hello");
+/// let world = Rope::from(" World");
+/// let rope = hello.concat(&world);
+/// assert_eq!(rope, "Hello World");
+///
+/// let rope = rope.insert(5, ",");
+/// assert_eq!(rope.slice(..6), "Hello,");
+/// assert_eq!(rope.remove(5..6), "Hello World");
+/// ```
+#[derive(Clone, Default)]
+pub struct Rope {
+    root: Option<Trc<Node>>,
+}
+
+impl Rope {
+    /// Creates a new, empty `Rope`. This does not allocate.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::rope::Rope;
+    ///
+    /// let rope = Rope::new();
+    /// assert!(rope.is_empty());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { root: None }
+    }
+
+    /// Return the length of the `Rope` in bytes.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::rope::Rope;
+    ///
+    /// let rope = Rope::from("Trc");
+    /// assert_eq!(rope.len(), 3);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.root.as_ref().map_or(0, |root| root.len())
+    }
+
+    /// Return `true` if the `Rope` has a length of zero bytes.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::rope::Rope;
+    ///
+    /// assert!(Rope::from("").is_empty());
+    /// assert!(!Rope::from("Trc").is_empty());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Create a new `Rope` containing the contents of `self` followed by the contents of `other`.
+    /// Both `Rope`s are left unchanged and share their chunks with the result.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::rope::Rope;
+    ///
+    /// let rope = Rope::from("Hello").concat(&Rope::from(" World"));
+    /// assert_eq!(rope, "Hello World");
+    /// ```
+    #[must_use]
+    pub fn concat(&self, other: &Rope) -> Rope {
+        Rope {
+            root: join_opt(self.root.clone(), other.root.clone()),
+        }
+    }
+
+    /// Append a string slice to the end of this `Rope`. The string is copied into a new chunk.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::rope::Rope;
+    ///
+    /// let mut rope = Rope::from("Hello");
+    /// rope.push_str(" World");
+    /// assert_eq!(rope, "Hello World");
+    /// ```
+    pub fn push_str(&mut self, string: &str) {
+        *self = self.concat(&Rope::from(string));
+    }
+
+    /// Create a new `Rope` containing the provided byte range of this `Rope`.
+    ///
+    /// # Panics
+    /// Panics if the range is out of bounds, or if either end does not lie on a `char` boundary.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::rope::Rope;
+    ///
+    /// let rope = Rope::from("Hello").concat(&Rope::from(" World"));
+    /// assert_eq!(rope.slice(3..8), "lo Wo");
+    /// assert_eq!(rope.slice(6..), "World");
+    /// ```
+    #[must_use]
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> Rope {
+        let (start, end) = self.bounds(range);
+        let Some(root) = &self.root else {
+            return Rope::new();
+        };
+
+        let (head, _) = split(root, end);
+        let root = head.and_then(|head| split(&head, start).1);
+        Rope { root }
+    }
+
+    /// Create a new `Rope` with `string` inserted at the byte offset `at`.
+    ///
+    /// # Panics
+    /// Panics if `at` is out of bounds or does not lie on a `char` boundary.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::rope::Rope;
+    ///
+    /// let rope = Rope::from("Hello World");
+    /// assert_eq!(rope.insert(5, ","), "Hello, World");
+    /// ```
+    #[must_use]
+    pub fn insert(&self, at: usize, string: &str) -> Rope {
+        self.insert_rope(at, &Rope::from(string))
+    }
+
+    /// Create a new `Rope` with the contents of `other` inserted at the byte offset `at`.
+    ///
+    /// # Panics
+    /// Panics if `at` is out of bounds or does not lie on a `char` boundary.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::rope::Rope;
+    ///
+    /// let rope = Rope::from("Hello World");
+    /// let other = Rope::from(" there");
+    /// assert_eq!(rope.insert_rope(5, &other), "Hello there World");
+    /// ```
+    #[must_use]
+    pub fn insert_rope(&self, at: usize, other: &Rope) -> Rope {
+        let (head, tail) = self.split_at(at);
+        head.concat(other).concat(&tail)
+    }
+
+    /// Create a new `Rope` with the provided byte range removed.
+    ///
+    /// # Panics
+    /// Panics if the range is out of bounds, or if either end does not lie on a `char` boundary.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::rope::Rope;
+    ///
+    /// let rope = Rope::from("Hello, World");
+    /// assert_eq!(rope.remove(5..6), "Hello World");
+    /// ```
+    #[must_use]
+    pub fn remove<R: RangeBounds<usize>>(&self, range: R) -> Rope {
+        let (start, end) = self.bounds(range);
+        let Some(root) = &self.root else {
+            return Rope::new();
+        };
+
+        let (head, _) = split(root, start);
+        let (_, tail) = split(root, end);
+        Rope {
+            root: join_opt(head, tail),
+        }
+    }
+
+    /// Split the `Rope` into two at the byte offset `at`. The first `Rope` contains the bytes `[0, at)`, and the second
+    /// contains the bytes `[at, len)`.
+    ///
+    /// # Panics
+    /// Panics if `at` is out of bounds or does not lie on a `char` boundary.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::rope::Rope;
+    ///
+    /// let (hello, world) = Rope::from("Hello World").split_at(5);
+    /// assert_eq!(hello, "Hello");
+    /// assert_eq!(world, " World");
+    /// ```
+    #[must_use]
+    pub fn split_at(&self, at: usize) -> (Rope, Rope) {
+        assert!(at <= self.len(), "Byte index {at} is out of bounds.");
+        match &self.root {
+            Some(root) => {
+                let (head, tail) = split(root, at);
+                (Rope { root: head }, Rope { root: tail })
+            }
+            None => (Rope::new(), Rope::new()),
+        }
+    }
+
+    /// Return an iterator over the chunks of this `Rope`, in order.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::rope::Rope;
+    ///
+    /// let rope = Rope::from("Hello").concat(&Rope::from(" World"));
+    /// assert_eq!(rope.chunks().collect::<Vec<_>>(), ["Hello", " World"]);
+    /// ```
+    #[must_use]
+    pub fn chunks(&self) -> Chunks<'_> {
+        Chunks {
+            stack: self.root.iter().map(|root| &**root).collect(),
+        }
+    }
+
+    fn bounds<R: RangeBounds<usize>>(&self, range: R) -> (usize, usize) {
+        let start = match range.start_bound() {
+            Bound::Included(&n) => n,
+            Bound::Excluded(&n) => n + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&n) => n + 1,
+            Bound::Excluded(&n) => n,
+            Bound::Unbounded => self.len(),
+        };
+        assert!(start <= end, "Range start {start} is after end {end}.");
+        assert!(end <= self.len(), "Byte index {end} is out of bounds.");
+        (start, end)
+    }
+}
+
+/// An iterator over the chunks of a [`Rope`], created by [`Rope::chunks`].
+pub struct Chunks<'a> {
+    stack: Vec<&'a Node>,
+}
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.stack.pop()? {
+                Node::Leaf { text, start, end } => return Some(&text[*start..*end]),
+                Node::Branch { left, right, .. } => {
+                    self.stack.push(right);
+                    self.stack.push(left);
+                }
+            }
+        }
+    }
+}
+
+impl From<&str> for Rope {
+    /// Create a `Rope` containing a copy of the string slice.
+    fn from(value: &str) -> Self {
+        Self::from(Trc::<str>::from(value))
+    }
+}
+
+impl From<String> for Rope {
+    /// Create a `Rope` containing a copy of the `String`.
+    fn from(value: String) -> Self {
+        Self::from(value.as_str())
+    }
+}
+
+impl From<Trc<str>> for Rope {
+    /// Create a `Rope` with a single chunk that shares the allocation of the `Trc<str>`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::Trc;
+    /// use trc::rope::Rope;
+    ///
+    /// let trc = Trc::<str>::from("Trc");
+    /// let rope = Rope::from(trc.clone());
+    /// assert_eq!(Trc::local_count(&trc), 2);
+    /// ```
+    fn from(value: Trc<str>) -> Self {
+        if value.is_empty() {
+            return Rope::new();
+        }
+        let len = value.len();
+        Self {
+            root: Some(leaf(value, 0, len)),
+        }
+    }
+}
+
+impl From<SharedTrc<str>> for Rope {
+    /// Create a `Rope` with a single chunk that shares the allocation of the `SharedTrc<str>`.
+    fn from(value: SharedTrc<str>) -> Self {
+        Self::from(SharedTrc::to_trc(value))
+    }
+}
+
+impl Display for Rope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for chunk in self.chunks() {
+            f.write_str(chunk)?;
+        }
+        Ok(())
+    }
+}
+
+impl Debug for Rope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&String::from_iter(self.chunks()), f)
+    }
+}
+
+impl PartialEq for Rope {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .chunks()
+                .flat_map(str::bytes)
+                .eq(other.chunks().flat_map(str::bytes))
+    }
+}
+
+impl Eq for Rope {}
+
+impl PartialEq<str> for Rope {
+    fn eq(&self, other: &str) -> bool {
+        self.len() == other.len() && self.chunks().flat_map(str::bytes).eq(other.bytes())
+    }
+}
+
+impl PartialEq<&str> for Rope {
+    fn eq(&self, other: &&str) -> bool {
+        *self == **other
+    }
+}
@@ -0,0 +1,38 @@
+//! Sharing `Trc`s with scoped threads.
+
+use std::thread::{Scope, ScopedJoinHandle};
+
+use crate::{SharedTrc, Trc};
+
+impl<T: ?Sized + Sync + Send> Trc<T> {
+    /// Spawn a thread in `scope` which calls `f` with a `Trc` of the same value, and return its join handle.
+    ///
+    /// The `Trc` is sent to the thread as a [`SharedTrc`] and converted back to a `Trc` there, so the new thread has its
+    /// own local count. The atomic count is released when the `Trc` is dropped, even if `f` panics.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::thread;
+    /// use trc::Trc;
+    ///
+    /// let trc = Trc::new(vec![1, 2, 3]);
+    /// thread::scope(|scope| {
+    ///     let handle = Trc::share_scoped(&trc, scope, |trc| trc.iter().sum::<i32>());
+    ///     assert_eq!(handle.join().unwrap(), 6);
+    /// });
+    /// assert_eq!(Trc::atomic_count(&trc), 1);
+    /// ```
+    pub fn share_scoped<'scope, 'env, F, R>(
+        this: &Self,
+        scope: &'scope Scope<'scope, 'env>,
+        f: F,
+    ) -> ScopedJoinHandle<'scope, R>
+    where
+        T: 'scope,
+        F: FnOnce(Self) -> R + Send + 'scope,
+        R: Send + 'scope,
+    {
+        let shared = SharedTrc::from_trc(this);
+        return scope.spawn(move || f(SharedTrc::to_trc(shared)));
+    }
+}
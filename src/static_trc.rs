@@ -0,0 +1,150 @@
+//! `Trc`s and `SharedTrc`s of values stored in `static`s, which need no allocation.
+
+use core::{
+    fmt::{self, Debug},
+    marker::PhantomData,
+    ops::Deref,
+    ptr::NonNull,
+};
+
+use crate::{
+    new_threadref, ordering::Relaxed, sum_value, sync::AtomicUsize, SharedTrc, SharedTrcInternal,
+    Trc, MAX_REFCOUNT,
+};
+
+/// The initial atomic and weak counts of a `StaticTrc`. They are far from both zero and the maximum count, so no number
+/// of handles which can exist at once makes them reach either, and the value is never dropped or deallocated.
+const STATIC_COUNT: usize = MAX_REFCOUNT / 4;
+
+/// The initial atomic count of a `StaticTrc`. With the `immortals` feature, it is immortal, so that its handles do not
+/// change it.
+#[cfg(feature = "immortals")]
+const STATIC_ATOMIC_COUNT: usize = crate::IMMORTAL;
+#[cfg(not(feature = "immortals"))]
+const STATIC_ATOMIC_COUNT: usize = STATIC_COUNT;
+
+/// A value stored together with reference counts, so that it can be placed in a `static` and shared with
+/// [`Trc::from_static`] and [`SharedTrc::from_static`] without a heap allocation, similar to `Bytes::from_static`.
+///
+/// The handles behave like other `Trc`s and `SharedTrc`s, except that the value is never dropped. Their counts start at
+/// a large value, so [`Trc::atomic_count`] does not reflect the number of handles, and the handles are never unique.
+/// With the `immortals` feature, the value is immortal, so cloning and dropping handles does not change the atomic
+/// count at all.
+///
+/// # Examples
+/// ```
+/// use trc::{SharedTrc, StaticTrc, Trc};
+///
+/// struct Config {
+///     retries: u32,
+/// }
+///
+/// static DEFAULT_CONFIG: StaticTrc<Config> = StaticTrc::new(Config { retries: 3 });
+///
+/// let config = Trc::from_static(&DEFAULT_CONFIG);
+/// let shared = SharedTrc::from_static(&DEFAULT_CONFIG);
+/// assert_eq!(config.retries, 3);
+/// assert!(Trc::ptr_eq(&config, &SharedTrc::to_trc(shared)));
+/// ```
+pub struct StaticTrc<T> {
+    internal: SharedTrcInternal<T>,
+}
+
+impl<T> StaticTrc<T> {
+    /// Create a new `StaticTrc` holding `value`. This is a `const fn`, so it can initialize a `static`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::StaticTrc;
+    ///
+    /// static NAME: StaticTrc<&str> = StaticTrc::new("trc");
+    /// assert_eq!(*NAME, "trc");
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn new(value: T) -> Self {
+        return Self {
+            internal: SharedTrcInternal {
+                atomicref: AtomicUsize::new(STATIC_ATOMIC_COUNT),
+                weakcount: AtomicUsize::new(STATIC_COUNT),
+                #[cfg(feature = "allocator_api")]
+                alloc: alloc::alloc::Global,
+                #[cfg(feature = "padded")]
+                padding: crate::CachePadding,
+                data: value,
+            },
+        };
+    }
+
+    /// Return the allocation of the value, holding a new atomic reference to it.
+    #[inline]
+    fn acquire(&'static self) -> NonNull<SharedTrcInternal<T>> {
+        sum_value(&self.internal.atomicref, 1, Relaxed);
+        return NonNull::from(&self.internal);
+    }
+}
+
+impl<T> Deref for StaticTrc<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        return &self.internal.data;
+    }
+}
+
+impl<T: Debug> Debug for StaticTrc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<T> Trc<T> {
+    /// Create a `Trc` of the value in a `static` [`StaticTrc`]. The value is not copied, and is never dropped.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{StaticTrc, Trc};
+    ///
+    /// static GREETING: StaticTrc<&str> = StaticTrc::new("Hello");
+    ///
+    /// let trc = Trc::from_static(&GREETING);
+    /// let trc2 = trc.clone();
+    /// assert_eq!(*trc2, "Hello");
+    /// assert!(Trc::get_mut(&mut Trc::from_static(&GREETING)).is_none());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn from_static(value: &'static StaticTrc<T>) -> Self {
+        return Self {
+            shared: value.acquire(),
+            threadref: new_threadref(),
+            phantom: PhantomData,
+        };
+    }
+}
+
+impl<T> SharedTrc<T> {
+    /// Create a `SharedTrc` of the value in a `static` [`StaticTrc`], without an allocation. The value is not copied,
+    /// and is never dropped.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::thread;
+    /// use trc::{SharedTrc, StaticTrc};
+    ///
+    /// static TABLE: StaticTrc<[u8; 4]> = StaticTrc::new([1, 2, 3, 4]);
+    ///
+    /// let shared = SharedTrc::from_static(&TABLE);
+    /// let handle = thread::spawn(move || shared.iter().sum::<u8>());
+    /// assert_eq!(handle.join().unwrap(), 10);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn from_static(value: &'static StaticTrc<T>) -> Self {
+        return Self {
+            data: value.acquire(),
+            phantom: PhantomData,
+        };
+    }
+}
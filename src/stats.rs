@@ -0,0 +1,72 @@
+//! Global statistics of the allocations of `Trc`s.
+
+use crate::{ordering::Relaxed, sync::StaticAtomicUsize as AtomicUsize};
+
+static LIVE_ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static PEAK_ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static TOTAL_ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static LIVE_BYTES: AtomicUsize = AtomicUsize::new(0);
+static PEAK_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+/// A snapshot of the allocations of `Trc`, `SharedTrc` and `Weak`, returned by [`stats`].
+///
+/// An allocation is live until the last `Trc`, `SharedTrc` and `Weak` to it is dropped, so values which are only
+/// referenced by `Weak`s still count towards `live_allocations` and `live_bytes`. The payload of an allocation is the
+/// size of its value, without the reference counts.
+///
+/// The counters are updated independently of each other, so a snapshot taken while other threads allocate may be
+/// slightly inconsistent.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TrcStats {
+    /// The number of live allocations.
+    pub live_allocations: usize,
+    /// The highest number of live allocations so far.
+    pub peak_allocations: usize,
+    /// The number of allocations made so far.
+    pub total_allocations: usize,
+    /// The total payload of the live allocations, in bytes.
+    pub live_bytes: usize,
+    /// The highest total payload of the live allocations so far, in bytes.
+    pub peak_bytes: usize,
+}
+
+/// Return the statistics of the allocations made by this crate. This is only available with the `stats` feature.
+///
+/// # Examples
+/// ```
+/// use trc::Trc;
+///
+/// let before = trc::stats();
+/// let trc = Trc::new([0u8; 64]);
+/// let after = trc::stats();
+///
+/// assert!(after.total_allocations > before.total_allocations);
+/// assert!(after.peak_bytes >= 64);
+/// ```
+#[must_use]
+pub fn stats() -> TrcStats {
+    return TrcStats {
+        live_allocations: LIVE_ALLOCATIONS.load(Relaxed),
+        peak_allocations: PEAK_ALLOCATIONS.load(Relaxed),
+        total_allocations: TOTAL_ALLOCATIONS.load(Relaxed),
+        live_bytes: LIVE_BYTES.load(Relaxed),
+        peak_bytes: PEAK_BYTES.load(Relaxed),
+    };
+}
+
+/// Record an allocation with a payload of `bytes`.
+#[inline]
+pub(crate) fn on_alloc(bytes: usize) {
+    let live = LIVE_ALLOCATIONS.fetch_add(1, Relaxed) + 1;
+    PEAK_ALLOCATIONS.fetch_max(live, Relaxed);
+    TOTAL_ALLOCATIONS.fetch_add(1, Relaxed);
+    let live_bytes = LIVE_BYTES.fetch_add(bytes, Relaxed) + bytes;
+    PEAK_BYTES.fetch_max(live_bytes, Relaxed);
+}
+
+/// Record the deallocation of an allocation with a payload of `bytes`.
+#[inline]
+pub(crate) fn on_dealloc(bytes: usize) {
+    LIVE_ALLOCATIONS.fetch_sub(1, Relaxed);
+    LIVE_BYTES.fetch_sub(bytes, Relaxed);
+}
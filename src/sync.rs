@@ -0,0 +1,163 @@
+//! The atomics, fences, spin hints and allocations of `Trc`s. When the crate is built with `RUSTFLAGS="--cfg loom"`, they are
+//! replaced by the ones of [`loom`](https://docs.rs/loom), so that the concurrency of the reference counts can be model
+//! checked, including leaks and use after free. With the `single-threaded` feature, they are replaced by plain cells,
+//! as every handle stays on the thread which created it.
+
+use core::ptr::NonNull;
+
+#[cfg(not(loom))]
+pub(crate) use alloc::alloc::{alloc, alloc_zeroed, dealloc};
+#[cfg(not(loom))]
+pub(crate) use core::hint::spin_loop;
+#[cfg(all(
+    not(loom),
+    not(feature = "single-threaded"),
+    not(feature = "portable-atomic")
+))]
+pub(crate) use core::sync::atomic::{fence, AtomicBool, AtomicPtr, AtomicUsize};
+#[cfg(loom)]
+pub(crate) use loom::{
+    alloc::{alloc, alloc_zeroed, dealloc},
+    hint::spin_loop,
+    sync::atomic::{fence, AtomicBool, AtomicPtr, AtomicUsize},
+};
+#[cfg(all(
+    not(loom),
+    not(feature = "single-threaded"),
+    feature = "portable-atomic"
+))]
+pub(crate) use portable_atomic::{fence, AtomicBool, AtomicPtr, AtomicUsize};
+#[cfg(all(not(loom), feature = "single-threaded"))]
+pub(crate) use single_threaded::{fence, AtomicBool, AtomicPtr, AtomicUsize};
+
+//Atomics in `static`s are never model checked, as the ones of loom cannot be created in a const context.
+#[cfg(not(feature = "portable-atomic"))]
+#[allow(unused_imports)]
+pub(crate) use core::sync::atomic::{
+    AtomicPtr as StaticAtomicPtr, AtomicUsize as StaticAtomicUsize,
+};
+#[cfg(feature = "portable-atomic")]
+#[allow(unused_imports)]
+pub(crate) use portable_atomic::{AtomicPtr as StaticAtomicPtr, AtomicUsize as StaticAtomicUsize};
+
+/// Move `value` into a new allocation, aborting if the allocation fails. This is `Box::leak(Box::new(value))`, except
+/// that the allocation is made with [`alloc`], so that it can be freed with [`dealloc`].
+#[inline]
+pub(crate) fn leak_box<T>(value: T) -> NonNull<T> {
+    #[cfg(not(loom))]
+    return NonNull::from(alloc::boxed::Box::leak(alloc::boxed::Box::new(value)));
+
+    #[cfg(loom)]
+    {
+        let layout = alloc::alloc::Layout::new::<T>();
+        let Some(ptr) = NonNull::new(unsafe { alloc(layout) }.cast::<T>()) else {
+            alloc::alloc::handle_alloc_error(layout);
+        };
+        unsafe { ptr.as_ptr().write(value) };
+        return ptr;
+    }
+}
+
+/// Non-atomic counterparts of the atomics of `core`, with the same methods. The orderings are ignored, as every access
+/// happens on one thread.
+#[cfg(all(not(loom), feature = "single-threaded"))]
+mod single_threaded {
+    use core::{cell::Cell, sync::atomic::Ordering};
+
+    pub(crate) type AtomicUsize = AtomicCell<usize>;
+    pub(crate) type AtomicBool = AtomicCell<bool>;
+    pub(crate) type AtomicPtr<T> = AtomicCell<*mut T>;
+
+    #[inline]
+    pub(crate) fn fence(_order: Ordering) {}
+
+    #[repr(transparent)]
+    pub(crate) struct AtomicCell<T: Copy>(Cell<T>);
+
+    //SAFETY: Without threads, no other thread can access the cell. This lets statics and the `Send` and `Sync` handles
+    //hold counts, as they do with atomics.
+    #[cfg(all(target_family = "wasm", not(target_feature = "atomics")))]
+    unsafe impl<T: Copy> Sync for AtomicCell<T> {}
+
+    impl<T: Copy + PartialEq> AtomicCell<T> {
+        #[inline]
+        pub(crate) const fn new(value: T) -> Self {
+            return Self(Cell::new(value));
+        }
+
+        #[inline]
+        pub(crate) fn load(&self, _order: Ordering) -> T {
+            return self.0.get();
+        }
+
+        #[inline]
+        pub(crate) fn store(&self, value: T, _order: Ordering) {
+            self.0.set(value);
+        }
+
+        #[inline]
+        pub(crate) fn swap(&self, value: T, _order: Ordering) -> T {
+            return self.0.replace(value);
+        }
+
+        #[inline]
+        pub(crate) fn compare_exchange(
+            &self,
+            current: T,
+            new: T,
+            _success: Ordering,
+            _failure: Ordering,
+        ) -> Result<T, T> {
+            let prev = self.0.get();
+            if prev != current {
+                return Err(prev);
+            }
+            self.0.set(new);
+            return Ok(prev);
+        }
+
+        #[inline]
+        pub(crate) fn compare_exchange_weak(
+            &self,
+            current: T,
+            new: T,
+            success: Ordering,
+            failure: Ordering,
+        ) -> Result<T, T> {
+            return self.compare_exchange(current, new, success, failure);
+        }
+
+        #[inline]
+        pub(crate) fn fetch_update(
+            &self,
+            _set_order: Ordering,
+            _fetch_order: Ordering,
+            mut f: impl FnMut(T) -> Option<T>,
+        ) -> Result<T, T> {
+            let prev = self.0.get();
+            match f(prev) {
+                Some(next) => {
+                    self.0.set(next);
+                    return Ok(prev);
+                }
+                None => return Err(prev),
+            }
+        }
+    }
+
+    impl AtomicCell<usize> {
+        #[inline]
+        pub(crate) fn fetch_add(&self, value: usize, _order: Ordering) -> usize {
+            let prev = self.0.get();
+            self.0.set(prev.wrapping_add(value));
+            return prev;
+        }
+
+        #[inline]
+        pub(crate) fn fetch_sub(&self, value: usize, _order: Ordering) -> usize {
+            let prev = self.0.get();
+            self.0.set(prev.wrapping_sub(value));
+            return prev;
+        }
+    }
+}
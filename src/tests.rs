@@ -45,6 +45,7 @@ fn test_refcount() {
     println!();
 }
 
+#[cfg(not(feature = "single-threaded"))]
 #[test]
 fn test_multithread1() {
     let data = Data {
@@ -71,6 +72,7 @@ fn test_multithread1() {
     println!("Thread0 Deref test! {}", thread_trc_main.string);
 }
 
+#[cfg(not(feature = "single-threaded"))]
 #[test]
 fn test_multithread2() {
     let trc = Trc::new(100);
@@ -97,6 +99,7 @@ fn test_weak() {
     println!("Deref test! {}", *new_trc);
 }
 
+#[cfg(not(feature = "single-threaded"))]
 #[test]
 fn test_multithread_weak() {
     let trc = Trc::new(100);
@@ -157,6 +160,7 @@ fn readme_single_trc() {
     assert_eq!(*trc, 200);
 }
 
+#[cfg(not(feature = "single-threaded"))]
 #[test]
 fn readme_multi_trc() {
     let trc = Trc::new(100);
@@ -182,6 +186,7 @@ fn readme_single_weak() {
     assert_eq!(*new_trc, 200);
 }
 
+#[cfg(not(feature = "single-threaded"))]
 #[test]
 fn readme_multi_weak() {
     let trc = Trc::new(100);
@@ -327,6 +332,32 @@ fn test_dispatchfromdyn_sharedtrc() {
     vehicle.drive();
 }
 
+#[cfg(feature = "dyn_unstable")]
+#[test]
+fn test_coerce_unsized_weak() {
+    trait Observer {
+        fn notify(&self) -> usize;
+    }
+
+    struct Counter(usize);
+
+    impl Observer for Counter {
+        fn notify(&self) -> usize {
+            self.0
+        }
+    }
+
+    let trc = Trc::new(Counter(5));
+    let weak: Weak<dyn Observer> = Trc::downgrade(&trc) as Weak<Counter>;
+    assert_eq!(weak.upgrade().unwrap().notify(), 5);
+
+    let dangling: Weak<dyn Observer> = Weak::<Counter>::new();
+    assert!(dangling.upgrade().is_none());
+
+    drop(trc);
+    assert!(weak.upgrade().is_none());
+}
+
 #[test]
 fn test_ex1() {
     let mut trc = Trc::new(100);
@@ -335,6 +366,7 @@ fn test_ex1() {
     assert_eq!(*trc, 200);
 }
 
+#[cfg(not(feature = "single-threaded"))]
 #[test]
 fn test_ex2() {
     use std::thread;
@@ -362,6 +394,7 @@ fn test_ex3() {
     assert_eq!(*new_trc, 200);
 }
 
+#[cfg(not(feature = "single-threaded"))]
 #[test]
 fn test_ex4() {
     use std::thread;
@@ -376,3 +409,2360 @@ fn test_ex4() {
     handle.join().unwrap();
     assert_eq!(*trc, 100);
 }
+
+#[test]
+fn test_from_utf8_lossy() {
+    let bytes = Trc::<[u8]>::from(&b"Hello \xF0\x90\x80World"[..]);
+    let other = bytes.clone();
+    let trc = Trc::<str>::from_utf8_lossy_owned(bytes);
+    assert_eq!(&*trc, "Hello \u{FFFD}World");
+    assert_eq!(Trc::local_count(&other), 1);
+
+    let bytes = Trc::<[u8]>::from(&b"Hello World"[..]);
+    let other = bytes.clone();
+    let trc = Trc::<str>::from_utf8_lossy_owned(bytes);
+    assert_eq!(&*trc, "Hello World");
+    assert_eq!(Trc::local_count(&trc), 2);
+    drop(other);
+}
+
+#[test]
+fn test_rope() {
+    use crate::rope::Rope;
+
+    let mut rope = Rope::new();
+    let mut string = String::new();
+    for i in 0..200 {
+        let piece = format!("{i},");
+        rope.push_str(&piece);
+        string.push_str(&piece);
+    }
+    assert_eq!(rope, string.as_str());
+
+    let rope = rope.insert(10, "é");
+    string.insert(10, 'é');
+    assert_eq!(rope.slice(5..40), &string[5..40]);
+    assert_eq!(
+        rope.remove(3..300),
+        format!("{}{}", &string[..3], &string[300..]).as_str()
+    );
+    assert_eq!(rope.to_string(), string);
+}
+
+#[test]
+fn test_new_cyclic_multi() {
+    struct Node {
+        next: Weak<Node>,
+    }
+
+    let ring: [Trc<Node>; 4] = Trc::new_cyclic_multi(|weaks| {
+        std::array::from_fn(|i| Node {
+            next: weaks[(i + 1) % 4].clone(),
+        })
+    });
+    for (i, node) in ring.iter().enumerate() {
+        let next = node.next.upgrade().unwrap();
+        assert!(Trc::ptr_eq(&next, &ring[(i + 1) % 4]));
+        assert_eq!(Trc::weak_count(node), 1);
+    }
+
+    let weak = ring[0].next.clone();
+    drop(ring);
+    assert!(weak.upgrade().is_none());
+}
+
+#[test]
+fn test_unsize_array() {
+    let trc = Trc::new([String::from("a"), String::from("b")]);
+    let weak = Weak::unsize_array(Trc::downgrade(&trc));
+    let slice = Trc::unsize_array(trc);
+    assert_eq!(slice.len(), 2);
+    let shared = SharedTrc::from_trc(&slice);
+    drop(slice);
+    assert_eq!(&shared[1], "b");
+    drop(shared);
+    assert!(weak.upgrade().is_none());
+}
+
+#[test]
+fn test_from_box() {
+    use std::fmt::Debug;
+
+    let trc = Trc::<[String]>::from(vec![String::from("a"), String::from("b")].into_boxed_slice());
+    assert_eq!(&*trc, ["a", "b"]);
+
+    let trc: Trc<dyn Debug> = Trc::from(Box::new(String::from("Trc")) as Box<dyn Debug>);
+    assert_eq!(format!("{:?}", &*trc), "\"Trc\"");
+    let weak = Trc::downgrade(&trc);
+    drop(trc);
+    assert!(weak.upgrade().is_none());
+
+    let trc = Trc::<()>::from(Box::new(()));
+    assert_eq!(*trc, ());
+}
+
+#[test]
+fn test_from_vec() {
+    let trc = Trc::<[String]>::from(vec![String::from("a"), String::from("b")]);
+    assert_eq!(&*trc, ["a", "b"]);
+
+    let empty = Trc::<[String]>::from(Vec::new());
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn test_from_exact_iter_panic() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    let counter = Trc::new(());
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        Trc::<[Trc<()>]>::from_exact_iter((0..10).map(|i| {
+            assert!(i < 5, "Iterator panicked.");
+            counter.clone()
+        }))
+    }));
+    assert!(result.is_err());
+    assert_eq!(Trc::local_count(&counter), 1);
+
+    struct Liar(usize);
+    impl Iterator for Liar {
+        type Item = Trc<()>;
+        fn next(&mut self) -> Option<Self::Item> {
+            self.0 -= 1;
+            (self.0 > 0).then(Trc::default)
+        }
+    }
+    impl ExactSizeIterator for Liar {
+        fn len(&self) -> usize {
+            10
+        }
+    }
+    assert!(catch_unwind(|| Trc::<[Trc<()>]>::from_exact_iter(Liar(5))).is_err());
+}
+
+#[test]
+fn test_new_with_large() {
+    const LEN: usize = 16 << 20;
+
+    let handle = thread::Builder::new()
+        .stack_size(64 << 10)
+        .spawn(|| {
+            let trc = unsafe {
+                Trc::<[u64; LEN / 8]>::new_with(|slot| {
+                    slot.as_mut_ptr().cast::<u64>().write_bytes(0, LEN / 8);
+                    (*slot.as_mut_ptr())[1] = 1;
+                })
+            };
+            assert_eq!(trc[1], 1);
+        })
+        .unwrap();
+    handle.join().unwrap();
+}
+
+#[test]
+fn test_new_slice_with() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    let trc = Trc::<[String]>::new_slice_with(3, |i| i.to_string());
+    assert_eq!(*trc, ["0", "1", "2"]);
+
+    let counter = Trc::new(());
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        Trc::<[Trc<()>]>::new_slice_with(10, |i| {
+            assert!(i < 5, "Generator panicked.");
+            counter.clone()
+        })
+    }));
+    assert!(result.is_err());
+    assert_eq!(Trc::local_count(&counter), 1);
+}
+
+#[test]
+fn test_filled() {
+    let counter = Trc::new(());
+    let filled = Trc::<[Trc<()>]>::filled(counter.clone(), 4);
+    assert_eq!(filled.len(), 4);
+    assert_eq!(Trc::local_count(&counter), 5);
+    drop(filled);
+    assert_eq!(Trc::local_count(&counter), 1);
+
+    assert!(Trc::<[i32]>::filled(1, 0).is_empty());
+}
+
+#[test]
+fn test_header_slice() {
+    use crate::HeaderSlice;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    let trc: Trc<HeaderSlice<String, [String]>> =
+        Trc::from_header_and_iter(String::from("header"), (0..3).map(|i| i.to_string()));
+    assert_eq!(trc.header, "header");
+    assert_eq!(trc.slice, ["0", "1", "2"]);
+
+    let empty: Trc<HeaderSlice<u8, [u64]>> = Trc::from_header_and_slice(1, &[]);
+    assert_eq!(empty.header, 1);
+    assert!(empty.slice.is_empty());
+
+    let counter = Trc::new(());
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        Trc::from_header_and_iter(
+            counter.clone(),
+            (0..10).map(|i| {
+                assert!(i < 5, "Iterator panicked.");
+                counter.clone()
+            }),
+        )
+    }));
+    assert!(result.is_err());
+    assert_eq!(Trc::local_count(&counter), 1);
+}
+
+#[test]
+fn test_try_into_box() {
+    let trc = Trc::new(String::from("value"));
+    let weak = Trc::downgrade(&trc);
+    let boxed = Trc::try_into_box(trc).unwrap();
+    assert_eq!(*boxed, "value");
+    assert!(weak.upgrade().is_none());
+
+    let trc = Trc::<str>::from("text");
+    let shared = SharedTrc::from_trc(&trc);
+    let trc = Trc::try_into_box(trc).err().unwrap();
+    drop(shared);
+    assert_eq!(&*Trc::try_into_box(trc).ok().unwrap(), "text");
+
+    let boxed = Trc::try_into_box(Trc::new(())).unwrap();
+    assert_eq!(*boxed, ());
+}
+
+#[test]
+fn test_pin_cyclic() {
+    use crate::PinnedWeak;
+    use std::marker::PhantomPinned;
+
+    struct Node {
+        this: PinnedWeak<Node>,
+        value: i32,
+        _pin: PhantomPinned,
+    }
+
+    let node = Trc::pin_cyclic(|weak| {
+        assert!(weak.upgrade().is_none());
+        Node {
+            this: weak.clone(),
+            value: 5,
+            _pin: PhantomPinned,
+        }
+    });
+    let weak = node.this.clone();
+    assert_eq!(weak.upgrade().unwrap().value, 5);
+    drop(node);
+    assert!(weak.upgrade().is_none());
+}
+
+#[test]
+fn test_write_uninit() {
+    let trc = Trc::<String>::new_uninit();
+    let weak = Trc::downgrade(&trc);
+    let trc = Trc::write(trc, String::from("value"));
+    assert_eq!(*trc, "value");
+    assert_eq!(Trc::atomic_count(&trc), 1);
+    assert!(weak.upgrade().is_some());
+
+    let trc = Trc::<i32>::new_uninit();
+    let trc2 = trc.clone();
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| Trc::write(trc, 1)));
+    assert!(result.is_err());
+    assert_eq!(Trc::local_count(&trc2), 1);
+}
+
+#[test]
+fn test_try_unwrap_into_vec() {
+    let trc = Trc::<[Trc<()>]>::filled(Trc::new(()), 2);
+    let trc2 = trc.clone();
+    let trc = Trc::try_unwrap_into_vec(trc).err().unwrap();
+    drop(trc2);
+
+    let vec = Trc::try_unwrap_into_vec(trc).ok().unwrap();
+    assert_eq!(vec.len(), 2);
+    assert_eq!(Trc::local_count(&vec[0]), 2);
+}
+
+#[test]
+fn test_default_unsized() {
+    use std::ffi::CStr;
+
+    #[derive(Default)]
+    struct Fields {
+        name: Trc<str>,
+        values: Trc<[String]>,
+        c_name: Trc<CStr>,
+    }
+
+    let fields = Fields::default();
+    assert_eq!(&*fields.name, "");
+    assert!(fields.values.is_empty());
+    assert_eq!(fields.c_name.to_bytes_with_nul(), b"\0");
+}
+
+#[cfg(not(feature = "single-threaded"))]
+#[test]
+fn test_shared_uninit() {
+    let handle = thread::spawn(|| {
+        let mut value = SharedTrc::<String>::new_uninit();
+        SharedTrc::get_mut(&mut value)
+            .unwrap()
+            .write(String::from("value"));
+        unsafe { value.assume_init() }
+    });
+    let value = handle.join().unwrap();
+    assert_eq!(*value, "value");
+
+    let mut values = SharedTrc::<[i32]>::new_uninit_slice(2);
+    let values2 = values.clone();
+    assert!(SharedTrc::get_mut(&mut values).is_none());
+    drop(values2);
+    for (i, value) in SharedTrc::get_mut(&mut values)
+        .unwrap()
+        .iter_mut()
+        .enumerate()
+    {
+        value.write(i as i32);
+    }
+    assert_eq!(*unsafe { values.assume_init() }, [0, 1]);
+}
+
+#[test]
+fn test_shared_slice_constructors() {
+    let counter = Trc::new(());
+    let shared = SharedTrc::<[Trc<()>]>::from(&[counter.clone(), counter.clone()][..]);
+    assert_eq!(shared.len(), 2);
+    assert_eq!(Trc::local_count(&counter), 3);
+    drop(shared);
+    assert_eq!(Trc::local_count(&counter), 1);
+
+    let shared: SharedTrc<[String]> = (0..3).map(|i| i.to_string()).collect();
+    assert_eq!(*shared, ["0", "1", "2"]);
+
+    let shared = SharedTrc::<[String]>::from(vec![String::from("a")]);
+    assert_eq!(*shared, ["a"]);
+}
+
+#[cfg(not(feature = "single-threaded"))]
+#[test]
+fn test_shared_from_box() {
+    let shared = SharedTrc::<[String]>::from(vec![String::from("a")].into_boxed_slice());
+    assert_eq!(*shared, ["a"]);
+
+    let shared = SharedTrc::from(Box::new(String::from("b")));
+    let handle = thread::spawn(move || SharedTrc::to_trc(shared).len());
+    assert_eq!(handle.join().unwrap(), 1);
+}
+
+#[test]
+fn test_is_unique() {
+    let trc = Trc::new(1);
+    assert!(Trc::is_unique(&trc));
+    let trc2 = trc.clone();
+    assert!(!Trc::is_unique(&trc));
+    drop(trc2);
+
+    let shared = SharedTrc::from_trc(&trc);
+    assert!(!Trc::is_unique(&trc));
+    assert!(!SharedTrc::is_unique(&shared));
+    drop(trc);
+    assert!(SharedTrc::is_unique(&shared));
+}
+
+#[test]
+fn test_shared_from_owned_trc() {
+    let trc = Trc::new(String::from("value"));
+    let trc2 = trc.clone();
+    let shared = SharedTrc::from(trc);
+    assert_eq!(SharedTrc::atomic_count(&shared), 2);
+    assert_eq!(Trc::local_count(&trc2), 1);
+
+    let shared2 = SharedTrc::from(trc2);
+    assert_eq!(SharedTrc::atomic_count(&shared2), 2);
+    drop(shared);
+    assert_eq!(SharedTrc::atomic_count(&shared2), 1);
+    assert_eq!(*shared2, "value");
+}
+
+#[test]
+fn test_threadref_reuse() {
+    let trc = Trc::new(1);
+    let threadref = trc.threadref;
+    let shared = SharedTrc::from_trc(&trc);
+    drop(trc);
+
+    let trc = SharedTrc::to_trc(shared);
+    assert_eq!(trc.threadref, threadref);
+    assert_eq!(Trc::local_count(&trc), 1);
+}
+
+#[test]
+fn test_clone_many() {
+    let shared = SharedTrc::new(String::from("value"));
+    let clones = SharedTrc::clone_many(&shared, 3);
+    assert_eq!(clones.len(), 3);
+    assert_eq!(SharedTrc::atomic_count(&shared), 4);
+    assert!(clones.iter().all(|clone| **clone == "value"));
+    drop(clones);
+    assert_eq!(SharedTrc::atomic_count(&shared), 1);
+
+    assert!(SharedTrc::clone_many(&shared, 0).is_empty());
+    assert_eq!(SharedTrc::atomic_count(&shared), 1);
+}
+
+#[test]
+fn test_shared_from_weak() {
+    let trc = Trc::new(100);
+    let weak = Trc::downgrade(&trc);
+    let shared = SharedTrc::from_weak(&weak).unwrap();
+    assert_eq!(SharedTrc::atomic_count(&shared), 2);
+    assert_eq!(Trc::local_count(&trc), 1);
+
+    drop(trc);
+    assert_eq!(*shared, 100);
+    drop(shared);
+    assert!(SharedTrc::from_weak(&weak).is_none());
+}
+
+#[test]
+fn test_weak_new_dangling() {
+    let weak = Weak::<String>::new();
+    assert!(weak.upgrade().is_none());
+    assert!(SharedTrc::from_weak(&weak).is_none());
+    assert_eq!(Weak::atomic_count(&weak), 0);
+    assert_eq!(Weak::weak_count(&weak), 0);
+
+    let weak2 = weak.clone();
+    let ptr = Weak::into_raw(weak2);
+    let weak2 = unsafe { Weak::from_raw(ptr) };
+    assert!(weak2.upgrade().is_none());
+
+    let weak: Weak<[String]> = Weak::unsize_array(Weak::<[String; 2]>::new());
+    assert!(weak.upgrade().is_none());
+}
+
+#[test]
+fn test_weak_raw_unsized() {
+    let trc = Trc::<str>::from("text");
+    let ptr = Weak::into_raw(Trc::downgrade(&trc));
+    let weak = unsafe { Weak::from_raw(ptr) };
+    assert_eq!(&*weak.upgrade().unwrap(), "text");
+
+    #[repr(align(64))]
+    struct Aligned(u8);
+
+    let trc = Trc::<[Aligned]>::from(vec![Aligned(1), Aligned(2)]);
+    let ptr = Weak::into_raw(Trc::downgrade(&trc));
+    let weak = unsafe { Weak::from_raw(ptr) };
+    assert!(Trc::ptr_eq(&weak.upgrade().unwrap(), &trc));
+    assert_eq!(Trc::weak_count(&trc), 1);
+    drop(weak);
+    assert_eq!(trc[1].0, 2);
+}
+
+#[cfg(not(feature = "single-threaded"))]
+#[test]
+fn test_weak_unsized_send() {
+    fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+    let trc = Trc::<[i32]>::from(vec![1, 2, 3]);
+    let weak = Trc::downgrade(&trc);
+    assert_send_sync(&weak);
+    let shared = SharedTrc::from_trc(&trc);
+    drop(trc);
+
+    let handle = thread::spawn(move || weak.upgrade().map(|trc| trc.len()));
+    assert_eq!(handle.join().unwrap(), Some(3));
+    drop(shared);
+}
+
+#[test]
+fn test_weak_count_std() {
+    let mut trc = Trc::new(1);
+    assert_eq!(Trc::weak_count(&trc), 0);
+    assert!(Trc::get_mut(&mut trc).is_some());
+
+    let weak = Trc::downgrade(&trc);
+    let shared = SharedTrc::from_trc(&trc);
+    assert_eq!(Trc::weak_count(&trc), 1);
+    assert_eq!(SharedTrc::weak_count(&shared), 1);
+    assert_eq!(Weak::weak_count(&weak), 1);
+
+    drop(trc);
+    drop(shared);
+    assert_eq!(Weak::weak_count(&weak), 0);
+}
+
+#[cfg(not(feature = "single-threaded"))]
+#[test]
+fn test_local_weak() {
+    use crate::LocalWeak;
+
+    let trc = Trc::new(String::from("data"));
+    let weak = LocalWeak::downgrade(&trc);
+    let weak2 = weak.clone();
+    assert_eq!(LocalWeak::local_count(&weak), 2);
+    assert_eq!(Trc::weak_count(&trc), 1);
+
+    let sent = LocalWeak::to_weak(&weak2);
+    assert_eq!(Trc::weak_count(&trc), 2);
+    thread::spawn(move || assert!(sent.upgrade().is_some()))
+        .join()
+        .unwrap();
+
+    drop(weak2);
+    let weak: Weak<String> = weak.into();
+    assert_eq!(Trc::weak_count(&trc), 1);
+    drop(trc);
+    assert!(LocalWeak::from(weak).upgrade().is_none());
+
+    let dangling = LocalWeak::from_weak(Weak::<String>::new());
+    assert!(dangling.clone().upgrade().is_none());
+}
+
+#[cfg(not(feature = "single-threaded"))]
+#[test]
+fn test_shared_unsized_send() {
+    let shared = SharedTrc::<str>::from(Trc::<str>::from("text"));
+    let handle = thread::spawn(move || SharedTrc::to_trc(shared).len());
+    assert_eq!(handle.join().unwrap(), 4);
+
+    let trc = Trc::<[i32]>::from(vec![1, 2, 3]);
+    let shared = SharedTrc::from_trc(&trc);
+    let handle = thread::spawn(move || shared.iter().sum::<i32>());
+    assert_eq!(handle.join().unwrap(), 6);
+}
+
+#[test]
+fn test_cross_type_cmp() {
+    let trc = Trc::new(5);
+    let shared = SharedTrc::from_trc(&trc);
+    assert_eq!(trc, shared);
+    assert_eq!(shared, trc);
+
+    let larger = SharedTrc::new(6);
+    assert!(trc < larger);
+    assert!(larger > trc);
+    assert_ne!(SharedTrc::to_trc(larger), shared);
+}
+
+#[test]
+fn test_unsized_eq() {
+    let string = Trc::<str>::from("abc");
+    assert_eq!(string, "abc");
+    assert_eq!(string, String::from("abc"));
+    assert_eq!("abc", string);
+    assert_eq!(string, Trc::<str>::from("abc"));
+    let larger = Trc::<str>::from("abd");
+    assert!(string < larger);
+
+    let slice = Trc::<[i32]>::from(vec![1, 2, 3]);
+    assert_eq!(slice, [1, 2, 3]);
+    assert_eq!(slice, vec![1, 2, 3]);
+    assert_eq!(slice, &[1, 2, 3][..]);
+    assert_ne!(slice, [1, 2]);
+}
+
+#[cfg(not(feature = "single-threaded"))]
+#[test]
+fn test_dyn_error() {
+    use std::error::Error;
+
+    fn assert_error<E: Error + ?Sized>(_: &E) {}
+
+    let err = Trc::from_error("Trc".parse::<i32>().unwrap_err());
+    assert_error(&err);
+    let shared = SharedTrc::from_trc(&err);
+    let handle = thread::spawn(move || shared.to_string());
+    assert_eq!(handle.join().unwrap(), err.to_string());
+
+    let err: Trc<dyn Error + Send + Sync> = Trc::from("message");
+    assert_eq!(err.to_string(), "message");
+    assert_eq!(format!("{err:?}"), "\"message\"");
+    let err: Trc<dyn Error> = Trc::from(String::from("message"));
+    assert!(err.source().is_none());
+}
+
+#[test]
+fn test_io_passthrough() {
+    use std::fs::{self, File};
+    use std::io::{Read, Seek, SeekFrom, Write};
+    use std::net::{TcpListener, TcpStream};
+
+    let path = std::env::temp_dir().join(format!("trc_io_{}", std::process::id()));
+    let mut file = Trc::new(
+        File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)
+            .unwrap(),
+    );
+    let mut shared = SharedTrc::from_trc(&file);
+    file.write_all(b"Trc").unwrap();
+    shared.seek(SeekFrom::Start(0)).unwrap();
+    let mut contents = String::new();
+    shared.read_to_string(&mut contents).unwrap();
+    assert_eq!(contents, "Trc");
+    drop((file, shared));
+    fs::remove_file(path).unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut client = Trc::new(TcpStream::connect(listener.local_addr().unwrap()).unwrap());
+    let mut server = SharedTrc::new(listener.accept().unwrap().0);
+    client.write_all(b"ping").unwrap();
+    let mut buf = [0; 4];
+    server.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"ping");
+}
+
+#[test]
+fn test_unwind_safe() {
+    use std::panic::{self, RefUnwindSafe, UnwindSafe};
+
+    fn assert_unwind_safe<T: UnwindSafe + RefUnwindSafe>(_: &T) {}
+
+    let trc = Trc::new(100);
+    let shared = SharedTrc::from_trc(&trc);
+    let weak = Trc::downgrade(&trc);
+    assert_unwind_safe(&trc);
+    assert_unwind_safe(&shared);
+    assert_unwind_safe(&weak);
+
+    let result = panic::catch_unwind(|| {
+        assert_eq!(*trc + *shared, 200);
+        assert!(weak.upgrade().is_some());
+        panic!("Unwinding with borrowed handles.");
+    });
+    assert!(result.is_err());
+    assert_eq!(*trc, 100);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde() {
+    use std::ffi::CStr;
+
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Config {
+        name: Trc<str>,
+        ports: SharedTrc<[u16]>,
+        value: Trc<i32>,
+        path: SharedTrc<CStr>,
+    }
+
+    let config = Config {
+        name: Trc::from("server"),
+        ports: SharedTrc::from(vec![80, 443]),
+        value: Trc::new(5),
+        path: SharedTrc::from(Box::<CStr>::from(c"/tmp")),
+    };
+    let json = serde_json::to_string(&config).unwrap();
+    assert_eq!(
+        json,
+        r#"{"name":"server","ports":[80,443],"value":5,"path":[47,116,109,112]}"#
+    );
+    assert_eq!(serde_json::from_str::<Config>(&json).unwrap(), config);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_identity() {
+    use std::cell::RefCell;
+
+    use serde::{Deserialize, Serialize};
+
+    use crate::identity;
+
+    #[derive(Serialize, Deserialize)]
+    struct Graph {
+        #[serde(with = "identity")]
+        a: Trc<Vec<i32>>,
+        #[serde(with = "identity")]
+        b: Trc<Vec<i32>>,
+        #[serde(with = "identity")]
+        c: Trc<Vec<i32>>,
+    }
+
+    let shared = Trc::new(vec![1, 2]);
+    let graph = Graph {
+        a: shared.clone(),
+        b: Trc::new(vec![3]),
+        c: shared,
+    };
+    let json = identity::scope(|| serde_json::to_string(&graph)).unwrap();
+    assert_eq!(json, r#"{"a":[0,[1,2]],"b":[1,[3]],"c":[0,null]}"#);
+
+    let graph: Graph = identity::scope(|| serde_json::from_str(&json)).unwrap();
+    assert!(Trc::ptr_eq(&graph.a, &graph.c));
+    assert!(!Trc::ptr_eq(&graph.a, &graph.b));
+    assert_eq!(Trc::local_count(&graph.a), 2);
+
+    let dangling = r#"{"a":[0,null],"b":[1,[3]],"c":[1,null]}"#;
+    assert!(identity::scope(|| serde_json::from_str::<Graph>(dangling)).is_err());
+
+    #[derive(Serialize)]
+    struct Cyclic {
+        #[serde(serialize_with = "serialize_next")]
+        next: RefCell<Option<Trc<Cyclic>>>,
+    }
+
+    fn serialize_next<S: serde::Serializer>(
+        next: &RefCell<Option<Trc<Cyclic>>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match &*next.borrow() {
+            Some(trc) => identity::serialize(trc, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    let cyclic = Trc::new(Cyclic {
+        next: RefCell::new(None),
+    });
+    *cyclic.next.borrow_mut() = Some(cyclic.clone());
+    let err = identity::scope(|| serde_json::to_string(&Wrapper(&cyclic))).unwrap_err();
+    assert!(err.to_string().contains("contains itself"));
+    cyclic.next.borrow_mut().take();
+
+    struct Wrapper<'a>(&'a Trc<Cyclic>);
+
+    impl Serialize for Wrapper<'_> {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            identity::serialize(self.0, serializer)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_direct() {
+    use serde::{Deserialize, Serialize};
+
+    use crate::as_bytes;
+
+    let trc: Trc<str> = serde_json::from_str(r#""borrowed""#).unwrap();
+    assert_eq!(trc, "borrowed");
+    let trc: SharedTrc<str> = serde_json::from_str(r#""esc\"aped""#).unwrap();
+    assert_eq!(&*trc, "esc\"aped");
+    let trc: Trc<str> = serde_json::from_value(serde_json::json!("owned")).unwrap();
+    assert_eq!(trc, "owned");
+
+    #[derive(Serialize, Deserialize)]
+    struct Packet {
+        #[serde(with = "as_bytes")]
+        local: Trc<[u8]>,
+        #[serde(with = "as_bytes")]
+        shared: SharedTrc<[u8]>,
+    }
+
+    let json = r#"{"local":"abc","shared":[1,2,3]}"#;
+    let packet: Packet = serde_json::from_str(json).unwrap();
+    assert_eq!(packet.local, *b"abc");
+    assert_eq!(*packet.shared, [1, 2, 3]);
+    assert_eq!(
+        serde_json::to_string(&packet).unwrap(),
+        r#"{"local":[97,98,99],"shared":[1,2,3]}"#
+    );
+    assert!(serde_json::from_str::<Packet>(r#"{"local":1,"shared":[]}"#).is_err());
+}
+
+#[cfg(feature = "rkyv")]
+#[test]
+fn test_rkyv() {
+    use rkyv::{rancor::Error, Archived};
+
+    type Model = (Trc<str>, SharedTrc<Vec<u32>>, Trc<Vec<u32>>);
+
+    let list = Trc::new(vec![1, 2, 3]);
+    let model: Model = (Trc::from("name"), SharedTrc::from_trc(&list), list.clone());
+    let bytes = rkyv::to_bytes::<Error>(&model).unwrap();
+
+    let archived = unsafe { rkyv::access_unchecked::<Archived<Model>>(&bytes) };
+    assert_eq!(archived.0, model.0);
+    assert_eq!(archived.1, model.1);
+    assert_eq!(archived.1.get().as_ptr(), archived.2.get().as_ptr());
+
+    let model: Model = rkyv::deserialize::<Model, Error>(archived).unwrap();
+    assert_eq!(&*model.0, "name");
+    assert_eq!(*model.2, [1, 2, 3]);
+    assert_eq!(SharedTrc::atomic_count(&model.1), 2);
+    assert!(Trc::ptr_eq(&SharedTrc::to_trc(model.1), &model.2));
+}
+
+#[cfg(feature = "arbitrary")]
+#[test]
+fn test_arbitrary() {
+    use arbitrary::{Arbitrary, Unstructured};
+
+    #[derive(Arbitrary, Debug)]
+    struct Node {
+        value: Trc<u32>,
+        name: SharedTrc<str>,
+        children: Trc<[u8]>,
+        parent: Weak<u32>,
+    }
+
+    let bytes: Vec<u8> = (0..=255).collect();
+    let mut u = Unstructured::new(&bytes);
+    for _ in 0..4 {
+        let node = Node::arbitrary(&mut u).unwrap();
+        assert!(node.parent.upgrade().is_none());
+        assert_eq!(Trc::local_count(&node.value), 1);
+        assert!(node.name.len() <= bytes.len());
+        assert!(node.children.len() <= bytes.len());
+    }
+}
+
+#[cfg(feature = "proptest")]
+mod proptest_tests {
+    use proptest::prelude::*;
+
+    use crate::{SharedTrc, Trc, Weak};
+
+    proptest! {
+        #[test]
+        fn test_proptest(value: Trc<u32>, shared: SharedTrc<String>, weak: Weak<u32>) {
+            prop_assert_eq!(Trc::local_count(&value), 1);
+            prop_assert_eq!(SharedTrc::atomic_count(&shared), 1);
+            prop_assert!(weak.upgrade().is_none());
+        }
+    }
+}
+
+#[cfg(not(feature = "single-threaded"))]
+#[cfg(feature = "bytes")]
+#[test]
+fn test_bytes() {
+    use bytes::{Buf, Bytes};
+
+    use crate::buf::TrcBuf;
+
+    let trc = Trc::<[u8]>::from(vec![0, 0, 0, 5, 1, 2]);
+    let mut buf = TrcBuf::new(trc.clone());
+    assert_eq!(buf.get_u32(), 5);
+    let mut cloned = buf.clone();
+    cloned.advance(1);
+    assert_eq!(cloned.chunk(), [2]);
+    assert_eq!(buf.position(), 4);
+
+    let bytes = Bytes::from(buf);
+    assert_eq!(bytes, [1, 2][..]);
+    assert_eq!(Trc::atomic_count(&trc), 2);
+    let handle = thread::spawn(move || bytes.slice(1..));
+    assert_eq!(handle.join().unwrap(), [2][..]);
+    assert_eq!(Trc::atomic_count(&trc), 1);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_rayon() {
+    use rayon::prelude::*;
+
+    let shared: SharedTrc<[usize]> = (0..10_000).collect();
+    let ptr = shared.as_ptr();
+    assert_eq!(shared.par_iter().filter(|x| *x % 2 == 0).count(), 5_000);
+    assert_eq!((&shared).into_par_iter().max(), Some(&9_999));
+    assert!(shared.par_windows(2).all(|w| w[0] + 1 == w[1]));
+    assert_eq!(shared.as_ptr(), ptr);
+
+    let trc = SharedTrc::to_trc(shared);
+    assert_eq!(trc.par_iter().copied().sum::<usize>(), 49_995_000);
+}
+
+#[cfg(not(feature = "single-threaded"))]
+#[test]
+fn test_waker() {
+    use crate::wake::{self, TrcWake};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::task::Waker;
+
+    struct Task {
+        wakeups: AtomicUsize,
+    }
+
+    impl TrcWake for Task {
+        fn wake(this: SharedTrc<Self>) {
+            this.wakeups.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let task = SharedTrc::new(Task {
+        wakeups: AtomicUsize::new(0),
+    });
+    let waker = wake::waker(task.clone());
+    assert_eq!(SharedTrc::atomic_count(&task), 2);
+
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let waker = waker.clone();
+            std::thread::spawn(move || {
+                waker.wake_by_ref();
+                waker.wake();
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    assert_eq!(task.wakeups.load(Ordering::Relaxed), 8);
+    assert_eq!(SharedTrc::atomic_count(&task), 2);
+
+    assert!(waker.will_wake(&waker.clone()));
+    assert!(waker.will_wake(&Waker::from(task.clone())));
+    let other = SharedTrc::new(Task {
+        wakeups: AtomicUsize::new(0),
+    });
+    assert!(!waker.will_wake(&Waker::from(other)));
+    drop(waker);
+    assert_eq!(SharedTrc::atomic_count(&task), 1);
+}
+
+#[cfg(not(feature = "single-threaded"))]
+#[cfg(feature = "fn_traits")]
+#[test]
+fn test_fn_traits() {
+    fn apply<F: Fn(i32) -> i32>(f: F, x: i32) -> i32 {
+        f(x)
+    }
+    fn apply_mut<F: FnMut(i32) -> i32>(mut f: F, x: i32) -> i32 {
+        f(x)
+    }
+
+    let offset = 10;
+    let add = Trc::new(move |x: i32| x + offset);
+    assert_eq!(apply(add.clone(), 1), 11);
+    assert_eq!(apply_mut(add.clone(), 2), 12);
+    assert_eq!(Trc::local_count(&add), 1);
+
+    let double: Box<dyn Fn(i32) -> i32> = Box::new(|x| x * 2);
+    let triple: Box<dyn Fn(i32) -> i32> = Box::new(|x| x * 3);
+    let callbacks: [Trc<dyn Fn(i32) -> i32>; 2] = [Trc::from(double), Trc::from(triple)];
+    let results: Vec<i32> = callbacks.iter().map(|f| apply(f.clone(), 3)).collect();
+    assert_eq!(results, [6, 9]);
+
+    let decrement: Box<dyn Fn(i32) -> i32 + Send + Sync> = Box::new(|x| x - 1);
+    let shared: SharedTrc<dyn Fn(i32) -> i32 + Send + Sync> = SharedTrc::from(decrement);
+    let handle = std::thread::spawn({
+        let shared = shared.clone();
+        move || apply(shared, 5)
+    });
+    assert_eq!(handle.join().unwrap(), 4);
+    assert_eq!([1, 2].map(shared), [0, 1]);
+}
+
+#[test]
+fn test_arc_rc_conversions() {
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    let arc = Arc::new(String::from("Trc"));
+    let ptr = arc.as_ptr();
+    let trc = Trc::from_arc(arc.clone());
+    assert_ne!(trc.as_ptr(), ptr);
+    let trc = Trc::from_arc(arc);
+    assert_eq!(trc.as_ptr(), ptr);
+
+    let trc2 = trc.clone();
+    let arc = Trc::into_arc(trc);
+    assert_ne!(arc.as_ptr(), ptr);
+    let arc = Trc::into_arc(trc2);
+    assert_eq!(arc.as_ptr(), ptr);
+
+    let rc = Rc::new(vec![1, 2, 3]);
+    let ptr = rc.as_ptr();
+    let trc = Trc::from_rc(rc);
+    assert_eq!(trc.as_ptr(), ptr);
+    let rc = Trc::into_rc(trc);
+    assert_eq!(rc.as_ptr(), ptr);
+
+    let shared = SharedTrc::new(5);
+    let shared2 = shared.clone();
+    assert_eq!(*SharedTrc::into_arc(shared), 5);
+    assert_eq!(SharedTrc::atomic_count(&shared2), 1);
+    assert_eq!(*SharedTrc::into_arc(shared2), 5);
+
+    let trc: Trc<[String]> = Trc::from(vec![String::from("a"), String::from("b")]);
+    let weak = Trc::downgrade(&trc);
+    let arc = Trc::try_into_arc(trc).ok().unwrap();
+    assert_eq!(*arc, ["a", "b"]);
+    assert!(weak.upgrade().is_none());
+}
+
+#[cfg(not(feature = "single-threaded"))]
+#[test]
+fn test_any_stable() {
+    use std::any::Any;
+    use std::cell::RefCell;
+
+    let trc = Trc::new_any(String::from("Trc"));
+    let shared = SharedTrc::from_trc(&trc);
+    let trc = trc.downcast::<i32>().unwrap_err();
+    let string = trc.downcast_checked::<String>().unwrap();
+    assert_eq!(*string, "Trc");
+    assert_eq!(Trc::atomic_count(&string), 2);
+
+    let handle = std::thread::spawn(move || shared.downcast::<String>().is_ok());
+    assert!(handle.join().unwrap());
+
+    let shared = SharedTrc::new_any(5u8);
+    assert_eq!(*shared.downcast::<u8>().unwrap(), 5);
+
+    let local = Trc::new_local_any(RefCell::new(vec![1]));
+    let clone = local.clone();
+    let cell = local.downcast::<RefCell<Vec<i32>>>().unwrap();
+    cell.borrow_mut().push(2);
+    assert_eq!(
+        *clone.downcast_ref::<RefCell<Vec<i32>>>().unwrap().borrow(),
+        [1, 2]
+    );
+
+    let upcast: Trc<dyn Any> = Trc::new_any(1.5f64).into();
+    let err = upcast.downcast_checked::<f32>().unwrap_err();
+    assert_eq!(err.expected_type_name(), "f32");
+    assert_eq!(*err.into_inner().downcast::<f64>().unwrap(), 1.5);
+}
+
+#[cfg(feature = "unsize")]
+#[test]
+fn test_unsize() {
+    use std::fmt::Display;
+    use unsize::{CoerceUnsize, Coercion};
+
+    let trc = Trc::new(String::from("Trc"));
+    let dyn_trc: Trc<dyn Display> = trc.clone().unsize(Coercion!(to dyn Display));
+    assert_eq!(dyn_trc.to_string(), "Trc");
+    assert_eq!(Trc::local_count(&trc), 2);
+    drop(dyn_trc);
+    assert_eq!(Trc::local_count(&trc), 1);
+
+    let shared: SharedTrc<[u8]> = SharedTrc::new([1, 2, 3]).unsize(Coercion::to_slice());
+    assert_eq!(*shared, [1, 2, 3]);
+
+    let weak: Weak<dyn Display> = Trc::downgrade(&trc).unsize(Coercion!(to dyn Display));
+    assert_eq!(weak.upgrade().unwrap().to_string(), "Trc");
+    drop(trc);
+    assert!(weak.upgrade().is_none());
+
+    let dangling: Weak<[i32]> = Weak::<[i32; 4]>::new().unsize(Coercion::to_slice());
+    assert!(dangling.upgrade().is_none());
+}
+
+#[test]
+fn test_by_ptr() {
+    use crate::ByPtr;
+    use std::collections::{BTreeSet, HashMap};
+
+    let a = Trc::new(String::from("Trc"));
+    let b = Trc::new(String::from("Trc"));
+    assert_eq!(a, b);
+    assert_ne!(ByPtr(a.clone()), ByPtr(b.clone()));
+    assert_eq!(ByPtr(a.clone()), ByPtr(a.clone()));
+
+    let mut names = HashMap::new();
+    names.insert(ByPtr(a.clone()), "a");
+    names.insert(ByPtr(b.clone()), "b");
+    assert_eq!(names[&ByPtr(a.clone())], "a");
+    assert_eq!(names[&ByPtr(b.clone())], "b");
+
+    let unsized_a: Trc<[i32]> = Trc::from(vec![1, 2]);
+    let set: BTreeSet<_> = [ByPtr(unsized_a.clone()), ByPtr(unsized_a.clone())]
+        .into_iter()
+        .collect();
+    assert_eq!(set.len(), 1);
+
+    let shared = SharedTrc::from_trc(&a);
+    assert_eq!(ByPtr(shared).as_ptr(), ByPtr(a.clone()).as_ptr());
+    let weak = Trc::downgrade(&a);
+    assert_eq!(ByPtr(weak).as_ptr(), ByPtr(a).as_ptr());
+    assert_eq!(ByPtr(Weak::<i32>::new()), ByPtr(Weak::<i32>::new()));
+}
+
+#[cfg(not(feature = "single-threaded"))]
+#[test]
+fn test_atomic_shared_trc() {
+    use crate::AtomicSharedTrc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    struct Versioned(usize);
+
+    impl Drop for Versioned {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let slot = AtomicSharedTrc::new(SharedTrc::new(Versioned(0)));
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                let mut last = 0;
+                for _ in 0..10_000 {
+                    let current = slot.load();
+                    assert!(current.0 >= last);
+                    last = current.0;
+                }
+            });
+        }
+        for _ in 0..2 {
+            scope.spawn(|| {
+                for _ in 0..500 {
+                    loop {
+                        let current = slot.load();
+                        let next = SharedTrc::new(Versioned(current.0 + 1));
+                        match slot.compare_exchange(&current, next) {
+                            Ok(previous) => {
+                                assert!(SharedTrc::ptr_eq(&previous, &current));
+                                break;
+                            }
+                            Err(next) => drop(next),
+                        }
+                    }
+                }
+            });
+        }
+    });
+
+    let last = slot.swap(SharedTrc::new(Versioned(0)));
+    assert_eq!(last.0, 1000);
+    assert_eq!(SharedTrc::atomic_count(&last), 1);
+    drop(last);
+    drop(slot);
+    //Every value created by the writers, the failed exchanges and the initial and final values have been dropped.
+    assert!(DROPS.load(Ordering::Relaxed) >= 1002);
+}
+
+#[test]
+fn test_trc_cell() {
+    use crate::TrcCell;
+
+    struct Node {
+        next: TrcCell<Option<Node>>,
+    }
+
+    let head = Node {
+        next: TrcCell::new(Trc::new(None)),
+    };
+    let tail = Trc::new(Some(Node {
+        next: TrcCell::default(),
+    }));
+    head.next.set(tail.clone());
+    assert_eq!(Trc::local_count(&tail), 2);
+    assert!(head.next.take().is_some());
+    assert!(head.next.get_clone().is_none());
+    assert_eq!(Trc::local_count(&tail), 1);
+
+    let a = TrcCell::new(Trc::<str>::from("a"));
+    let b = TrcCell::new(Trc::<str>::from("b"));
+    a.swap(&b);
+    a.swap(&a);
+    assert_eq!(&*a.get_clone(), "b");
+    assert_eq!(&*b.into_inner(), "a");
+}
+
+#[cfg(not(feature = "single-threaded"))]
+#[test]
+fn test_once_lazy() {
+    use crate::{LazyTrc, OnceTrc};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static INITS: AtomicUsize = AtomicUsize::new(0);
+    static ONCE: OnceTrc<Vec<u32>> = OnceTrc::new();
+    static LAZY: LazyTrc<String> = LazyTrc::new(|| {
+        INITS.fetch_add(1, Ordering::Relaxed);
+        String::from("lazy")
+    });
+
+    std::thread::scope(|scope| {
+        for i in 0..8 {
+            scope.spawn(move || {
+                let once = ONCE.get_or_init(|| vec![i]);
+                assert_eq!(once.len(), 1);
+                assert_eq!(*LAZY, "lazy");
+                let trc = LazyTrc::trc(&LAZY);
+                assert_eq!(*trc, "lazy");
+            });
+        }
+    });
+    assert_eq!(INITS.load(Ordering::Relaxed), 1);
+    assert_eq!(SharedTrc::atomic_count(LazyTrc::force(&LAZY)), 1);
+    assert!(ONCE.set(Vec::new()).is_err());
+
+    let lazy = LazyTrc::new(|| -> i32 { panic!("Initialization failed.") });
+    assert!(std::panic::catch_unwind(|| *lazy).is_err());
+    assert!(std::panic::catch_unwind(|| *lazy).is_err());
+}
+
+#[cfg(not(feature = "single-threaded"))]
+#[test]
+fn test_unique_trc() {
+    use crate::UniqueTrc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    struct Node {
+        value: i32,
+        this: Weak<Node>,
+    }
+
+    impl Drop for Node {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let mut unique = UniqueTrc::new(Node {
+        value: 0,
+        this: Weak::new(),
+    });
+    let weak = UniqueTrc::downgrade(&unique);
+    unique.this = weak.clone();
+    unique.value = 5;
+    assert!(weak.upgrade().is_none());
+    assert!(SharedTrc::from_weak(&weak).is_none());
+
+    let shared = UniqueTrc::into_shared(unique);
+    let handle = std::thread::spawn(move || {
+        let trc = weak.upgrade().unwrap();
+        assert_eq!(trc.value, 5);
+        drop(shared);
+        weak
+    });
+    let weak = handle.join().unwrap();
+    assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+    assert!(weak.upgrade().is_none());
+
+    //Dropping a `UniqueTrc` without sharing it frees the value, and its `Weak`s stay dead.
+    let unique = UniqueTrc::new(Node {
+        value: 1,
+        this: Weak::new(),
+    });
+    let weak = UniqueTrc::downgrade(&unique);
+    drop(unique);
+    assert_eq!(DROPS.load(Ordering::Relaxed), 2);
+    assert!(weak.upgrade().is_none());
+
+    let trc: Trc<String> = UniqueTrc::new(String::from("Trc")).into();
+    assert_eq!(Trc::atomic_count(&trc), 1);
+    assert_eq!(Trc::try_unwrap(trc).ok().unwrap(), "Trc");
+}
+
+#[test]
+fn test_trc_cow() {
+    use crate::TrcCow;
+
+    let original = Trc::new(vec![1, 2, 3]);
+    let mut cow = TrcCow::new(original.clone());
+    assert!(Trc::ptr_eq(cow.as_trc(), &original));
+
+    cow.to_mut().push(4);
+    assert!(cow.is_modified());
+    assert!(!Trc::ptr_eq(cow.as_trc(), &original));
+    let ptr = Trc::as_ptr(cow.as_trc());
+    cow.to_mut().push(5);
+    assert_eq!(Trc::as_ptr(cow.as_trc()), ptr);
+    assert_eq!(*cow, [1, 2, 3, 4, 5]);
+    assert_eq!(*original, [1, 2, 3]);
+
+    //With only a `Weak` left, the value is moved into a new allocation without cloning, and the `Weak` is left with the
+    //old allocation.
+    struct NoClone(i32);
+
+    impl Clone for NoClone {
+        fn clone(&self) -> Self {
+            panic!("The value was cloned.");
+        }
+    }
+
+    let mut trc = Trc::new(NoClone(1));
+    let weak = Trc::downgrade(&trc);
+    Trc::make_mut(&mut trc).0 += 1;
+    assert!(weak.upgrade().is_none());
+    assert_eq!(Weak::weak_count(&weak), 0);
+    assert_eq!(trc.0, 2);
+
+    //Without other pointers, the value stays in place.
+    let ptr = Trc::as_ptr(&trc);
+    Trc::make_mut(&mut trc).0 += 1;
+    assert_eq!(Trc::as_ptr(&trc), ptr);
+    assert_eq!(trc.0, 3);
+}
+
+#[cfg(not(feature = "single-threaded"))]
+#[test]
+fn test_thin_trc() {
+    use crate::{ThinSharedTrc, ThinTrc};
+    use core::{any::Any, mem::size_of};
+    use std::fmt::Display;
+
+    assert_eq!(size_of::<ThinTrc<[u8]>>(), size_of::<usize>());
+    assert_eq!(size_of::<ThinSharedTrc<dyn Any>>(), size_of::<usize>());
+
+    //The value is stored after the header, in the same allocation.
+    let thin: ThinTrc<dyn Display> = ThinTrc::from(Box::new(100u64) as Box<dyn Display>);
+    let header = unsafe { *(&thin as *const ThinTrc<dyn Display>).cast::<usize>() };
+    let offset = ThinTrc::as_ptr(&thin).cast::<u8>() as usize - header;
+    assert!(offset <= 4 * size_of::<usize>());
+    assert_eq!(thin.to_string(), "100");
+
+    //Moving a value between the fat and thin forms requires a unique handle.
+    let trc: Trc<[i32]> = Trc::from(vec![1, 2, 3]);
+    let weak = Trc::downgrade(&trc);
+    let trc2 = trc.clone();
+    let trc = ThinTrc::try_from(trc).unwrap_err();
+    drop(trc2);
+    let thin = ThinTrc::try_from(trc).ok().unwrap();
+    assert!(weak.upgrade().is_none());
+    let thin2 = thin.clone();
+    assert_eq!(ThinTrc::count(&thin), 2);
+    assert!(ThinTrc::ptr_eq(&thin, &thin2));
+    let thin = Trc::<[i32]>::try_from(thin).unwrap_err();
+    let thin = ThinSharedTrc::try_from(thin).unwrap_err();
+    drop(thin2);
+
+    //A unique `ThinTrc` becomes a `ThinSharedTrc` in place.
+    let ptr = ThinTrc::as_ptr(&thin);
+    let thin_shared = ThinSharedTrc::try_from(thin).ok().unwrap();
+    assert_eq!(ThinSharedTrc::as_ptr(&thin_shared), ptr);
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let thin_shared = thin_shared.clone();
+            std::thread::spawn(move || thin_shared.iter().sum::<i32>())
+        })
+        .collect();
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), 6);
+    }
+    assert_eq!(ThinSharedTrc::count(&thin_shared), 1);
+    let shared = SharedTrc::<[i32]>::try_from(thin_shared).ok().unwrap();
+    assert_eq!(*shared, [1, 2, 3]);
+    let thin_shared = ThinSharedTrc::try_from(shared).ok().unwrap();
+    let thin = ThinTrc::try_from(thin_shared).ok().unwrap();
+    let trc = Trc::<[i32]>::try_from(thin).ok().unwrap();
+    assert_eq!(*trc, [1, 2, 3]);
+
+    //The value is dropped once, with the last handle.
+    let dropped = Trc::new(());
+    let weak = Trc::downgrade(&dropped);
+    let thin = ThinSharedTrc::new(dropped);
+    drop(thin.clone());
+    assert!(weak.upgrade().is_some());
+    drop(thin);
+    assert!(weak.upgrade().is_none());
+    assert_eq!(&*ThinSharedTrc::from("Trc"), "Trc");
+    assert_eq!(*ThinTrc::from(vec![String::from("a")]), ["a"]);
+}
+
+#[cfg(not(feature = "single-threaded"))]
+#[test]
+fn test_projected_trc() {
+    use crate::{ProjectedSharedTrc, ProjectedTrc};
+
+    struct Pair {
+        name: String,
+        values: Vec<i32>,
+    }
+
+    let pair = Trc::new(Pair {
+        name: String::from("pair"),
+        values: vec![1, 2, 3],
+    });
+    let weak = Trc::downgrade(&pair);
+    let name: ProjectedTrc<str> = Trc::map(pair.clone(), |pair| pair.name.as_str());
+    let values = Trc::map(pair, |pair| &pair.values);
+    let values = ProjectedTrc::try_map(values, |values| values.get(3)).unwrap_err();
+    let last = ProjectedTrc::try_map(values, |values| values.last()).unwrap();
+    let last2 = last.clone();
+    drop(last);
+    assert_eq!(&*name, "pair");
+    assert_eq!(*last2, 3);
+
+    //The value is only dropped with the last projection.
+    drop(name);
+    assert!(weak.upgrade().is_some());
+    drop(last2);
+    assert!(weak.upgrade().is_none());
+
+    let trc = Trc::new((String::from("a"), vec![4, 5]));
+    let weak = Trc::downgrade(&trc);
+    let shared = SharedTrc::from(trc);
+    let projected: ProjectedSharedTrc<[i32]> = SharedTrc::map(shared, |pair| pair.1.as_slice());
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let projected = projected.clone();
+            thread::spawn(move || projected.iter().sum::<i32>())
+        })
+        .collect();
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), 9);
+    }
+    drop(projected);
+    assert!(weak.upgrade().is_none());
+}
+
+#[test]
+fn test_projected_weak() {
+    use crate::ProjectedTrc;
+
+    let trc = Trc::new((String::from("name"), 1));
+    let name = Trc::map(trc.clone(), |pair| pair.0.as_str());
+    let weak = ProjectedTrc::downgrade(&name);
+    let weak2 = weak.clone();
+    assert_eq!(Trc::weak_count(&trc), 2);
+    drop(name);
+
+    //Upgrading creates a new local count, which takes an atomic reference.
+    let upgraded = weak.upgrade().unwrap();
+    assert_eq!(&*upgraded, "name");
+    assert_eq!(Trc::atomic_count(&trc), 2);
+    drop(upgraded);
+    drop(trc);
+
+    assert!(weak.upgrade().is_none());
+    assert!(weak2.upgrade().is_none());
+    drop(weak);
+    drop(weak2);
+}
+
+#[cfg(not(feature = "single-threaded"))]
+#[test]
+fn test_trc_bytes() {
+    use crate::TrcBytes;
+
+    let trc: Trc<[u8]> = Trc::from(b"GET /index.html HTTP/1.1".to_vec());
+    let weak = Trc::downgrade(&trc);
+    let mut line = TrcBytes::from(trc);
+    let method = line.split_to(3);
+    line.advance(1);
+    let version = line.split_off(line.len() - 8);
+    line.truncate(line.len() - 1);
+    assert_eq!(method, b"GET"[..]);
+    assert_eq!(line, b"/index.html"[..]);
+    assert_eq!(version, b"HTTP/1.1"[..]);
+    assert_eq!(version.slice(5..), b"1.1"[..]);
+    assert!(version.slice(8..).is_empty());
+
+    //The parts share the allocation, which is freed with the last of them.
+    let handle = thread::spawn(move || line.len());
+    assert_eq!(handle.join().unwrap(), 11);
+    drop(method);
+    assert!(weak.upgrade().is_some());
+    drop(version);
+    assert!(weak.upgrade().is_none());
+
+    let empty = TrcBytes::from_static(b"");
+    assert_eq!(empty, TrcBytes::default());
+    assert!(std::panic::catch_unwind(|| TrcBytes::from_static(b"Trc").slice(2..4)).is_err());
+}
+
+#[test]
+fn test_trc_str() {
+    use crate::TrcStr;
+    use std::collections::HashSet;
+
+    let source = TrcStr::from(String::from("naïve café"));
+    let words: Vec<TrcStr> = source
+        .split(' ')
+        .map(|word| source.slice_ref(word))
+        .collect();
+    assert_eq!(words, ["naïve", "café"]);
+    assert_eq!(Trc::local_count(source.as_trc()), 3);
+    assert_eq!(words[1].substr(..3), "caf");
+    assert_eq!(words[1].substr(3..), "é");
+
+    let set: HashSet<TrcStr> = words.into_iter().collect();
+    assert!(set.contains("café"));
+
+    //Splitting inside a multi-byte character and referencing outside the substring are rejected.
+    assert!(std::panic::catch_unwind(|| TrcStr::from("ï").substr(1..)).is_err());
+    assert!(std::panic::catch_unwind(|| {
+        let string = TrcStr::from("abc");
+        let sub = string.substr(1..);
+        sub.slice_ref(&string[..1])
+    })
+    .is_err());
+}
+
+#[test]
+fn test_trc_slice() {
+    use crate::TrcSlice;
+
+    let trc: Trc<[String]> = (0..6).map(|i| i.to_string()).collect::<Vec<_>>().into();
+    let weak = Trc::downgrade(&trc);
+    let slice = Trc::slice(&trc, 1..5);
+    let (left, right) = TrcSlice::split_at(&slice, 2);
+    assert_eq!(*left, ["1", "2"]);
+    assert_eq!(*right.slice(1..=1), ["4"]);
+    assert!(right.slice(2..).is_empty());
+    assert_eq!(Trc::local_count(&trc), 4);
+    assert!(std::panic::catch_unwind(|| Trc::slice(&Trc::<[u8]>::from(vec![1]), ..2)).is_err());
+
+    drop(trc);
+    drop(slice);
+    drop(left);
+    assert!(weak.upgrade().is_some());
+    drop(right);
+    assert!(weak.upgrade().is_none());
+}
+
+#[cfg(not(feature = "single-threaded"))]
+#[test]
+fn test_trc_pool() {
+    use crate::TrcPool;
+
+    let pool = TrcPool::with_capacity(2);
+    let a = pool.get_or(|| String::from("a"));
+    let b = pool.get_or(|| String::from("b"));
+    let c = pool.get_or(|| String::from("c"));
+    assert_eq!(pool.len(), 2);
+
+    //A value dropped on another thread frees its allocation for reuse.
+    let ptr = Trc::as_ptr(&b);
+    let shared = SharedTrc::from(b);
+    thread::spawn(move || drop(shared)).join().unwrap();
+    assert_eq!(pool.available(), 1);
+
+    let weak = Trc::downgrade(&a);
+    let d = pool.get_or(|| String::from("d"));
+    assert_eq!(Trc::as_ptr(&d), ptr);
+    assert_eq!(*d, "d");
+    assert_eq!(Trc::weak_count(&d), 1);
+    assert_eq!(pool.available(), 0);
+
+    //An allocation with another `Weak` is not reused, as the `Weak` could observe the new value.
+    drop(a);
+    assert_eq!(pool.available(), 0);
+    drop(weak);
+    assert_eq!(pool.available(), 1);
+
+    drop(pool);
+    assert_eq!(*c, "c");
+    assert_eq!(*d, "d");
+}
+
+#[test]
+fn test_shared_drop_with_weak() {
+    //The last `SharedTrc` drops the value even if `Weak`s are left.
+    let trc = Trc::new(String::from("value"));
+    let weak = Trc::downgrade(&trc);
+    drop(SharedTrc::from(trc));
+    assert!(weak.upgrade().is_none());
+}
+
+#[cfg(feature = "cycles")]
+#[test]
+fn test_collect_cycles() {
+    use crate::cycles::{self, Trace, Tracer};
+    use std::cell::RefCell;
+
+    struct Node {
+        children: RefCell<Vec<Trc<Node>>>,
+    }
+
+    unsafe impl Trace for Node {
+        fn trace(&self, tracer: &mut Tracer) {
+            self.children.trace(tracer);
+        }
+    }
+
+    let node = || {
+        Trc::new_traced(Node {
+            children: RefCell::new(Vec::new()),
+        })
+    };
+
+    //A cycle of three nodes, with a fourth node hanging off it.
+    let a = node();
+    let b = node();
+    let c = node();
+    let d = node();
+    a.children.borrow_mut().push(b.clone());
+    b.children.borrow_mut().push(c.clone());
+    c.children.borrow_mut().push(a.clone());
+    c.children.borrow_mut().push(d.clone());
+    let weak_a = Trc::downgrade(&a);
+    let weak_d = Trc::downgrade(&d);
+    drop((a, b, d));
+
+    //`c` is still in use, so the whole cycle is reachable.
+    assert_eq!(cycles::collect_cycles(), 0);
+
+    //A handle in a value which is not traced is an external reference too.
+    let holder = vec![c.clone()];
+    drop(c);
+    assert_eq!(cycles::collect_cycles(), 0);
+    drop(holder);
+
+    assert!(weak_a.upgrade().is_some());
+    //`d` is not part of the cycle, but it is only reachable from it.
+    assert_eq!(cycles::collect_cycles(), 4);
+    assert!(weak_a.upgrade().is_none());
+    assert!(weak_d.upgrade().is_none());
+    assert_eq!(cycles::collect_cycles(), 0);
+    assert_eq!(cycles::tracked(), 0);
+}
+
+#[cfg(feature = "debug-graph")]
+#[test]
+fn test_debug_graph() {
+    use crate::cycles::{Trace, Tracer};
+    use crate::debug;
+    use std::cell::RefCell;
+
+    struct Node {
+        children: RefCell<Vec<Trc<Node>>>,
+        parent: RefCell<Option<Weak<Node>>>,
+    }
+
+    unsafe impl Trace for Node {
+        fn trace(&self, tracer: &mut Tracer) {
+            self.children.trace(tracer);
+            self.parent.trace(tracer);
+        }
+    }
+
+    let node = || {
+        Trc::new_traced(Node {
+            children: RefCell::new(Vec::new()),
+            parent: RefCell::new(None),
+        })
+    };
+
+    //A tree with weak parent references has no cycles.
+    let root = node();
+    let child = node();
+    root.children.borrow_mut().push(child.clone());
+    *child.parent.borrow_mut() = Some(Trc::downgrade(&root));
+    assert!(debug::find_cycles().is_empty());
+
+    let dot = debug::dump_graphviz();
+    assert_eq!(dot.matches(" -> ").count(), 2);
+    assert_eq!(dot.matches("[style=dashed]").count(), 1);
+
+    //A strong reference to the parent creates one.
+    let other = node();
+    child.children.borrow_mut().push(root.clone());
+    let cycles = debug::find_cycles();
+    assert_eq!(cycles.len(), 1);
+    let mut addresses: Vec<usize> = cycles[0]
+        .iter()
+        .map(|allocation| allocation.address)
+        .collect();
+    addresses.sort_unstable();
+    let mut expected = vec![Trc::as_ptr(&root) as usize, Trc::as_ptr(&child) as usize];
+    expected.sort_unstable();
+    assert_eq!(addresses, expected);
+    assert!(!addresses.contains(&(Trc::as_ptr(&other) as usize)));
+
+    child.children.borrow_mut().clear();
+    assert!(debug::find_cycles().is_empty());
+    assert_eq!(debug::allocations().len(), 3);
+}
+
+#[cfg(feature = "hooks")]
+#[test]
+fn test_hooks() {
+    use crate::hooks::{self, HandleKind};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    };
+
+    //Other tests run at the same time, so only the events of one allocation are recorded.
+    static TARGET: AtomicUsize = AtomicUsize::new(0);
+    static EVENTS: Mutex<Vec<(&str, HandleKind)>> = Mutex::new(Vec::new());
+
+    fn on_clone(alloc_id: usize, kind: HandleKind) {
+        if alloc_id == TARGET.load(Ordering::Relaxed) {
+            EVENTS.lock().unwrap().push(("clone", kind));
+        }
+    }
+
+    fn on_drop(alloc_id: usize, kind: HandleKind) {
+        if alloc_id == TARGET.load(Ordering::Relaxed) {
+            EVENTS.lock().unwrap().push(("drop", kind));
+        }
+    }
+
+    let trc = Trc::new(100);
+    TARGET.store(trc.shared.as_ptr() as usize, Ordering::Relaxed);
+    hooks::set_on_clone(Some(on_clone));
+    hooks::set_on_drop(Some(on_drop));
+
+    drop(trc.clone());
+    let shared = SharedTrc::from_trc(&trc);
+    let shared2 = shared.clone();
+    let weak = Trc::downgrade(&trc);
+    drop(weak.clone());
+
+    //Converting to a `Trc` is not reported, even if it joins the local count of an existing `Trc`.
+    let first = SharedTrc::to_trc(shared);
+    let second = SharedTrc::to_trc(shared2);
+    assert_eq!(Trc::local_count(&first), 2);
+    drop(first);
+    drop(second);
+    drop(trc);
+    drop(weak);
+
+    hooks::set_on_clone(None);
+    hooks::set_on_drop(None);
+    assert_eq!(
+        *EVENTS.lock().unwrap(),
+        [
+            ("clone", HandleKind::Trc),
+            ("drop", HandleKind::Trc),
+            ("clone", HandleKind::SharedTrc),
+            ("clone", HandleKind::Weak),
+            ("drop", HandleKind::Weak),
+            ("drop", HandleKind::Trc),
+            ("drop", HandleKind::Trc),
+            ("drop", HandleKind::Trc),
+            ("drop", HandleKind::Weak),
+            ("drop", HandleKind::Weak),
+        ]
+    );
+}
+
+#[cfg(not(feature = "single-threaded"))]
+#[cfg(feature = "leak-debug")]
+#[test]
+fn test_leak_debug() {
+    let trc = Trc::new(100);
+    assert!(Trc::live_handle_traces(&trc).is_empty());
+
+    let trc2 = trc.clone();
+    let trc3 = trc.clone();
+    let shared = SharedTrc::from_trc(&trc);
+    let shared2 = shared.clone();
+    let traces = Trc::live_handle_traces(&trc);
+    assert_eq!(traces.len(), 3);
+    assert_eq!(traces.iter().filter(|trace| trace.is_shared()).count(), 1);
+
+    drop(trc3);
+    drop(shared2);
+    assert_eq!(Trc::live_handle_traces(&trc).len(), 1);
+
+    //A clone which is moved to another thread is released there.
+    let handle = std::thread::spawn(move || {
+        let trc = SharedTrc::to_trc(shared);
+        let clone = trc.clone();
+        assert_eq!(Trc::live_handle_traces(&clone).len(), 2);
+    });
+    handle.join().unwrap();
+    assert_eq!(Trc::live_handle_traces(&trc).len(), 1);
+
+    drop(trc2);
+    assert!(Trc::live_handle_traces(&trc).is_empty());
+}
+
+#[cfg(feature = "stats")]
+#[test]
+fn test_stats() {
+    //Other tests allocate concurrently, so only the counters which never decrease are compared exactly.
+    let before = crate::stats();
+    let trc = Trc::new(0u64);
+    let slice: Trc<[u32]> = Trc::from(vec![1, 2, 3]);
+    let weak = Trc::downgrade(&trc);
+    let after = crate::stats();
+    assert!(after.total_allocations >= before.total_allocations + 2);
+    assert!(after.peak_allocations >= 2);
+    assert!(after.peak_bytes >= 8 + 12);
+
+    //The allocation is live until the `Weak` is dropped.
+    drop(trc);
+    drop(slice);
+    assert!(crate::stats().live_allocations >= 1);
+    drop(weak);
+    let end = crate::stats();
+    assert!(end.peak_allocations >= end.live_allocations);
+    assert!(end.peak_bytes >= end.live_bytes);
+}
+
+#[cfg(not(feature = "single-threaded"))]
+#[test]
+fn test_trc_from_self() {
+    use crate::{SelfRef, TrcFromSelf};
+
+    struct Node {
+        self_ref: SelfRef<Node>,
+        value: i32,
+    }
+
+    impl TrcFromSelf for Node {
+        fn self_ref(&self) -> &SelfRef<Self> {
+            &self.self_ref
+        }
+    }
+
+    let node = Trc::new_with_self_ref(|self_ref| Node { self_ref, value: 1 });
+    let trc = node.trc_from_self();
+    assert!(Trc::ptr_eq(&trc, &node));
+    assert_eq!(Trc::atomic_count(&node), 2);
+
+    let shared = node.shared_from_self();
+    assert_eq!(shared.value, 1);
+    assert_eq!(Trc::atomic_count(&node), 3);
+
+    let weak = node.weak_from_self();
+    drop((node, trc, shared));
+    assert!(weak.upgrade().is_none());
+
+    let shared = SharedTrc::new_with_self_ref(|self_ref| Node { self_ref, value: 2 });
+    let handle = std::thread::spawn(move || shared.trc_from_self().value);
+    assert_eq!(handle.join().unwrap(), 2);
+}
+
+#[cfg(not(feature = "single-threaded"))]
+#[test]
+fn test_finalizer() {
+    use crate::Finalized;
+    use std::sync::{Arc, Mutex};
+
+    //The finalizer runs once, before the value is dropped, on the thread which drops the last strong handle.
+    let events = Arc::new(Mutex::new(Vec::new()));
+    struct Value(Arc<Mutex<Vec<&'static str>>>);
+    impl Drop for Value {
+        fn drop(&mut self) {
+            self.0.lock().unwrap().push("drop");
+        }
+    }
+
+    let finalizer_events = events.clone();
+    let trc = Trc::new_with_finalizer(Value(events.clone()), move |_| {
+        finalizer_events.lock().unwrap().push("finalize");
+    });
+    let weak = Trc::downgrade(&trc);
+    let shared = SharedTrc::from_trc(&trc);
+    drop(trc);
+    assert!(events.lock().unwrap().is_empty());
+    std::thread::spawn(move || drop(shared)).join().unwrap();
+    assert_eq!(*events.lock().unwrap(), ["finalize", "drop"]);
+    assert!(weak.upgrade().is_none());
+
+    //A cancelled finalizer never runs.
+    let mut trc = Trc::new_with_finalizer(1, |_| panic!("The finalizer was cancelled."));
+    let weak = Trc::downgrade(&trc);
+    assert!(!Trc::cancel_finalizer(&mut trc));
+    drop(weak);
+    assert!(Trc::cancel_finalizer(&mut trc));
+    assert!(!Finalized::has_finalizer(&trc));
+    drop(trc);
+}
+
+#[cfg(not(feature = "single-threaded"))]
+#[test]
+fn test_static_trc() {
+    use crate::StaticTrc;
+
+    static VALUES: StaticTrc<[i32; 3]> = StaticTrc::new([1, 2, 3]);
+
+    let before = Trc::atomic_count(&Trc::from_static(&VALUES));
+    let trc = Trc::from_static(&VALUES);
+    let shared = SharedTrc::from_static(&VALUES);
+    let weak = Trc::downgrade(&trc);
+    #[cfg(not(feature = "immortals"))]
+    assert_eq!(Trc::atomic_count(&trc), before + 1);
+    assert!(core::ptr::eq(&*trc, &*VALUES));
+
+    let handle = std::thread::spawn(move || shared.iter().sum::<i32>());
+    assert_eq!(handle.join().unwrap(), 6);
+
+    let mut trc = trc;
+    assert!(Trc::get_mut(&mut trc).is_none());
+    assert!(Trc::try_unwrap(trc).is_err());
+    assert_eq!(*weak.upgrade().unwrap(), [1, 2, 3]);
+    assert_eq!(Trc::atomic_count(&Trc::from_static(&VALUES)), before);
+}
+
+#[cfg(not(feature = "single-threaded"))]
+#[cfg(feature = "immortals")]
+#[test]
+fn test_immortals() {
+    use crate::StaticTrc;
+
+    let trc = Trc::new_immortal(vec![1, 2, 3]);
+    assert!(Trc::is_immortal(&trc));
+    assert_eq!(Trc::atomic_count(&trc), usize::MAX);
+
+    //Handles do not change the atomic count, and the value is never dropped.
+    let weak = Trc::downgrade(&trc);
+    let shared = SharedTrc::from_trc(&trc);
+    let clones = SharedTrc::clone_many(&shared, 4);
+    let handles: Vec<_> = clones
+        .into_iter()
+        .map(|shared| std::thread::spawn(move || SharedTrc::to_trc(shared).len()))
+        .collect();
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), 3);
+    }
+    drop((trc, shared));
+    assert!(Weak::is_immortal(&weak));
+    assert_eq!(*weak.upgrade().unwrap(), [1, 2, 3]);
+
+    let mut trc = weak.upgrade().unwrap();
+    assert!(Trc::get_mut(&mut trc).is_none());
+    assert!(Trc::into_inner(trc).is_none());
+    assert_eq!(Trc::atomic_count(&weak.upgrade().unwrap()), usize::MAX);
+
+    assert!(!Trc::is_immortal(&Trc::new(1)));
+    static VALUE: StaticTrc<i32> = StaticTrc::new(1);
+    assert!(SharedTrc::is_immortal(&SharedTrc::from_static(&VALUE)));
+}
+
+#[cfg(not(feature = "single-threaded"))]
+#[cfg(feature = "arena")]
+#[test]
+fn test_arena() {
+    use crate::arena::TrcArena;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+    struct Node(usize);
+
+    impl Drop for Node {
+        fn drop(&mut self) {
+            DROPPED.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let arena = TrcArena::new();
+    let count = arena.chunk_capacity() * 2 + 1;
+    let nodes: Vec<Trc<Node>> = (0..count).map(|i| arena.alloc(Node(i))).collect();
+    let weak = Trc::downgrade(&nodes[0]);
+    let shared = arena.alloc_shared(Node(count));
+
+    //Values are dropped as usual, while their chunks stay alive.
+    drop(nodes);
+    assert_eq!(DROPPED.load(Ordering::Relaxed), count);
+    assert!(weak.upgrade().is_none());
+
+    //The chunks outlive the arena, until the last handle to their values is dropped.
+    drop(arena);
+    let handle = std::thread::spawn(move || shared.0);
+    assert_eq!(handle.join().unwrap(), count);
+    assert_eq!(DROPPED.load(Ordering::Relaxed), count + 1);
+    drop(weak);
+
+    //The counts of values in an arena behave like those of other values.
+    let arena = TrcArena::new();
+    let mut trc = arena.alloc(vec![1]);
+    assert_eq!(Trc::weak_count(&trc), 0);
+    Trc::get_mut(&mut trc).unwrap().push(2);
+    let weak = Trc::downgrade(&trc);
+    assert_eq!(Trc::weak_count(&trc), 1);
+    assert_eq!(Weak::weak_count(&weak), 1);
+    assert!(Trc::get_mut(&mut trc).is_none());
+    drop(weak);
+    assert!(Trc::get_mut(&mut trc).is_some());
+
+    let mut shared = TrcArena::new().alloc_shared(3);
+    assert_eq!(SharedTrc::weak_count(&shared), 0);
+    *SharedTrc::get_mut(&mut shared).unwrap() += 1;
+    assert_eq!(
+        SharedTrc::wait_until_unique(&mut shared, std::time::Duration::from_secs(60)),
+        Ok(&mut 4)
+    );
+    drop(arena);
+    assert_eq!(Trc::try_unwrap(trc).unwrap(), [1, 2]);
+}
+
+#[cfg(not(feature = "single-threaded"))]
+#[cfg(feature = "allocator_api")]
+#[test]
+fn test_allocator_api() {
+    use std::alloc::{AllocError, Allocator, Global, Layout};
+    use std::ptr::NonNull;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct Counting {
+        live: AtomicUsize,
+    }
+
+    unsafe impl Allocator for &Counting {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            self.live.fetch_add(1, Ordering::Relaxed);
+            Global.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            self.live.fetch_sub(1, Ordering::Relaxed);
+            Global.deallocate(ptr, layout);
+        }
+    }
+
+    let counting = Counting::default();
+    let trc = Trc::new_in(vec![1, 2, 3], &counting);
+    let trc2 = trc.clone();
+    assert_eq!(counting.live.load(Ordering::Relaxed), 1);
+    assert!(std::ptr::eq(*Trc::allocator(&trc), &counting));
+    drop(trc);
+    assert_eq!(*trc2, [1, 2, 3]);
+    drop(trc2);
+    assert_eq!(counting.live.load(Ordering::Relaxed), 0);
+
+    let mut shared = SharedTrc::<u32, _>::new_uninit_in(&counting);
+    SharedTrc::get_mut(&mut shared).unwrap().write(5);
+    let shared = unsafe { shared.assume_init() };
+    let shared2 = shared.clone();
+    assert!(!SharedTrc::is_unique(&shared));
+    std::thread::scope(|scope| {
+        scope.spawn(move || assert_eq!(*shared2, 5));
+    });
+    drop(shared);
+    assert_eq!(counting.live.load(Ordering::Relaxed), 0);
+}
+
+#[cfg(not(feature = "single-threaded"))]
+#[test]
+fn test_share_scoped() {
+    let trc = Trc::new(String::from("Trc"));
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..4)
+            .map(|_| Trc::share_scoped(&trc, scope, |trc| trc.len()))
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), 3);
+        }
+
+        //The atomic count is released even if the thread panics.
+        let handle = Trc::share_scoped(&trc, scope, |trc| {
+            let _trc2 = trc.clone();
+            panic!("{}", *trc);
+        });
+        assert!(handle.join().is_err());
+    });
+    assert_eq!(Trc::atomic_count(&trc), 1);
+    assert!(Trc::is_unique(&trc));
+}
+
+#[cfg(not(feature = "single-threaded"))]
+#[test]
+fn test_wait_unique_async() {
+    use std::future::Future;
+    use std::pin::pin;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake};
+
+    struct ThreadWaker(std::thread::Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let waker = Arc::new(ThreadWaker(std::thread::current())).into();
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => std::thread::park(),
+            }
+        }
+    }
+
+    let mut shared = SharedTrc::new(vec![0]);
+    let handles: Vec<_> = (1..=8)
+        .map(|i| {
+            let shared = shared.clone();
+            std::thread::spawn(move || {
+                let trc = SharedTrc::to_trc(shared);
+                let weak = Trc::downgrade(&trc);
+                let _local = trc.clone();
+                std::thread::sleep(std::time::Duration::from_millis(i));
+                drop(trc);
+                drop(weak);
+            })
+        })
+        .collect();
+    block_on(SharedTrc::wait_unique(&mut shared)).push(1);
+    assert_eq!(*shared, [0, 1]);
+
+    let weak = Trc::downgrade(&SharedTrc::to_trc(shared.clone()));
+    let clone = shared.clone();
+    let handle = std::thread::spawn(move || drop(clone));
+    assert_eq!(block_on(SharedTrc::into_inner_async(shared)), [0, 1]);
+    assert!(weak.upgrade().is_none());
+    handle.join().unwrap();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
+
+#[cfg(not(feature = "single-threaded"))]
+#[test]
+fn test_wait_until_unique() {
+    use std::time::Duration;
+
+    let mut shared = SharedTrc::new(0);
+    let clone = shared.clone();
+    assert!(SharedTrc::wait_until_unique(&mut shared, Duration::from_millis(1)).is_err());
+
+    let handle = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(10));
+        drop(clone);
+    });
+    *SharedTrc::wait_until_unique(&mut shared, Duration::from_secs(60)).unwrap() = 1;
+    handle.join().unwrap();
+
+    //A release which races with the registration of the waiter is not missed.
+    for _ in 0..1000 {
+        let clone = shared.clone();
+        let handle = std::thread::spawn(move || drop(clone));
+        assert!(SharedTrc::wait_until_unique(&mut shared, Duration::from_secs(60)).is_ok());
+        handle.join().unwrap();
+    }
+
+    let trc = SharedTrc::to_trc(shared.clone());
+    let weak = Trc::downgrade(&trc);
+    let handle = std::thread::spawn(move || SharedTrc::into_inner_blocking(shared));
+    std::thread::sleep(Duration::from_millis(10));
+    drop(trc);
+    assert_eq!(handle.join().unwrap(), 1);
+    assert!(weak.upgrade().is_none());
+}
+
+#[cfg(not(feature = "single-threaded"))]
+#[test]
+fn test_local_counts_by_allocation() {
+    let shared = SharedTrc::new(100);
+    let weak = Trc::downgrade(&SharedTrc::to_trc(SharedTrc::clone(&shared)));
+
+    //Moving the value to a thread repeatedly joins the local count of the `Trc` already there.
+    let (sender, receiver) = std::sync::mpsc::channel::<SharedTrc<i32>>();
+    let handle = thread::spawn(move || {
+        let mut trcs = Vec::new();
+        for shared in receiver {
+            trcs.push(SharedTrc::to_trc(shared));
+            assert_eq!(Trc::local_count(&trcs[0]), trcs.len());
+        }
+        trcs.push(weak.upgrade().unwrap());
+        assert_eq!(Trc::local_count(&trcs[0]), 11);
+        assert_eq!(Trc::atomic_count(&trcs[0]), 2);
+    });
+    for _ in 0..10 {
+        sender.send(SharedTrc::clone(&shared)).unwrap();
+    }
+    drop(sender);
+    handle.join().unwrap();
+    assert_eq!(SharedTrc::atomic_count(&shared), 1);
+
+    //Once the last `Trc` of a local count is dropped, it is not joined again.
+    let trc = SharedTrc::to_trc(SharedTrc::clone(&shared));
+    drop(trc);
+    let trc = SharedTrc::to_trc(shared);
+    assert_eq!(Trc::local_count(&trc), 1);
+    assert_eq!(Trc::try_unwrap(trc).ok(), Some(100));
+    let trc = SharedTrc::to_trc(SharedTrc::new(200));
+    assert_eq!(Trc::local_count(&trc), 1);
+
+    //`into_inner` on `Trc`s which share a local count returns the value once, from the last of them.
+    let shared = SharedTrc::new(300);
+    let a = SharedTrc::to_trc(shared.clone());
+    let b = SharedTrc::to_trc(shared);
+    assert_eq!(Trc::into_inner(b), None);
+    assert_eq!(Trc::atomic_count(&a), 1);
+    assert_eq!(Trc::into_inner(a), Some(300));
+}
+
+#[cfg(feature = "padded")]
+#[test]
+fn test_padded() {
+    use crate::SharedTrcInternal;
+
+    assert!(core::mem::offset_of!(SharedTrcInternal<u8>, data) >= 64);
+    let trc = Trc::new(1u8);
+    let shared = SharedTrc::new([1u8, 2, 3]);
+    let slice: Trc<[u8]> = Trc::from(&[1u8, 2, 3][..]);
+    for data in [
+        Trc::as_ptr(&trc),
+        SharedTrc::as_ptr(&shared).cast(),
+        Trc::as_ptr(&slice).cast(),
+    ] {
+        assert_eq!(data as usize % 64, 0);
+    }
+}
+
+#[cfg(not(feature = "single-threaded"))]
+#[test]
+fn test_downgrade_while_checking_unique() {
+    let shared = SharedTrc::new(100);
+    let other = SharedTrc::clone(&shared);
+    let keep = SharedTrc::clone(&shared);
+    let handle = thread::spawn(move || {
+        let trc = SharedTrc::to_trc(other);
+        for _ in 0..10000 {
+            drop(Trc::downgrade(&trc));
+        }
+    });
+    //`is_unique` locks the weak count while there are no `Weak`s, which `downgrade` must wait for.
+    while !handle.is_finished() {
+        assert!(!SharedTrc::is_unique(&shared));
+    }
+    handle.join().unwrap();
+    drop(keep);
+    assert_eq!(SharedTrc::weak_count(&shared), 0);
+    assert!(SharedTrc::is_unique(&shared));
+}
+
+#[cfg(all(feature = "overflow-panic", not(feature = "overflow-saturate")))]
+#[test]
+#[should_panic(expected = "Overflow of maximum atomic reference count.")]
+fn test_overflow_panic() {
+    let shared = SharedTrc::new(100);
+    unsafe { shared.data.as_ref() }.atomicref.store(
+        crate::MAX_REFCOUNT + 1,
+        std::sync::atomic::Ordering::Relaxed,
+    );
+    let _ = SharedTrc::clone(&shared);
+}
+
+#[cfg(feature = "overflow-saturate")]
+#[test]
+fn test_overflow_saturate() {
+    let trc = Trc::new(100);
+    unsafe { trc.shared.as_ref() }.atomicref.store(
+        crate::MAX_REFCOUNT + 1,
+        std::sync::atomic::Ordering::Relaxed,
+    );
+    let shared = SharedTrc::from_trc(&trc);
+    let saturated = Trc::atomic_count(&trc);
+    assert!(saturated > crate::MAX_REFCOUNT + 2);
+
+    //The count stays saturated, so the value is never dropped.
+    let weak = Trc::downgrade(&trc);
+    drop(shared);
+    drop(trc);
+    assert_eq!(*weak.upgrade().unwrap(), 100);
+    assert_eq!(
+        SharedTrc::atomic_count(&weak.upgrade_shared().unwrap()),
+        saturated
+    );
+
+    let trc = Trc::new(100);
+    unsafe { *trc.threadref.as_ptr() = crate::MAX_REFCOUNT };
+    let clone = trc.clone();
+    assert!(Trc::local_count(&clone) > crate::MAX_REFCOUNT);
+}
+
+#[cfg(not(feature = "single-threaded"))]
+#[cfg(feature = "seqcst")]
+#[test]
+fn test_seqcst() {
+    use crate::ordering::{Acquire, Relaxed, Release};
+    use std::sync::atomic::Ordering;
+
+    assert_eq!([Relaxed, Acquire, Release], [Ordering::SeqCst; 3]);
+    let shared = SharedTrc::new(100);
+    let handle = thread::spawn({
+        let shared = shared.clone();
+        move || *shared
+    });
+    assert_eq!(handle.join().unwrap(), 100);
+    assert!(SharedTrc::is_unique(&shared));
+}
+
+#[cfg(not(feature = "single-threaded"))]
+#[cfg(feature = "portable-atomic")]
+#[test]
+fn test_portable_atomic() {
+    let trc = Trc::new(100);
+    let shared = SharedTrc::from_trc(&trc);
+    let weak = Trc::downgrade(&trc);
+    let handle = thread::spawn(move || {
+        let trc = SharedTrc::to_trc(shared);
+        assert_eq!(*trc, 100);
+    });
+    handle.join().unwrap();
+    assert_eq!(Trc::atomic_count(&trc), 1);
+    drop(trc);
+    assert!(weak.upgrade().is_none());
+}
+
+#[test]
+fn test_shared_from_raw_over_aligned() {
+    #[repr(align(64))]
+    struct Aligned(u8);
+
+    let shared = SharedTrc::new(Aligned(7));
+    let addr = SharedTrc::addr(&shared);
+    let ptr = SharedTrc::into_raw(shared);
+    assert_eq!(ptr.addr(), addr);
+    let shared = unsafe { SharedTrc::from_raw(ptr) };
+    assert_eq!(shared.0, 7);
+    assert_eq!(SharedTrc::atomic_count(&shared), 1);
+}
+
+#[cfg(feature = "dropck_eyepatch")]
+#[test]
+#[allow(clippy::needless_late_init)]
+fn test_dropck_eyepatch() {
+    //The handles are dropped after `value`, which they borrow, as with `Arc`.
+    let trc;
+    let shared;
+    let weak;
+    let value = String::from("borrowed");
+    trc = Trc::new(&value);
+    shared = SharedTrc::from_trc(&trc);
+    weak = Trc::downgrade(&trc);
+    assert_eq!(**shared, "borrowed");
+    assert!(weak.upgrade().is_some());
+}
+
+#[cfg(feature = "single-threaded")]
+#[test]
+fn test_single_threaded_counts() {
+    let trc = Trc::new(100);
+    let shared = SharedTrc::from_trc(&trc);
+    let clones = SharedTrc::clone_many(&shared, 3);
+    assert_eq!(Trc::atomic_count(&trc), 5);
+    drop(clones);
+
+    let other = SharedTrc::to_trc(shared);
+    assert_eq!(Trc::atomic_count(&trc), 2);
+    drop(other);
+    assert_eq!(Trc::atomic_count(&trc), 1);
+}
+
+#[cfg(feature = "single-threaded")]
+#[test]
+fn test_single_threaded_weak() {
+    let mut trc = Trc::new(100);
+    let weak = Trc::downgrade(&trc);
+    assert!(!Trc::is_unique(&trc));
+    assert!(Trc::get_mut(&mut trc).is_none());
+    assert_eq!(*weak.upgrade().unwrap(), 100);
+    drop(weak);
+
+    *Trc::get_mut(&mut trc).unwrap() += 1;
+    let weak = Trc::downgrade(&trc);
+    drop(trc);
+    assert!(weak.upgrade().is_none());
+}
+
+#[cfg(feature = "single-threaded")]
+#[test]
+fn test_single_threaded_atomic_shared_trc() {
+    use crate::AtomicSharedTrc;
+
+    let slot = AtomicSharedTrc::new(SharedTrc::new(1));
+    let old = slot.swap(SharedTrc::new(2));
+    assert_eq!(*old, 1);
+    assert!(SharedTrc::is_unique(&old));
+    assert_eq!(*slot.load(), 2);
+}
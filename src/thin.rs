@@ -0,0 +1,781 @@
+//! Single-pointer handles to unsized values.
+//!
+//! A `Trc<[T]>` or `Trc<dyn Trait>` is three words: the fat pointer to the allocation and the pointer to the local count.
+//! [`ThinTrc`] and [`ThinSharedTrc`] use their own allocation, which starts with a header holding the count and the
+//! metadata of the value (the length of a slice or the vtable of a trait object), followed by the value. The handles
+//! point to the header, so they are a single word, and the fat pointer to the value is rebuilt from the metadata when it
+//! is dereferenced. This is useful when many handles are stored, for example in a `Vec` or in an enum which should stay
+//! small.
+//!
+//! The count of a `ThinTrc` is not atomic, and `ThinTrc` cannot be sent to other threads. The count of a
+//! `ThinSharedTrc` is atomic, like the atomic count of a `SharedTrc`. As the allocation has a different layout than the
+//! one of a `Trc`, a value is moved between the thin and fat forms with [`TryFrom`], which only succeeds if the handle
+//! is the only one to its value. A unique `ThinTrc` and `ThinSharedTrc` are converted into each other without moving
+//! the value.
+
+use alloc::{
+    alloc::{handle_alloc_error, Layout},
+    boxed::Box,
+    vec::Vec,
+};
+use core::{
+    fmt::{self, Debug, Display},
+    marker::PhantomData,
+    mem::ManuallyDrop,
+    ops::Deref,
+    ptr::{self, addr_of, addr_of_mut, slice_from_raw_parts_mut, write, NonNull},
+};
+
+use crate::{
+    free_threadref, move_into_internal, new_threadref,
+    ordering::{Acquire, Relaxed, Release},
+    overflow, set_data_ptr,
+    sync::{alloc, dealloc, fence, AtomicUsize},
+    SharedTrc, Trc, Weak, MAX_REFCOUNT,
+};
+
+/// The start of the allocation of a thin handle. It holds a fat pointer to the whole allocation, whose metadata is the
+/// metadata of the value.
+#[repr(C)]
+struct ThinHeader<T: ?Sized> {
+    count: AtomicUsize,
+    inner: NonNull<ThinInner<T>>,
+}
+
+/// The allocation of a thin handle.
+#[repr(C)]
+struct ThinInner<T: ?Sized> {
+    header: ThinHeader<T>,
+    data: T,
+}
+
+/// Move the (possibly unsized) value at `src` into a new thin allocation with a count of 1.
+///
+/// # Safety
+/// `src` must point to a valid value, which is not used or dropped afterwards.
+unsafe fn move_into_thin<T: ?Sized>(src: *mut T) -> NonNull<ThinHeader<T>> {
+    let value_layout = Layout::for_value(unsafe { &*src });
+    let (layout, _) = Layout::new::<ThinHeader<T>>().extend(value_layout).unwrap();
+    let layout = layout.pad_to_align();
+
+    let mem = unsafe { alloc(layout) };
+    if mem.is_null() {
+        handle_alloc_error(layout);
+    }
+    #[cfg(feature = "stats")]
+    crate::stats::on_alloc(value_layout.size());
+
+    //Keep the metadata (length or vtable) of the value and point it at the new allocation.
+    let inner = unsafe { set_data_ptr(src as *mut ThinInner<T>, mem) };
+    unsafe {
+        write(
+            addr_of_mut!((*inner).header),
+            ThinHeader {
+                count: AtomicUsize::new(1),
+                inner: NonNull::new_unchecked(inner),
+            },
+        );
+        ptr::copy_nonoverlapping(
+            src.cast::<u8>(),
+            addr_of_mut!((*inner).data).cast::<u8>(),
+            value_layout.size(),
+        );
+        return NonNull::new_unchecked(inner).cast();
+    }
+}
+
+/// Move the value out of the allocation of `header` with `take`, and free the allocation without dropping the value.
+///
+/// # Safety
+/// The caller must own the last handle to the allocation, and `take` must move the value out.
+unsafe fn take_thin<T: ?Sized, R>(
+    header: NonNull<ThinHeader<T>>,
+    take: impl FnOnce(*mut T) -> R,
+) -> R {
+    let inner = unsafe { header.as_ref() }.inner.as_ptr();
+    let layout = Layout::for_value(unsafe { &*inner });
+    #[cfg(feature = "stats")]
+    crate::stats::on_dealloc(core::mem::size_of_val(unsafe { &(*inner).data }));
+
+    let res = take(unsafe { addr_of_mut!((*inner).data) });
+    unsafe { dealloc(inner.cast(), layout) };
+    return res;
+}
+
+/// Drop the value in the allocation of `header`, and free the allocation.
+///
+/// # Safety
+/// The last handle to the allocation must have been dropped.
+unsafe fn drop_thin<T: ?Sized>(header: NonNull<ThinHeader<T>>) {
+    unsafe { take_thin(header, |data| ptr::drop_in_place(data)) };
+}
+
+/// Return a raw pointer to the value in the allocation of `header`.
+#[inline]
+fn thin_data<T: ?Sized>(header: NonNull<ThinHeader<T>>) -> *const T {
+    let inner = unsafe { header.as_ref() }.inner;
+    return unsafe { addr_of!((*inner.as_ptr()).data) };
+}
+
+/// A single-pointer, single-threaded handle to a value. The count and the metadata of the value, which is the length of
+/// a slice or the vtable of a trait object, are stored in a header at the start of the allocation, so the handle is one
+/// word even if `T` is unsized.
+///
+/// Cloning a `ThinTrc` increments the count without an atomic operation. Like `Trc`, `ThinTrc` cannot be sent to other
+/// threads; convert a unique `ThinTrc` into a [`ThinSharedTrc`] to do so.
+///
+/// # Examples
+/// ```
+/// use std::fmt::Display;
+/// use trc::{ThinTrc, Trc};
+///
+/// let thin: ThinTrc<dyn Display> = ThinTrc::from(Box::new(100) as Box<dyn Display>);
+/// assert_eq!(std::mem::size_of_val(&thin), std::mem::size_of::<usize>());
+/// assert_eq!(thin.to_string(), "100");
+///
+/// let trc = Trc::<dyn Display>::try_from(thin).ok().unwrap();
+/// assert_eq!(trc.to_string(), "100");
+/// ```
+pub struct ThinTrc<T: ?Sized> {
+    header: NonNull<ThinHeader<T>>,
+    phantom: PhantomData<ThinInner<T>>,
+}
+
+impl<T> ThinTrc<T> {
+    /// Create a new `ThinTrc` holding `value`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::ThinTrc;
+    ///
+    /// let thin = ThinTrc::new(100);
+    /// assert_eq!(*thin, 100);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn new(value: T) -> Self {
+        let mut value = ManuallyDrop::new(value);
+        return Self {
+            header: unsafe { move_into_thin(&mut *value as *mut T) },
+            phantom: PhantomData,
+        };
+    }
+}
+
+impl<T: ?Sized> ThinTrc<T> {
+    /// Return the number of `ThinTrc`s to the value.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::ThinTrc;
+    ///
+    /// let thin = ThinTrc::new(100);
+    /// let thin2 = thin.clone();
+    /// assert_eq!(ThinTrc::count(&thin), 2);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn count(this: &Self) -> usize {
+        return unsafe { this.header.as_ref() }.count.load(Relaxed);
+    }
+
+    /// Return whether two `ThinTrc`s point to the same allocation.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::ThinTrc;
+    ///
+    /// let thin = ThinTrc::new(100);
+    /// let thin2 = thin.clone();
+    /// assert!(ThinTrc::ptr_eq(&thin, &thin2));
+    /// assert!(!ThinTrc::ptr_eq(&thin, &ThinTrc::new(100)));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        return this.header == other.header;
+    }
+
+    /// Return a raw pointer to the value.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::ThinTrc;
+    ///
+    /// let thin = ThinTrc::new(100);
+    /// assert_eq!(unsafe { *ThinTrc::as_ptr(&thin) }, 100);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn as_ptr(this: &Self) -> *const T {
+        return thin_data(this.header);
+    }
+}
+
+impl<T: ?Sized> Clone for ThinTrc<T> {
+    /// Clone a `ThinTrc` (increment its count).
+    /// The process is aborted if the count overflows, see [overflow](crate#reference-count-overflow).
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::ThinTrc;
+    ///
+    /// let thin = ThinTrc::new(100);
+    /// let thin2 = thin.clone();
+    /// assert!(ThinTrc::ptr_eq(&thin, &thin2));
+    /// ```
+    #[inline]
+    fn clone(&self) -> Self {
+        //The count is only accessed by the thread which holds the `ThinTrc`s, so it is not incremented atomically.
+        let count = &unsafe { self.header.as_ref() }.count;
+        let new = count.load(Relaxed) + 1;
+        count.store(new, Relaxed);
+        if new > MAX_REFCOUNT {
+            overflow::atomic(count, "Overflow of maximum local reference count.");
+        }
+        return Self {
+            header: self.header,
+            phantom: PhantomData,
+        };
+    }
+}
+
+impl<T: ?Sized> Drop for ThinTrc<T> {
+    #[inline]
+    fn drop(&mut self) {
+        let count = &unsafe { self.header.as_ref() }.count;
+        let new = count.load(Relaxed) - 1;
+        count.store(new, Relaxed);
+        if new != 0 {
+            return;
+        }
+
+        unsafe { drop_thin(self.header) };
+    }
+}
+
+impl<T: ?Sized> Deref for ThinTrc<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        return unsafe { &*Self::as_ptr(self) };
+    }
+}
+
+impl<T: ?Sized> From<Box<T>> for ThinTrc<T> {
+    /// Move a boxed value into a new `ThinTrc`. This works for unsized values such as `Box<[T]>`, `Box<str>` and
+    /// `Box<dyn Trait>`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::ThinTrc;
+    ///
+    /// let thin: ThinTrc<[i32]> = ThinTrc::from(Box::from([1, 2, 3]));
+    /// assert_eq!(*thin, [1, 2, 3]);
+    /// ```
+    fn from(value: Box<T>) -> Self {
+        return Self {
+            header: box_into_thin(value),
+            phantom: PhantomData,
+        };
+    }
+}
+
+impl<T> From<Vec<T>> for ThinTrc<[T]> {
+    /// Move the elements of a `Vec` into a new `ThinTrc<[T]>`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::ThinTrc;
+    ///
+    /// let thin: ThinTrc<[i32]> = ThinTrc::from(vec![1, 2, 3]);
+    /// assert_eq!(*thin, [1, 2, 3]);
+    /// ```
+    fn from(value: Vec<T>) -> Self {
+        return Self {
+            header: vec_into_thin(value),
+            phantom: PhantomData,
+        };
+    }
+}
+
+impl From<&str> for ThinTrc<str> {
+    /// Copy a string slice into a new `ThinTrc<str>`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::ThinTrc;
+    ///
+    /// let thin: ThinTrc<str> = ThinTrc::from("Trc");
+    /// assert_eq!(&*thin, "Trc");
+    /// ```
+    fn from(value: &str) -> Self {
+        return Self {
+            header: str_into_thin(value),
+            phantom: PhantomData,
+        };
+    }
+}
+
+impl<T: ?Sized> TryFrom<Trc<T>> for ThinTrc<T> {
+    type Error = Trc<T>;
+
+    /// Move the value of a `Trc` into a new `ThinTrc`, if the `Trc` is the only strong reference, including the local
+    /// one. Otherwise, the `Trc` is returned. `Weak`s to the value can no longer be upgraded afterwards.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{ThinTrc, Trc};
+    ///
+    /// let trc: Trc<[i32]> = Trc::from(vec![1, 2, 3]);
+    /// let trc2 = trc.clone();
+    /// let trc = ThinTrc::try_from(trc).unwrap_err();
+    ///
+    /// drop(trc2);
+    /// let thin = ThinTrc::try_from(trc).ok().unwrap();
+    /// assert_eq!(*thin, [1, 2, 3]);
+    /// ```
+    fn try_from(value: Trc<T>) -> Result<Self, Trc<T>> {
+        //Setting the atomic count to 0 prevents `Weak`s from upgrading while the value is moved out.
+        if *unsafe { value.threadref.as_ref() } != 1
+            || unsafe { value.shared.as_ref() }
+                .atomicref
+                .compare_exchange(1, 0, Acquire, Relaxed)
+                .is_err()
+        {
+            return Err(value);
+        }
+
+        let value = ManuallyDrop::new(value);
+        unsafe {
+            let header = move_into_thin(addr_of_mut!((*value.shared.as_ptr()).data));
+            free_threadref(value.shared.as_ptr(), value.threadref);
+            //Clean up implicit self-reference
+            drop(Weak { data: value.shared });
+
+            return Ok(Self {
+                header,
+                phantom: PhantomData,
+            });
+        }
+    }
+}
+
+impl<T: ?Sized> TryFrom<ThinTrc<T>> for Trc<T> {
+    type Error = ThinTrc<T>;
+
+    /// Move the value of a `ThinTrc` into a new `Trc`, if the `ThinTrc` is the only one to its value. Otherwise, the
+    /// `ThinTrc` is returned.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{ThinTrc, Trc};
+    ///
+    /// let thin = ThinTrc::new(100);
+    /// let trc = Trc::<i32>::try_from(thin).ok().unwrap();
+    /// assert_eq!(Trc::atomic_count(&trc), 1);
+    /// ```
+    fn try_from(value: ThinTrc<T>) -> Result<Self, ThinTrc<T>> {
+        if ThinTrc::count(&value) != 1 {
+            return Err(value);
+        }
+
+        let value = ManuallyDrop::new(value);
+        let shared = unsafe { take_thin(value.header, |data| move_into_internal(data)) };
+        return Ok(Self {
+            shared,
+            threadref: new_threadref(),
+            phantom: PhantomData,
+        });
+    }
+}
+
+impl<T: ?Sized> TryFrom<ThinSharedTrc<T>> for ThinTrc<T> {
+    type Error = ThinSharedTrc<T>;
+
+    /// Convert a `ThinSharedTrc` into a `ThinTrc`, if the `ThinSharedTrc` is the only one to its value. Otherwise, the
+    /// `ThinSharedTrc` is returned. The value is not moved.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{ThinSharedTrc, ThinTrc};
+    ///
+    /// let shared = ThinSharedTrc::new(100);
+    /// let ptr = ThinSharedTrc::as_ptr(&shared);
+    /// let thin = ThinTrc::try_from(shared).ok().unwrap();
+    /// assert_eq!(ThinTrc::as_ptr(&thin), ptr);
+    /// ```
+    fn try_from(value: ThinSharedTrc<T>) -> Result<Self, ThinSharedTrc<T>> {
+        //Synchronizes with the drops of the other `ThinSharedTrc`s.
+        if unsafe { value.header.as_ref() }.count.load(Acquire) != 1 {
+            return Err(value);
+        }
+
+        let value = ManuallyDrop::new(value);
+        return Ok(Self {
+            header: value.header,
+            phantom: PhantomData,
+        });
+    }
+}
+
+impl<T: ?Sized + Debug> Debug for ThinTrc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized + Display> Display for ThinTrc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&**self, f)
+    }
+}
+
+/// A single-pointer version of [`SharedTrc<T>`]. The count and the metadata of the value, which is the length of a slice
+/// or the vtable of a trait object, are stored in a header at the start of the allocation, so the handle is one word
+/// even if `T` is unsized.
+///
+/// Cloning a `ThinSharedTrc` atomically increments its count, and `ThinSharedTrc` can be sent to other threads if `T` is
+/// `Send + Sync`.
+///
+/// # Examples
+#[cfg_attr(feature = "single-threaded", doc = "```ignore")]
+#[cfg_attr(not(feature = "single-threaded"), doc = "```")]
+/// use std::thread;
+/// use trc::ThinSharedTrc;
+///
+/// let thin: ThinSharedTrc<[i32]> = ThinSharedTrc::from(vec![1, 2, 3]);
+/// assert_eq!(std::mem::size_of_val(&thin), std::mem::size_of::<usize>());
+///
+/// let thin2 = thin.clone();
+/// let handle = thread::spawn(move || thin2.iter().sum::<i32>());
+/// assert_eq!(handle.join().unwrap(), 6);
+/// ```
+pub struct ThinSharedTrc<T: ?Sized> {
+    header: NonNull<ThinHeader<T>>,
+    phantom: PhantomData<ThinInner<T>>,
+}
+
+if_thread_safe! {
+    unsafe impl<T: ?Sized + Sync + Send> Send for ThinSharedTrc<T> {}
+    unsafe impl<T: ?Sized + Sync + Send> Sync for ThinSharedTrc<T> {}
+}
+
+impl<T> ThinSharedTrc<T> {
+    /// Create a new `ThinSharedTrc` holding `value`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::ThinSharedTrc;
+    ///
+    /// let thin = ThinSharedTrc::new(100);
+    /// assert_eq!(*thin, 100);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn new(value: T) -> Self {
+        let mut value = ManuallyDrop::new(value);
+        return Self {
+            header: unsafe { move_into_thin(&mut *value as *mut T) },
+            phantom: PhantomData,
+        };
+    }
+}
+
+impl<T: ?Sized> ThinSharedTrc<T> {
+    /// Return the number of `ThinSharedTrc`s to the value, on any thread.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::ThinSharedTrc;
+    ///
+    /// let thin = ThinSharedTrc::new(100);
+    /// let thin2 = thin.clone();
+    /// assert_eq!(ThinSharedTrc::count(&thin), 2);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn count(this: &Self) -> usize {
+        return unsafe { this.header.as_ref() }.count.load(Relaxed);
+    }
+
+    /// Return whether two `ThinSharedTrc`s point to the same allocation.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::ThinSharedTrc;
+    ///
+    /// let thin = ThinSharedTrc::new(100);
+    /// let thin2 = thin.clone();
+    /// assert!(ThinSharedTrc::ptr_eq(&thin, &thin2));
+    /// assert!(!ThinSharedTrc::ptr_eq(&thin, &ThinSharedTrc::new(100)));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        return this.header == other.header;
+    }
+
+    /// Return a raw pointer to the value.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::ThinSharedTrc;
+    ///
+    /// let thin = ThinSharedTrc::new(100);
+    /// assert_eq!(unsafe { *ThinSharedTrc::as_ptr(&thin) }, 100);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn as_ptr(this: &Self) -> *const T {
+        return thin_data(this.header);
+    }
+}
+
+impl<T: ?Sized> Clone for ThinSharedTrc<T> {
+    /// Clone a `ThinSharedTrc` (atomically increment its count).
+    /// The process is aborted if the count overflows, see [overflow](crate#reference-count-overflow).
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::ThinSharedTrc;
+    ///
+    /// let thin = ThinSharedTrc::new(100);
+    /// let thin2 = thin.clone();
+    /// assert!(ThinSharedTrc::ptr_eq(&thin, &thin2));
+    /// ```
+    #[inline]
+    fn clone(&self) -> Self {
+        let prev = unsafe { self.header.as_ref() }.count.fetch_add(1, Relaxed);
+        if prev > MAX_REFCOUNT {
+            overflow::atomic(
+                &unsafe { self.header.as_ref() }.count,
+                "Overflow of maximum atomic reference count.",
+            );
+        }
+        return Self {
+            header: self.header,
+            phantom: PhantomData,
+        };
+    }
+}
+
+impl<T: ?Sized> Drop for ThinSharedTrc<T> {
+    #[inline]
+    fn drop(&mut self) {
+        if unsafe { self.header.as_ref() }.count.fetch_sub(1, Release) != 1 {
+            return;
+        }
+
+        fence(Acquire);
+        unsafe { drop_thin(self.header) };
+    }
+}
+
+impl<T: ?Sized> Deref for ThinSharedTrc<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        return unsafe { &*Self::as_ptr(self) };
+    }
+}
+
+impl<T: ?Sized> From<Box<T>> for ThinSharedTrc<T> {
+    /// Move a boxed value into a new `ThinSharedTrc`. This works for unsized values such as `Box<[T]>`, `Box<str>` and
+    /// `Box<dyn Trait + Send + Sync>`.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::fmt::Display;
+    /// use trc::ThinSharedTrc;
+    ///
+    /// let thin: ThinSharedTrc<dyn Display + Send + Sync> = ThinSharedTrc::from(Box::new(100) as Box<_>);
+    /// assert_eq!(thin.to_string(), "100");
+    /// ```
+    fn from(value: Box<T>) -> Self {
+        return Self {
+            header: box_into_thin(value),
+            phantom: PhantomData,
+        };
+    }
+}
+
+impl<T> From<Vec<T>> for ThinSharedTrc<[T]> {
+    /// Move the elements of a `Vec` into a new `ThinSharedTrc<[T]>`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::ThinSharedTrc;
+    ///
+    /// let thin: ThinSharedTrc<[i32]> = ThinSharedTrc::from(vec![1, 2, 3]);
+    /// assert_eq!(*thin, [1, 2, 3]);
+    /// ```
+    fn from(value: Vec<T>) -> Self {
+        return Self {
+            header: vec_into_thin(value),
+            phantom: PhantomData,
+        };
+    }
+}
+
+impl From<&str> for ThinSharedTrc<str> {
+    /// Copy a string slice into a new `ThinSharedTrc<str>`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::ThinSharedTrc;
+    ///
+    /// let thin: ThinSharedTrc<str> = ThinSharedTrc::from("Trc");
+    /// assert_eq!(&*thin, "Trc");
+    /// ```
+    fn from(value: &str) -> Self {
+        return Self {
+            header: str_into_thin(value),
+            phantom: PhantomData,
+        };
+    }
+}
+
+impl<T: ?Sized> TryFrom<SharedTrc<T>> for ThinSharedTrc<T> {
+    type Error = SharedTrc<T>;
+
+    /// Move the value of a `SharedTrc` into a new `ThinSharedTrc`, if there are no other `Trc`s or `SharedTrc`s to it.
+    /// Otherwise, the `SharedTrc` is returned. `Weak`s to the value can no longer be upgraded afterwards.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{SharedTrc, ThinSharedTrc};
+    ///
+    /// let shared = SharedTrc::new(100);
+    /// let shared2 = shared.clone();
+    /// let shared = ThinSharedTrc::try_from(shared).unwrap_err();
+    ///
+    /// drop(shared2);
+    /// let thin = ThinSharedTrc::try_from(shared).ok().unwrap();
+    /// assert_eq!(*thin, 100);
+    /// ```
+    fn try_from(value: SharedTrc<T>) -> Result<Self, SharedTrc<T>> {
+        //Setting the atomic count to 0 prevents `Weak`s from upgrading while the value is moved out.
+        if unsafe { value.data.as_ref() }
+            .atomicref
+            .compare_exchange(1, 0, Acquire, Relaxed)
+            .is_err()
+        {
+            return Err(value);
+        }
+
+        let value = ManuallyDrop::new(value);
+        unsafe {
+            let header = move_into_thin(addr_of_mut!((*value.data.as_ptr()).data));
+            //Clean up implicit self-reference
+            drop(Weak { data: value.data });
+
+            return Ok(Self {
+                header,
+                phantom: PhantomData,
+            });
+        }
+    }
+}
+
+impl<T: ?Sized> TryFrom<ThinSharedTrc<T>> for SharedTrc<T> {
+    type Error = ThinSharedTrc<T>;
+
+    /// Move the value of a `ThinSharedTrc` into a new `SharedTrc`, if the `ThinSharedTrc` is the only one to its value.
+    /// Otherwise, the `ThinSharedTrc` is returned.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{SharedTrc, ThinSharedTrc};
+    ///
+    /// let thin = ThinSharedTrc::new(100);
+    /// let shared = SharedTrc::try_from(thin).ok().unwrap();
+    /// assert_eq!(SharedTrc::atomic_count(&shared), 1);
+    /// ```
+    fn try_from(value: ThinSharedTrc<T>) -> Result<Self, ThinSharedTrc<T>> {
+        //Synchronizes with the drops of the other `ThinSharedTrc`s.
+        if unsafe { value.header.as_ref() }.count.load(Acquire) != 1 {
+            return Err(value);
+        }
+
+        let value = ManuallyDrop::new(value);
+        let data = unsafe { take_thin(value.header, |data| move_into_internal(data)) };
+        return Ok(Self {
+            data,
+            phantom: PhantomData,
+        });
+    }
+}
+
+impl<T: ?Sized> TryFrom<ThinTrc<T>> for ThinSharedTrc<T> {
+    type Error = ThinTrc<T>;
+
+    /// Convert a `ThinTrc` into a `ThinSharedTrc`, if the `ThinTrc` is the only one to its value. Otherwise, the
+    /// `ThinTrc` is returned. The value is not moved.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{ThinSharedTrc, ThinTrc};
+    ///
+    /// let thin = ThinTrc::new(100);
+    /// let thin2 = thin.clone();
+    /// let thin = ThinSharedTrc::try_from(thin).unwrap_err();
+    ///
+    /// drop(thin2);
+    /// let shared = ThinSharedTrc::try_from(thin).ok().unwrap();
+    /// assert_eq!(*shared, 100);
+    /// ```
+    fn try_from(value: ThinTrc<T>) -> Result<Self, ThinTrc<T>> {
+        if ThinTrc::count(&value) != 1 {
+            return Err(value);
+        }
+
+        let value = ManuallyDrop::new(value);
+        return Ok(Self {
+            header: value.header,
+            phantom: PhantomData,
+        });
+    }
+}
+
+impl<T: ?Sized + Debug> Debug for ThinSharedTrc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized + Display> Display for ThinSharedTrc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&**self, f)
+    }
+}
+
+/// Move the (possibly unsized) value out of a `Box` into a new thin allocation, and free the `Box` without dropping the
+/// value.
+fn box_into_thin<T: ?Sized>(value: Box<T>) -> NonNull<ThinHeader<T>> {
+    let src = Box::into_raw(value);
+    unsafe {
+        let header = move_into_thin(src);
+        //Free the `Box` without dropping the value, which was moved.
+        drop(Box::from_raw(src as *mut ManuallyDrop<T>));
+        return header;
+    }
+}
+
+/// Move the elements of a `Vec` into a new thin allocation, and free the buffer of the `Vec` without dropping the
+/// elements.
+fn vec_into_thin<T>(mut value: Vec<T>) -> NonNull<ThinHeader<[T]>> {
+    unsafe {
+        let header = move_into_thin(slice_from_raw_parts_mut(value.as_mut_ptr(), value.len()));
+        value.set_len(0);
+        return header;
+    }
+}
+
+/// Copy a string slice into a new thin allocation.
+fn str_into_thin(value: &str) -> NonNull<ThinHeader<str>> {
+    //The bytes of a `str` are only read, and need no drop.
+    return unsafe { move_into_thin(value as *const str as *mut str) };
+}
@@ -0,0 +1,490 @@
+//! A cheaply cloneable and sliceable byte buffer.
+
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt::{self, Debug},
+    hash::{Hash, Hasher},
+    ops::{Deref, RangeBounds},
+};
+
+use crate::{slice_range, SharedTrc, Trc};
+
+#[derive(Clone)]
+enum Repr {
+    Static(&'static [u8]),
+    Shared(SharedTrc<[u8]>),
+}
+
+/// A contiguous range of bytes in a shared allocation, similar to `bytes::Bytes`.
+///
+/// Cloning a `TrcBytes` increments the atomic count of the allocation, and [`TrcBytes::slice`], [`TrcBytes::split_to`]
+/// and [`TrcBytes::split_off`] create handles to parts of it without copying. A `TrcBytes` can also point to a
+/// `&'static [u8]`, in which case no allocation or reference counting is needed at all.
+///
+/// # Examples
+/// ```
+/// use trc::TrcBytes;
+///
+/// let mut packet = TrcBytes::from(b"\x00\x03abcrest".to_vec());
+/// let header = packet.split_to(2);
+/// let len = u16::from_be_bytes([header[0], header[1]]) as usize;
+/// let payload = packet.split_to(len);
+///
+/// assert_eq!(payload, b"abc"[..]);
+/// assert_eq!(packet, b"rest"[..]);
+/// ```
+#[derive(Clone)]
+pub struct TrcBytes {
+    repr: Repr,
+    start: usize,
+    end: usize,
+}
+
+impl TrcBytes {
+    /// Create a new, empty `TrcBytes`. This does not allocate.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::TrcBytes;
+    ///
+    /// let bytes = TrcBytes::new();
+    /// assert!(bytes.is_empty());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self {
+        return Self::from_static(&[]);
+    }
+
+    /// Create a `TrcBytes` pointing to a static slice. This does not allocate, and the bytes are never copied.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::TrcBytes;
+    ///
+    /// let bytes = TrcBytes::from_static(b"Trc");
+    /// assert_eq!(bytes, b"Trc"[..]);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn from_static(bytes: &'static [u8]) -> Self {
+        return Self {
+            repr: Repr::Static(bytes),
+            start: 0,
+            end: bytes.len(),
+        };
+    }
+
+    /// Return the number of bytes in this `TrcBytes`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::TrcBytes;
+    ///
+    /// let bytes = TrcBytes::from(vec![1, 2, 3]);
+    /// assert_eq!(bytes.len(), 3);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        return self.end - self.start;
+    }
+
+    /// Return whether this `TrcBytes` is empty.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::TrcBytes;
+    ///
+    /// let mut bytes = TrcBytes::from(vec![1]);
+    /// assert!(!bytes.is_empty());
+    /// bytes.clear();
+    /// assert!(bytes.is_empty());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        return self.start == self.end;
+    }
+
+    /// Return the bytes as a slice.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::TrcBytes;
+    ///
+    /// let bytes = TrcBytes::from(vec![1, 2, 3]);
+    /// assert_eq!(bytes.as_slice(), [1, 2, 3]);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn as_slice(&self) -> &[u8] {
+        let all: &[u8] = match &self.repr {
+            Repr::Static(bytes) => bytes,
+            Repr::Shared(shared) => shared,
+        };
+        return &all[self.start..self.end];
+    }
+
+    /// Return a `TrcBytes` pointing to the bytes in `range`, which shares this allocation.
+    ///
+    /// # Panics
+    /// Panics if `range` is out of bounds.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::TrcBytes;
+    ///
+    /// let bytes = TrcBytes::from(b"hello world".to_vec());
+    /// assert_eq!(bytes.slice(6..), b"world"[..]);
+    /// assert_eq!(bytes.slice(..=4), b"hello"[..]);
+    /// ```
+    #[must_use]
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> Self {
+        let range = slice_range(range, self.len());
+        return Self {
+            repr: self.repr.clone(),
+            start: self.start + range.start,
+            end: self.start + range.end,
+        };
+    }
+
+    /// Split the bytes at `at`. `self` is left with the bytes from `at` to the end, and the bytes before `at` are
+    /// returned. Both share this allocation.
+    ///
+    /// # Panics
+    /// Panics if `at` is greater than the length.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::TrcBytes;
+    ///
+    /// let mut bytes = TrcBytes::from(b"key=value".to_vec());
+    /// let key = bytes.split_to(3);
+    /// assert_eq!(key, b"key"[..]);
+    /// assert_eq!(bytes, b"=value"[..]);
+    /// ```
+    #[must_use = "use `advance` to discard the bytes"]
+    pub fn split_to(&mut self, at: usize) -> Self {
+        assert!(at <= self.len(), "Split index is out of bounds.");
+        let front = Self {
+            repr: self.repr.clone(),
+            start: self.start,
+            end: self.start + at,
+        };
+        self.start += at;
+        return front;
+    }
+
+    /// Split the bytes at `at`. `self` is left with the bytes before `at`, and the bytes from `at` to the end are
+    /// returned. Both share this allocation.
+    ///
+    /// # Panics
+    /// Panics if `at` is greater than the length.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::TrcBytes;
+    ///
+    /// let mut bytes = TrcBytes::from(b"key=value".to_vec());
+    /// let value = bytes.split_off(4);
+    /// assert_eq!(bytes, b"key="[..]);
+    /// assert_eq!(value, b"value"[..]);
+    /// ```
+    #[must_use = "use `truncate` to discard the bytes"]
+    pub fn split_off(&mut self, at: usize) -> Self {
+        assert!(at <= self.len(), "Split index is out of bounds.");
+        let back = Self {
+            repr: self.repr.clone(),
+            start: self.start + at,
+            end: self.end,
+        };
+        self.end = self.start + at;
+        return back;
+    }
+
+    /// Discard the first `count` bytes.
+    ///
+    /// # Panics
+    /// Panics if `count` is greater than the length.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::TrcBytes;
+    ///
+    /// let mut bytes = TrcBytes::from_static(b"  Trc");
+    /// bytes.advance(2);
+    /// assert_eq!(bytes, b"Trc"[..]);
+    /// ```
+    #[inline]
+    pub fn advance(&mut self, count: usize) {
+        assert!(count <= self.len(), "Advance count is out of bounds.");
+        self.start += count;
+    }
+
+    /// Shorten the bytes to `len`, discarding the rest. This has no effect if `len` is greater than the length.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::TrcBytes;
+    ///
+    /// let mut bytes = TrcBytes::from_static(b"Trc\n");
+    /// bytes.truncate(3);
+    /// assert_eq!(bytes, b"Trc"[..]);
+    /// ```
+    #[inline]
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len() {
+            self.end = self.start + len;
+        }
+    }
+
+    /// Discard all bytes. The allocation is kept alive until this `TrcBytes` is dropped.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::TrcBytes;
+    ///
+    /// let mut bytes = TrcBytes::from_static(b"Trc");
+    /// bytes.clear();
+    /// assert!(bytes.is_empty());
+    /// ```
+    #[inline]
+    pub fn clear(&mut self) {
+        self.end = self.start;
+    }
+}
+
+impl Default for TrcBytes {
+    /// Create a new, empty `TrcBytes`. See [`TrcBytes::new`].
+    #[inline]
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+impl Deref for TrcBytes {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        return self.as_slice();
+    }
+}
+
+impl AsRef<[u8]> for TrcBytes {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        return self.as_slice();
+    }
+}
+
+impl Borrow<[u8]> for TrcBytes {
+    #[inline]
+    fn borrow(&self) -> &[u8] {
+        return self.as_slice();
+    }
+}
+
+impl From<&'static [u8]> for TrcBytes {
+    /// Create a `TrcBytes` pointing to a static slice. See [`TrcBytes::from_static`].
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::TrcBytes;
+    ///
+    /// let bytes = TrcBytes::from(&b"Trc"[..]);
+    /// assert_eq!(bytes.len(), 3);
+    /// ```
+    #[inline]
+    fn from(value: &'static [u8]) -> Self {
+        return Self::from_static(value);
+    }
+}
+
+impl From<&'static str> for TrcBytes {
+    /// Create a `TrcBytes` pointing to the bytes of a static string.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::TrcBytes;
+    ///
+    /// let bytes = TrcBytes::from("Trc");
+    /// assert_eq!(bytes, b"Trc"[..]);
+    /// ```
+    #[inline]
+    fn from(value: &'static str) -> Self {
+        return Self::from_static(value.as_bytes());
+    }
+}
+
+impl From<SharedTrc<[u8]>> for TrcBytes {
+    /// Create a `TrcBytes` pointing to all bytes of `value`, without copying them.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{SharedTrc, TrcBytes};
+    ///
+    /// let shared: SharedTrc<[u8]> = SharedTrc::from(vec![1, 2, 3]);
+    /// let bytes = TrcBytes::from(shared);
+    /// assert_eq!(bytes, [1, 2, 3][..]);
+    /// ```
+    #[inline]
+    fn from(value: SharedTrc<[u8]>) -> Self {
+        let end = value.len();
+        return Self {
+            repr: Repr::Shared(value),
+            start: 0,
+            end,
+        };
+    }
+}
+
+impl From<Trc<[u8]>> for TrcBytes {
+    /// Create a `TrcBytes` pointing to all bytes of `value`, without copying them.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{Trc, TrcBytes};
+    ///
+    /// let trc: Trc<[u8]> = Trc::from(vec![1, 2, 3]);
+    /// let bytes = TrcBytes::from(trc);
+    /// assert_eq!(bytes, [1, 2, 3][..]);
+    /// ```
+    #[inline]
+    fn from(value: Trc<[u8]>) -> Self {
+        return Self::from(SharedTrc::from(value));
+    }
+}
+
+impl From<Vec<u8>> for TrcBytes {
+    /// Move the bytes of `value` into a new allocation.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::TrcBytes;
+    ///
+    /// let bytes = TrcBytes::from(vec![1, 2, 3]);
+    /// assert_eq!(bytes, [1, 2, 3][..]);
+    /// ```
+    #[inline]
+    fn from(value: Vec<u8>) -> Self {
+        return Self::from(SharedTrc::from(value));
+    }
+}
+
+impl From<Box<[u8]>> for TrcBytes {
+    /// Move the bytes of `value` into a new allocation.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::TrcBytes;
+    ///
+    /// let bytes = TrcBytes::from(vec![1, 2, 3].into_boxed_slice());
+    /// assert_eq!(bytes, [1, 2, 3][..]);
+    /// ```
+    #[inline]
+    fn from(value: Box<[u8]>) -> Self {
+        return Self::from(SharedTrc::from(value));
+    }
+}
+
+impl From<String> for TrcBytes {
+    /// Move the bytes of `value` into a new allocation.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::TrcBytes;
+    ///
+    /// let bytes = TrcBytes::from(String::from("Trc"));
+    /// assert_eq!(bytes, b"Trc"[..]);
+    /// ```
+    #[inline]
+    fn from(value: String) -> Self {
+        return Self::from(value.into_bytes());
+    }
+}
+
+impl From<TrcBytes> for Vec<u8> {
+    /// Copy the bytes into a new `Vec`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::TrcBytes;
+    ///
+    /// let bytes = TrcBytes::from_static(b"Trc");
+    /// assert_eq!(Vec::from(bytes), b"Trc");
+    /// ```
+    #[inline]
+    fn from(value: TrcBytes) -> Self {
+        return value.as_slice().to_vec();
+    }
+}
+
+impl FromIterator<u8> for TrcBytes {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = u8>>(iter: I) -> Self {
+        return Self::from(iter.into_iter().collect::<Vec<u8>>());
+    }
+}
+
+impl PartialEq for TrcBytes {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        return self.as_slice() == other.as_slice();
+    }
+}
+
+impl Eq for TrcBytes {}
+
+impl PartialEq<[u8]> for TrcBytes {
+    #[inline]
+    fn eq(&self, other: &[u8]) -> bool {
+        return self.as_slice() == other;
+    }
+}
+
+impl PartialEq<&[u8]> for TrcBytes {
+    #[inline]
+    fn eq(&self, other: &&[u8]) -> bool {
+        return self.as_slice() == *other;
+    }
+}
+
+impl PartialOrd for TrcBytes {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+impl Ord for TrcBytes {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        return self.as_slice().cmp(other.as_slice());
+    }
+}
+
+impl Hash for TrcBytes {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state);
+    }
+}
+
+impl Debug for TrcBytes {
+    /// Format the bytes as a byte string literal, escaping non-printable bytes.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::TrcBytes;
+    ///
+    /// let bytes = TrcBytes::from_static(b"Trc\n\x00");
+    /// assert_eq!(format!("{bytes:?}"), r#"b"Trc\n\x00""#);
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "b\"{}\"", self.as_slice().escape_ascii())
+    }
+}
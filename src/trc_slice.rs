@@ -0,0 +1,254 @@
+//! A shared sub-slice of a `Trc<[T]>`.
+
+use alloc::vec::Vec;
+use core::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt::{self, Debug},
+    hash::{Hash, Hasher},
+    ops::{Deref, RangeBounds},
+};
+
+use crate::{slice_range, Trc};
+
+/// A range of the elements of a [`Trc<[T]>`](Trc), which shares its allocation. It is created with [`Trc::slice`].
+///
+/// Cloning a `TrcSlice` increments the local count of the `Trc`, and [`TrcSlice::slice`] and [`TrcSlice::split_at`]
+/// create handles to parts of it without copying. The whole allocation is kept alive while any `TrcSlice` of it exists.
+///
+/// # Examples
+/// ```
+/// use trc::{Trc, TrcSlice};
+///
+/// let rows: Trc<[u32]> = Trc::from(vec![1, 2, 3, 4, 5, 6]);
+/// let columns: Vec<TrcSlice<u32>> = (0..3).map(|i| Trc::slice(&rows, i * 2..i * 2 + 2)).collect();
+///
+/// assert_eq!(*columns[1], [3, 4]);
+/// assert_eq!(Trc::local_count(&rows), 4);
+/// ```
+pub struct TrcSlice<T> {
+    trc: Trc<[T]>,
+    start: usize,
+    end: usize,
+}
+
+impl<T> TrcSlice<T> {
+    /// Create a `TrcSlice` of all elements of `trc`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{Trc, TrcSlice};
+    ///
+    /// let slice = TrcSlice::new(Trc::from(vec![1, 2, 3]));
+    /// assert_eq!(*slice, [1, 2, 3]);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn new(trc: Trc<[T]>) -> Self {
+        let end = trc.len();
+        return Self { trc, start: 0, end };
+    }
+
+    /// Return the elements as a slice.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{Trc, TrcSlice};
+    ///
+    /// let slice = TrcSlice::new(Trc::from(vec![1, 2, 3]));
+    /// assert_eq!(slice.as_slice(), [1, 2, 3]);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn as_slice(&self) -> &[T] {
+        return &self.trc[self.start..self.end];
+    }
+
+    /// Return a `TrcSlice` of the elements in `range` of this one, which shares the allocation.
+    ///
+    /// # Panics
+    /// Panics if `range` is out of bounds.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{Trc, TrcSlice};
+    ///
+    /// let trc: Trc<[i32]> = Trc::from(vec![1, 2, 3, 4]);
+    /// let slice = Trc::slice(&trc, 1..);
+    /// assert_eq!(*slice.slice(..2), [2, 3]);
+    /// ```
+    #[must_use]
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> Self {
+        let range = slice_range(range, self.len());
+        return Self {
+            trc: self.trc.clone(),
+            start: self.start + range.start,
+            end: self.start + range.end,
+        };
+    }
+
+    /// Split the elements at `mid`, returning a `TrcSlice` of the elements before it and one of the elements from it to
+    /// the end. Both share the allocation.
+    ///
+    /// # Panics
+    /// Panics if `mid` is greater than the length.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{Trc, TrcSlice};
+    ///
+    /// let slice = TrcSlice::new(Trc::from(vec![1, 2, 3]));
+    /// let (left, right) = TrcSlice::split_at(&slice, 1);
+    /// assert_eq!(*left, [1]);
+    /// assert_eq!(*right, [2, 3]);
+    /// ```
+    #[must_use]
+    pub fn split_at(this: &Self, mid: usize) -> (Self, Self) {
+        assert!(mid <= this.len(), "Split index is out of bounds.");
+        return (this.slice(..mid), this.slice(mid..));
+    }
+
+    /// Return the `Trc` holding all elements, including the ones outside of this `TrcSlice`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{Trc, TrcSlice};
+    ///
+    /// let trc: Trc<[i32]> = Trc::from(vec![1, 2, 3]);
+    /// let slice = Trc::slice(&trc, 2..);
+    /// assert!(Trc::ptr_eq(TrcSlice::as_trc(&slice), &trc));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn as_trc(this: &Self) -> &Trc<[T]> {
+        return &this.trc;
+    }
+}
+
+impl<T> Clone for TrcSlice<T> {
+    /// Clone a `TrcSlice` (increment the local count of the `Trc`).
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{Trc, TrcSlice};
+    ///
+    /// let trc: Trc<[i32]> = Trc::from(vec![1, 2, 3]);
+    /// let slice = Trc::slice(&trc, 1..);
+    /// let slice2 = slice.clone();
+    /// assert_eq!(Trc::local_count(&trc), 3);
+    /// ```
+    #[inline]
+    fn clone(&self) -> Self {
+        return Self {
+            trc: self.trc.clone(),
+            start: self.start,
+            end: self.end,
+        };
+    }
+}
+
+impl<T> Deref for TrcSlice<T> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &[T] {
+        return self.as_slice();
+    }
+}
+
+impl<T> AsRef<[T]> for TrcSlice<T> {
+    #[inline]
+    fn as_ref(&self) -> &[T] {
+        return self.as_slice();
+    }
+}
+
+impl<T> Borrow<[T]> for TrcSlice<T> {
+    #[inline]
+    fn borrow(&self) -> &[T] {
+        return self.as_slice();
+    }
+}
+
+impl<T> From<Trc<[T]>> for TrcSlice<T> {
+    /// Create a `TrcSlice` of all elements of `value`. See [`TrcSlice::new`].
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{Trc, TrcSlice};
+    ///
+    /// let trc: Trc<[i32]> = Trc::from(vec![1, 2, 3]);
+    /// let slice = TrcSlice::from(trc);
+    /// assert_eq!(slice.len(), 3);
+    /// ```
+    #[inline]
+    fn from(value: Trc<[T]>) -> Self {
+        return Self::new(value);
+    }
+}
+
+impl<T> From<Vec<T>> for TrcSlice<T> {
+    /// Move the elements of `value` into a new allocation.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::TrcSlice;
+    ///
+    /// let slice = TrcSlice::from(vec![1, 2, 3]);
+    /// assert_eq!(*slice, [1, 2, 3]);
+    /// ```
+    #[inline]
+    fn from(value: Vec<T>) -> Self {
+        return Self::new(Trc::from(value));
+    }
+}
+
+impl<T> FromIterator<T> for TrcSlice<T> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        return Self::from(iter.into_iter().collect::<Vec<T>>());
+    }
+}
+
+impl<T: PartialEq> PartialEq for TrcSlice<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        return self.as_slice() == other.as_slice();
+    }
+}
+
+impl<T: Eq> Eq for TrcSlice<T> {}
+
+impl<T: PartialEq> PartialEq<[T]> for TrcSlice<T> {
+    #[inline]
+    fn eq(&self, other: &[T]) -> bool {
+        return self.as_slice() == other;
+    }
+}
+
+impl<T: PartialOrd> PartialOrd for TrcSlice<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        return self.as_slice().partial_cmp(other.as_slice());
+    }
+}
+
+impl<T: Ord> Ord for TrcSlice<T> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        return self.as_slice().cmp(other.as_slice());
+    }
+}
+
+impl<T: Hash> Hash for TrcSlice<T> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state);
+    }
+}
+
+impl<T: Debug> Debug for TrcSlice<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(self.as_slice(), f)
+    }
+}
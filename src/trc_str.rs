@@ -0,0 +1,343 @@
+//! A shared substring.
+
+use alloc::string::String;
+use core::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt::{self, Debug, Display},
+    hash::{Hash, Hasher},
+    ops::{Deref, RangeBounds},
+};
+
+use crate::{slice_range, Trc};
+
+/// A substring of a [`Trc<str>`], which shares its allocation.
+///
+/// Cloning a `TrcStr` increments the local count of the `Trc`, and [`TrcStr::substr`] and [`TrcStr::slice_ref`] create
+/// handles to parts of the string without copying. This makes it cheap to hand out the tokens, identifiers or header
+/// values found in a larger string. A `TrcStr` compares, orders and hashes by its contents, like a `str`.
+///
+/// # Examples
+/// ```
+/// use trc::TrcStr;
+///
+/// let source = TrcStr::from("let answer = 42;");
+/// let tokens: Vec<TrcStr> = source
+///     .split_whitespace()
+///     .map(|token| source.slice_ref(token))
+///     .collect();
+///
+/// assert_eq!(tokens, ["let", "answer", "=", "42;"]);
+/// ```
+#[derive(Clone)]
+pub struct TrcStr {
+    trc: Trc<str>,
+    start: usize,
+    end: usize,
+}
+
+impl TrcStr {
+    /// Create a `TrcStr` of the whole string in `trc`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{Trc, TrcStr};
+    ///
+    /// let string = TrcStr::new(Trc::from("Trc"));
+    /// assert_eq!(string, "Trc");
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn new(trc: Trc<str>) -> Self {
+        let end = trc.len();
+        return Self { trc, start: 0, end };
+    }
+
+    /// Return the substring as a `str`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::TrcStr;
+    ///
+    /// let string = TrcStr::from("Trc");
+    /// assert_eq!(string.as_str(), "Trc");
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        return &self.trc[self.start..self.end];
+    }
+
+    /// Return a `TrcStr` of the bytes in `range` of this substring, which shares the allocation.
+    ///
+    /// # Panics
+    /// Panics if `range` is out of bounds, or if it does not start and end on a `char` boundary.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::TrcStr;
+    ///
+    /// let string = TrcStr::from("Content-Type: text/plain");
+    /// assert_eq!(string.substr(..12), "Content-Type");
+    /// assert_eq!(string.substr(14..), "text/plain");
+    /// ```
+    #[must_use]
+    pub fn substr<R: RangeBounds<usize>>(&self, range: R) -> Self {
+        let range = slice_range(range, self.len());
+        assert!(
+            self.is_char_boundary(range.start) && self.is_char_boundary(range.end),
+            "Substring range is not on a `char` boundary."
+        );
+        return Self {
+            trc: self.trc.clone(),
+            start: self.start + range.start,
+            end: self.start + range.end,
+        };
+    }
+
+    /// Return a `TrcStr` of `substring`, which must be a part of this substring, for example one returned by
+    /// [`str::split`] or [`str::trim`]. The new `TrcStr` shares the allocation.
+    ///
+    /// # Panics
+    /// Panics if `substring` is not contained in this substring.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::TrcStr;
+    ///
+    /// let line = TrcStr::from("  key = value  ");
+    /// let (key, value) = line.split_once('=').unwrap();
+    /// assert_eq!(line.slice_ref(key.trim()), "key");
+    /// assert_eq!(line.slice_ref(value.trim()), "value");
+    /// ```
+    #[must_use]
+    pub fn slice_ref(&self, substring: &str) -> Self {
+        let this = self.as_str().as_bytes().as_ptr_range();
+        let sub = substring.as_bytes().as_ptr_range();
+        assert!(
+            this.start <= sub.start && sub.end <= this.end,
+            "The substring is not a part of this `TrcStr`."
+        );
+        let start = sub.start.addr() - this.start.addr();
+        return Self {
+            trc: self.trc.clone(),
+            start: self.start + start,
+            end: self.start + start + substring.len(),
+        };
+    }
+
+    /// Return the `Trc` holding the whole string, including the parts outside of this substring.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::TrcStr;
+    ///
+    /// let string = TrcStr::from("Trc").substr(1..);
+    /// assert_eq!(string, "rc");
+    /// assert_eq!(&**string.as_trc(), "Trc");
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn as_trc(&self) -> &Trc<str> {
+        return &self.trc;
+    }
+}
+
+impl Default for TrcStr {
+    /// Create an empty `TrcStr`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::TrcStr;
+    ///
+    /// assert!(TrcStr::default().is_empty());
+    /// ```
+    #[inline]
+    fn default() -> Self {
+        return Self::from("");
+    }
+}
+
+impl Deref for TrcStr {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        return self.as_str();
+    }
+}
+
+impl AsRef<str> for TrcStr {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        return self.as_str();
+    }
+}
+
+impl AsRef<[u8]> for TrcStr {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        return self.as_str().as_bytes();
+    }
+}
+
+impl Borrow<str> for TrcStr {
+    #[inline]
+    fn borrow(&self) -> &str {
+        return self.as_str();
+    }
+}
+
+impl From<&str> for TrcStr {
+    /// Copy `value` into a new allocation.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::TrcStr;
+    ///
+    /// let string = TrcStr::from("Trc");
+    /// assert_eq!(string.len(), 3);
+    /// ```
+    #[inline]
+    fn from(value: &str) -> Self {
+        return Self::new(Trc::from(value));
+    }
+}
+
+impl From<String> for TrcStr {
+    /// Move `value` into a new allocation.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::TrcStr;
+    ///
+    /// let string = TrcStr::from(String::from("Trc"));
+    /// assert_eq!(string, "Trc");
+    /// ```
+    #[inline]
+    fn from(value: String) -> Self {
+        return Self::new(Trc::from(value.into_boxed_str()));
+    }
+}
+
+impl From<Trc<str>> for TrcStr {
+    /// Create a `TrcStr` of the whole string in `value`. See [`TrcStr::new`].
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{Trc, TrcStr};
+    ///
+    /// let trc: Trc<str> = Trc::from("Trc");
+    /// let string = TrcStr::from(trc.clone());
+    /// assert_eq!(Trc::local_count(&trc), 2);
+    /// ```
+    #[inline]
+    fn from(value: Trc<str>) -> Self {
+        return Self::new(value);
+    }
+}
+
+impl From<TrcStr> for String {
+    /// Copy the substring into a new `String`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::TrcStr;
+    ///
+    /// let string = TrcStr::from("Trc");
+    /// assert_eq!(String::from(string), "Trc");
+    /// ```
+    #[inline]
+    fn from(value: TrcStr) -> Self {
+        return String::from(value.as_str());
+    }
+}
+
+impl PartialEq for TrcStr {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        return self.as_str() == other.as_str();
+    }
+}
+
+impl Eq for TrcStr {}
+
+impl PartialEq<str> for TrcStr {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        return self.as_str() == other;
+    }
+}
+
+impl PartialEq<&str> for TrcStr {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        return self.as_str() == *other;
+    }
+}
+
+impl PartialEq<String> for TrcStr {
+    #[inline]
+    fn eq(&self, other: &String) -> bool {
+        return self.as_str() == other;
+    }
+}
+
+impl PartialEq<TrcStr> for str {
+    #[inline]
+    fn eq(&self, other: &TrcStr) -> bool {
+        return self == other.as_str();
+    }
+}
+
+impl PartialEq<TrcStr> for &str {
+    #[inline]
+    fn eq(&self, other: &TrcStr) -> bool {
+        return *self == other.as_str();
+    }
+}
+
+impl PartialOrd for TrcStr {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+impl Ord for TrcStr {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        return self.as_str().cmp(other.as_str());
+    }
+}
+
+impl Hash for TrcStr {
+    /// Hash the contents of the substring, like a `str`, so that a `TrcStr` can be looked up by a `&str` in a
+    /// `HashMap`.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::collections::HashMap;
+    /// use trc::TrcStr;
+    ///
+    /// let mut map = HashMap::new();
+    /// map.insert(TrcStr::from("key: value").substr(..3), 1);
+    /// assert_eq!(map["key"], 1);
+    /// ```
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
+    }
+}
+
+impl Debug for TrcStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl Display for TrcStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(self.as_str(), f)
+    }
+}
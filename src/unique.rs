@@ -0,0 +1,242 @@
+//! An exclusively owned allocation which can later be shared.
+
+use core::{
+    fmt::{self, Debug, Display},
+    marker::PhantomData,
+    mem::ManuallyDrop,
+    ops::{Deref, DerefMut},
+    ptr::{addr_of_mut, write, NonNull},
+};
+
+use crate::{
+    alloc_uninit_internal, new_threadref,
+    ordering::{Relaxed, Release},
+    overflow, weak_count_value, SharedTrc, SharedTrcInternal, Trc, Weak, MAX_WEAKCOUNT,
+};
+
+/// A uniquely owned allocation with the same layout as a [`Trc`], which allows mutable access to the value.
+///
+/// [`Weak`]s to the value can be created with [`UniqueTrc::downgrade`], but they cannot be upgraded until the
+/// `UniqueTrc` is converted into a `Trc` or `SharedTrc` with [`UniqueTrc::into_trc`] or [`UniqueTrc::into_shared`].
+/// This makes it possible to build cyclic structures in several steps, where [`Trc::new_cyclic`] would require a single
+/// closure. If the `UniqueTrc` is dropped instead, the `Weak`s never become upgradeable.
+///
+/// # Examples
+/// ```
+/// use trc::{Trc, UniqueTrc, Weak};
+///
+/// struct Parent {
+///     children: Vec<Trc<Child>>,
+/// }
+///
+/// struct Child {
+///     parent: Weak<Parent>,
+/// }
+///
+/// let mut parent = UniqueTrc::new(Parent { children: Vec::new() });
+/// for _ in 0..3 {
+///     let child = Trc::new(Child { parent: UniqueTrc::downgrade(&parent) });
+///     assert!(child.parent.upgrade().is_none());
+///     parent.children.push(child);
+/// }
+///
+/// let parent = UniqueTrc::into_trc(parent);
+/// let child = &parent.children[0];
+/// assert!(Trc::ptr_eq(&child.parent.upgrade().unwrap(), &parent));
+/// ```
+pub struct UniqueTrc<T> {
+    //The atomic count is 0 until the value is shared, so that `Weak`s cannot be upgraded.
+    data: NonNull<SharedTrcInternal<T>>,
+    phantom: PhantomData<T>,
+}
+
+//`Weak`s created from a `UniqueTrc` may be sent to other threads and upgraded there after it is shared, like `Weak`s of a
+//`Trc`.
+if_thread_safe! {
+    unsafe impl<T: Send + Sync> Send for UniqueTrc<T> {}
+    unsafe impl<T: Send + Sync> Sync for UniqueTrc<T> {}
+}
+
+impl<T> UniqueTrc<T> {
+    /// Create a new `UniqueTrc` holding `value`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::UniqueTrc;
+    ///
+    /// let mut unique = UniqueTrc::new(vec![1]);
+    /// unique.push(2);
+    /// assert_eq!(*unique, [1, 2]);
+    /// ```
+    #[must_use]
+    pub fn new(value: T) -> Self {
+        let data = alloc_uninit_internal::<T>().cast::<SharedTrcInternal<T>>();
+        unsafe {
+            (*data.as_ptr()).atomicref.store(0, Release);
+            write(addr_of_mut!((*data.as_ptr()).data), value);
+        }
+        return Self {
+            data,
+            phantom: PhantomData,
+        };
+    }
+
+    /// Create a `Weak` to the value, which can be upgraded once the `UniqueTrc` has been shared.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::UniqueTrc;
+    ///
+    /// let unique = UniqueTrc::new(100);
+    /// let weak = UniqueTrc::downgrade(&unique);
+    /// assert!(weak.upgrade().is_none());
+    ///
+    /// let trc = UniqueTrc::into_trc(unique);
+    /// assert_eq!(*weak.upgrade().unwrap(), 100);
+    /// ```
+    #[must_use]
+    pub fn downgrade(this: &Self) -> Weak<T> {
+        let prev = unsafe { this.data.as_ref() }
+            .weakcount
+            .fetch_add(1, Relaxed);
+        if weak_count_value(prev) > MAX_WEAKCOUNT {
+            overflow::atomic(
+                &unsafe { this.data.as_ref() }.weakcount,
+                "Overflow of maximum weak reference count.",
+            );
+        }
+        return Weak { data: this.data };
+    }
+
+    /// Convert the `UniqueTrc` into a `Trc`, which allows the `Weak`s created from it to be upgraded.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{Trc, UniqueTrc};
+    ///
+    /// let unique = UniqueTrc::new(100);
+    /// let weak = UniqueTrc::downgrade(&unique);
+    /// let trc = UniqueTrc::into_trc(unique);
+    /// assert_eq!(Trc::weak_count(&trc), 1);
+    /// ```
+    #[must_use]
+    pub fn into_trc(this: Self) -> Trc<T> {
+        let this = ManuallyDrop::new(this);
+        //Publish the value to the `Weak`s which upgrade after this.
+        unsafe { this.data.as_ref() }.atomicref.store(1, Release);
+        return Trc {
+            shared: this.data,
+            threadref: new_threadref(),
+            phantom: PhantomData,
+        };
+    }
+
+    /// Convert the `UniqueTrc` into a `SharedTrc`, which allows the `Weak`s created from it to be upgraded.
+    ///
+    /// # Examples
+    #[cfg_attr(feature = "single-threaded", doc = "```ignore")]
+    #[cfg_attr(not(feature = "single-threaded"), doc = "```")]
+    /// use trc::{SharedTrc, UniqueTrc};
+    ///
+    /// let mut unique = UniqueTrc::new(1);
+    /// *unique += 1;
+    /// let shared = UniqueTrc::into_shared(unique);
+    /// let handle = std::thread::spawn(move || *shared);
+    /// assert_eq!(handle.join().unwrap(), 2);
+    /// ```
+    #[must_use]
+    pub fn into_shared(this: Self) -> SharedTrc<T> {
+        let this = ManuallyDrop::new(this);
+        unsafe { this.data.as_ref() }.atomicref.store(1, Release);
+        return SharedTrc {
+            data: this.data,
+            phantom: PhantomData,
+        };
+    }
+}
+
+impl<T> Deref for UniqueTrc<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        return unsafe { &self.data.as_ref().data };
+    }
+}
+
+impl<T> DerefMut for UniqueTrc<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        //No `Weak` can access the value until it is shared.
+        return unsafe { &mut (*self.data.as_ptr()).data };
+    }
+}
+
+impl<T> Drop for UniqueTrc<T> {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe { core::ptr::drop_in_place(addr_of_mut!((*self.data.as_ptr()).data)) };
+        //Clean up implicit self-reference
+        drop(Weak { data: self.data });
+    }
+}
+
+impl<T: Default> Default for UniqueTrc<T> {
+    /// Create a new `UniqueTrc` holding the default value of `T`.
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::UniqueTrc;
+    ///
+    /// let unique = UniqueTrc::<i32>::default();
+    /// assert_eq!(*unique, 0);
+    /// ```
+    #[inline]
+    fn default() -> Self {
+        return Self::new(T::default());
+    }
+}
+
+impl<T> From<UniqueTrc<T>> for Trc<T> {
+    /// Convert a `UniqueTrc` into a `Trc`. See [`UniqueTrc::into_trc`].
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{Trc, UniqueTrc};
+    ///
+    /// let trc: Trc<i32> = UniqueTrc::new(100).into();
+    /// assert_eq!(*trc, 100);
+    /// ```
+    #[inline]
+    fn from(value: UniqueTrc<T>) -> Self {
+        return UniqueTrc::into_trc(value);
+    }
+}
+
+impl<T> From<UniqueTrc<T>> for SharedTrc<T> {
+    /// Convert a `UniqueTrc` into a `SharedTrc`. See [`UniqueTrc::into_shared`].
+    ///
+    /// # Examples
+    /// ```
+    /// use trc::{SharedTrc, UniqueTrc};
+    ///
+    /// let shared: SharedTrc<i32> = UniqueTrc::new(100).into();
+    /// assert_eq!(*shared, 100);
+    /// ```
+    #[inline]
+    fn from(value: UniqueTrc<T>) -> Self {
+        return UniqueTrc::into_shared(value);
+    }
+}
+
+impl<T: Debug> Debug for UniqueTrc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: Display> Display for UniqueTrc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&**self, f)
+    }
+}
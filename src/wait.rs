@@ -0,0 +1,450 @@
+//! Waiting until a `SharedTrc` is the only handle to its value.
+//!
+//! Waiters are kept in a global registry, keyed by the address of the allocation. When a handle is released and one is
+//! left, the waiters of its allocation are woken, and check again whether they can proceed. Releasing a handle only
+//! checks a global count of waiters without any fence, and locks the registry if anything waits, so releasing handles is
+//! not slowed down otherwise.
+//!
+//! A waiter which registers while the last other handle is released must not miss it. The decrements of the counts
+//! acquire as well as release, and a waiter performs a read-modify-write on both counts after it registers. Either the
+//! waiter's read-modify-write comes later, and it sees the decremented count when it checks again, or the decrement
+//! comes later and synchronizes with it, so the releasing thread sees the waiter.
+
+use alloc::{collections::BTreeMap, sync::Arc, task::Wake, vec::Vec};
+use core::{
+    error::Error,
+    fmt::{self, Debug, Display},
+    future::Future,
+    mem::ManuallyDrop,
+    pin::{pin, Pin},
+    ptr::{self, addr_of},
+    sync::atomic::AtomicUsize,
+    task::{Context, Poll, Waker},
+};
+use std::{
+    sync::Mutex,
+    thread::{self, Thread},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    ordering::{AcqRel, Acquire, Relaxed},
+    SharedTrc, SharedTrcInternal, Weak,
+};
+
+/// The number of registered waiters, so that releasing a handle only locks the registry if anything waits.
+static WAITERS: AtomicUsize = AtomicUsize::new(0);
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+//The waiters of each allocation, with the id of the future or thread which registered them.
+static REGISTRY: Mutex<BTreeMap<usize, Vec<(usize, Waker)>>> = Mutex::new(BTreeMap::new());
+
+fn with_registry<R>(f: impl FnOnce(&mut BTreeMap<usize, Vec<(usize, Waker)>>) -> R) -> R {
+    let mut registry = REGISTRY
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    return f(&mut registry);
+}
+
+/// Wake the waiters of the allocation at `alloc` if one of its counts was decremented from `prev` to 1, so that a single
+/// handle may be left. The decrement must have been `AcqRel`, see the [module documentation](self).
+#[inline]
+pub(crate) fn on_release<T: ?Sized>(prev: usize, alloc: *const T) {
+    if prev == 2 && WAITERS.load(Relaxed) != 0 {
+        notify(alloc.addr());
+    }
+}
+
+#[cold]
+fn notify(alloc: usize) {
+    let wakers = with_registry(|registry| {
+        let wakers = registry.remove(&alloc)?;
+        WAITERS.fetch_sub(wakers.len(), Relaxed);
+        Some(wakers)
+    });
+    for (_, waker) in wakers.into_iter().flatten() {
+        waker.wake();
+    }
+}
+
+/// The registration of a future or thread which waits for an allocation.
+pub(crate) struct Waiter {
+    alloc: usize,
+    id: usize,
+    registered: bool,
+}
+
+impl Waiter {
+    pub(crate) fn new<T: ?Sized>(alloc: *const T) -> Self {
+        return Self {
+            alloc: alloc.addr(),
+            id: NEXT_ID.fetch_add(1, Relaxed),
+            registered: false,
+        };
+    }
+
+    /// Register `waker` to be woken when a handle to `inner` is released. The condition which is waited for must be
+    /// checked again afterwards, as the handle may have been released before.
+    pub(crate) fn register<T: ?Sized>(&mut self, waker: &Waker, inner: &SharedTrcInternal<T>) {
+        with_registry(|registry| {
+            let waiters = registry.entry(self.alloc).or_default();
+            match waiters.iter_mut().find(|(id, _)| *id == self.id) {
+                Some((_, registered)) => registered.clone_from(waker),
+                None => {
+                    waiters.push((self.id, waker.clone()));
+                    WAITERS.fetch_add(1, Relaxed);
+                }
+            }
+        });
+        self.registered = true;
+        //Pairs with the `AcqRel` decrements: either the check which follows sees the decremented count, or the releasing
+        //thread sees the waiter.
+        inner.atomicref.fetch_add(0, AcqRel);
+        inner.weakcount.fetch_add(0, AcqRel);
+    }
+
+    /// Remove the registration, if it was not already removed by waking it.
+    pub(crate) fn unregister(&mut self) {
+        if !self.registered {
+            return;
+        }
+        self.registered = false;
+        with_registry(|registry| {
+            let Some(waiters) = registry.get_mut(&self.alloc) else {
+                return;
+            };
+            let Some(i) = waiters.iter().position(|(id, _)| *id == self.id) else {
+                return;
+            };
+            waiters.swap_remove(i);
+            WAITERS.fetch_sub(1, Relaxed);
+            if waiters.is_empty() {
+                registry.remove(&self.alloc);
+            }
+        });
+    }
+}
+
+impl Drop for Waiter {
+    fn drop(&mut self) {
+        self.unregister();
+    }
+}
+
+/// Move the value out of `shared` if it is the only strong handle, even if there are `Weak`s. Otherwise, return it.
+pub(crate) fn try_take<T>(shared: SharedTrc<T>) -> Result<T, SharedTrc<T>> {
+    //Setting the atomic count to 0 prevents `Weak`s from upgrading while the value is moved out.
+    if unsafe { shared.data.as_ref() }
+        .atomicref
+        .compare_exchange(1, 0, Acquire, Relaxed)
+        .is_err()
+    {
+        return Err(shared);
+    }
+
+    let shared = ManuallyDrop::new(shared);
+    let value = unsafe { ptr::read(addr_of!((*shared.data.as_ptr()).data)) };
+    //Clean up implicit self-reference
+    drop(Weak { data: shared.data });
+    return Ok(value);
+}
+
+/// A future which resolves to a mutable reference to the value of a `SharedTrc` once there are no other `Trc`s,
+/// `SharedTrc`s or `Weak`s to it. It is returned by [`SharedTrc::wait_unique`].
+#[must_use = "Futures do nothing unless they are awaited."]
+pub struct WaitUnique<'a, T: ?Sized> {
+    shared: Option<&'a mut SharedTrc<T>>,
+    waiter: Waiter,
+}
+
+impl<'a, T: ?Sized> Future for WaitUnique<'a, T> {
+    type Output = &'a mut T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<&'a mut T> {
+        let this = self.get_mut();
+        let shared = this
+            .shared
+            .take()
+            .expect("`WaitUnique` polled after completion.");
+        if !SharedTrc::is_unique(shared) {
+            this.waiter
+                .register(cx.waker(), unsafe { shared.data.as_ref() });
+            if !SharedTrc::is_unique(shared) {
+                this.shared = Some(shared);
+                return Poll::Pending;
+            }
+        }
+
+        this.waiter.unregister();
+        //There are no other handles, so none can be created until the reference is released.
+        return Poll::Ready(unsafe { &mut (*shared.data.as_ptr()).data });
+    }
+}
+
+impl<T: ?Sized> Debug for WaitUnique<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WaitUnique").finish_non_exhaustive()
+    }
+}
+
+/// A future which resolves to the value of a `SharedTrc` once there are no other `Trc`s or `SharedTrc`s to it. It is
+/// returned by [`SharedTrc::into_inner_async`].
+#[must_use = "Futures do nothing unless they are awaited."]
+pub struct IntoInnerAsync<T> {
+    shared: Option<SharedTrc<T>>,
+    waiter: Waiter,
+}
+
+impl<T> Future for IntoInnerAsync<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let this = self.get_mut();
+        let shared = this
+            .shared
+            .take()
+            .expect("`IntoInnerAsync` polled after completion.");
+        let shared = match try_take(shared) {
+            Ok(value) => {
+                this.waiter.unregister();
+                return Poll::Ready(value);
+            }
+            Err(shared) => shared,
+        };
+
+        this.waiter
+            .register(cx.waker(), unsafe { shared.data.as_ref() });
+        return match try_take(shared) {
+            Ok(value) => {
+                this.waiter.unregister();
+                Poll::Ready(value)
+            }
+            Err(shared) => {
+                this.shared = Some(shared);
+                Poll::Pending
+            }
+        };
+    }
+}
+
+impl<T> Debug for IntoInnerAsync<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IntoInnerAsync").finish_non_exhaustive()
+    }
+}
+
+/// The error returned by [`SharedTrc::wait_until_unique`] when the other handles were not dropped before the timeout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TimeoutError;
+
+impl Display for TimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Timed out waiting for the other handles to be dropped.")
+    }
+}
+
+impl Error for TimeoutError {}
+
+/// Wakes a thread which is blocked in [`block_on`].
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Poll `future` on the current thread, parking the thread while it is pending, until it completes or `deadline` passes.
+fn block_on<F: Future>(future: F, deadline: Option<Instant>) -> Option<F::Output> {
+    let mut future = pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return Some(output);
+        }
+        match deadline {
+            Some(deadline) => {
+                let now = Instant::now();
+                if now >= deadline {
+                    return None;
+                }
+                thread::park_timeout(deadline - now);
+            }
+            None => thread::park(),
+        }
+    }
+}
+
+impl<T: ?Sized> SharedTrc<T> {
+    /// Return a future which resolves to a mutable reference to the value once there are no other `Trc`s, `SharedTrc`s
+    /// or `Weak`s to it, on any thread. This is the condition under which [`SharedTrc::get_mut`] succeeds.
+    ///
+    /// Dropping the other handles wakes the future, so it does not poll the counts.
+    ///
+    /// # Examples
+    #[cfg_attr(feature = "single-threaded", doc = "```ignore")]
+    #[cfg_attr(not(feature = "single-threaded"), doc = "```")]
+    /// # use std::{future::Future, pin::pin, sync::Arc, task::{Context, Poll, Wake}, thread::{self, Thread}};
+    /// # struct ThreadWaker(Thread);
+    /// # impl Wake for ThreadWaker {
+    /// #     fn wake(self: Arc<Self>) {
+    /// #         self.0.unpark();
+    /// #     }
+    /// # }
+    /// # fn block_on<F: Future>(future: F) -> F::Output {
+    /// #     let mut future = pin!(future);
+    /// #     let waker = Arc::new(ThreadWaker(thread::current())).into();
+    /// #     let mut cx = Context::from_waker(&waker);
+    /// #     loop {
+    /// #         match future.as_mut().poll(&mut cx) {
+    /// #             Poll::Ready(output) => return output,
+    /// #             Poll::Pending => thread::park(),
+    /// #         }
+    /// #     }
+    /// # }
+    /// use trc::SharedTrc;
+    ///
+    /// let mut connections = SharedTrc::new(vec!["a", "b"]);
+    /// let worker = connections.clone();
+    /// let handle = std::thread::spawn(move || drop(worker));
+    ///
+    /// block_on(async {
+    ///     let connections = SharedTrc::wait_unique(&mut connections).await;
+    ///     connections.clear();
+    /// });
+    /// handle.join().unwrap();
+    /// assert!(connections.is_empty());
+    /// ```
+    #[inline]
+    pub fn wait_unique(this: &mut Self) -> WaitUnique<'_, T> {
+        let waiter = Waiter::new(this.data.as_ptr());
+        return WaitUnique {
+            shared: Some(this),
+            waiter,
+        };
+    }
+
+    /// Block the current thread until there are no other `Trc`s, `SharedTrc`s or `Weak`s to the value, and return a
+    /// mutable reference to it. If that takes longer than `timeout`, return a [`TimeoutError`]. This is the blocking
+    /// counterpart of [`SharedTrc::wait_unique`].
+    ///
+    /// The thread is parked while it waits, and unparked when the other handles are dropped.
+    ///
+    /// # Examples
+    #[cfg_attr(feature = "single-threaded", doc = "```ignore")]
+    #[cfg_attr(not(feature = "single-threaded"), doc = "```")]
+    /// use std::time::Duration;
+    /// use trc::SharedTrc;
+    ///
+    /// let mut pool = SharedTrc::new(vec![1, 2, 3]);
+    /// let workers: Vec<_> = (0..4)
+    ///     .map(|_| {
+    ///         let pool = pool.clone();
+    ///         std::thread::spawn(move || pool.len())
+    ///     })
+    ///     .collect();
+    ///
+    /// let pool = SharedTrc::wait_until_unique(&mut pool, Duration::from_secs(60)).unwrap();
+    /// pool.clear();
+    /// # for worker in workers {
+    /// #     worker.join().unwrap();
+    /// # }
+    ///
+    /// let mut shared = SharedTrc::new(100);
+    /// let _shared2 = shared.clone();
+    /// assert!(SharedTrc::wait_until_unique(&mut shared, Duration::from_millis(10)).is_err());
+    /// ```
+    pub fn wait_until_unique(this: &mut Self, timeout: Duration) -> Result<&mut T, TimeoutError> {
+        let deadline = Instant::now().checked_add(timeout);
+        return block_on(Self::wait_unique(this), deadline).ok_or(TimeoutError);
+    }
+}
+
+impl<T> SharedTrc<T> {
+    /// Return a future which resolves to the value once there are no other `Trc`s or `SharedTrc`s to it, on any thread.
+    /// `Weak`s may be left, and can no longer be upgraded.
+    ///
+    /// Dropping the other handles wakes the future, so it does not poll the counts. If the future is dropped before it
+    /// completes, the `SharedTrc` is dropped with it.
+    ///
+    /// # Examples
+    #[cfg_attr(feature = "single-threaded", doc = "```ignore")]
+    #[cfg_attr(not(feature = "single-threaded"), doc = "```")]
+    /// # use std::{future::Future, pin::pin, sync::Arc, task::{Context, Poll, Wake}, thread::{self, Thread}};
+    /// # struct ThreadWaker(Thread);
+    /// # impl Wake for ThreadWaker {
+    /// #     fn wake(self: Arc<Self>) {
+    /// #         self.0.unpark();
+    /// #     }
+    /// # }
+    /// # fn block_on<F: Future>(future: F) -> F::Output {
+    /// #     let mut future = pin!(future);
+    /// #     let waker = Arc::new(ThreadWaker(thread::current())).into();
+    /// #     let mut cx = Context::from_waker(&waker);
+    /// #     loop {
+    /// #         match future.as_mut().poll(&mut cx) {
+    /// #             Poll::Ready(output) => return output,
+    /// #             Poll::Pending => thread::park(),
+    /// #         }
+    /// #     }
+    /// # }
+    /// use trc::SharedTrc;
+    ///
+    /// let server = SharedTrc::new(String::from("server"));
+    /// let workers: Vec<_> = (0..4)
+    ///     .map(|_| {
+    ///         let server = server.clone();
+    ///         std::thread::spawn(move || server.len())
+    ///     })
+    ///     .collect();
+    ///
+    /// //Graceful shutdown: wait for the workers to release the server, then take it.
+    /// let server = block_on(SharedTrc::into_inner_async(server));
+    /// assert_eq!(server, "server");
+    /// # for worker in workers {
+    /// #     worker.join().unwrap();
+    /// # }
+    /// ```
+    #[inline]
+    pub fn into_inner_async(this: Self) -> IntoInnerAsync<T> {
+        return IntoInnerAsync {
+            waiter: Waiter::new(this.data.as_ptr()),
+            shared: Some(this),
+        };
+    }
+
+    /// Block the current thread until there are no other `Trc`s or `SharedTrc`s to the value, and return it. `Weak`s may
+    /// be left, and can no longer be upgraded. This is the blocking counterpart of [`SharedTrc::into_inner_async`].
+    ///
+    /// The thread is parked while it waits, and unparked when the other handles are dropped.
+    ///
+    /// # Examples
+    #[cfg_attr(feature = "single-threaded", doc = "```ignore")]
+    #[cfg_attr(not(feature = "single-threaded"), doc = "```")]
+    /// use trc::SharedTrc;
+    ///
+    /// let log = SharedTrc::new(std::sync::Mutex::new(Vec::new()));
+    /// let workers: Vec<_> = (0..4)
+    ///     .map(|i| {
+    ///         let log = log.clone();
+    ///         std::thread::spawn(move || log.lock().unwrap().push(i))
+    ///     })
+    ///     .collect();
+    ///
+    /// let log = SharedTrc::into_inner_blocking(log).into_inner().unwrap();
+    /// assert_eq!(log.len(), 4);
+    /// # for worker in workers {
+    /// #     worker.join().unwrap();
+    /// # }
+    /// ```
+    pub fn into_inner_blocking(this: Self) -> T {
+        return block_on(Self::into_inner_async(this), None)
+            .expect("Waiting without a deadline does not time out.");
+    }
+}
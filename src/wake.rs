@@ -0,0 +1,126 @@
+//! Creating [`Waker`]s from `SharedTrc`s.
+//!
+//! The standard library's `Wake` trait can only be used with `Arc`. [`TrcWake`] is its counterpart for [`SharedTrc`], so
+//! an executor can keep its tasks in `Trc`s and only use atomic reference counting for the wakers it hands out.
+//!
+//! # Examples
+//! ```
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//! use trc::{wake::{self, TrcWake}, SharedTrc};
+//!
+//! struct Task {
+//!     wakeups: AtomicUsize,
+//! }
+//!
+//! impl TrcWake for Task {
+//!     fn wake(this: SharedTrc<Self>) {
+//!         this.wakeups.fetch_add(1, Ordering::Relaxed);
+//!     }
+//! }
+//!
+//! let task = SharedTrc::new(Task { wakeups: AtomicUsize::new(0) });
+//! let waker = wake::waker(task.clone());
+//! waker.wake_by_ref();
+//! waker.clone().wake();
+//! assert_eq!(task.wakeups.load(Ordering::Relaxed), 2);
+//! ```
+
+use core::{
+    marker::PhantomData,
+    mem::ManuallyDrop,
+    task::{RawWaker, RawWakerVTable, Waker},
+};
+
+use crate::SharedTrc;
+
+/// The implementation of waking a task which is shared through a [`SharedTrc`]. This is the counterpart of the standard
+/// library's `Wake` trait, and is used by [`waker`] to create a [`Waker`].
+pub trait TrcWake {
+    /// Wake this task, consuming the handle.
+    fn wake(this: SharedTrc<Self>);
+
+    /// Wake this task without consuming the handle. The default implementation clones the handle and calls
+    /// [`wake`](TrcWake::wake).
+    #[inline]
+    fn wake_by_ref(this: &SharedTrc<Self>) {
+        Self::wake(this.clone());
+    }
+}
+
+/// Create a [`Waker`] which holds `task` and calls [`TrcWake::wake`] on it. Cloning and dropping the `Waker` clones and
+/// drops the `SharedTrc`.
+///
+/// # Examples
+/// ```
+/// use trc::{wake::{self, TrcWake}, SharedTrc};
+///
+/// struct Task;
+///
+/// impl TrcWake for Task {
+///     fn wake(_this: SharedTrc<Self>) {}
+/// }
+///
+/// let task = SharedTrc::new(Task);
+/// let waker = wake::waker(task.clone());
+/// assert_eq!(SharedTrc::atomic_count(&task), 2);
+/// drop(waker);
+/// assert_eq!(SharedTrc::atomic_count(&task), 1);
+/// ```
+#[must_use]
+pub fn waker<W: TrcWake + Send + Sync + 'static>(task: SharedTrc<W>) -> Waker {
+    let raw = RawWaker::new(SharedTrc::into_raw(task).cast(), &VTable::<W>::VTABLE);
+    //The vtable functions uphold the `RawWaker` contract for any `W: Send + Sync`.
+    return unsafe { Waker::from_raw(raw) };
+}
+
+impl<W: TrcWake + Send + Sync + 'static> From<SharedTrc<W>> for Waker {
+    /// Create a [`Waker`] from a `SharedTrc`. See [`waker`].
+    ///
+    /// # Examples
+    /// ```
+    /// use core::task::Waker;
+    /// use trc::{wake::TrcWake, SharedTrc};
+    ///
+    /// struct Task;
+    ///
+    /// impl TrcWake for Task {
+    ///     fn wake(_this: SharedTrc<Self>) {}
+    /// }
+    ///
+    /// let waker = Waker::from(SharedTrc::new(Task));
+    /// waker.wake();
+    /// ```
+    #[inline]
+    fn from(task: SharedTrc<W>) -> Self {
+        return waker(task);
+    }
+}
+
+struct VTable<W>(PhantomData<W>);
+
+impl<W: TrcWake + Send + Sync + 'static> VTable<W> {
+    const VTABLE: RawWakerVTable = RawWakerVTable::new(
+        Self::clone_waker,
+        Self::wake,
+        Self::wake_by_ref,
+        Self::drop_waker,
+    );
+
+    unsafe fn clone_waker(ptr: *const ()) -> RawWaker {
+        unsafe { SharedTrc::increment_local_count(ptr.cast::<W>()) };
+        return RawWaker::new(ptr, &Self::VTABLE);
+    }
+
+    unsafe fn wake(ptr: *const ()) {
+        W::wake(unsafe { SharedTrc::from_raw(ptr.cast::<W>()) });
+    }
+
+    unsafe fn wake_by_ref(ptr: *const ()) {
+        let task = ManuallyDrop::new(unsafe { SharedTrc::from_raw(ptr.cast::<W>()) });
+        W::wake_by_ref(&task);
+    }
+
+    unsafe fn drop_waker(ptr: *const ()) {
+        unsafe { SharedTrc::decrement_local_count(ptr.cast::<W>()) };
+    }
+}